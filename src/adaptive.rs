@@ -0,0 +1,115 @@
+//! 自適應模擬次數 - 分位數估計足夠精確時停止
+//!
+//! 以 seed 1, 2, ... 依序分批計算，每批結束後估計跡統計量（特徵值總和）的目標分位數
+//! 與其 95% 信賴區間；區間半寬相對於估計值小於目標精度時停止派送新的 seed。
+//! 標準誤以批次法估計，見 `batch_quantile_standard_error`。
+
+use crate::data_storage::EigenvalueSimulation;
+use crate::display_utils::format_number_with_commas;
+use crate::simulation_analyzers::SumAggregator;
+use crate::stats::percentile::{batch_quantile_standard_error, nth_percentile};
+
+/// 估計標準誤時的批次數量
+const QUANTILE_BATCHES: usize = 20;
+
+/// 95% 信賴區間的常態臨界值
+const Z_95: f64 = 1.96;
+
+/// 預設每次檢查之間至少增加的模擬次數
+pub const DEFAULT_CHECK_EVERY: usize = 10_000;
+
+/// 自適應模擬的停止條件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveTarget {
+    /// 目標分位數（0 與 1 之間，不含端點）
+    pub quantile: f64,
+    /// 95% 信賴區間半寬相對於分位數估計值的上限
+    pub precision: f64,
+    /// 兩次檢查之間至少增加的模擬次數（也是第一次檢查時的次數）
+    pub check_every: usize,
+}
+
+/// 自適應模擬停止時的狀態
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveResult {
+    /// 最終的模擬次數（seed 1..=runs 皆已寫入檔案）
+    pub runs: usize,
+    /// 目標分位數的估計值
+    pub estimate: f64,
+    /// 估計值的標準誤
+    pub standard_error: f64,
+    /// 是否達到目標精度（否則為達到 `num_runs` 上限而停止）
+    pub converged: bool,
+}
+
+impl AdaptiveResult {
+    /// 95% 信賴區間半寬相對於估計值的比例
+    pub fn relative_half_width(&self) -> f64 {
+        Z_95 * self.standard_error / self.estimate.abs()
+    }
+}
+
+impl EigenvalueSimulation {
+    /// 分批計算 seed 1, 2, ...，直到跡統計量的目標分位數達到指定精度
+    ///
+    /// `num_runs` 為模擬次數的上限，`seeds` 設定會被忽略。既有檔案中的結果會沿用，
+    /// 因此中斷後重新執行會從已完成的次數繼續檢查。每次檢查後模擬次數至少增加
+    /// `check_every`，且至少增加 25%，使重新讀取檔案的次數只隨總次數對數成長。
+    ///
+    /// `num_runs` 為 0 時沒有可計算的 seed，返回 `ErrorKind::InvalidInput` 錯誤。
+    pub fn run_adaptive(
+        &self,
+        target: AdaptiveTarget,
+        quiet: bool,
+    ) -> std::io::Result<AdaptiveResult> {
+        if self.num_runs == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "adaptive runs need an upper bound of at least 1 run",
+            ));
+        }
+        let mut batch = self.clone();
+        batch.seeds = None;
+        batch.num_runs = self
+            .completed_count()?
+            .max(target.check_every)
+            .clamp(1, self.num_runs);
+
+        loop {
+            batch.try_run_simulation_quiet()?;
+            let values = batch
+                .read_data_sorted()?
+                .iter()
+                .map(|(_, eigenvalues)| batch.record_statistic(&SumAggregator, eigenvalues))
+                .collect::<std::io::Result<Vec<f64>>>()?;
+
+            let standard_error =
+                batch_quantile_standard_error(&values, target.quantile, QUANTILE_BATCHES);
+            let mut result = AdaptiveResult {
+                runs: batch.num_runs,
+                estimate: nth_percentile(&mut values.clone(), target.quantile),
+                standard_error,
+                converged: false,
+            };
+            // NaN（樣本不足以估計標準誤）不會滿足條件
+            result.converged = result.relative_half_width() <= target.precision;
+
+            if !quiet {
+                println!(
+                    "Adaptive check: {} runs, {:.1}th percentile {:.6} ± {:.6} (relative {:.5})",
+                    format_number_with_commas(result.runs),
+                    target.quantile * 100.0,
+                    result.estimate,
+                    Z_95 * result.standard_error,
+                    result.relative_half_width()
+                );
+            }
+            if result.converged || batch.num_runs >= self.num_runs {
+                return Ok(result);
+            }
+
+            let grown = (batch.num_runs + target.check_every).max(batch.num_runs * 5 / 4);
+            batch.num_runs = grown.min(self.num_runs);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! 批次配置檔案 - 以 JSON 描述多個模擬工作
+//!
+//! 檔案內容為 `EigenvalueSimulation` 的 JSON 陣列，每個元素為一個工作，例如
+//! `[{"model": 2, "dim": 3, "steps": 10000, "num_runs": 100000}]`。
+//! `model` 可寫模型編號或變體名稱；其餘選用欄位（如 `master_seed`、`data_dir`）
+//! 省略時使用 `EigenvalueSimulation::new` 的預設值。
+
+use crate::data_storage::EigenvalueSimulation;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// 讀取批次配置檔案並驗證每個工作的參數
+pub fn read_batch_config<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<EigenvalueSimulation>> {
+    let reader = BufReader::new(File::open(path)?);
+    // 逐一反序列化工作，使錯誤訊息能指出是第幾個工作
+    let jobs: Vec<serde_json::Value> = serde_json::from_reader(reader).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("batch config: {e}"),
+        )
+    })?;
+
+    if jobs.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "batch config contains no jobs",
+        ));
+    }
+    jobs.into_iter()
+        .enumerate()
+        .map(|(index, job)| {
+            serde_json::from_value(job)
+                .map_err(|e| e.to_string())
+                .and_then(|job| validate_job(&job).map(|()| job))
+                .map_err(|message| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("batch config job {}: {message}", index + 1),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// 檢查 `EigenvalueSimulation::validate` 以外的條件（與命令行參數的驗證一致）
+///
+/// 反序列化時已呼叫 `validate`，此處不再重複檢查步數、捨棄步數、top-k
+/// 與每筆記錄實際儲存的特徵值數量（已計入 top-k 與聚合模式）。
+fn validate_job(job: &EigenvalueSimulation) -> Result<(), String> {
+    if job.dim == 0 || job.steps == 0 || job.num_runs == 0 {
+        return Err("dim, steps and num_runs must be greater than 0".to_string());
+    }
+    if let Some(min_condition) = job.min_condition {
+        if !(min_condition > 0.0 && min_condition <= 1.0) {
+            return Err(format!("min_condition ({min_condition}) must be in (0, 1]"));
+        }
+    }
+    if job
+        .checkpoint_every
+        .is_some_and(|interval| interval.is_zero())
+    {
+        return Err("checkpoint_every must be greater than 0".to_string());
+    }
+    if job.seeds.as_ref().is_some_and(|seeds| seeds.is_empty()) {
+        return Err("seeds cannot be empty".to_string());
+    }
+    Ok(())
+}
@@ -2,12 +2,74 @@
 //!
 //! 提供命令行參數的解析、驗證和幫助信息顯示功能。
 
-use crate::johansen_models::JohansenModel;
+use crate::adaptive::{AdaptiveTarget, DEFAULT_CHECK_EVERY};
+use crate::data_storage::config::{MAX_WRITE_BUFFER_CAPACITY, MIN_WRITE_BUFFER_CAPACITY};
+use crate::data_storage::file_format::{MAX_EIGENVALUES_PER_RUN, MAX_STEPS};
+use crate::data_storage::progress::read_seed_file;
+use crate::johansen_models::{JohansenModel, ModelError};
+use crate::johansen_statistics::{AggregateMode, SortOrder};
+use crate::rng_matrix::SeedHashing;
 use std::io::{self, Write};
 
 // 導入條件性輸出宏
 use crate::conditional_println;
 
+/// 預設的步數警告門檻：步數較少時離散的布朗運動近似會使臨界值偏誤
+pub const DEFAULT_MIN_STEPS_WARNING: usize = 1_000;
+
+/// `--export` 的輸出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `seed,eigenvalue_1,...` 文字檔（彙總檔案為 `seed,trace,max`）
+    Csv,
+    /// 欄式 Parquet 檔案（需要 `parquet` feature）
+    Parquet,
+}
+
+/// 命令行參數錯誤
+///
+/// 每一類錯誤對應不同的程序結束代碼（`exit_code`），讓包裝腳本能區分錯誤的種類；
+/// 顯示幫助或版本信息不是錯誤，結束代碼為 0。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// 不認得的參數
+    UnknownArgument(String),
+    /// 參數缺少值
+    MissingValue(String),
+    /// 參數值無法解析或無法讀取
+    InvalidValue(String),
+    /// 參數值超出範圍或組合不合法
+    Invalid(String),
+    /// 使用者在線程數量確認時取消執行
+    Cancelled,
+}
+
+impl CliError {
+    /// 程序結束代碼
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::UnknownArgument(_) => 2,
+            CliError::MissingValue(_) => 3,
+            CliError::InvalidValue(_) => 4,
+            CliError::Invalid(_) => 5,
+            CliError::Cancelled => 6,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownArgument(arg) => write!(f, "unknown argument '{arg}'"),
+            CliError::MissingValue(param) => write!(f, "{param} parameter requires a value"),
+            CliError::InvalidValue(message) | CliError::Invalid(message) => f.write_str(message),
+            CliError::Cancelled => f.write_str("execution cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
 /// 命令行參數配置
 #[derive(Debug, Clone)]
 pub struct CliArgs {
@@ -16,8 +78,92 @@ pub struct CliArgs {
     pub num_runs: usize,
     pub dim_start: usize,
     pub dim_end: usize,
+    /// 指定的維度列表（設定時取代 `dim_start..=dim_end`）
+    pub dim_list: Option<Vec<usize>>,
     pub models: Option<Vec<JohansenModel>>,
     pub quiet: bool,
+    pub profile: bool,
+    pub sort_order: SortOrder,
+    pub stream_id: u32,
+    /// 與 seed 混合的主種子
+    pub master_seed: Option<u64>,
+    /// 子串流 0 的 seed 轉換為 RNG 種子的方式
+    pub seed_hashing: SeedHashing,
+    pub top_k: Option<usize>,
+    /// 絕對值低於此下限的特徵值在儲存前設為 0
+    pub eigenvalue_floor: Option<f64>,
+    /// 每筆記錄儲存全部特徵值或只存跡與最大特徵值
+    pub aggregate: AggregateMode,
+    /// 捨棄的布朗運動起始時間步數（0 為不捨棄）
+    pub warmup: usize,
+    /// 在每筆記錄中存放計算時間
+    pub timestamps: bool,
+    /// 同時將每次模擬的跡寫入資料檔案旁的 `.trace` 檔案
+    pub trace_file: bool,
+    /// `steps` 低於此值時警告離散化偏誤（0 為不警告）
+    pub min_steps_warning: usize,
+    pub min_condition: Option<f64>,
+    /// 回報含有不可忽略虛部之特徵值的 seed
+    pub diagnose_complex: bool,
+    pub single_thread: bool,
+    /// 每個 seed 只生成一次布朗運動路徑，供所有選定的模型共用
+    pub shared_paths: bool,
+    pub checkpoint_every: Option<usize>,
+    /// 資料檔案寫入緩衝區大小（bytes，`None` 為預設值）
+    pub write_buffer: Option<usize>,
+    /// 開始計算後經過此秒數即停止派送新的 seed
+    pub time_budget: Option<usize>,
+    pub count: bool,
+    /// 輸出批次的總模擬次數與預估輸出大小後結束，不進行計算
+    pub dry_run: bool,
+    /// 以串流方式估計既有資料檔案的百分位數後結束，不進行計算也不載入全部數據
+    pub summary_only: bool,
+    /// 以完整數據重新分析既有資料檔案的百分位數後結束，不進行計算
+    pub analyze_only: bool,
+    /// 移除資料檔案中 seed 不在 `1..=num_runs` 的記錄後結束
+    pub prune_out_of_range: bool,
+    /// 修復既有資料檔案（截斷、移除多餘結束標記、重複與範圍外的 seed）後結束
+    pub repair: bool,
+    /// 略過計算前的磁碟空間預檢
+    pub ignore_space: bool,
+    pub only_missing_models: bool,
+    pub stdout_ndjson: bool,
+    /// 以資料檔案格式將記錄串流至標準輸出，不讀寫 `data/` 目錄（僅限單一模型與單一維度）
+    pub no_data_dir: bool,
+    pub dump_normals: Option<u32>,
+    /// `--rerun-seed` 重新計算並輸出的 seed（僅限單一模型與單一維度）
+    pub rerun_seed: Option<u32>,
+    pub header_path: Option<String>,
+    pub import_csv: Option<String>,
+    /// `--export` 的輸出格式與路徑（僅限單一模型與單一維度）
+    pub export: Option<(ExportFormat, String)>,
+    /// `--seed-file` 讀入的 seed 列表
+    pub seeds: Option<Vec<u32>>,
+    /// `--config` 指定的批次配置檔案（需要 `serde` feature）
+    pub config: Option<String>,
+    pub benchmark: bool,
+    /// `--output-file` 指定的資料檔案路徑（僅限單一模型與單一維度）
+    pub output_file: Option<String>,
+    /// `--validate-against` 指定的參考臨界值 CSV（僅限單一模型）
+    pub validate_against: Option<String>,
+    /// 臨界值比對的相對容許誤差
+    pub tolerance: f64,
+    /// `--table-out` 指定的臨界值表 CSV 路徑
+    pub table_out: Option<String>,
+    /// 模型模擬失敗時記錄錯誤並繼續處理其餘模型
+    pub continue_on_error: bool,
+    /// 分析時只報告上尾百分位數
+    pub tail_only: bool,
+    /// `--tail-only` 時額外列出的最大順序統計量數量
+    pub order_stats: usize,
+    /// 分析報告的百分位數附上 bootstrap 信賴區間時的重抽次數
+    pub bootstrap: Option<usize>,
+    /// 依分位數精度決定模擬次數（`num_runs` 為上限）
+    pub adaptive: bool,
+    /// `--adaptive` 的目標分位數
+    pub target_quantile: Option<f64>,
+    /// `--adaptive` 的相對精度（95% 信賴區間半寬除以估計值）
+    pub target_precision: Option<f64>,
 }
 
 impl Default for CliArgs {
@@ -28,167 +174,516 @@ impl Default for CliArgs {
             num_runs: 1e7 as usize,
             dim_start: 1,
             dim_end: 12,
+            dim_list: None,
             models: None,
             quiet: false, // 預設為 false
+            profile: false,
+            sort_order: SortOrder::Descending,
+            stream_id: 0,
+            master_seed: None,
+            seed_hashing: SeedHashing::default(),
+            top_k: None,
+            eigenvalue_floor: None,
+            aggregate: AggregateMode::default(),
+            warmup: 0,
+            timestamps: false,
+            trace_file: false,
+            min_steps_warning: DEFAULT_MIN_STEPS_WARNING,
+            min_condition: None,
+            diagnose_complex: false,
+            single_thread: false,
+            shared_paths: false,
+            checkpoint_every: None,
+            write_buffer: None,
+            time_budget: None,
+            count: false,
+            dry_run: false,
+            summary_only: false,
+            analyze_only: false,
+            prune_out_of_range: false,
+            repair: false,
+            ignore_space: false,
+            only_missing_models: false,
+            stdout_ndjson: false,
+            no_data_dir: false,
+            dump_normals: None,
+            rerun_seed: None,
+            header_path: None,
+            import_csv: None,
+            export: None,
+            seeds: None,
+            config: None,
+            benchmark: false,
+            output_file: None,
+            validate_against: None,
+            tolerance: 0.01,
+            table_out: None,
+            continue_on_error: false,
+            tail_only: false,
+            order_stats: 0,
+            bootstrap: None,
+            adaptive: false,
+            target_quantile: None,
+            target_precision: None,
         }
     }
 }
 
 impl CliArgs {
     /// 從命令行參數解析配置
-    pub fn parse() -> Option<Self> {
+    ///
+    /// 返回 `Ok(None)` 代表已顯示幫助或版本信息，不需繼續執行。
+    pub fn parse() -> Result<Option<Self>, CliError> {
         let args: Vec<String> = std::env::args().collect();
+        Self::parse_from(&args)
+    }
+
+    /// 從參數列表解析配置（`args[0]` 為程式名稱）
+    pub fn parse_from(args: &[String]) -> Result<Option<Self>, CliError> {
         let mut config = Self::default();
 
         // 顯示幫助信息
         if args.len() > 1 && (args[1] == "--help" || args[1] == "-h") {
             Self::print_help(&args[0]);
-            return None;
+            return Ok(None);
         }
 
         // 顯示版本信息
         if args.len() > 1 && (args[1] == "--version" || args[1] == "-v") {
             Self::print_version();
-            return None;
+            return Ok(None);
         }
 
         // 參數解析
         let mut i = 1;
+        let mut dim_range_given = false;
+        let mut excluded_models = None;
         while i < args.len() {
             match args[i].as_str() {
                 "--threads" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--threads")? {
-                        config.num_threads = Some(value);
-                    } else {
-                        return None;
-                    }
+                    config.num_threads = Some(Self::parse_next_arg(args, i, "--threads")?);
                     i += 2;
                 }
                 "--steps" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--steps")? {
-                        config.steps = value;
-                    } else {
-                        return None;
-                    }
+                    config.steps = Self::parse_next_arg(args, i, "--steps")?;
+                    i += 2;
+                }
+                "--min-steps-warning" => {
+                    config.min_steps_warning =
+                        Self::parse_next_arg(args, i, "--min-steps-warning")?;
                     i += 2;
                 }
                 "--runs" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--runs")? {
-                        config.num_runs = value;
-                    } else {
-                        return None;
-                    }
+                    config.num_runs = Self::parse_next_arg(args, i, "--runs")?;
                     i += 2;
                 }
                 "--dim-start" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--dim-start")? {
-                        config.dim_start = value;
-                    } else {
-                        return None;
-                    }
+                    config.dim_start = Self::parse_next_arg(args, i, "--dim-start")?;
+                    dim_range_given = true;
                     i += 2;
                 }
                 "--dim-end" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--dim-end")? {
-                        config.dim_end = value;
-                    } else {
-                        return None;
-                    }
+                    config.dim_end = Self::parse_next_arg(args, i, "--dim-end")?;
+                    dim_range_given = true;
                     i += 2;
                 }
                 "--dim" => {
-                    if let Some(value) = Self::parse_next_arg(&args, i, "--dim")? {
-                        config.dim_start = value;
-                        config.dim_end = value;
-                    } else {
-                        return None;
-                    }
+                    let value = Self::parse_next_arg(args, i, "--dim")?;
+                    config.dim_start = value;
+                    config.dim_end = value;
+                    dim_range_given = true;
+                    i += 2;
+                }
+                "--dim-list" => {
+                    let value = Self::parse_next_string(args, i, "--dim-list")?;
+                    let dims = Self::parse_dim_list(&value).map_err(CliError::InvalidValue)?;
+                    config.dim_list = Some(dims);
                     i += 2;
                 }
                 "--model" => {
-                    if let Some(value) = Self::parse_next_string(&args, i, "--model")? {
-                        match Self::parse_models(&value) {
-                            Ok(models) => config.models = Some(models),
-                            Err(e) => {
-                                eprintln!("Error: {e}");
-                                return None;
-                            }
-                        }
-                    } else {
-                        return None;
-                    }
+                    let value = Self::parse_next_string(args, i, "--model")?;
+                    let models = Self::parse_models(&value).map_err(CliError::InvalidValue)?;
+                    config.models = Some(models);
+                    i += 2;
+                }
+                "--exclude-models" => {
+                    let value = Self::parse_next_string(args, i, "--exclude-models")?;
+                    let models = Self::parse_models(&value).map_err(CliError::InvalidValue)?;
+                    excluded_models = Some(models);
+                    i += 2;
+                }
+                "--stream" => {
+                    let value = Self::parse_next_arg(args, i, "--stream")?;
+                    config.stream_id = u32::try_from(value).map_err(|_| {
+                        CliError::InvalidValue(format!("--stream parameter exceeds {}", u32::MAX))
+                    })?;
+                    i += 2;
+                }
+                "--master-seed" => {
+                    let value = Self::parse_next_string(args, i, "--master-seed")?;
+                    let master_seed = value.trim().parse::<u64>().map_err(|_| {
+                        CliError::InvalidValue(format!(
+                            "--master-seed parameter must be an integer between 0 and {}",
+                            u64::MAX
+                        ))
+                    })?;
+                    config.master_seed = Some(master_seed);
+                    i += 2;
+                }
+                "--seed-hashing" => {
+                    let value = Self::parse_next_string(args, i, "--seed-hashing")?;
+                    config.seed_hashing = value.parse().map_err(CliError::InvalidValue)?;
+                    i += 2;
+                }
+                "--top-k" => {
+                    config.top_k = Some(Self::parse_next_arg(args, i, "--top-k")?);
+                    i += 2;
+                }
+                "--aggregate" => {
+                    let value = Self::parse_next_string(args, i, "--aggregate")?;
+                    config.aggregate = value.parse().map_err(CliError::InvalidValue)?;
+                    i += 2;
+                }
+                "--warmup" => {
+                    config.warmup = Self::parse_next_arg(args, i, "--warmup")?;
+                    i += 2;
+                }
+                "--trace-file" => {
+                    config.trace_file = true;
+                    i += 1;
+                }
+                "--timestamps" => {
+                    config.timestamps = true;
+                    i += 1;
+                }
+                "--continue-on-error" => {
+                    config.continue_on_error = true;
+                    i += 1;
+                }
+                "--tail-only" => {
+                    config.tail_only = true;
+                    i += 1;
+                }
+                "--adaptive" => {
+                    config.adaptive = true;
+                    i += 1;
+                }
+                "--target-quantile" => {
+                    let value = Self::parse_next_string(args, i, "--target-quantile")?;
+                    config.target_quantile = Some(value.trim().parse::<f64>().map_err(|_| {
+                        CliError::InvalidValue(
+                            "--target-quantile parameter must be a number (e.g., 0.95)".to_string(),
+                        )
+                    })?);
+                    i += 2;
+                }
+                "--target-precision" => {
+                    let value = Self::parse_next_string(args, i, "--target-precision")?;
+                    config.target_precision = Some(value.trim().parse::<f64>().map_err(|_| {
+                        CliError::InvalidValue(
+                            "--target-precision parameter must be a number (e.g., 0.001)"
+                                .to_string(),
+                        )
+                    })?);
+                    i += 2;
+                }
+                "--order-stats" => {
+                    config.order_stats = Self::parse_next_arg(args, i, "--order-stats")?;
+                    i += 2;
+                }
+                "--bootstrap" => {
+                    config.bootstrap = Some(Self::parse_next_arg(args, i, "--bootstrap")?);
+                    i += 2;
+                }
+                "--eigenvalue-floor" => {
+                    let value = Self::parse_next_string(args, i, "--eigenvalue-floor")?;
+                    let floor = value.trim().parse::<f64>().map_err(|_| {
+                        CliError::InvalidValue(
+                            "--eigenvalue-floor parameter must be a number (e.g., 1e-12)"
+                                .to_string(),
+                        )
+                    })?;
+                    config.eigenvalue_floor = Some(floor);
+                    i += 2;
+                }
+                "--min-condition" => {
+                    let value = Self::parse_next_string(args, i, "--min-condition")?;
+                    let min_condition = value.trim().parse::<f64>().map_err(|_| {
+                        CliError::InvalidValue(
+                            "--min-condition parameter must be a number (e.g., 1e-12)".to_string(),
+                        )
+                    })?;
+                    config.min_condition = Some(min_condition);
+                    i += 2;
+                }
+                "--sort-order" => {
+                    let value = Self::parse_next_string(args, i, "--sort-order")?;
+                    config.sort_order = value.parse().map_err(CliError::InvalidValue)?;
                     i += 2;
                 }
                 "--quiet" => {
                     config.quiet = true;
                     i += 1;
                 }
+                "--checkpoint-every" => {
+                    config.checkpoint_every =
+                        Some(Self::parse_next_arg(args, i, "--checkpoint-every")?);
+                    i += 2;
+                }
+                "--write-buffer" => {
+                    config.write_buffer = Some(Self::parse_next_arg(args, i, "--write-buffer")?);
+                    i += 2;
+                }
+                "--time-budget" => {
+                    config.time_budget = Some(Self::parse_next_arg(args, i, "--time-budget")?);
+                    i += 2;
+                }
+                "--config" => {
+                    config.config = Some(Self::parse_next_string(args, i, "--config")?);
+                    i += 2;
+                }
+                "--diagnose-complex" => {
+                    config.diagnose_complex = true;
+                    i += 1;
+                }
+                "--single-thread" => {
+                    config.single_thread = true;
+                    i += 1;
+                }
+                "--shared-paths" => {
+                    config.shared_paths = true;
+                    i += 1;
+                }
+                "--only-missing-models" => {
+                    config.only_missing_models = true;
+                    i += 1;
+                }
+                "--stdout-ndjson" => {
+                    // 標準輸出只保留 NDJSON，強制關閉進度輸出
+                    config.stdout_ndjson = true;
+                    config.quiet = true;
+                    i += 1;
+                }
+                "--no-data-dir" => {
+                    // 標準輸出只保留二進位資料，強制關閉進度輸出
+                    config.no_data_dir = true;
+                    config.quiet = true;
+                    i += 1;
+                }
+                "--dump-normals" => {
+                    // 隱藏的診斷模式：不列在說明中
+                    let value = Self::parse_next_arg(args, i, "--dump-normals")?;
+                    let seed = u32::try_from(value).map_err(|_| {
+                        CliError::InvalidValue(format!(
+                            "--dump-normals parameter exceeds {}",
+                            u32::MAX
+                        ))
+                    })?;
+                    config.dump_normals = Some(seed);
+                    i += 2;
+                }
+                "--rerun-seed" => {
+                    let value = Self::parse_next_arg(args, i, "--rerun-seed")?;
+                    let seed = u32::try_from(value).map_err(|_| {
+                        CliError::InvalidValue(format!(
+                            "--rerun-seed parameter exceeds {}",
+                            u32::MAX
+                        ))
+                    })?;
+                    config.rerun_seed = Some(seed);
+                    i += 2;
+                }
+                "--header" => {
+                    config.header_path = Some(Self::parse_next_string(args, i, "--header")?);
+                    i += 2;
+                }
+                "--import-csv" => {
+                    config.import_csv = Some(Self::parse_next_string(args, i, "--import-csv")?);
+                    i += 2;
+                }
+                "--export" => {
+                    let format = match Self::parse_next_string(args, i, "--export")?.as_str() {
+                        "csv" => ExportFormat::Csv,
+                        "parquet" => ExportFormat::Parquet,
+                        other => {
+                            return Err(CliError::InvalidValue(format!(
+                                "--export format must be csv or parquet, got '{other}'"
+                            )));
+                        }
+                    };
+                    let path = Self::parse_next_string(args, i + 1, "--export")?;
+                    config.export = Some((format, path));
+                    i += 3;
+                }
+                "--output-file" => {
+                    config.output_file = Some(Self::parse_next_string(args, i, "--output-file")?);
+                    i += 2;
+                }
+                "--validate-against" => {
+                    config.validate_against =
+                        Some(Self::parse_next_string(args, i, "--validate-against")?);
+                    i += 2;
+                }
+                "--table-out" => {
+                    config.table_out = Some(Self::parse_next_string(args, i, "--table-out")?);
+                    i += 2;
+                }
+                "--tolerance" => {
+                    let value = Self::parse_next_string(args, i, "--tolerance")?;
+                    config.tolerance = value.trim().parse::<f64>().map_err(|_| {
+                        CliError::InvalidValue(
+                            "--tolerance parameter must be a number (e.g., 0.01)".to_string(),
+                        )
+                    })?;
+                    i += 2;
+                }
+                "--seed-file" => {
+                    let value = Self::parse_next_string(args, i, "--seed-file")?;
+                    let seeds = read_seed_file(&value).map_err(|e| {
+                        CliError::InvalidValue(format!("failed to read seed file {value}: {e}"))
+                    })?;
+                    if seeds.is_empty() {
+                        return Err(CliError::InvalidValue(format!(
+                            "seed file {value} contains no seeds"
+                        )));
+                    }
+                    config.seeds = Some(seeds);
+                    i += 2;
+                }
+                "--count" => {
+                    config.count = true;
+                    i += 1;
+                }
+                "--dry-run" => {
+                    config.dry_run = true;
+                    i += 1;
+                }
+                "--summary-only" => {
+                    config.summary_only = true;
+                    i += 1;
+                }
+                "--analyze-only" => {
+                    config.analyze_only = true;
+                    i += 1;
+                }
+                "--prune-out-of-range" => {
+                    config.prune_out_of_range = true;
+                    i += 1;
+                }
+                "--repair" => {
+                    config.repair = true;
+                    i += 1;
+                }
+                "--ignore-space" => {
+                    config.ignore_space = true;
+                    i += 1;
+                }
+                "--profile" => {
+                    config.profile = true;
+                    i += 1;
+                }
+                "--benchmark" => {
+                    config.benchmark = true;
+                    i += 1;
+                }
                 _ => {
-                    eprintln!("Error: unknown argument '{}'", args[i]);
-                    eprintln!("Use --help to see available options");
-                    return None;
+                    return Err(CliError::UnknownArgument(args[i].clone()));
                 }
             }
         }
 
-        // 參數驗證
-        if !config.validate() {
-            return None;
+        // 維度列表與範圍參數互斥；其最小與最大值沿用範圍的驗證
+        if let Some(dims) = &config.dim_list {
+            if dim_range_given {
+                return Err(CliError::Invalid(
+                    "--dim-list cannot be combined with --dim, --dim-start or --dim-end"
+                        .to_string(),
+                ));
+            }
+            config.dim_start = dims[0];
+            config.dim_end = dims[dims.len() - 1];
         }
 
-        Some(config)
-    }
-
-    /// 解析下一個參數值（支援逗號分隔的數字）
-    fn parse_next_arg(args: &[String], index: usize, param_name: &str) -> Option<Option<usize>> {
-        if index + 1 >= args.len() {
-            eprintln!("Error: {param_name} parameter requires a value");
-            return None;
+        // 排除列表與模型列表互斥；其餘模型依序作為模型列表
+        if let Some(excluded) = excluded_models {
+            if config.models.is_some() {
+                return Err(CliError::Invalid(
+                    "--exclude-models cannot be combined with --model".to_string(),
+                ));
+            }
+            let models: Vec<JohansenModel> = JohansenModel::all_models()
+                .into_iter()
+                .filter(|model| !excluded.contains(model))
+                .collect();
+            if models.is_empty() {
+                return Err(CliError::Invalid(
+                    "--exclude-models leaves no models to compute".to_string(),
+                ));
+            }
+            config.models = Some(models);
         }
 
-        let input = &args[index + 1];
-        // 移除逗號後再解析
-        let cleaned_input = input.replace(',', "");
+        // 參數驗證
+        config.validate()?;
 
-        match cleaned_input.parse::<usize>() {
-            Ok(value) => Some(Some(value)),
-            Err(_) => {
-                eprintln!(
-                    "Error: {param_name} parameter must be a positive integer (commas allowed, e.g., 1,000,000)"
-                );
-                None
-            }
-        }
+        Ok(Some(config))
     }
+
+    /// 解析下一個參數值（支援逗號分隔、科學記號與 k/M/G 後綴的數字）
+    fn parse_next_arg(args: &[String], index: usize, param_name: &str) -> Result<usize, CliError> {
+        let input = Self::parse_next_string(args, index, param_name)?;
+        parse_count(&input)
+            .map_err(|reason| CliError::InvalidValue(format!("{param_name} parameter {reason}")))
+    }
+
     /// 解析下一個參數字串
     fn parse_next_string(
         args: &[String],
         index: usize,
         param_name: &str,
-    ) -> Option<Option<String>> {
-        if index + 1 >= args.len() {
-            eprintln!("Error: {param_name} parameter requires a value");
-            return None;
+    ) -> Result<String, CliError> {
+        match args.get(index + 1) {
+            Some(value) => Ok(value.clone()),
+            None => Err(CliError::MissingValue(param_name.to_string())),
         }
-        Some(Some(args[index + 1].clone()))
+    }
+
+    /// 分割逗號分隔的列表，略過空白項目
+    fn split_list(s: &str) -> impl Iterator<Item = &str> {
+        s.split(',').map(str::trim).filter(|part| !part.is_empty())
+    }
+
+    /// 從逗號分隔的字串解析維度列表（由小到大排序並移除重複）
+    fn parse_dim_list(s: &str) -> Result<Vec<usize>, String> {
+        let mut dims = Vec::new();
+        for part in Self::split_list(s) {
+            let dim = part
+                .parse::<usize>()
+                .map_err(|_| format!("invalid dimension in --dim-list: {part}"))?;
+            if dim == 0 {
+                return Err("dimensions in --dim-list must be greater than 0".to_string());
+            }
+            dims.push(dim);
+        }
+        if dims.is_empty() {
+            return Err("--dim-list cannot be empty".to_string());
+        }
+        dims.sort_unstable();
+        dims.dedup();
+        Ok(dims)
     }
 
     /// 從逗號分隔的字串解析模型列表
     fn parse_models(s: &str) -> Result<Vec<JohansenModel>, String> {
         let mut models = Vec::new();
-        for part in s.split(',') {
-            let part_trim = part.trim();
-            if part_trim.is_empty() {
-                continue;
-            }
-            match part_trim
+        for part_trim in Self::split_list(s) {
+            let number = part_trim
                 .parse::<u8>()
-                .ok()
-                .and_then(JohansenModel::from_number)
-            {
-                Some(m) => models.push(m),
-                None => return Err(format!("無效的模型代號: {part_trim}")),
+                .map_err(|_| format!("無效的模型代號: {part_trim}"))?;
+            if !JohansenModel::is_valid_number(number) {
+                return Err(ModelError::InvalidNumber(number).to_string());
             }
+            models.extend(JohansenModel::from_number(number));
         }
         if models.is_empty() {
             Err("模型列表不可為空".to_string())
@@ -197,35 +692,311 @@ impl CliArgs {
         }
     }
 
+    /// 要計算的維度：`--dim-list` 的列表，或 `dim_start..=dim_end`
+    pub fn dimensions(&self) -> Vec<usize> {
+        match &self.dim_list {
+            Some(dims) => dims.clone(),
+            None => (self.dim_start..=self.dim_end).collect(),
+        }
+    }
+
+    /// `--adaptive` 的停止條件（未啟用時為 `None`），未指定的欄位使用預設值
+    pub fn adaptive_target(&self) -> Option<AdaptiveTarget> {
+        self.adaptive.then(|| AdaptiveTarget {
+            quantile: self.target_quantile.unwrap_or(0.95),
+            precision: self.target_precision.unwrap_or(0.001),
+            check_every: DEFAULT_CHECK_EVERY,
+        })
+    }
+
+    /// 步數低於 `min_steps_warning` 時的警告訊息（安靜模式或門檻為 0 時不警告）
+    pub fn steps_warning(&self) -> Option<String> {
+        if self.quiet || self.steps >= self.min_steps_warning {
+            return None;
+        }
+        Some(format!(
+            "--steps {} is below {}; the discretized Brownian motion approximates the limiting \
+             distribution coarsely and biases critical values (raise --steps, or pass \
+             --min-steps-warning 0 to silence this warning)",
+            self.steps, self.min_steps_warning
+        ))
+    }
+
     /// 驗證參數的有效性
-    fn validate(&self) -> bool {
+    fn validate(&self) -> Result<(), CliError> {
         // 檢查維度範圍
         if self.dim_start > self.dim_end {
-            eprintln!(
-                "Error: dimension start ({}) cannot be greater than end ({})",
+            return Err(CliError::Invalid(format!(
+                "dimension start ({}) cannot be greater than end ({})",
                 self.dim_start, self.dim_end
-            );
-            return false;
+            )));
         }
 
         // 檢查正整數參數
         if self.steps == 0 || self.num_runs == 0 || self.dim_start == 0 {
-            eprintln!("Error: steps, runs, and dimension parameters must be greater than 0");
-            return false;
+            return Err(CliError::Invalid(
+                "steps, runs, and dimension parameters must be greater than 0".to_string(),
+            ));
+        }
+
+        // 檔案標頭以 u32 儲存步數
+        if self.steps > MAX_STEPS {
+            return Err(CliError::Invalid(format!(
+                "--steps ({}) cannot exceed {MAX_STEPS} (stored as u32 in the file header)",
+                self.steps
+            )));
+        }
+
+        // 截斷數量須介於 1 與每個維度之間
+        if let Some(top_k) = self.top_k {
+            if top_k == 0 || top_k > self.dim_start {
+                return Err(CliError::Invalid(format!(
+                    "--top-k ({top_k}) must be between 1 and the smallest dimension ({})",
+                    self.dim_start
+                )));
+            }
+        }
+
+        // 每筆記錄以 u8 儲存特徵值數量，須在計算開始前拒絕過大的維度；
+        // `--top-k` 與 `--aggregate both` 只儲存部分數值，因此依實際儲存的數量檢查
+        let models = self
+            .models
+            .clone()
+            .unwrap_or_else(|| JohansenModel::all_models().to_vec());
+        for model in models {
+            let eigenvalues_per_run = match self.aggregate {
+                AggregateMode::Both => 2,
+                AggregateMode::Full => self
+                    .top_k
+                    .unwrap_or_else(|| model.eigenvalues_per_run(self.dim_end)),
+            };
+            if eigenvalues_per_run > MAX_EIGENVALUES_PER_RUN {
+                return Err(CliError::Invalid(format!(
+                    "dimension {} needs {eigenvalues_per_run} eigenvalues per run for model {}, exceeding the storage limit of {MAX_EIGENVALUES_PER_RUN}",
+                    self.dim_end,
+                    model.to_number()
+                )));
+            }
+        }
+
+        if let Some(floor) = self.eigenvalue_floor {
+            if !(floor > 0.0 && floor.is_finite()) {
+                return Err(CliError::Invalid(format!(
+                    "--eigenvalue-floor ({floor}) must be a positive number"
+                )));
+            }
+        }
+
+        // 只存聚合值的資料檔案已含有跡，跡檔案的加總則會誤將最大特徵值計入
+        if self.trace_file && self.aggregate == AggregateMode::Both {
+            return Err(CliError::Invalid(
+                "--trace-file cannot be combined with --aggregate both (the data file already stores the trace)"
+                    .to_string(),
+            ));
+        }
+
+        // 捨棄的時間步數須少於總步數，至少保留一個增量
+        if self.warmup >= self.steps {
+            return Err(CliError::Invalid(format!(
+                "--warmup ({}) must be less than --steps ({})",
+                self.warmup, self.steps
+            )));
+        }
+
+        // 倒數條件數介於 0 與 1 之間
+        if let Some(min_condition) = self.min_condition {
+            if !(min_condition > 0.0 && min_condition <= 1.0) {
+                return Err(CliError::Invalid(format!(
+                    "--min-condition ({min_condition}) must be in (0, 1]"
+                )));
+            }
+        }
+
+        // 匯入 CSV 與指定輸出檔案都須指定單一模型與單一維度
+        let single_model = self.models.as_ref().is_some_and(|models| models.len() == 1);
+        let single_target = single_model && self.dim_start == self.dim_end;
+        if self.import_csv.is_some() && !single_target {
+            return Err(CliError::Invalid(
+                "--import-csv requires a single --model and a single --dim".to_string(),
+            ));
+        }
+        if self.rerun_seed.is_some() && !single_target {
+            return Err(CliError::Invalid(
+                "--rerun-seed requires a single --model and a single --dim".to_string(),
+            ));
+        }
+        if self.no_data_dir {
+            if !single_target {
+                return Err(CliError::Invalid(
+                    "--no-data-dir requires a single --model and a single --dim".to_string(),
+                ));
+            }
+            let conflict = [
+                (self.stdout_ndjson, "--stdout-ndjson"),
+                (self.output_file.is_some(), "--output-file"),
+                (self.seeds.is_some(), "--seed-file"),
+                (self.shared_paths, "--shared-paths"),
+                (self.adaptive, "--adaptive"),
+                (self.time_budget.is_some(), "--time-budget"),
+                (self.table_out.is_some(), "--table-out"),
+                (self.validate_against.is_some(), "--validate-against"),
+                (self.config.is_some(), "--config"),
+                (self.trace_file, "--trace-file"),
+            ]
+            .into_iter()
+            .find_map(|(set, flag)| set.then_some(flag));
+            if let Some(flag) = conflict {
+                return Err(CliError::Invalid(format!(
+                    "--no-data-dir cannot be combined with {flag}"
+                )));
+            }
+        }
+        if self.export.is_some() && !single_target {
+            return Err(CliError::Invalid(
+                "--export requires a single --model and a single --dim".to_string(),
+            ));
+        }
+        let parquet_export = matches!(self.export, Some((ExportFormat::Parquet, _)));
+        if parquet_export && cfg!(not(feature = "parquet")) {
+            return Err(CliError::Invalid(
+                "--export parquet requires building with the `parquet` feature".to_string(),
+            ));
+        }
+        if self.output_file.is_some() && !single_target {
+            return Err(CliError::Invalid(
+                "--output-file requires a single --model and a single --dim".to_string(),
+            ));
+        }
+
+        // 參考表只對應一個模型；容許誤差為非負的相對誤差
+        if self.validate_against.is_some() && !single_model {
+            return Err(CliError::Invalid(
+                "--validate-against requires a single --model".to_string(),
+            ));
+        }
+        if !(self.tolerance >= 0.0 && self.tolerance.is_finite()) {
+            return Err(CliError::Invalid(format!(
+                "--tolerance ({}) must be a non-negative number",
+                self.tolerance
+            )));
+        }
+
+        // 順序統計量只在上尾分析中輸出
+        if self.order_stats > 0 && !self.tail_only {
+            return Err(CliError::Invalid(
+                "--order-stats requires --tail-only".to_string(),
+            ));
+        }
+        if self.bootstrap == Some(0) {
+            return Err(CliError::Invalid(
+                "--bootstrap must be greater than 0 resamples".to_string(),
+            ));
+        }
+        if self.bootstrap.is_some() && self.order_stats > 0 {
+            return Err(CliError::Invalid(
+                "--bootstrap cannot be combined with --order-stats".to_string(),
+            ));
+        }
+
+        // 自適應模式自行決定 seed 範圍，且最終次數只在計算時才知道
+        if !self.adaptive && (self.target_quantile.is_some() || self.target_precision.is_some()) {
+            return Err(CliError::Invalid(
+                "--target-quantile and --target-precision require --adaptive".to_string(),
+            ));
+        }
+        if self.adaptive {
+            let conflict = [
+                (self.seeds.is_some(), "--seed-file"),
+                (self.shared_paths, "--shared-paths"),
+                (self.table_out.is_some(), "--table-out"),
+                (self.validate_against.is_some(), "--validate-against"),
+                (self.time_budget.is_some(), "--time-budget"),
+            ]
+            .into_iter()
+            .find_map(|(set, flag)| set.then_some(flag));
+            if let Some(flag) = conflict {
+                return Err(CliError::Invalid(format!(
+                    "--adaptive cannot be combined with {flag}"
+                )));
+            }
+        }
+        if let Some(quantile) = self.target_quantile {
+            if !(quantile > 0.0 && quantile < 1.0) {
+                return Err(CliError::Invalid(format!(
+                    "--target-quantile ({quantile}) must be between 0 and 1"
+                )));
+            }
+        }
+        if let Some(precision) = self.target_precision {
+            if !(precision > 0.0 && precision.is_finite()) {
+                return Err(CliError::Invalid(format!(
+                    "--target-precision ({precision}) must be a positive number"
+                )));
+            }
+        }
+
+        // 批次工作的模型、維度與路徑由配置檔案決定
+        if self.config.is_some() {
+            if cfg!(not(feature = "serde")) {
+                return Err(CliError::Invalid(
+                    "--config requires building with the `serde` feature".to_string(),
+                ));
+            }
+            let conflict = [
+                (self.seeds.is_some(), "--seed-file"),
+                (self.output_file.is_some(), "--output-file"),
+                (self.import_csv.is_some(), "--import-csv"),
+                (self.shared_paths, "--shared-paths"),
+                (self.table_out.is_some(), "--table-out"),
+                (self.validate_against.is_some(), "--validate-against"),
+                (self.adaptive, "--adaptive"),
+                (self.time_budget.is_some(), "--time-budget"),
+            ]
+            .into_iter()
+            .find_map(|(set, flag)| set.then_some(flag));
+            if let Some(flag) = conflict {
+                return Err(CliError::Invalid(format!(
+                    "--config cannot be combined with {flag}"
+                )));
+            }
+        }
+
+        // 檢查點間隔與時間預算須為正數
+        if self.checkpoint_every == Some(0) {
+            return Err(CliError::Invalid(
+                "--checkpoint-every must be greater than 0 seconds".to_string(),
+            ));
+        }
+        if self.time_budget == Some(0) {
+            return Err(CliError::Invalid(
+                "--time-budget must be greater than 0 seconds".to_string(),
+            ));
+        }
+        if let Some(bytes) = self.write_buffer.filter(|bytes| {
+            !(MIN_WRITE_BUFFER_CAPACITY..=MAX_WRITE_BUFFER_CAPACITY).contains(bytes)
+        }) {
+            return Err(CliError::Invalid(format!(
+                "--write-buffer ({bytes}) must be between {MIN_WRITE_BUFFER_CAPACITY} and {MAX_WRITE_BUFFER_CAPACITY} bytes"
+            )));
+        }
+
+        // 單執行緒模式與指定多個線程互斥
+        if self.single_thread && self.num_threads.is_some_and(|threads| threads != 1) {
+            return Err(CliError::Invalid(
+                "--single-thread cannot be combined with --threads greater than 1".to_string(),
+            ));
         }
 
         // 檢查線程數量
         if let Some(threads) = self.num_threads {
-            if !self.validate_thread_count(threads) {
-                return false;
-            }
+            self.validate_thread_count(threads)?;
         }
 
-        true
+        Ok(())
     }
 
     /// 驗證線程數量
-    fn validate_thread_count(&self, threads: usize) -> bool {
+    fn validate_thread_count(&self, threads: usize) -> Result<(), CliError> {
         let available_threads = num_cpus::get();
 
         if threads > available_threads {
@@ -238,8 +1009,9 @@ impl CliArgs {
 
             // 如果超過太多，直接拒絕
             if threads > available_threads * 2 {
-                eprintln!("Error: excessive thread count rejected to avoid system overload.");
-                return false;
+                return Err(CliError::Invalid(
+                    "excessive thread count rejected to avoid system overload.".to_string(),
+                ));
             }
 
             // 詢問是否繼續
@@ -253,12 +1025,11 @@ impl CliArgs {
             let input = input.trim().to_lowercase();
 
             if input != "y" && input != "yes" {
-                println!("Execution cancelled.");
-                return false;
+                return Err(CliError::Cancelled);
             }
         }
 
-        true
+        Ok(())
     }
 
     /// 顯示版本信息
@@ -279,17 +1050,159 @@ impl CliArgs {
             "  --threads <int>      number of threads for parallel computation (default: {} logical cores)",
             num_cpus::get()
         );
-        println!("  --steps <int>        number of simulation steps (default: 10,000)");
-        println!("  --runs <int>         number of runs per model (default: 10,000,000)");
+        println!(
+            "  --steps <int>        number of simulation steps (default: 10,000; at most 4,294,967,295)"
+        );
+        println!(
+            "  --min-steps-warning <int> warn when --steps is below this value (default: 1,000; 0 disables)"
+        );
+        println!(
+            "  --runs <int>         number of runs per model, e.g. 10,000,000, 1e7 or 10M (default: 10,000,000)"
+        );
         println!("  --dim-start <int>    starting matrix dimension (default: 1)");
         println!("  --dim-end <int>      ending matrix dimension (default: 12)");
         println!(
             "  --dim <int>          run a single dimension (sets start and end to the same value)"
         );
         println!(
-            "  --model <list>       comma separated list of model numbers to compute (default: 0,1,2,3,4)"
+            "  --dim-list <list>    comma separated list of dimensions to compute (e.g., 2,5,10)"
+        );
+        let all_models = JohansenModel::all_model_numbers().map(|n| n.to_string());
+        println!(
+            "  --model <list>       comma separated list of model numbers to compute (default: {})",
+            all_models.join(",")
+        );
+        println!(
+            "  --exclude-models <list> comma separated list of model numbers to skip (cannot be combined with --model)"
+        );
+        println!("  --sort-order <order> order of stored eigenvalues: asc or desc (default: desc)");
+        println!(
+            "  --master-seed <int>  mix this seed into every run's RNG seed for an independent dataset"
+        );
+        println!(
+            "  --seed-hashing <method> derive RNG seeds with splitmix or legacy (plain cast, for resuming older files) (default: splitmix)"
+        );
+        println!(
+            "  --top-k <int>        store only the K largest eigenvalues (trace becomes their sum)"
+        );
+        println!(
+            "  --eigenvalue-floor <eps> store eigenvalues with magnitude below eps as 0 (zeroed, not removed)"
+        );
+        println!(
+            "  --aggregate <mode>   store all eigenvalues (full) or only each run's trace and max (both) (default: full)"
+        );
+        println!(
+            "  --warmup <int>       discard the first W steps of each Brownian path (must be less than --steps)"
+        );
+        println!(
+            "  --timestamps         store the computation time (epoch minutes) with each record"
+        );
+        println!(
+            "  --trace-file         also append each run's trace (eigenvalue sum) to a .trace file next to the .dat"
+        );
+        println!(
+            "  --min-condition <f>  warn about seeds whose solve is ill-conditioned (reciprocal condition below f)"
+        );
+        println!(
+            "  --diagnose-complex   warn about seeds whose solve yields eigenvalues with non-negligible imaginary parts"
+        );
+        println!(
+            "  --single-thread      compute seeds sequentially without rayon (deterministic order)"
+        );
+        println!(
+            "  --shared-paths       generate each seed's Brownian path once and solve all selected models from it"
+        );
+        println!(
+            "  --checkpoint-every <secs> also flush written data every <secs> seconds (bounds loss on crash)"
+        );
+        println!(
+            "  --write-buffer <bytes> size of the data file write buffer (default 2 MiB, minimum 4096, maximum 256 MiB)"
+        );
+        println!(
+            "  --time-budget <secs> stop starting new seeds after <secs> seconds; rerun to resume"
+        );
+        println!(
+            "  --ignore-space       start even if the projected output exceeds free disk space"
+        );
+        println!("  --seed-file <path>   compute only the seeds listed in the file (one per line)");
+        println!(
+            "  --config <path>      run the jobs in a JSON array of {{model, dim, steps, num_runs}} objects (requires the serde feature)"
+        );
+        println!(
+            "  --output-file <path> write to this .dat file instead of data/ (single --model and --dim only)"
+        );
+        println!(
+            "  --validate-against <path> after simulating, compare trace critical values with a dim,percentile,value CSV and exit 1 on mismatch (single --model only)"
+        );
+        println!(
+            "  --tolerance <f>      relative tolerance for --validate-against (default: 0.01)"
+        );
+        println!(
+            "  --table-out <path>   after simulating, write model,dim,percentile,trace_value,maxeig_value CSV for all models and dims"
+        );
+        println!(
+            "  --adaptive           stop each model once the target quantile is precise enough (--runs is the upper bound)"
+        );
+        println!(
+            "  --target-quantile <f> quantile of the trace statistic checked by --adaptive (default: 0.95)"
+        );
+        println!(
+            "  --target-precision <f> relative 95% CI half-width at which --adaptive stops (default: 0.001)"
+        );
+        println!(
+            "  --tail-only          report only upper-tail percentiles (90th to 99.9th) after each model"
+        );
+        println!(
+            "  --order-stats <int>  with --tail-only, also list the K largest values of each statistic"
+        );
+        println!(
+            "  --bootstrap <int>    print a 95% bootstrap CI from <int> resamples after each reported percentile"
         );
         println!("  --quiet              suppress progress output");
+        println!(
+            "  --continue-on-error  log a failed model run and continue with the rest (exit 1 at the end)"
+        );
+        println!("  --only-missing-models skip models whose data is already complete");
+        println!(
+            "  --stdout-ndjson      print each run as a JSON line to stdout (implies --quiet)"
+        );
+        println!(
+            "  --no-data-dir        compute --model and --dim and write the binary .dat stream to stdout instead of data/ (implies --quiet)"
+        );
+        println!(
+            "  --count              print completed runs for each model and dimension, then exit"
+        );
+        println!(
+            "  --dry-run            print the total runs and estimated output size of the batch, then exit"
+        );
+        println!(
+            "  --summary-only       estimate trace/maxeig percentiles of existing files with bounded memory, then exit"
+        );
+        println!(
+            "  --analyze-only       recompute trace/maxeig percentiles of existing complete files without simulating, then exit"
+        );
+        println!(
+            "  --prune-out-of-range rewrite existing files without records whose seed is outside 1..=runs, then exit"
+        );
+        println!(
+            "  --repair             fix existing files (partial records, stray EOF markers, duplicate and out-of-range seeds), then exit"
+        );
+        println!(
+            "  --rerun-seed <int>   recompute one seed of --model and --dim and print its eigenvalues, trace and max, then exit"
+        );
+        println!("  --header <path>      print the metadata of a .dat file, then exit");
+        println!(
+            "  --import-csv <path>  convert a seed,eigenvalues CSV into a .dat file for --model, --dim and --steps"
+        );
+        println!(
+            "  --export <fmt> <path> write the records of --model and --dim as csv or parquet (parquet needs the parquet feature), then exit"
+        );
+        println!(
+            "  --profile            report per-stage timing (Brownian motion, outer products, eigen solve)"
+        );
+        println!(
+            "  --benchmark          time up to 1,000 in-memory runs per model and dimension, report runs/sec, then exit"
+        );
         println!("  -h, --help           show this help message");
         println!("  -v, --version        show version information");
         println!();
@@ -308,18 +1221,31 @@ impl CliArgs {
     }
 
     /// 配置 Rayon 線程池
+    ///
+    /// 工作執行緒一律命名為 `johansen-worker-{i}`，便於在效能剖析工具中辨識。
     pub fn configure_rayon(&self) {
-        if let Some(threads) = self.num_threads {
+        let builder =
+            rayon::ThreadPoolBuilder::new().thread_name(|i| format!("johansen-worker-{i}"));
+        if self.single_thread {
+            // 矩陣運算中的其他並行迭代也限制為單一執行緒
+            conditional_println!(self.quiet, "Using single-threaded sequential computation");
+            builder
+                .num_threads(1)
+                .build_global()
+                .expect("Failed to build thread pool");
+        } else if let Some(threads) = self.num_threads {
             conditional_println!(
                 self.quiet,
                 "Using {} threads for parallel computation",
                 threads
             );
-            rayon::ThreadPoolBuilder::new()
+            builder
                 .num_threads(threads)
                 .build_global()
                 .expect("Failed to build thread pool");
         } else {
+            // 預設執行緒數量；須在查詢執行緒數量之前建立，否則會先初始化未命名的全域線程池
+            builder.build_global().expect("Failed to build thread pool");
             conditional_println!(
                 self.quiet,
                 "Using default thread count: {}",
@@ -328,3 +1254,37 @@ impl CliArgs {
         }
     }
 }
+
+/// 解析非負整數，支援 `1,000,000`、`1e7`、`2.5e6` 以及 `5k`、`10M`、`1G` 等寫法
+///
+/// 科學記號與後綴的結果須為整數且不超過 `usize`；錯誤時返回原因說明。
+fn parse_count(input: &str) -> Result<usize, String> {
+    // 移除逗號後再解析
+    let cleaned = input.replace(',', "");
+    let cleaned = cleaned.trim();
+    if let Ok(value) = cleaned.parse::<usize>() {
+        return Ok(value);
+    }
+
+    let (number, multiplier) = match cleaned.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&cleaned[..i], 1e3),
+        Some((i, 'M')) => (&cleaned[..i], 1e6),
+        Some((i, 'G')) => (&cleaned[..i], 1e9),
+        _ => (cleaned, 1.0),
+    };
+    let value = number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .map(|value| value * multiplier)
+        .ok_or_else(|| "must be a positive integer (e.g., 1,000,000, 1e6 or 1M)".to_string())?;
+
+    if value.fract() != 0.0 {
+        return Err(format!("{input} is not a whole number"));
+    }
+    // usize::MAX 轉為 f64 會進位為 2^64，因此以 >= 判斷溢位
+    if value >= usize::MAX as f64 {
+        return Err(format!("{input} exceeds the maximum of {}", usize::MAX));
+    }
+    Ok(value as usize)
+}
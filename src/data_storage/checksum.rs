@@ -0,0 +1,74 @@
+//! CRC32 校驗碼 - 偵測資料區段的靜默損壞
+//!
+//! 提供校驗碼不符的錯誤類型，以及在讀取時同步計算 CRC32 的讀取器包裝
+
+use std::io::Read;
+
+/// CRC32 校驗失敗錯誤
+///
+/// 以 `std::io::Error`（`ErrorKind::InvalidData`）包裝回傳，
+/// 可透過 `get_ref()` 與 `downcast_ref::<ChecksumError>()` 取得詳細資訊。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumError {
+    /// 檔案中儲存的 CRC32
+    pub stored: u32,
+    /// 依實際資料計算出的 CRC32
+    pub computed: u32,
+}
+
+impl std::fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Checksum verification failed: stored CRC32 {:#010x}, computed {:#010x} (data may be corrupted)",
+            self.stored, self.computed
+        )
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+impl From<ChecksumError> for std::io::Error {
+    fn from(err: ChecksumError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// 判斷 IO 錯誤是否為 CRC32 校驗失敗
+pub fn is_checksum_error(err: &std::io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<ChecksumError>())
+}
+
+/// 在讀取的同時計算 CRC32 的讀取器包裝
+pub struct ChecksumReader<R> {
+    inner: R,
+    hasher: crc32fast::Hasher,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    /// 回傳目前為止讀取資料的 CRC32
+    pub fn checksum(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+
+    /// 取出內部的 CRC32 計算器，以便繼續累加後續資料
+    pub fn into_hasher(self) -> crc32fast::Hasher {
+        self.hasher
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
@@ -6,6 +6,14 @@ pub const FLUSH_INTERVAL: usize = DEFAULT_BATCH_SIZE;
 
 /// write buffer capacity in bytes for AppendOnlyWriter
 pub const WRITE_BUFFER_CAPACITY: usize = 2 * 1024 * 1024; // 2 MiB
+/// `--write-buffer` 允許的最小寫入緩衝區（須容納單筆記錄，重試寫入才不會放入部分資料）
+pub const MIN_WRITE_BUFFER_CAPACITY: usize = 4 * 1024; // 4 KiB
+/// `--write-buffer` 允許的最大寫入緩衝區（緩衝區在開啟檔案時一次配置）
+pub const MAX_WRITE_BUFFER_CAPACITY: usize = 256 * 1024 * 1024; // 256 MiB
+
+/// 暫時性寫入錯誤的重試配置
+pub const IO_RETRY_ATTEMPTS: u32 = 4; // 首次嘗試之後最多重試的次數
+pub const IO_RETRY_BASE_DELAY_MS: u64 = 10; // 第一次重試前的等待時間，之後每次加倍
 
 /// 讀取緩衝區配置
 pub const MIN_READ_BUFFER_SIZE: usize = 64 * 1024; // 64 KB - 最小讀取緩衝區
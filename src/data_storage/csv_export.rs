@@ -0,0 +1,107 @@
+//! CSV 匯出 - 將 `.dat` 資料寫成 `seed,eigenvalue_1,eigenvalue_2,...` 格式
+//!
+//! 欄位名稱前綴、定點或科學記號以及小數位數可透過 `CsvExportOptions` 調整，
+//! 以符合下游工具預期的格式。輸出可再以 `csv_import` 匯入。
+//! 彙總檔案（`--aggregate both`）的記錄只有跡與最大特徵值，欄位為 `seed,trace,max`。
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 特徵值的數字格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// 定點小數（`{:.N}`）
+    #[default]
+    Fixed,
+    /// 科學記號（`{:.Ne}`）
+    #[allow(dead_code)]
+    Scientific,
+}
+
+/// CSV 匯出選項
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvExportOptions {
+    /// 特徵值欄位名稱前綴，欄位依序為 `{prefix}1`、`{prefix}2`...
+    pub column_prefix: String,
+    pub format: NumberFormat,
+    /// 小數位數（科學記號時為尾數的小數位數）
+    pub precision: usize,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            column_prefix: "eigenvalue_".to_string(),
+            format: NumberFormat::Fixed,
+            precision: 12,
+        }
+    }
+}
+
+impl CsvExportOptions {
+    /// 依選項格式化單一特徵值
+    #[allow(dead_code)]
+    pub fn format_value(&self, value: f64) -> String {
+        let precision = self.precision;
+        match self.format {
+            NumberFormat::Fixed => format!("{value:.precision$}"),
+            NumberFormat::Scientific => format!("{value:.precision$e}"),
+        }
+    }
+}
+
+/// 彙總檔案每筆記錄的欄位名稱，依序為跡與最大特徵值
+pub const AGGREGATE_COLUMNS: [&str; 2] = ["trace", "max"];
+
+/// 數值欄位名稱：彙總檔案為 `AGGREGATE_COLUMNS`，其他為 `{prefix}1`...`{prefix}{count}`
+pub fn value_column_names(prefix: &str, count: usize, aggregates: bool) -> Vec<String> {
+    if aggregates {
+        AGGREGATE_COLUMNS.map(str::to_string).to_vec()
+    } else {
+        (1..=count).map(|i| format!("{prefix}{i}")).collect()
+    }
+}
+
+/// 以 CSV 寫出特徵值資料，欄位數量取自第一筆記錄
+///
+/// `aggregates` 為資料是否來自彙總檔案（記錄為 `[跡, 最大特徵值]`），此時不使用欄位名稱前綴。
+#[allow(dead_code)]
+pub fn write_eigenvalues_csv(
+    data: &[(u32, Vec<f64>)],
+    aggregates: bool,
+    options: &CsvExportOptions,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let columns = data.first().map_or(0, |(_, eigenvalues)| eigenvalues.len());
+    let mut header = vec!["seed".to_string()];
+    header.extend(value_column_names(
+        &options.column_prefix,
+        columns,
+        aggregates,
+    ));
+    writeln!(out, "{}", header.join(","))?;
+
+    for (seed, eigenvalues) in data {
+        let values: Vec<String> = eigenvalues
+            .iter()
+            .map(|&value| options.format_value(value))
+            .collect();
+        writeln!(out, "{seed},{}", values.join(","))?;
+    }
+    Ok(())
+}
+
+/// 將特徵值資料寫入 CSV 檔案（已存在時覆寫）
+#[allow(dead_code)]
+pub fn export_csv<P: AsRef<Path>>(
+    path: P,
+    data: &[(u32, Vec<f64>)],
+    aggregates: bool,
+    options: &CsvExportOptions,
+) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_eigenvalues_csv(data, aggregates, options, &mut out)?;
+    out.flush()
+}
@@ -0,0 +1,115 @@
+//! CSV 匯入 - 將外部產生的特徵值資料轉換為 `.dat` 格式
+//!
+//! CSV 每列為 `seed,eigenvalue_1,eigenvalue_2,...`，第一列若無法解析為 seed 則視為標題列。
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::writer::AppendOnlyWriter;
+
+/// 讀取帶有 seed 欄位的特徵值 CSV
+///
+/// 所有資料列的特徵值數量必須相同，空白列會被略過。
+pub fn read_eigenvalues_csv_with_seed<P: AsRef<Path>>(
+    path: P,
+) -> std::io::Result<Vec<(u32, Vec<f64>)>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut data: Vec<(u32, Vec<f64>)> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::trim);
+        let seed_field = fields.next().unwrap_or_default();
+        let seed = match seed_field.parse::<u32>() {
+            Ok(seed) => seed,
+            Err(_) if line_number == 1 => continue, // 標題列
+            Err(_) => {
+                return Err(invalid_row(
+                    line_number,
+                    format!("invalid seed '{seed_field}'"),
+                ));
+            }
+        };
+
+        let eigenvalues = fields
+            .map(|field| {
+                field
+                    .parse::<f64>()
+                    .map_err(|_| invalid_row(line_number, format!("invalid eigenvalue '{field}'")))
+            })
+            .collect::<std::io::Result<Vec<f64>>>()?;
+
+        if eigenvalues.is_empty() {
+            return Err(invalid_row(line_number, "no eigenvalues".to_string()));
+        }
+        if let Some((_, first)) = data.first() {
+            if first.len() != eigenvalues.len() {
+                return Err(invalid_row(
+                    line_number,
+                    format!(
+                        "expected {} eigenvalues, found {}",
+                        first.len(),
+                        eigenvalues.len()
+                    ),
+                ));
+            }
+        }
+
+        data.push((seed, eigenvalues));
+    }
+
+    Ok(data)
+}
+
+/// 將 CSV 資料寫入新的 `.dat` 檔案並寫入結束標記，返回匯入的記錄數量
+///
+/// 目標檔案已存在時返回錯誤，避免與既有資料混合；
+/// 每列的特徵值數量與 `num_eigenvalues` 不符時返回錯誤，不建立檔案。
+pub fn import_csv_to_dat<P: AsRef<Path>, Q: AsRef<Path>>(
+    csv_path: P,
+    dat_path: Q,
+    header: super::file_format::FileHeader,
+    num_eigenvalues: usize,
+) -> std::io::Result<usize> {
+    let dat_path = dat_path.as_ref();
+    if dat_path.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dat_path.display()),
+        ));
+    }
+
+    let data = read_eigenvalues_csv_with_seed(csv_path)?;
+    if data.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "CSV contains no data rows",
+        ));
+    }
+    // 所有資料列的數量已確認一致，只需檢查第一列
+    let found = data[0].1.len();
+    if found != num_eigenvalues {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("CSV rows have {found} eigenvalues, expected {num_eigenvalues}"),
+        ));
+    }
+
+    let mut writer = AppendOnlyWriter::with_header(dat_path, None, header, None, true)?;
+    let count = writer.append_all(data)?;
+    writer.finish()?;
+    Ok(count)
+}
+
+fn invalid_row(line_number: usize, reason: String) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("CSV line {line_number}: {reason}"),
+    )
+}
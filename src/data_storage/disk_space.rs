@@ -0,0 +1,127 @@
+//! 磁碟空間預檢 - 長時間計算開始前確認輸出檔案放得下
+//!
+//! 以 `calculate_expected_file_size` 估計每個資料檔案完成時的大小，扣除既有檔案已佔用的部分，
+//! 依所在目錄加總後與該目錄的可用空間比較，避免計算數天後才因磁碟已滿而中斷。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::EigenvalueSimulation;
+use super::file_format::{TIMESTAMP_SIZE, calculate_expected_file_size, extension_size};
+use crate::display_utils::format_bytes;
+
+/// 批次計算的規劃摘要（`--dry-run`）
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchPlan {
+    /// 資料檔案數量（模型數 × 維度數）
+    pub files: usize,
+    /// 所有資料檔案合計的模擬次數
+    pub total_runs: u64,
+    /// 所有資料檔案完成時的預估總大小（bytes）
+    pub total_bytes: u64,
+}
+
+/// 規劃批次中每個配置各計算 `num_runs` 次
+///
+/// 每個配置為一個資料檔案；預估大小為各檔案完成時的大小（見 `projected_file_size`），
+/// 不扣除既有檔案已寫入的部分。配置無效時返回錯誤。
+#[allow(dead_code)]
+pub fn plan_batch(simulations: &[EigenvalueSimulation]) -> std::io::Result<BatchPlan> {
+    let mut plan = BatchPlan {
+        files: simulations.len(),
+        total_runs: 0,
+        total_bytes: 0,
+    };
+    for simulation in simulations {
+        plan.total_runs += simulation.num_runs as u64;
+        plan.total_bytes += simulation.projected_file_size()?;
+    }
+    Ok(plan)
+}
+
+impl EigenvalueSimulation {
+    /// 資料檔案完成時的預估大小（依 `num_runs` 估計）
+    ///
+    /// 計入每筆記錄實際儲存的數值數量（`top_k` 與 `aggregate`）、標頭延伸欄位與時間戳記。
+    pub fn projected_file_size(&self) -> std::io::Result<u64> {
+        let header = self.file_header()?;
+        let timestamp_bytes = if header.has_timestamps() {
+            TIMESTAMP_SIZE * self.num_runs as u64
+        } else {
+            0
+        };
+        Ok(
+            calculate_expected_file_size(self.num_runs, self.num_eigenvalues())
+                + extension_size(header.flags)
+                + timestamp_bytes,
+        )
+    }
+
+    /// 資料檔案完成時尚需寫入的位元組數（`projected_file_size` 扣除既有檔案的大小）
+    pub fn projected_remaining_bytes(&self) -> std::io::Result<u64> {
+        let expected = self.projected_file_size()?;
+        let existing = match std::fs::metadata(self.try_get_filename(self.model)?) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        Ok(expected.saturating_sub(existing))
+    }
+}
+
+/// 路徑所在檔案系統的可用空間；路徑尚不存在時查詢最近的既有上層目錄
+#[allow(dead_code)]
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+    let mut dir = path;
+    while !dir.exists() {
+        match dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => dir = parent,
+            _ => {
+                dir = Path::new(".");
+                break;
+            }
+        }
+    }
+    fs2::available_space(dir)
+}
+
+/// 比較預估寫入量與可用空間，不足時返回說明兩者大小的錯誤
+pub fn check_free_space(dir: &Path, required: u64, available: u64) -> std::io::Result<()> {
+    if required <= available {
+        return Ok(());
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::StorageFull,
+        format!(
+            "projected output needs {} in {} but only {} is free (pass --ignore-space to run anyway)",
+            format_bytes(required),
+            dir.display(),
+            format_bytes(available)
+        ),
+    ))
+}
+
+/// 依資料檔案所在目錄加總預估寫入量，並逐一檢查可用空間
+///
+/// `available` 通常為 `available_space`，測試時可替換為固定值。
+#[allow(dead_code)]
+pub fn check_projected_space(
+    simulations: &[EigenvalueSimulation],
+    available: impl Fn(&Path) -> std::io::Result<u64>,
+) -> std::io::Result<()> {
+    let mut required: BTreeMap<PathBuf, u64> = BTreeMap::new();
+    for simulation in simulations {
+        let filename = PathBuf::from(simulation.try_get_filename(simulation.model)?);
+        let dir = filename
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        *required.entry(dir).or_default() += simulation.projected_remaining_bytes()?;
+    }
+
+    for (dir, bytes) in required {
+        check_free_space(&dir, bytes, available(&dir)?)?;
+    }
+    Ok(())
+}
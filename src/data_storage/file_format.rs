@@ -5,22 +5,461 @@
 use super::config::{MAX_READ_BUFFER_SIZE, MIN_READ_BUFFER_SIZE};
 
 /// 檔案格式常數
-pub const MAGIC_HEADER: &[u8] = b"EIGENVALS_V6"; // 12 bytes
+pub const MAGIC_HEADER: &[u8] = b"EIGENVALS_V7"; // 12 bytes
+pub const MAGIC_HEADER_V6: &[u8] = b"EIGENVALS_V6"; // 舊版格式，沒有 flags 欄位
+pub const MAGIC_HEADER_V5: &[u8] = b"EIGENVALS_V5"; // 更舊的寫入路徑所用，結構與 V6 相同
 pub const EOF_MARKER: &[u8] = b"EOF_MARK"; // 8 bytes
 
+/// 標頭以 u32 儲存時間步數，可模擬的最大步數
+pub const MAX_STEPS: usize = u32::MAX as usize;
+
+/// 每筆記錄以 u8 儲存特徵值數量，每次模擬可儲存的最大特徵值數量
+pub const MAX_EIGENVALUES_PER_RUN: usize = u8::MAX as usize;
+
+/// 檔案末尾元數據大小：eof_marker(8) + total_count(8) + eigenvalues_per_run(1)
+pub const METADATA_SIZE: u64 = 8 + 8 + 1;
+
+/// 標頭旗標：資料區段之後、EOF 標記之前存有 CRC32 校驗碼
+pub const FLAG_CRC32: u16 = 0x0001;
+/// CRC32 校驗碼大小（u32）
+pub const CRC32_SIZE: u64 = 4;
+/// 標頭旗標：固定標頭之後存有 RNG 子串流編號（u32）
+pub const FLAG_STREAM_ID: u16 = 0x0002;
+/// 標頭旗標：每筆記錄只保留最大的 K 個特徵值，K 存於延伸欄位（u8）
+pub const FLAG_TOP_K: u16 = 0x0004;
+/// 標頭旗標：布朗運動路徑捨棄了前 W 個時間步（burn-in），W 存於延伸欄位（u32）
+pub const FLAG_WARMUP: u16 = 0x0008;
+/// 標頭旗標：每筆記錄在 seed 之後存有計算時間（u32，Unix epoch 分鐘數）
+pub const FLAG_TIMESTAMPS: u16 = 0x0010;
+/// 每筆記錄時間戳記的大小（u32）
+pub const TIMESTAMP_SIZE: u64 = 4;
+/// 標頭旗標：RNG 種子混合了主種子，主種子存於延伸欄位（u64）
+pub const FLAG_MASTER_SEED: u16 = 0x0020;
+/// 標頭旗標：絕對值低於下限的特徵值已設為 0，下限存於延伸欄位（f64）
+pub const FLAG_EIGENVALUE_FLOOR: u16 = 0x0040;
+/// 標頭旗標：子串流 0 的 seed 先經 SplitMix64 混合才作為 RNG 種子（沒有延伸欄位）
+///
+/// 未設定時為直接以 seed 作為種子（`--seed-hashing legacy`）；非 0 子串流兩種設定相同，不設定此旗標。
+pub const FLAG_MIXED_SEEDS: u16 = 0x0080;
+/// 標頭旗標：每筆記錄只存 `[跡, 最大特徵值]` 兩個值，而非全部特徵值（沒有延伸欄位）
+pub const FLAG_AGGREGATES: u16 = 0x0100;
+
+/// 此版本能解讀的所有標頭旗標
+///
+/// 新的選用區段以新的旗標位元加入。讀取器遇到不認得的位元時無法得知其延伸欄位的長度，
+/// 因此以 `ErrorKind::Unsupported` 拒絕該檔案，而不是誤讀資料或在其後續寫。
+pub const KNOWN_FLAGS: u16 = FLAG_CRC32
+    | FLAG_STREAM_ID
+    | FLAG_TOP_K
+    | FLAG_WARMUP
+    | FLAG_TIMESTAMPS
+    | FLAG_MASTER_SEED
+    | FLAG_EIGENVALUE_FLOOR
+    | FLAG_MIXED_SEEDS
+    | FLAG_AGGREGATES;
+
+/// 新檔案預設啟用的標頭旗標
+pub const DEFAULT_FLAGS: u16 = FLAG_CRC32;
+
+/// 檔案與預期的 seed 混合方式（`FLAG_MIXED_SEEDS`）不同
+///
+/// 兩種設定使用相同檔名，既有檔案的資料仍然有效（例如由先前版本以 legacy 種子產生），
+/// 因此與其他參數不符不同，不可刪除後重新計算，須以 `--seed-hashing` 指定相符的設定。
+/// 以 `std::io::Error`（`ErrorKind::InvalidData`）包裝回傳，見 `is_seed_hashing_mismatch`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedHashingMismatch {
+    /// 檔案是否設定 `FLAG_MIXED_SEEDS`
+    pub file_mixed: bool,
+    /// 預期是否設定 `FLAG_MIXED_SEEDS`
+    pub expected_mixed: bool,
+}
+
+impl std::fmt::Display for SeedHashingMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |mixed: bool| if mixed { "splitmix" } else { "legacy" };
+        write!(
+            f,
+            "Seed hashing mismatch: file uses {} seeds, expected {} (pass --seed-hashing {} to use this file)",
+            describe(self.file_mixed),
+            describe(self.expected_mixed),
+            describe(self.file_mixed)
+        )
+    }
+}
+
+impl std::error::Error for SeedHashingMismatch {}
+
+impl From<SeedHashingMismatch> for std::io::Error {
+    fn from(err: SeedHashingMismatch) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// 判斷 IO 錯誤是否為 seed 混合方式不符
+#[allow(dead_code)]
+pub fn is_seed_hashing_mismatch(err: &std::io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<SeedHashingMismatch>())
+}
+
+/// 檔案標頭的模擬參數與預期不符（seed 混合方式除外，見 `SeedHashingMismatch`）
+///
+/// 這類參數都會反映在預設檔名中，因此不符的檔案是過時或不相容的資料。
+/// 以 `std::io::Error`（`ErrorKind::InvalidData`）包裝回傳，見 `is_header_mismatch`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderMismatch {
+    /// 不符的參數與兩邊的值
+    pub message: String,
+}
+
+impl std::fmt::Display for HeaderMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for HeaderMismatch {}
+
+impl From<HeaderMismatch> for std::io::Error {
+    fn from(err: HeaderMismatch) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// 判斷 IO 錯誤是否為標頭參數不符（`HeaderMismatch`）
+pub fn is_header_mismatch(err: &std::io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<HeaderMismatch>())
+}
+
+/// 檔案標頭資訊
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileHeader {
+    /// 標頭旗標（V5/V6 檔案固定為 0）
+    pub flags: u16,
+    pub model: u8,
+    pub dim: u8,
+    pub steps: u32,
+    /// RNG 子串流編號（未設定 `FLAG_STREAM_ID` 時為 0）
+    pub stream_id: u32,
+    /// 每筆記錄保留的特徵值數量（未設定 `FLAG_TOP_K` 時為 0，代表未截斷）
+    pub top_k: u8,
+    /// 捨棄的起始時間步數（未設定 `FLAG_WARMUP` 時為 0）；實際使用的步數為 `steps - warmup`
+    pub warmup: u32,
+    /// 與 seed 混合的主種子（未設定 `FLAG_MASTER_SEED` 時為 `None`）
+    pub master_seed: Option<u64>,
+    /// 特徵值下限（未設定 `FLAG_EIGENVALUE_FLOOR` 時為 `None`）
+    pub eigenvalue_floor: Option<f64>,
+    /// 資料區段起始位置（即標頭長度）
+    pub data_offset: u64,
+}
+
+impl FileHeader {
+    /// 建立新檔案使用的 V7 標頭
+    pub fn new(flags: u16, model: u8, dim: u8, steps: u32) -> Self {
+        Self {
+            flags,
+            model,
+            dim,
+            steps,
+            stream_id: 0,
+            top_k: 0,
+            warmup: 0,
+            master_seed: None,
+            eigenvalue_floor: None,
+            data_offset: header_size(),
+        }
+        .with_extension_offset()
+    }
+
+    /// 設定 RNG 子串流編號（0 為預設串流，不寫入標頭）
+    pub fn with_stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = stream_id;
+        if stream_id == 0 {
+            self.flags &= !FLAG_STREAM_ID;
+        } else {
+            self.flags |= FLAG_STREAM_ID;
+        }
+        self.with_extension_offset()
+    }
+
+    /// 設定每筆記錄保留的特徵值數量（0 代表未截斷，不寫入標頭）
+    pub fn with_top_k(mut self, top_k: u8) -> Self {
+        self.top_k = top_k;
+        if top_k == 0 {
+            self.flags &= !FLAG_TOP_K;
+        } else {
+            self.flags |= FLAG_TOP_K;
+        }
+        self.with_extension_offset()
+    }
+
+    /// 設定捨棄的起始時間步數（0 代表不捨棄，不寫入標頭）
+    pub fn with_warmup(mut self, warmup: u32) -> Self {
+        self.warmup = warmup;
+        if warmup == 0 {
+            self.flags &= !FLAG_WARMUP;
+        } else {
+            self.flags |= FLAG_WARMUP;
+        }
+        self.with_extension_offset()
+    }
+
+    /// 設定與 seed 混合的主種子（`None` 不寫入標頭）
+    pub fn with_master_seed(mut self, master_seed: Option<u64>) -> Self {
+        self.master_seed = master_seed;
+        if master_seed.is_some() {
+            self.flags |= FLAG_MASTER_SEED;
+        } else {
+            self.flags &= !FLAG_MASTER_SEED;
+        }
+        self.with_extension_offset()
+    }
+
+    /// 設定特徵值下限（`None` 不寫入標頭）
+    pub fn with_eigenvalue_floor(mut self, eigenvalue_floor: Option<f64>) -> Self {
+        self.eigenvalue_floor = eigenvalue_floor;
+        if eigenvalue_floor.is_some() {
+            self.flags |= FLAG_EIGENVALUE_FLOOR;
+        } else {
+            self.flags &= !FLAG_EIGENVALUE_FLOOR;
+        }
+        self.with_extension_offset()
+    }
+
+    /// 設定是否在每筆記錄中存放計算時間（不影響標頭延伸欄位）
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        if timestamps {
+            self.flags |= FLAG_TIMESTAMPS;
+        } else {
+            self.flags &= !FLAG_TIMESTAMPS;
+        }
+        self
+    }
+
+    /// 設定子串流 0 的 seed 是否經 SplitMix64 混合（不影響標頭延伸欄位）
+    pub fn with_mixed_seeds(mut self, mixed_seeds: bool) -> Self {
+        if mixed_seeds {
+            self.flags |= FLAG_MIXED_SEEDS;
+        } else {
+            self.flags &= !FLAG_MIXED_SEEDS;
+        }
+        self
+    }
+
+    /// 設定每筆記錄是否只存 `[跡, 最大特徵值]`（不影響標頭延伸欄位）
+    pub fn with_aggregates(mut self, aggregates: bool) -> Self {
+        if aggregates {
+            self.flags |= FLAG_AGGREGATES;
+        } else {
+            self.flags &= !FLAG_AGGREGATES;
+        }
+        self
+    }
+
+    /// 依旗標重新計算資料區段起始位置
+    fn with_extension_offset(mut self) -> Self {
+        self.data_offset = header_size() + extension_size(self.flags);
+        self
+    }
+
+    /// 檢查檔案標頭的模擬參數是否與預期相符
+    pub fn check_matches(self, expected: FileHeader) -> std::io::Result<()> {
+        let mismatch = |message: String| Err(HeaderMismatch { message }.into());
+
+        if self.model != expected.model {
+            return mismatch(format!(
+                "Model mismatch: file has model {}, expected {}",
+                self.model, expected.model
+            ));
+        }
+        if self.dim != expected.dim {
+            return mismatch(format!(
+                "Dimension mismatch: file has dim {}, expected {}",
+                self.dim, expected.dim
+            ));
+        }
+        if self.steps != expected.steps {
+            return mismatch(format!(
+                "Steps mismatch: file has steps {}, expected {}",
+                self.steps, expected.steps
+            ));
+        }
+        if self.stream_id != expected.stream_id {
+            return mismatch(format!(
+                "Stream mismatch: file has stream {}, expected {}",
+                self.stream_id, expected.stream_id
+            ));
+        }
+        if self.top_k != expected.top_k {
+            return mismatch(format!(
+                "Top-k mismatch: file has top-k {}, expected {}",
+                self.top_k, expected.top_k
+            ));
+        }
+        if self.warmup != expected.warmup {
+            return mismatch(format!(
+                "Warmup mismatch: file has warmup {}, expected {}",
+                self.warmup, expected.warmup
+            ));
+        }
+        if self.master_seed != expected.master_seed {
+            let describe = |master_seed: Option<u64>| {
+                master_seed.map_or_else(|| "none".to_string(), |seed| seed.to_string())
+            };
+            return mismatch(format!(
+                "Master seed mismatch: file has master seed {}, expected {}",
+                describe(self.master_seed),
+                describe(expected.master_seed)
+            ));
+        }
+        if self.eigenvalue_floor != expected.eigenvalue_floor {
+            let describe = |floor: Option<f64>| {
+                floor.map_or_else(|| "none".to_string(), |floor| format!("{floor:e}"))
+            };
+            return mismatch(format!(
+                "Eigenvalue floor mismatch: file has floor {}, expected {}",
+                describe(self.eigenvalue_floor),
+                describe(expected.eigenvalue_floor)
+            ));
+        }
+        // RNG 種子不同，續寫時混用會使同一檔案的 seed 不可重現
+        if self.has_mixed_seeds() != expected.has_mixed_seeds() {
+            return Err(SeedHashingMismatch {
+                file_mixed: self.has_mixed_seeds(),
+                expected_mixed: expected.has_mixed_seeds(),
+            }
+            .into());
+        }
+        // 記錄內容不同，續寫時混用會使聚合值與特徵值無法區分
+        if self.has_aggregates() != expected.has_aggregates() {
+            let describe = |aggregates: bool| {
+                if aggregates {
+                    "trace and max only"
+                } else {
+                    "all eigenvalues"
+                }
+            };
+            return mismatch(format!(
+                "Aggregate mismatch: file stores {}, expected {}",
+                describe(self.has_aggregates()),
+                describe(expected.has_aggregates())
+            ));
+        }
+        // 記錄格式不同，續寫時混用會使檔案無法解析
+        if self.has_timestamps() != expected.has_timestamps() {
+            return mismatch(format!(
+                "Timestamp mismatch: file {} per-record timestamps, expected {}",
+                if self.has_timestamps() {
+                    "has"
+                } else {
+                    "has no"
+                },
+                if expected.has_timestamps() {
+                    "timestamps"
+                } else {
+                    "none"
+                }
+            ));
+        }
+        Ok(())
+    }
+
+    /// 檔案是否帶有 CRC32 校驗碼
+    pub fn has_checksum(self) -> bool {
+        self.flags & FLAG_CRC32 != 0
+    }
+
+    /// 每筆記錄是否帶有計算時間
+    pub fn has_timestamps(self) -> bool {
+        self.flags & FLAG_TIMESTAMPS != 0
+    }
+
+    /// 子串流 0 的 seed 是否經 SplitMix64 混合
+    pub fn has_mixed_seeds(self) -> bool {
+        self.flags & FLAG_MIXED_SEEDS != 0
+    }
+
+    /// 每筆記錄是否只存 `[跡, 最大特徵值]`
+    pub fn has_aggregates(self) -> bool {
+        self.flags & FLAG_AGGREGATES != 0
+    }
+
+    /// 完成的檔案在資料區段之後的尾端長度（CRC32 + 元數據）
+    pub fn trailer_size(self) -> u64 {
+        if self.has_checksum() {
+            CRC32_SIZE + METADATA_SIZE
+        } else {
+            METADATA_SIZE
+        }
+    }
+
+    /// 序列化為 V7 標頭位元組
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(header_size() as usize);
+        bytes.extend_from_slice(MAGIC_HEADER);
+        bytes.extend_from_slice(&self.flags.to_le_bytes());
+        bytes.push(self.model);
+        bytes.push(self.dim);
+        bytes.extend_from_slice(&self.steps.to_le_bytes());
+        if self.flags & FLAG_STREAM_ID != 0 {
+            bytes.extend_from_slice(&self.stream_id.to_le_bytes());
+        }
+        if self.flags & FLAG_TOP_K != 0 {
+            bytes.push(self.top_k);
+        }
+        if self.flags & FLAG_WARMUP != 0 {
+            bytes.extend_from_slice(&self.warmup.to_le_bytes());
+        }
+        if self.flags & FLAG_MASTER_SEED != 0 {
+            bytes.extend_from_slice(&self.master_seed.unwrap_or(0).to_le_bytes());
+        }
+        if self.flags & FLAG_EIGENVALUE_FLOOR != 0 {
+            bytes.extend_from_slice(&self.eigenvalue_floor.unwrap_or(0.0).to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// V7 標頭大小：magic(12) + flags(2) + model(1) + dim(1) + steps(4)
+pub fn header_size() -> u64 {
+    MAGIC_HEADER.len() as u64 + 2 + 1 + 1 + 4
+}
+
+/// 固定標頭之後、依旗標出現的延伸欄位總長度
+pub fn extension_size(flags: u16) -> u64 {
+    let mut size = 0;
+    if flags & FLAG_STREAM_ID != 0 {
+        size += 4; // stream_id(u32)
+    }
+    if flags & FLAG_TOP_K != 0 {
+        size += 1; // top_k(u8)
+    }
+    if flags & FLAG_WARMUP != 0 {
+        size += 4; // warmup(u32)
+    }
+    if flags & FLAG_MASTER_SEED != 0 {
+        size += 8; // master_seed(u64)
+    }
+    if flags & FLAG_EIGENVALUE_FLOOR != 0 {
+        size += 8; // eigenvalue_floor(f64)
+    }
+    size
+}
+
 /// 計算預期檔案大小以便預先配置磁碟空間
 ///
 /// 注意：由於 seed 現在使用 ULEB128 編碼，檔案大小會因 seed 值而異
 /// 這個函數使用公式精確計算 1 到 num_runs 範圍內所有 seed 的編碼總大小
 pub fn calculate_expected_file_size(num_runs: usize, eigenvalues_per_run: usize) -> u64 {
-    let header = MAGIC_HEADER.len() as u64 + 1 + 1 + 4; // magic + model(1) + dim(1) + steps(4)
+    let header = header_size();
 
     // 直接計算所有 seed 的 ULEB128 編碼總大小
     let total_seed_bytes = calculate_total_uleb128_size(num_runs as u32);
 
     let eigenvalues_total_bytes = eigenvalues_per_run as u64 * 8 * num_runs as u64; // 每個 eigenvalue 8 bytes
     let eigenvalue_counts_bytes = num_runs as u64; // 每個記錄的 eigenvalue count (1 byte)
-    let metadata = EOF_MARKER.len() as u64 + 8 + 1; // eof_marker + total_count + eigenvalues_per_run(u8)
+    let metadata = FileHeader::new(DEFAULT_FLAGS, 0, 0, 0).trailer_size(); // [crc32] + eof_marker + total_count + eigenvalues_per_run(u8)
 
     header + total_seed_bytes + eigenvalue_counts_bytes + eigenvalues_total_bytes + metadata
 }
@@ -0,0 +1,92 @@
+//! 寫入鎖 - 防止多個程序同時續寫同一個資料檔案
+//!
+//! 兩個程序同時續寫同一檔案時都會移除 EOF 標記並交錯追加記錄，使檔案損壞。
+//! 寫入前開啟 `<檔名>.lock` 旁檔並取得作業系統的獨佔建議鎖（內容為持有者的程序 ID），
+//! 寫入器完成或被丟棄時刪除旁檔。程序被強制終止時作業系統會釋放鎖，
+//! 殘留的旁檔不會阻擋下一次執行。
+
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 資料檔案的鎖定旁檔路徑
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// 持有中的寫入鎖，丟棄時刪除旁檔並釋放鎖
+#[derive(Debug)]
+pub struct WriteLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl WriteLock {
+    /// 為資料檔案取得寫入鎖；已被其他寫入器持有時立即返回 `ResourceBusy` 錯誤
+    pub fn acquire(path: &Path) -> std::io::Result<Self> {
+        let lock_path = lock_path(path);
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&lock_path)?;
+            if let Err(e) = file.try_lock_exclusive() {
+                if e.raw_os_error() == fs2::lock_contended_error().raw_os_error()
+                    || e.kind() == std::io::ErrorKind::WouldBlock
+                {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::ResourceBusy,
+                        format!(
+                            "{} is already in progress in another process",
+                            path.display()
+                        ),
+                    ));
+                }
+                return Err(e);
+            }
+            // 前一個持有者在釋放鎖之前會刪除旁檔；鎖到已被刪除的旁檔時重新開啟
+            if !is_current_lock_file(&file, &lock_path)? {
+                continue;
+            }
+
+            file.set_len(0)?;
+            writeln!(file, "{}", std::process::id())?;
+            return Ok(Self {
+                path: lock_path,
+                file,
+            });
+        }
+    }
+}
+
+/// 已鎖定的檔案是否仍是 `lock_path` 所指的旁檔
+#[cfg(unix)]
+fn is_current_lock_file(file: &File, lock_path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let held = file.metadata()?;
+    match std::fs::metadata(lock_path) {
+        Ok(current) => Ok(held.dev() == current.dev() && held.ino() == current.ino()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// 非 Unix 平台無法比對 inode，視為仍是目前的旁檔
+#[cfg(not(unix))]
+fn is_current_lock_file(_file: &File, _lock_path: &Path) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+impl Drop for WriteLock {
+    fn drop(&mut self) {
+        // 先刪除旁檔再釋放鎖；已開啟舊旁檔的程序取得鎖後會發現旁檔已被刪除而重新開啟
+        let _ = std::fs::remove_file(&self.path);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
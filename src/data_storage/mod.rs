@@ -3,12 +3,23 @@
 //! This module provides functionality for running large-scale simulations
 //! and storing eigenvalue data efficiently with resumable append-only writing.
 
-mod config;
+pub(crate) mod checksum; // CRC32 校驗
+pub(crate) mod config; // 批次、緩衝區與重試設定
+pub(crate) mod csv_export; // .dat 資料匯出為 CSV
+pub(crate) mod csv_import; // 外部 CSV 轉換為 .dat
+pub(crate) mod disk_space; // 計算前的磁碟空間預檢與批次規劃
 pub(crate) mod file_format;
+pub(crate) mod lock; // 防止多個程序同時寫入同一檔案
 pub(crate) mod parallel_compute; // 並行計算引擎
+#[cfg(feature = "parquet")]
+pub(crate) mod parquet_export; // .dat 資料匯出為 Parquet
 pub(crate) mod progress;
+pub(crate) mod prune; // 移除 seed 超出 1..=num_runs 的記錄
 pub(crate) mod reader;
+pub(crate) mod recovery; // 截斷不完整的末尾記錄
+pub(crate) mod repair; // 結合各項修復的整體檔案修復
 pub(crate) mod simulation;
+pub(crate) mod sink; // 可替換的結果輸出後端
 pub(crate) mod thread_manager;
 pub(crate) mod uleb128; // ULEB128 編碼/解碼
 pub(crate) mod writer;
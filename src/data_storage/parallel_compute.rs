@@ -1,47 +1,365 @@
 use super::config::BATCH_SIZE;
-use super::progress::{check_append_progress, get_remaining_seeds};
-use super::reader::read_append_file;
+use super::file_format::is_header_mismatch;
+use super::progress::{
+    check_append_progress_with_header, filter_completed_seeds, get_remaining_seeds,
+};
+use super::reader::{read_append_file, read_file_header, read_storage_summary};
 use super::simulation::EigenvalueSimulation;
-use super::thread_manager::spawn_append_writer_thread;
+use super::sink::{ResultSink, trace_file_path};
+use super::thread_manager::{WriterResult, spawn_append_writer_thread, spawn_sink_thread};
 use crate::display_utils::format_number_with_commas;
 use crate::johansen_models::JohansenModel;
-use crate::johansen_statistics::calculate_eigenvalues;
+use crate::johansen_statistics::{
+    EigenvalueOptions, SolveDiagnostics, calculate_eigenvalues_shared_path,
+    calculate_eigenvalues_with_diagnostics, calculate_eigenvalues_with_options,
+};
 use rayon::prelude::*;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::thread;
+use std::time::Instant;
 
-/// 使用指定seeds進行並行計算
+/// 以位元表示的 seed 集合，索引為 seed 在共用路徑 seed 列表中的位置
+///
+/// 共用路徑模式需對每個模型查詢 seed 是否待計算；每個 seed 只佔 1 bit，
+/// 遠小於 `HashSet<u32>`。
+struct SeedMask {
+    words: Vec<u64>,
+}
+
+impl SeedMask {
+    /// 由 `remaining` 建立集合；`remaining` 的每個 seed 都須在遞增排序的 `seeds` 中
+    fn new(seeds: &[u32], remaining: &[u32]) -> Self {
+        let mut words = vec![0u64; seeds.len().div_ceil(64)];
+        for seed in remaining {
+            if let Ok(position) = seeds.binary_search(seed) {
+                words[position / 64] |= 1 << (position % 64);
+            }
+        }
+        Self { words }
+    }
+
+    fn contains(&self, position: usize) -> bool {
+        (self.words[position / 64] >> (position % 64)) & 1 == 1
+    }
+}
+
+/// 是否已超過截止時間（未設定時永遠為 false，且不讀取時鐘）
+fn past_deadline(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| now() >= deadline)
+}
+
+#[cfg(test)]
+thread_local! {
+    /// 目前執行緒的手動時鐘：(下次讀取的時間, 每次讀取前進的間隔)
+    static MANUAL_CLOCK: std::cell::Cell<Option<(Instant, std::time::Duration)>> =
+        const { std::cell::Cell::new(None) };
+}
+
+#[cfg(test)]
+fn now() -> Instant {
+    MANUAL_CLOCK.with(|clock| match clock.get() {
+        Some((now, tick)) => {
+            clock.set(Some((now + tick, tick)));
+            now
+        }
+        None => Instant::now(),
+    })
+}
+
+#[cfg(not(test))]
+#[inline]
+fn now() -> Instant {
+    Instant::now()
+}
+
+/// 以每次讀取前進固定間隔的手動時鐘取代目前執行緒的系統時鐘，離開範圍時恢復
+///
+/// 測試用：搭配 `with_single_thread` 使截止時間前完成的 seed 數量固定。
+#[cfg(test)]
+#[allow(dead_code)]
+pub struct ManualClockGuard {
+    previous: Option<(Instant, std::time::Duration)>,
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+impl ManualClockGuard {
+    pub fn start(start: Instant, tick: std::time::Duration) -> Self {
+        let previous = MANUAL_CLOCK.with(|clock| clock.replace(Some((start, tick))));
+        Self { previous }
+    }
+}
+
+#[cfg(test)]
+impl Drop for ManualClockGuard {
+    fn drop(&mut self) {
+        MANUAL_CLOCK.with(|clock| clock.set(self.previous));
+    }
+}
+
+/// 使用指定seeds進行並行計算，返回診斷出問題的 seed 與實際送出的結果數量
+///
+/// 設定 `min_condition` 或 `diagnose_complex` 時，返回被 `EigenvalueOptions::flags`
+/// 標記的 seed 及其診斷資訊；這些 seed 的結果仍會寫入檔案。`single_thread` 時依序計算，結果送出順序與 seed 順序相同。
+/// 設定 `deadline` 時，超過截止時間後尚未開始的 seed 不再計算，已開始的 seed 仍會完成。
 fn calculate_eigenvalues_parallel(
-    dim: usize,
-    steps: usize,
+    simulation: &EigenvalueSimulation,
     seeds: &[u32],
-    model: JohansenModel,
     sender: mpsc::Sender<(u32, Vec<f64>)>,
+    deadline: Option<Instant>,
     quiet: bool,
-) {
+) -> (Vec<(u32, SolveDiagnostics)>, usize) {
+    let (dim, steps, model) = (simulation.dim, simulation.steps, simulation.model);
+    let options = simulation.eigenvalue_options();
     let chunk_size = BATCH_SIZE;
     let total_seeds = seeds.len();
     let total_chunks = total_seeds.div_ceil(chunk_size);
+    let mut ill_conditioned = Vec::new();
+    let computed = AtomicUsize::new(0);
 
     for chunk_idx in 0..total_chunks {
+        if past_deadline(deadline) {
+            break;
+        }
         let chunk_start = chunk_idx * chunk_size;
         let chunk_end = ((chunk_idx + 1) * chunk_size).min(total_seeds);
         let chunk_seeds = &seeds[chunk_start..chunk_end];
 
-        // 並行計算這個chunk的結果
-        chunk_seeds.into_par_iter().for_each(|&seed| {
-            let eigenvalues = calculate_eigenvalues(dim, steps, seed, model);
+        let compute = |&seed: &u32| {
+            if past_deadline(deadline) {
+                return None;
+            }
+            let (eigenvalues, flagged) = if options.diagnoses() {
+                let (eigenvalues, diagnostics) =
+                    calculate_eigenvalues_with_diagnostics(dim, steps, seed, model, options);
+                let flagged = options.flags(diagnostics).then_some((seed, diagnostics));
+                (eigenvalues, flagged)
+            } else {
+                (
+                    calculate_eigenvalues_with_options(dim, steps, seed, model, options),
+                    None,
+                )
+            };
 
             // 發送結果給寫入執行緒
-            if sender.send((seed, eigenvalues)).is_err() && !quiet {
+            if sender.send((seed, eigenvalues)).is_ok() {
+                computed.fetch_add(1, Ordering::Relaxed);
+            } else if !quiet {
                 eprintln!("Failed to send results to writer thread");
             }
-        });
+            flagged
+        };
+
+        // 計算這個chunk的結果（預設並行）
+        let flagged: Vec<_> = if simulation.single_thread {
+            chunk_seeds.iter().filter_map(compute).collect()
+        } else {
+            chunk_seeds.into_par_iter().filter_map(compute).collect()
+        };
+        ill_conditioned.extend(flagged);
     }
+
+    (ill_conditioned, computed.into_inner())
+}
+
+/// 以共用布朗運動路徑並行計算多個模型，結果送至各模型的寫入執行緒
+///
+/// `seeds` 為遞增排序的所有待計算 seed；`models[i]` 只計算 `wanted[i]` 中的 seed，
+/// 結果送至 `senders[i]`。每個 seed 的路徑只生成一次。返回被標記的 (模型索引, seed, 診斷資訊)
+/// 與各模型實際送出的結果數量；`deadline` 的處理同 `calculate_eigenvalues_parallel`。
+fn calculate_shared_path_parallel(
+    simulation: &EigenvalueSimulation,
+    models: &[JohansenModel],
+    wanted: &[SeedMask],
+    seeds: &[u32],
+    senders: Vec<mpsc::Sender<(u32, Vec<f64>)>>,
+    deadline: Option<Instant>,
+    quiet: bool,
+) -> (Vec<(usize, u32, SolveDiagnostics)>, Vec<usize>) {
+    let (dim, steps) = (simulation.dim, simulation.steps);
+    let options = simulation.eigenvalue_options();
+    let mut ill_conditioned = Vec::new();
+    let computed: Vec<AtomicUsize> = models.iter().map(|_| AtomicUsize::new(0)).collect();
+
+    let compute = |position: usize| {
+        if past_deadline(deadline) {
+            return vec![];
+        }
+        let seed = seeds[position];
+        let indices: Vec<usize> = (0..models.len())
+            .filter(|&i| wanted[i].contains(position))
+            .collect();
+        let seed_models: Vec<JohansenModel> = indices.iter().map(|&i| models[i]).collect();
+        let results = calculate_eigenvalues_shared_path(
+            dim,
+            steps,
+            seed,
+            &seed_models,
+            options,
+            options.diagnoses(),
+        );
+
+        let mut flagged = Vec::new();
+        for (&i, (eigenvalues, diagnostics)) in indices.iter().zip(results) {
+            if let Some(diagnostics) = diagnostics.filter(|&d| options.flags(d)) {
+                flagged.push((i, seed, diagnostics));
+            }
+            if senders[i].send((seed, eigenvalues)).is_ok() {
+                computed[i].fetch_add(1, Ordering::Relaxed);
+            } else if !quiet {
+                eprintln!("Failed to send results to writer thread");
+            }
+        }
+        flagged
+    };
+
+    for chunk_start in (0..seeds.len()).step_by(BATCH_SIZE) {
+        if past_deadline(deadline) {
+            break;
+        }
+        let positions = chunk_start..(chunk_start + BATCH_SIZE).min(seeds.len());
+        let flagged: Vec<_> = if simulation.single_thread {
+            positions.flat_map(compute).collect()
+        } else {
+            positions.into_par_iter().flat_map_iter(compute).collect()
+        };
+        ill_conditioned.extend(flagged);
+    }
+
+    let computed = computed.into_iter().map(AtomicUsize::into_inner).collect();
+    (ill_conditioned, computed)
+}
+
+/// 依啟用的檢查回報被標記的 seed（寫至標準錯誤，安靜模式下仍會輸出）
+fn report_diagnostics(flagged: &[(u32, SolveDiagnostics)], options: EigenvalueOptions) {
+    if let Some(min_condition) = options.min_condition {
+        let ill_conditioned: Vec<_> = flagged
+            .iter()
+            .copied()
+            .filter(|(_, diagnostics)| diagnostics.is_ill_conditioned(min_condition))
+            .collect();
+        report_ill_conditioned(&ill_conditioned, min_condition);
+    }
+    if options.diagnose_complex {
+        let complex: Vec<_> = flagged
+            .iter()
+            .copied()
+            .filter(|(_, diagnostics)| diagnostics.has_complex_eigenvalues())
+            .collect();
+        report_complex_eigenvalues(&complex);
+    }
+}
+
+/// 列出前 10 個 seed，其餘以省略號表示
+fn seed_preview(seeds: &[u32]) -> String {
+    let preview: Vec<String> = seeds.iter().take(10).map(|seed| seed.to_string()).collect();
+    format!(
+        "{}{}",
+        preview.join(", "),
+        if seeds.len() > preview.len() {
+            ", ..."
+        } else {
+            ""
+        }
+    )
+}
+
+/// 回報倒數條件數低於門檻的 seed
+fn report_ill_conditioned(ill_conditioned: &[(u32, SolveDiagnostics)], min_condition: f64) {
+    if ill_conditioned.is_empty() {
+        return;
+    }
+
+    let worst = ill_conditioned
+        .iter()
+        .map(|(_, diagnostics)| diagnostics.reciprocal_condition)
+        .fold(f64::INFINITY, f64::min);
+    let mut seeds: Vec<u32> = ill_conditioned.iter().map(|(seed, _)| *seed).collect();
+    seeds.sort_unstable();
+
+    eprintln!(
+        "WARNING: {} seeds have reciprocal condition below {min_condition:e} (worst {worst:e}); \
+         their eigenvalues may be unreliable, consider increasing --steps",
+        format_number_with_commas(seeds.len())
+    );
+    eprintln!("  Seeds: {}", seed_preview(&seeds));
+}
+
+/// 回報求解結果含有不可忽略虛部的 seed 與各 seed 的複數特徵值數量
+fn report_complex_eigenvalues(complex: &[(u32, SolveDiagnostics)]) {
+    if complex.is_empty() {
+        return;
+    }
+
+    let mut complex = complex.to_vec();
+    complex.sort_unstable_by_key(|(seed, _)| *seed);
+    let total: usize = complex
+        .iter()
+        .map(|(_, diagnostics)| diagnostics.complex_eigenvalues)
+        .sum();
+
+    eprintln!(
+        "WARNING: {} seeds produced {} eigenvalues with non-negligible imaginary parts; \
+         the solve is numerically unreliable, consider increasing --steps",
+        format_number_with_commas(complex.len()),
+        format_number_with_commas(total)
+    );
+    let counts: Vec<String> = complex
+        .iter()
+        .take(10)
+        .map(|(seed, diagnostics)| format!("{seed} ({})", diagnostics.complex_eigenvalues))
+        .collect();
+    eprintln!(
+        "  Seeds (complex eigenvalues): {}{}",
+        counts.join(", "),
+        if complex.len() > counts.len() {
+            ", ..."
+        } else {
+            ""
+        }
+    );
+}
+
+/// 計算 seed 1..=num_runs 的特徵值並直接歸約，不保存資料也不寫入檔案
+///
+/// 每個執行緒以 `init` 的複本為起點用 `fold` 累加，最後以 `combine` 合併各執行緒的結果，
+/// 因此 `init` 必須是 `combine` 的單位元素，且 `combine` 須滿足結合律。
+#[allow(dead_code)]
+pub fn fold_model_runs<T, F, C>(
+    simulation: &EigenvalueSimulation,
+    init: T,
+    fold: F,
+    combine: C,
+) -> T
+where
+    T: Clone + Send + Sync,
+    F: Fn(T, u32, &[f64]) -> T + Sync + Send,
+    C: Fn(T, T) -> T + Sync + Send,
+{
+    let (dim, steps, model) = (simulation.dim, simulation.steps, simulation.model);
+    let options = simulation.eigenvalue_options();
+    let seeds = 1..=simulation.num_runs as u32;
+    let fold_seed = |acc: T, seed: u32| {
+        let eigenvalues = calculate_eigenvalues_with_options(dim, steps, seed, model, options);
+        fold(acc, seed, &eigenvalues)
+    };
+
+    if simulation.single_thread {
+        return seeds.fold(init, fold_seed);
+    }
+
+    seeds
+        .into_par_iter()
+        .fold(|| init.clone(), fold_seed)
+        .reduce(|| init.clone(), combine)
 }
 
 /// 驗證檔案寫入結果
-fn validate_output_file(filename: &str, expected_count: usize) {
+///
+/// 數量不符或一般讀取錯誤只輸出訊息；檔案格式不相容時返回錯誤。
+fn validate_output_file(filename: &str, expected_count: usize) -> std::io::Result<()> {
     match read_append_file(filename) {
         Ok((loaded_data, _model, _dim, _steps)) => {
             if loaded_data.len() == expected_count {
@@ -55,16 +373,19 @@ fn validate_output_file(filename: &str, expected_count: usize) {
             }
         }
         Err(e) => {
-            // 對於魔術標頭不匹配這類嚴重的文件格式錯誤，應該 panic
+            // 對於魔術標頭不匹配這類嚴重的文件格式錯誤，應該中止
             if e.to_string()
                 .contains("File format error: magic header mismatch")
             {
-                panic!("CRITICAL ERROR: File format incompatibility detected - {e}");
-            } else {
-                println!("ERROR: failed to read append file: {e}");
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("CRITICAL ERROR: File format incompatibility detected - {e}"),
+                ));
             }
+            println!("ERROR: failed to read append file: {e}");
         }
     }
+    Ok(())
 }
 
 /// 輸出模型資訊
@@ -74,6 +395,7 @@ fn display_model_info(simulation: &EigenvalueSimulation, quiet: bool) {
             "Using model: {} (supports resuming from checkpoint)",
             simulation.model
         );
+        println!("Configuration: {simulation}");
     }
 }
 
@@ -83,12 +405,8 @@ fn load_progress(
     filename: &str,
     quiet: bool,
 ) -> std::io::Result<(usize, Vec<u32>)> {
-    let (completed_runs, completed_seeds) = check_append_progress(
-        filename,
-        simulation.model.to_number(),
-        simulation.dim as u8,
-        simulation.steps as u32,
-    )?;
+    let (completed_runs, completed_seeds) =
+        check_append_progress_with_header(filename, simulation.file_header()?)?;
 
     if completed_runs > 0 && !quiet {
         let max_completed_seed = completed_seeds.iter().max().copied().unwrap_or(0);
@@ -105,6 +423,72 @@ fn load_progress(
     Ok((completed_runs, completed_seeds))
 }
 
+/// 讀取進度；既有檔案的參數不相符時刪除檔案並從頭開始
+///
+/// `with_output_file` 指定的檔案由使用者命名，可能是其他計算的結果，
+/// 標頭不符或無法辨識時返回錯誤而不刪除。
+fn load_or_reset_progress(
+    simulation: &EigenvalueSimulation,
+    filename: &str,
+    quiet: bool,
+) -> std::io::Result<(usize, Vec<u32>)> {
+    let explicit = simulation
+        .output_file
+        .as_deref()
+        .is_some_and(|path| path == Path::new(filename));
+    let non_empty = std::fs::metadata(filename).is_ok_and(|meta| meta.len() > 0);
+    if explicit && non_empty {
+        read_file_header(filename)
+            .and_then(|header| header.check_matches(simulation.file_header()?))
+            .map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!("{filename} was set as the output file and will not be replaced: {e}"),
+                )
+            })?;
+    }
+
+    match load_progress(simulation, filename, quiet) {
+        Ok(res) => Ok(res),
+        // 只刪除標頭參數不符的檔案；seed 混合方式不同的檔案仍是有效資料，不可刪除
+        Err(e) if is_header_mismatch(&e) => {
+            if !quiet {
+                println!("WARNING: Existing file has incompatible parameters:");
+                println!("  {e}");
+                println!(
+                    "  The existing file will be removed and recreated with correct parameters."
+                );
+            }
+            if let Err(remove_err) = std::fs::remove_file(filename) {
+                if !quiet {
+                    println!("WARNING: Failed to remove incompatible file: {remove_err}");
+                }
+            }
+            if !quiet {
+                println!("Starting fresh calculation with correct parameters...");
+            }
+            load_progress(simulation, filename, quiet)
+        }
+        Err(e) => Err(std::io::Error::new(
+            e.kind(),
+            format!("Failed to check progress: {e}"),
+        )),
+    }
+}
+
+/// 尚未完成的 seed：指定 seed 列表時為列表中未完成者，否則為 1..=num_runs 中未完成者
+fn remaining_seeds(
+    simulation: &EigenvalueSimulation,
+    completed_runs: usize,
+    completed_seeds: &[u32],
+) -> Vec<u32> {
+    match &simulation.seeds {
+        Some(seeds) => filter_completed_seeds(seeds, completed_seeds),
+        None if completed_runs >= simulation.num_runs => vec![],
+        None => get_remaining_seeds(simulation.num_runs, completed_seeds),
+    }
+}
+
 /// 啟動寫入執行緒
 fn start_writer_thread(
     filename: String,
@@ -112,82 +496,89 @@ fn start_writer_thread(
     completed_runs: usize,
     receiver: mpsc::Receiver<(u32, Vec<f64>)>,
     quiet: bool,
-) -> thread::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+) -> std::io::Result<thread::JoinHandle<WriterResult<()>>> {
+    let trace_file = simulation
+        .trace_file
+        .then(|| trace_file_path(Path::new(&filename)));
     let writer_config = crate::data_storage::thread_manager::WriterConfig {
         filename,
         total_runs: simulation.num_runs,
         completed_runs,
         dim: simulation.dim,
-        steps: simulation.steps,
         model: simulation.model,
+        header: simulation.file_header()?,
         quiet,
+        checkpoint_every: simulation.checkpoint_every,
+        write_buffer: simulation.write_buffer,
+        trace_file,
     };
-    spawn_append_writer_thread(writer_config, receiver)
+    Ok(spawn_append_writer_thread(writer_config, receiver))
 }
 
-/// 等待寫入執行緒結束
+/// 等待寫入執行緒結束，寫入失敗或執行緒 panic 時返回錯誤
 fn wait_for_writer(
-    writer_handle: thread::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+    writer_handle: thread::JoinHandle<WriterResult<()>>,
     filename: &str,
     quiet: bool,
-) {
+) -> std::io::Result<()> {
     match writer_handle.join() {
         Ok(Ok(())) => {
             if !quiet {
                 println!("Saved to {filename}");
             }
+            Ok(())
         }
-        Ok(Err(e)) => {
-            panic!("Writer thread error: {e}");
-        }
-        Err(_) => {
-            panic!("Writer thread panic");
-        }
+        Ok(Err(e)) => Err(std::io::Error::other(format!("Writer thread error: {e}"))),
+        Err(_) => Err(std::io::Error::other("Writer thread panic")),
     }
 }
 
-/// 支援斷點續傳的單一模型模擬計算
-pub fn run_model_simulation(simulation: &EigenvalueSimulation, quiet: bool) {
-    display_model_info(simulation, quiet);
+/// 計算 seed 1..=num_runs 並將結果送至 `make_sink` 建立的接收端（安靜模式、不續傳）
+///
+/// 接收端在寫入執行緒中建立並依序接收結果，完成後返回其 `finish` 的結果。
+#[allow(dead_code)]
+pub fn run_model_with_sink<S, F>(
+    simulation: &EigenvalueSimulation,
+    make_sink: F,
+) -> std::io::Result<S::Output>
+where
+    S: ResultSink + 'static,
+    S::Output: 'static,
+    F: FnOnce() -> std::io::Result<S> + Send + 'static,
+{
+    simulation.validate()?;
+    let seeds: Vec<u32> = (1..=simulation.num_runs as u32).collect();
 
-    let filename = simulation.get_filename(simulation.model);
+    let (sender, receiver) = mpsc::channel::<(u32, Vec<f64>)>();
+    let sink_handle = spawn_sink_thread(make_sink, simulation.num_runs, 0, true, receiver);
 
-    let (completed_runs, completed_seeds) = match load_progress(simulation, &filename, quiet) {
-        Ok(res) => res,
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("mismatch") {
-                if !quiet {
-                    println!("WARNING: Existing file has incompatible parameters:");
-                    println!("  {error_msg}");
-                    println!(
-                        "  The existing file will be removed and recreated with correct parameters."
-                    );
-                }
-                if let Err(remove_err) = std::fs::remove_file(&filename) {
-                    if !quiet {
-                        println!("WARNING: Failed to remove incompatible file: {remove_err}");
-                    }
-                }
-                if !quiet {
-                    println!("Starting fresh calculation with correct parameters...");
-                }
-                return run_model_simulation(simulation, quiet);
-            } else {
-                panic!("Failed to check progress: {e}");
-            }
-        }
+    // 接收端需要完整的結果，不套用截止時間
+    let (ill_conditioned, _) =
+        calculate_eigenvalues_parallel(simulation, &seeds, sender, None, true);
+
+    let output = match sink_handle.join() {
+        Ok(result) => result.map_err(std::io::Error::other)?,
+        Err(_) => panic!("Writer thread panic"),
     };
 
-    if completed_runs >= simulation.num_runs {
-        if !quiet {
-            println!("SUCCESS: calculation for this model already completed, skipping");
-            println!("===============================\n");
-        }
-        return;
-    }
+    report_diagnostics(&ill_conditioned, simulation.eigenvalue_options());
+
+    Ok(output)
+}
+
+/// 支援斷點續傳的單一模型模擬計算
+///
+/// 無法讀取進度、寫入執行緒失敗或檔案格式不相容時返回錯誤。
+pub fn run_model_simulation(simulation: &EigenvalueSimulation, quiet: bool) -> std::io::Result<()> {
+    simulation.validate()?;
+    display_model_info(simulation, quiet);
 
-    let remaining_seeds = get_remaining_seeds(simulation.num_runs, &completed_seeds);
+    let filename = simulation.try_get_filename(simulation.model)?;
+
+    let (completed_runs, completed_seeds) = load_or_reset_progress(simulation, &filename, quiet)?;
+
+    // 指定 seed 列表時只計算列表中尚未完成的 seed
+    let remaining_seeds = remaining_seeds(simulation, completed_runs, &completed_seeds);
     let remaining_count = remaining_seeds.len();
 
     if remaining_count == 0 {
@@ -195,7 +586,7 @@ pub fn run_model_simulation(simulation: &EigenvalueSimulation, quiet: bool) {
             println!("SUCCESS: calculation for this model already completed");
             println!("===============================\n");
         }
-        return;
+        return Ok(());
     }
 
     if !quiet {
@@ -212,21 +603,159 @@ pub fn run_model_simulation(simulation: &EigenvalueSimulation, quiet: bool) {
         completed_runs,
         receiver,
         quiet,
-    );
+    )?;
 
-    calculate_eigenvalues_parallel(
-        simulation.dim,
-        simulation.steps,
+    let (ill_conditioned, computed) = calculate_eigenvalues_parallel(
+        simulation,
         &remaining_seeds,
-        simulation.model,
         sender,
+        simulation.deadline,
         quiet,
     );
 
-    wait_for_writer(writer_handle, &filename, quiet);
+    wait_for_writer(writer_handle, &filename, quiet)?;
+
+    report_diagnostics(&ill_conditioned, simulation.eigenvalue_options());
 
     if !quiet {
-        validate_output_file(&filename, simulation.num_runs);
-        println!("===============================\n");
+        report_deadline_stop(computed, remaining_count);
+        report_output_file(&filename, completed_runs + computed)?;
+    }
+    Ok(())
+}
+
+/// 截止時間前未完成所有 seed 時輸出提示
+fn report_deadline_stop(computed: usize, remaining: usize) {
+    if computed < remaining {
+        println!(
+            "Time budget reached after {} of {} calculations; rerun to resume the remaining {}",
+            format_number_with_commas(computed),
+            format_number_with_commas(remaining),
+            format_number_with_commas(remaining - computed)
+        );
+    }
+}
+
+/// 驗證輸出檔案並顯示儲存效率
+fn report_output_file(filename: &str, expected_count: usize) -> std::io::Result<()> {
+    validate_output_file(filename, expected_count)?;
+    // 實際的檔案大小與預估值比較，顯示 ULEB128 seed 編碼的效果
+    match read_storage_summary(filename) {
+        Ok(summary) => println!("{summary}"),
+        Err(e) => println!("ERROR: failed to summarize storage: {e}"),
+    }
+    println!("===============================\n");
+    Ok(())
+}
+
+/// 以共用布朗運動路徑計算多個模型，支援斷點續傳
+///
+/// `simulations` 除 `model` 外的參數須相同，每個模型寫入各自的資料檔案。
+/// 每個 seed 只生成一次布朗運動路徑，再求解所有仍缺少此 seed 的模型；
+/// 結果與逐一執行 `run_model_simulation` 相同。錯誤處理同 `run_model_simulation`。
+pub fn run_shared_path_simulation(
+    simulations: &[EigenvalueSimulation],
+    quiet: bool,
+) -> std::io::Result<()> {
+    let Some(first) = simulations.first() else {
+        return Ok(());
+    };
+    for simulation in simulations {
+        simulation.validate()?;
+    }
+
+    // 各模型尚未完成的 seed
+    let mut pending = Vec::new();
+    for simulation in simulations {
+        display_model_info(simulation, quiet);
+        let filename = simulation.try_get_filename(simulation.model)?;
+        let (completed_runs, completed_seeds) =
+            load_or_reset_progress(simulation, &filename, quiet)?;
+        let remaining = remaining_seeds(simulation, completed_runs, &completed_seeds);
+        if remaining.is_empty() {
+            if !quiet {
+                println!("SUCCESS: calculation for this model already completed, skipping");
+            }
+            continue;
+        }
+        if !quiet {
+            println!(
+                "Remaining {} calculations to complete",
+                format_number_with_commas(remaining.len())
+            );
+        }
+        pending.push((simulation, filename, completed_runs, remaining));
+    }
+    if pending.is_empty() {
+        if !quiet {
+            println!("===============================\n");
+        }
+        return Ok(());
+    }
+
+    // 所有模型待計算 seed 的聯集（遞增排序）；
+    // 各模型待計算的 seed 改以位元集合保存，只留下數量供回報
+    let mut seeds: Vec<u32> = Vec::new();
+    for (.., remaining) in &pending {
+        seeds.extend(remaining);
+        seeds.sort_unstable();
+        seeds.dedup();
+    }
+    let wanted: Vec<SeedMask> = pending
+        .iter()
+        .map(|(.., remaining)| SeedMask::new(&seeds, remaining))
+        .collect();
+    let pending: Vec<_> = pending
+        .into_iter()
+        .map(|(simulation, filename, completed_runs, remaining)| {
+            (simulation, filename, completed_runs, remaining.len())
+        })
+        .collect();
+
+    let mut senders = Vec::with_capacity(pending.len());
+    let mut writer_handles = Vec::with_capacity(pending.len());
+    for (index, (simulation, filename, completed_runs, _)) in pending.iter().enumerate() {
+        let (sender, receiver) = mpsc::channel::<(u32, Vec<f64>)>();
+        // 各模型同步前進，只由第一個模型的寫入執行緒輸出進度
+        let writer_quiet = quiet || index > 0;
+        senders.push(sender);
+        writer_handles.push(start_writer_thread(
+            filename.clone(),
+            simulation,
+            *completed_runs,
+            receiver,
+            writer_quiet,
+        )?);
+    }
+
+    let models: Vec<JohansenModel> = pending.iter().map(|(sim, ..)| sim.model).collect();
+    let (ill_conditioned, computed) = calculate_shared_path_parallel(
+        first,
+        &models,
+        &wanted,
+        &seeds,
+        senders,
+        first.deadline,
+        quiet,
+    );
+
+    for (index, ((simulation, filename, completed_runs, remaining_count), writer_handle)) in
+        pending.iter().zip(writer_handles).enumerate()
+    {
+        wait_for_writer(writer_handle, filename, quiet)?;
+
+        let flagged: Vec<(u32, SolveDiagnostics)> = ill_conditioned
+            .iter()
+            .filter(|(i, ..)| *i == index)
+            .map(|&(_, seed, diagnostics)| (seed, diagnostics))
+            .collect();
+        report_diagnostics(&flagged, simulation.eigenvalue_options());
+
+        if !quiet {
+            println!("{}:", simulation.model);
+            report_deadline_stop(computed[index], *remaining_count);
+            report_output_file(filename, completed_runs + computed[index])?;
+        }
     }
+    Ok(())
 }
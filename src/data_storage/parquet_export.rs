@@ -0,0 +1,75 @@
+//! Parquet 匯出 - 將 `.dat` 資料寫成欄式檔案
+//!
+//! 欄位為 `seed`（UInt32）以及 `eigenvalue_1`、`eigenvalue_2`...（Float64），
+//! 與 CSV 匯出的欄位名稱相同。特徵值數量隨模型（`dim` 或 `dim + 1`）與 `top_k` 而不同，
+//! 欄位數量取自最長的記錄；較短的記錄在多出的欄位寫入 null。
+//! 彙總檔案的欄位則為 `trace` 與 `max`。
+
+use arrow_array::builder::{Float64Builder, UInt32Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+
+use super::csv_export::value_column_names;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 每個 RecordBatch（同時也是 row group 的寫入單位）的記錄數量
+const BATCH_ROWS: usize = 1 << 16;
+
+/// 以 Parquet 寫出特徵值資料，`aggregates` 為資料是否來自彙總檔案
+pub fn write_eigenvalues_parquet<W: Write + Send>(
+    data: &[(u32, Vec<f64>)],
+    aggregates: bool,
+    out: W,
+) -> std::io::Result<()> {
+    let columns = data
+        .iter()
+        .map(|(_, eigenvalues)| eigenvalues.len())
+        .max()
+        .unwrap_or(0);
+    let mut fields = vec![Field::new("seed", DataType::UInt32, false)];
+    fields.extend(
+        value_column_names("eigenvalue_", columns, aggregates)
+            .into_iter()
+            .map(|name| Field::new(name, DataType::Float64, true)),
+    );
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut writer =
+        ArrowWriter::try_new(out, schema.clone(), None).map_err(std::io::Error::other)?;
+    for chunk in data.chunks(BATCH_ROWS) {
+        let mut seeds = UInt32Builder::with_capacity(chunk.len());
+        let mut values: Vec<Float64Builder> = (0..columns)
+            .map(|_| Float64Builder::with_capacity(chunk.len()))
+            .collect();
+        for (seed, eigenvalues) in chunk {
+            seeds.append_value(*seed);
+            for (i, builder) in values.iter_mut().enumerate() {
+                builder.append_option(eigenvalues.get(i).copied());
+            }
+        }
+
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(seeds.finish())];
+        arrays.extend(
+            values
+                .iter_mut()
+                .map(|builder| Arc::new(builder.finish()) as ArrayRef),
+        );
+        let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(std::io::Error::other)?;
+        writer.write(&batch).map_err(std::io::Error::other)?;
+    }
+    writer.close().map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+/// 將特徵值資料寫入 Parquet 檔案（已存在時覆寫）
+pub fn export_parquet<P: AsRef<Path>>(
+    path: P,
+    data: &[(u32, Vec<f64>)],
+    aggregates: bool,
+) -> std::io::Result<()> {
+    write_eigenvalues_parquet(data, aggregates, File::create(path)?)
+}
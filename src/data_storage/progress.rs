@@ -5,53 +5,97 @@
 use std::collections::HashSet;
 use std::path::Path;
 
-use super::reader::read_append_file;
+use super::checksum::is_checksum_error;
+use super::file_format::{DEFAULT_FLAGS, FileHeader};
+use super::reader::{read_append_file, read_file_header, read_record_count};
 
 /// 檢查檔案進度（追加格式）並驗證參數匹配
+#[allow(dead_code)]
 pub fn check_append_progress<P: AsRef<Path>>(
     path: P,
     expected_model: u8,
     expected_dim: u8,
     expected_steps: u32,
+) -> std::io::Result<(usize, Vec<u32>)> {
+    let expected = FileHeader::new(DEFAULT_FLAGS, expected_model, expected_dim, expected_steps);
+    check_append_progress_with_header(path, expected)
+}
+
+/// 檢查檔案進度（追加格式）並驗證標頭參數（含延伸欄位）匹配
+pub fn check_append_progress_with_header<P: AsRef<Path>>(
+    path: P,
+    expected: FileHeader,
+) -> std::io::Result<(usize, Vec<u32>)> {
+    read_progress(path, expected, ProgressDetail::Seeds)
+}
+
+/// 讀取進度時需要的內容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProgressDetail {
+    /// 只需要記錄數量：已完成的檔案只讀取標頭與末尾元數據
+    Count,
+    /// 需要已完成的 seed 列表：讀取所有記錄
+    Seeds,
+}
+
+/// 讀取檔案進度並驗證標頭參數匹配，返回記錄數量與 seed 列表（`ProgressDetail::Count` 時為空）
+///
+/// 檔案不存在、損壞或無法讀取時視為尚未開始；校驗失敗與較新版本的檔案返回錯誤，
+/// 避免覆寫或續寫這些資料。
+fn read_progress<P: AsRef<Path>>(
+    path: P,
+    expected: FileHeader,
+    detail: ProgressDetail,
 ) -> std::io::Result<(usize, Vec<u32>)> {
     if !path.as_ref().exists() {
         return Ok((0, Vec::new()));
     }
 
-    match read_append_file(&path) {
-        Ok((data, file_model, file_dim, file_steps)) => {
+    let read = match detail {
+        ProgressDetail::Count => {
+            read_record_count(&path).map(|(header, count)| (header, count, Vec::new()))
+        }
+        ProgressDetail::Seeds => read_append_file(&path).and_then(|(data, ..)| {
+            let seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
+            Ok((read_file_header(&path)?, data.len(), seeds))
+        }),
+    };
+
+    match read {
+        Ok((header, count, seeds)) => {
             // 驗證參數是否匹配
-            if file_model != expected_model {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!(
-                        "Model mismatch: file has model {file_model}, expected {expected_model}"
-                    ),
-                ));
-            }
-            if file_dim != expected_dim {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Dimension mismatch: file has dim {file_dim}, expected {expected_dim}"),
-                ));
-            }
-            if file_steps != expected_steps {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!(
-                        "Steps mismatch: file has steps {file_steps}, expected {expected_steps}"
-                    ),
-                ));
-            }
-
-            let completed_runs = data.len();
-            let completed_seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
-            Ok((completed_runs, completed_seeds))
+            header.check_matches(expected)?;
+            Ok((count, seeds))
         }
-        Err(_) => Ok((0, Vec::new())), // 檔案損壞或無法讀取，重新開始
+        Err(e) if is_checksum_error(&e) => Err(e), // 校驗失敗，不可覆寫或續寫損壞的資料
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => Err(e), // 較新版本的檔案，不可覆寫
+        Err(_) => Ok((0, Vec::new())),             // 檔案損壞或無法讀取，重新開始
     }
 }
 
+/// 只取得檔案中已完成的記錄數量（追加格式）並驗證參數匹配
+///
+/// 與 `check_append_progress` 不同，已完成的檔案只需讀取標頭與末尾元數據，
+/// 不必掃描所有記錄，適合用於狀態顯示與進度條。
+#[allow(dead_code)]
+pub fn count_append_progress<P: AsRef<Path>>(
+    path: P,
+    expected_model: u8,
+    expected_dim: u8,
+    expected_steps: u32,
+) -> std::io::Result<usize> {
+    let expected = FileHeader::new(DEFAULT_FLAGS, expected_model, expected_dim, expected_steps);
+    count_append_progress_with_header(path, expected)
+}
+
+/// 只取得檔案中已完成的記錄數量，並驗證標頭參數（含延伸欄位）匹配
+pub fn count_append_progress_with_header<P: AsRef<Path>>(
+    path: P,
+    expected: FileHeader,
+) -> std::io::Result<usize> {
+    read_progress(path, expected, ProgressDetail::Count).map(|(count, _)| count)
+}
+
 /// 獲取尚未完成的seed列表
 pub fn get_remaining_seeds(total_runs: usize, completed_seeds: &[u32]) -> Vec<u32> {
     let completed_set: HashSet<u32> = completed_seeds.iter().copied().collect();
@@ -59,3 +103,34 @@ pub fn get_remaining_seeds(total_runs: usize, completed_seeds: &[u32]) -> Vec<u3
         .filter(|seed| !completed_set.contains(seed))
         .collect()
 }
+
+/// 從指定的 seed 列表中移除已完成與重複的 seed（保留原本順序）
+pub fn filter_completed_seeds(seeds: &[u32], completed_seeds: &[u32]) -> Vec<u32> {
+    let mut seen: HashSet<u32> = completed_seeds.iter().copied().collect();
+    seeds
+        .iter()
+        .copied()
+        .filter(|seed| seen.insert(*seed))
+        .collect()
+}
+
+/// 讀取以換行分隔的 seed 檔案，略過空白列與 `#` 開頭的註解列
+#[allow(dead_code)]
+pub fn read_seed_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<u32>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut seeds = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let seed = line.parse::<u32>().map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("seed file line {}: invalid seed '{line}'", index + 1),
+            )
+        })?;
+        seeds.push(seed);
+    }
+    Ok(seeds)
+}
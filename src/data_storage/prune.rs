@@ -0,0 +1,90 @@
+//! 移除範圍外的 seed - 清理先前計算留下的多餘記錄
+//!
+//! `read_data` 只使用 seed 在 `1..=num_runs` 的記錄，其餘記錄會被默默略過，
+//! 例如同一檔案先前以較大的 `num_runs` 計算過。這裡將檔案重寫為只保留範圍內的記錄。
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use super::file_format::FileHeader;
+use super::lock::WriteLock;
+use super::reader::{read_append_file_with_timestamps, read_file_header};
+use super::writer::AppendOnlyWriter;
+
+/// 重寫時使用的暫存檔路徑（原路徑加上 `suffix`）
+fn temp_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 以 `header` 與 `(seed, 特徵值, 計算時間)` 記錄重寫 `path`
+///
+/// 先寫入 `path` 加上 `temp_suffix` 的暫存檔再取代原檔，重寫後的檔案帶有結束標記；
+/// 失敗時刪除暫存檔，原檔不變。呼叫端須已持有 `path` 的寫入鎖。
+pub(super) fn rewrite_records<'a>(
+    path: &Path,
+    temp_suffix: &str,
+    header: FileHeader,
+    records: impl IntoIterator<Item = (u32, &'a [f64], u32)>,
+) -> std::io::Result<()> {
+    rewrite_with(path, temp_suffix, header, |writer| {
+        for (seed, eigenvalues, minutes) in records {
+            writer.append_with_timestamp(seed, eigenvalues, minutes)?;
+        }
+        Ok(())
+    })
+}
+
+/// 以 `header` 重寫 `path`，記錄由 `fill` 逐筆寫入暫存檔的寫入器
+///
+/// 暫存檔與取代原檔的方式同 `rewrite_records`，`fill` 失敗時原檔不變。
+pub(super) fn rewrite_with(
+    path: &Path,
+    temp_suffix: &str,
+    header: FileHeader,
+    fill: impl FnOnce(&mut AppendOnlyWriter) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let temp_path = temp_path(path, temp_suffix);
+    let written = (|| {
+        let file = File::create(&temp_path)?;
+        let mut writer = AppendOnlyWriter::from_existing_handle(file, header, true)?;
+        fill(&mut writer)?;
+        writer.finish()
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    std::fs::rename(&temp_path, path)
+}
+
+/// 移除 seed 不在 `1..=num_runs` 的記錄，返回移除的記錄數量
+///
+/// 沒有範圍外的 seed 時不修改檔案。否則保留原本的標頭欄位與時間戳記，
+/// 先寫入暫存檔再取代原檔，重寫後的檔案帶有結束標記（未完成檔案末尾的不完整記錄會被捨棄）。
+/// 重寫期間持有寫入鎖，其他程序正在寫入同一檔案時返回錯誤。
+pub fn prune_out_of_range<P: AsRef<Path>>(path: P, num_runs: usize) -> std::io::Result<usize> {
+    let path = path.as_ref();
+    let _lock = WriteLock::acquire(path)?;
+
+    let header = read_file_header(path)?;
+    let (data, times) = read_append_file_with_timestamps(path)?;
+    let in_range = |seed: u32| seed >= 1 && seed as usize <= num_runs;
+    let pruned = data.iter().filter(|(seed, _)| !in_range(*seed)).count();
+    if pruned == 0 {
+        return Ok(0);
+    }
+
+    let kept = data
+        .iter()
+        .enumerate()
+        .filter(|(_, (seed, _))| in_range(*seed))
+        .map(|(index, (seed, eigenvalues))| {
+            let minutes = times.get(index).copied().unwrap_or(0);
+            (*seed, eigenvalues.as_slice(), minutes)
+        });
+    rewrite_records(path, ".prune.tmp", header, kept)?;
+    Ok(pruned)
+}
@@ -2,40 +2,83 @@
 //!
 //! 實現了帶有元數據的快速讀取和掃描式讀取
 
-use crate::display_utils::format_number_with_commas;
+use crate::display_utils::{format_bytes, format_number_with_commas};
 use std::fs::File;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
-use super::file_format::{EOF_MARKER, MAGIC_HEADER, calculate_read_buffer_size};
+use super::checksum::{ChecksumError, ChecksumReader};
+use super::file_format::{
+    CRC32_SIZE, EOF_MARKER, FLAG_EIGENVALUE_FLOOR, FLAG_MASTER_SEED, FLAG_STREAM_ID, FLAG_TOP_K,
+    FLAG_WARMUP, FileHeader, KNOWN_FLAGS, MAGIC_HEADER, MAGIC_HEADER_V5, MAGIC_HEADER_V6,
+    METADATA_SIZE, TIMESTAMP_SIZE, calculate_expected_file_size, calculate_read_buffer_size,
+    extension_size, header_size,
+};
 use super::uleb128;
 
 /// 檔案讀取結果類型別名
 pub type FileReadResult = std::io::Result<(Vec<(u32, Vec<f64>)>, u8, u8, u32)>;
 
-/// 從 BufReader 讀取 ULEB128 編碼的 u32 值
-fn read_uleb128(reader: &mut BufReader<File>) -> std::io::Result<u32> {
-    uleb128::read_from_reader(reader)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+/// 帶時間戳記的讀取結果：記錄與逐筆對應的計算時間（Unix epoch 分鐘數）
+///
+/// 檔案未設定 `FLAG_TIMESTAMPS` 時時間戳記為空向量。
+pub type TimestampedReadResult = std::io::Result<(Vec<(u32, Vec<f64>)>, Vec<u32>)>;
+
+/// 平坦讀取結果：seed、連續存放的特徵值與每筆記錄的特徵值數量（stride）
+pub type FlatReadResult = std::io::Result<(Vec<u32>, Vec<f64>, usize)>;
+
+/// 記錄與時間戳記（未設定 `FLAG_TIMESTAMPS` 時為空）
+type Records = (Vec<(u32, Vec<f64>)>, Vec<u32>);
+
+/// 掃描式讀取遇到特徵值數量與第一筆記錄不一致時的處理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanStrictness {
+    /// 返回 `InvalidData` 錯誤（預設）
+    #[default]
+    Strict,
+    /// 略過該筆記錄並輸出警告，繼續讀取後續記錄
+    #[allow(dead_code)]
+    Lenient,
 }
 
-/// 讀取追加格式的檔案
-pub fn read_append_file<P: AsRef<Path>>(path: P) -> FileReadResult {
-    let file = File::open(&path)?;
-    let file_size = file.metadata()?.len();
+/// V5/V6 標頭大小：magic(12) + model(1) + dim(1) + steps(4)
+const HEADER_SIZE_V6: u64 = 18;
 
-    // 根據檔案大小計算最佳緩衝區大小
-    let buffer_size = calculate_read_buffer_size(file_size);
-    let mut reader = BufReader::with_capacity(buffer_size, file);
+/// 從讀取器讀取 ULEB128 編碼的 u32 值
+fn read_uleb128<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    uleb128::read_from_reader(reader)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+}
 
+/// 讀取並解析檔案標頭（支援 V5、V6 與 V7）
+fn read_header<R: Read>(reader: &mut R) -> std::io::Result<FileHeader> {
     // 檢查魔術標頭
     let mut magic_buf = [0u8; 12];
     reader.read_exact(&mut magic_buf)?;
-    if magic_buf != MAGIC_HEADER {
+
+    let (flags, data_offset) = if magic_buf == MAGIC_HEADER {
+        let mut flags_buf = [0u8; 2];
+        reader.read_exact(&mut flags_buf)?;
+        (u16::from_le_bytes(flags_buf), header_size())
+    } else if magic_buf == MAGIC_HEADER_V6 || magic_buf == MAGIC_HEADER_V5 {
+        // V5 與 V6 結構相同，皆沒有 flags 欄位
+        (0, HEADER_SIZE_V6)
+    } else {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             "File format error: magic header mismatch",
         ));
+    };
+
+    // 不認得的旗標代表較新版本加入的選用區段，無法確定其位置與長度
+    let unknown_flags = flags & !KNOWN_FLAGS;
+    if unknown_flags != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "Unsupported header flags 0x{unknown_flags:04x}: file was written by a newer version"
+            ),
+        ));
     }
 
     // 讀取檔案參數
@@ -47,15 +90,469 @@ pub fn read_append_file<P: AsRef<Path>>(path: P) -> FileReadResult {
     reader.read_exact(&mut dim_buf)?;
     reader.read_exact(&mut steps_buf)?;
 
-    let model = u8::from_le_bytes(model_buf);
-    let dim = u8::from_le_bytes(dim_buf);
-    let steps = u32::from_le_bytes(steps_buf);
+    // 讀取依旗標出現的延伸欄位
+    let mut stream_id = 0;
+    if flags & FLAG_STREAM_ID != 0 {
+        let mut stream_buf = [0u8; 4];
+        reader.read_exact(&mut stream_buf)?;
+        stream_id = u32::from_le_bytes(stream_buf);
+    }
+    let mut top_k = 0;
+    if flags & FLAG_TOP_K != 0 {
+        let mut top_k_buf = [0u8; 1];
+        reader.read_exact(&mut top_k_buf)?;
+        top_k = top_k_buf[0];
+    }
+    let mut warmup = 0;
+    if flags & FLAG_WARMUP != 0 {
+        let mut warmup_buf = [0u8; 4];
+        reader.read_exact(&mut warmup_buf)?;
+        warmup = u32::from_le_bytes(warmup_buf);
+    }
+    let mut master_seed = None;
+    if flags & FLAG_MASTER_SEED != 0 {
+        let mut master_seed_buf = [0u8; 8];
+        reader.read_exact(&mut master_seed_buf)?;
+        master_seed = Some(u64::from_le_bytes(master_seed_buf));
+    }
+    let mut eigenvalue_floor = None;
+    if flags & FLAG_EIGENVALUE_FLOOR != 0 {
+        let mut eigenvalue_floor_buf = [0u8; 8];
+        reader.read_exact(&mut eigenvalue_floor_buf)?;
+        eigenvalue_floor = Some(f64::from_le_bytes(eigenvalue_floor_buf));
+    }
+
+    Ok(FileHeader {
+        flags,
+        model: u8::from_le_bytes(model_buf),
+        dim: u8::from_le_bytes(dim_buf),
+        steps: u32::from_le_bytes(steps_buf),
+        stream_id,
+        top_k,
+        warmup,
+        master_seed,
+        eigenvalue_floor,
+        data_offset: data_offset + extension_size(flags),
+    })
+}
+
+/// 只讀取檔案標頭，不讀取資料記錄
+pub fn read_file_header<P: AsRef<Path>>(path: P) -> std::io::Result<FileHeader> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read_header(&mut reader)
+}
+
+/// 檔案標頭與末尾元數據的摘要
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct FileSummary {
+    /// 魔術標頭中的格式版本（5、6 或 7）
+    pub version: u8,
+    pub header: FileHeader,
+    /// 末尾元數據中的 (總數, 每筆特徵值數量)；沒有結束標記時為 `None`
+    pub metadata: Option<(usize, usize)>,
+}
+
+/// 只讀取標頭與末尾元數據，不讀取資料記錄
+#[allow(dead_code)]
+pub fn read_file_summary<P: AsRef<Path>>(path: P) -> std::io::Result<FileSummary> {
+    let file = File::open(&path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let header = read_header(&mut reader)?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut magic_buf = [0u8; 12];
+    reader.read_exact(&mut magic_buf)?;
+    // read_header 已驗證魔術標頭，最後一個字元即版本號
+    let version = magic_buf[11] - b'0';
+
+    let metadata = if file_len < header.data_offset + METADATA_SIZE {
+        None
+    } else {
+        read_file_metadata(&mut reader, file_len)?
+    };
+
+    Ok(FileSummary {
+        version,
+        header,
+        metadata,
+    })
+}
+
+/// 已完成檔案的儲存效率摘要
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageSummary {
+    /// 實際檔案大小（bytes）
+    pub file_size: u64,
+    /// 記錄數量
+    pub records: usize,
+    /// 以 `calculate_expected_file_size` 估計的大小（假設 seed 為 1..=records，
+    /// 並計入標頭延伸欄位與時間戳記）
+    pub expected_size: u64,
+}
+
+impl StorageSummary {
+    /// 每筆記錄平均佔用的 bytes（含標頭與尾端）；沒有記錄時為 NaN
+    pub fn bytes_per_record(&self) -> f64 {
+        self.file_size as f64 / self.records as f64
+    }
+}
+
+impl std::fmt::Display for StorageSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let difference = self.file_size as f64 / self.expected_size as f64 * 100.0 - 100.0;
+        write!(
+            f,
+            "Storage: {} for {} records ({:.2} bytes/record), estimate {} ({:+.2}%)",
+            format_bytes(self.file_size),
+            format_number_with_commas(self.records),
+            self.bytes_per_record(),
+            format_bytes(self.expected_size),
+            difference
+        )
+    }
+}
+
+/// 讀取已完成檔案的大小與記錄數量，並與預估大小比較
+///
+/// seed 以 ULEB128 編碼，實際每筆記錄的大小取決於 seed 的值；
+/// 只處理過部分或較大 seed 的檔案會與連續 seed 的估計值不同。
+/// 沒有結束標記的未完成檔案返回 `InvalidData` 錯誤。
+pub fn read_storage_summary<P: AsRef<Path>>(path: P) -> std::io::Result<StorageSummary> {
+    let file_size = std::fs::metadata(&path)?.len();
+    let summary = read_file_summary(&path)?;
+    let Some((records, eigenvalues_per_run)) = summary.metadata else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Storage summary requires a finished file (EOF marker missing)",
+        ));
+    };
+
+    let header = summary.header;
+    let timestamp_bytes = if header.has_timestamps() {
+        TIMESTAMP_SIZE * records as u64
+    } else {
+        0
+    };
+    let expected_size = calculate_expected_file_size(records, eigenvalues_per_run)
+        + extension_size(header.flags)
+        + timestamp_bytes;
+
+    Ok(StorageSummary {
+        file_size,
+        records,
+        expected_size,
+    })
+}
+
+/// 取得檔案中的記錄總數，不讀取特徵值資料
+///
+/// 已完成的檔案直接使用末尾元數據中的總數（此時不驗證 CRC32）；
+/// 沒有結束標記的未完成檔案才退回掃描式計數。
+pub fn read_record_count<P: AsRef<Path>>(path: P) -> std::io::Result<(FileHeader, usize)> {
+    let file = File::open(&path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let header = read_header(&mut reader)?;
+    if file_len < header.data_offset + METADATA_SIZE {
+        return Ok((header, 0)); // 檔案太小，可能是空檔案
+    }
+
+    if let Some((total_count, _eigenvalues_per_run)) = read_file_metadata(&mut reader, file_len)? {
+        return Ok((header, total_count));
+    }
+
+    let (data, _) = scan_read_data(&mut reader, &header, ScanStrictness::default())?;
+    Ok((header, data.len()))
+}
+
+/// 逐筆讀取記錄的迭代器，記憶體用量與檔案大小無關
+///
+/// 已完成的檔案依末尾元數據讀取固定數量的記錄，任何讀取錯誤都會回報，
+/// 啟用 CRC32 時於最後一筆記錄之後比對校驗值；未完成的檔案讀到不完整的記錄即結束。
+/// 特徵值數量與第一筆記錄不一致時回報錯誤。回報錯誤後迭代器即結束。
+pub struct RecordStream {
+    reader: ChecksumReader<BufReader<File>>,
+    timestamps: bool,
+    /// 已完成檔案尚未讀取的記錄數量（未完成的檔案為 `None`）
+    remaining: Option<usize>,
+    eigenvalues_per_run: Option<usize>,
+    /// 已完成檔案在結束標記前儲存的 CRC32
+    stored_checksum: Option<u32>,
+    done: bool,
+}
+
+impl RecordStream {
+    /// 讀取下一筆記錄；沒有更多記錄時返回 `None`
+    fn read_next(&mut self) -> std::io::Result<Option<(u32, Vec<f64>)>> {
+        if self.remaining == Some(0) {
+            if let Some(stored) = self.stored_checksum {
+                let computed = self.reader.checksum();
+                if stored != computed {
+                    return Err(ChecksumError { stored, computed }.into());
+                }
+            }
+            return Ok(None);
+        }
+
+        // 未完成的檔案讀到不完整的記錄即結束，已完成的檔案則回報錯誤
+        let finished = self.remaining.is_some();
+        let incomplete = |e: std::io::Error| if finished { Err(e) } else { Ok(None) };
+
+        let seed = match read_uleb128(&mut self.reader) {
+            Ok(seed) => seed,
+            Err(e) => return incomplete(e),
+        };
+        if self.timestamps {
+            let mut time_buf = [0u8; TIMESTAMP_SIZE as usize];
+            if let Err(e) = self.reader.read_exact(&mut time_buf) {
+                return incomplete(e);
+            }
+        }
+
+        let mut count_buf = [0u8; 1];
+        if let Err(e) = self.reader.read_exact(&mut count_buf) {
+            return incomplete(e);
+        }
+        let eigenvalue_count = count_buf[0] as usize;
+        if eigenvalue_count == 0 {
+            return incomplete(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid eigenvalue count: cannot be zero",
+            ));
+        }
+        let expected = *self.eigenvalues_per_run.get_or_insert(eigenvalue_count);
+        if eigenvalue_count != expected {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Inconsistent eigenvalue count for seed {seed}: expected {expected}, found {eigenvalue_count}"
+                ),
+            ));
+        }
+
+        let mut values_buf = vec![0u8; eigenvalue_count * 8];
+        if let Err(e) = self.reader.read_exact(&mut values_buf) {
+            return incomplete(e);
+        }
+        let eigenvalues = values_buf
+            .chunks_exact(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().expect("chunk of 8 bytes")))
+            .collect();
+
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        Ok(Some((seed, eigenvalues)))
+    }
+}
+
+impl Iterator for RecordStream {
+    type Item = std::io::Result<(u32, Vec<f64>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_next().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+/// 開啟追加格式的檔案並返回標頭與逐筆讀取記錄的迭代器
+///
+/// 與 `read_append_file` 讀到相同的記錄，但不會一次載入所有資料，
+/// 適合分析大於記憶體的檔案。時間戳記會被略過。
+pub fn stream_append_file<P: AsRef<Path>>(path: P) -> std::io::Result<(FileHeader, RecordStream)> {
+    let file = File::open(&path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::with_capacity(calculate_read_buffer_size(file_len), file);
+
+    let header = read_header(&mut reader)?;
+    let metadata = if file_len < header.data_offset + METADATA_SIZE {
+        Some((0, 0)) // 檔案太小，可能是空檔案
+    } else {
+        read_file_metadata(&mut reader, file_len)?
+    };
+
+    let mut stored_checksum = None;
+    if let Some((total_count, _)) = metadata {
+        if total_count > 0 && header.has_checksum() {
+            reader.seek(SeekFrom::Start(file_len - METADATA_SIZE - CRC32_SIZE))?;
+            let mut crc_buf = [0u8; 4];
+            reader.read_exact(&mut crc_buf)?;
+            stored_checksum = Some(u32::from_le_bytes(crc_buf));
+        }
+    }
+    reader.seek(SeekFrom::Start(header.data_offset))?;
+
+    let stream = RecordStream {
+        reader: ChecksumReader::new(reader),
+        timestamps: header.has_timestamps(),
+        remaining: metadata.map(|(total_count, _)| total_count),
+        eigenvalues_per_run: metadata
+            .map(|(_, eigenvalues_per_run)| eigenvalues_per_run)
+            .filter(|&n| n > 0),
+        stored_checksum,
+        done: false,
+    };
+    Ok((header, stream))
+}
+
+/// 讀取追加格式的檔案
+pub fn read_append_file<P: AsRef<Path>>(path: P) -> FileReadResult {
+    read_append_file_with_strictness(path, ScanStrictness::default())
+}
+
+/// 讀取追加格式的檔案，並指定未完成檔案中特徵值數量不一致時的處理方式
+///
+/// 已完成的檔案以末尾元數據中的數量讀取，任何不一致都會返回錯誤，不受 `strictness` 影響。
+pub fn read_append_file_with_strictness<P: AsRef<Path>>(
+    path: P,
+    strictness: ScanStrictness,
+) -> FileReadResult {
+    let (header, (data, _)) = read_records_from_path(path, strictness)?;
+    Ok((data, header.model, header.dim, header.steps))
+}
+
+/// 讀取追加格式的檔案，並返回每筆記錄的計算時間
+#[allow(dead_code)]
+pub fn read_append_file_with_timestamps<P: AsRef<Path>>(path: P) -> TimestampedReadResult {
+    let (_, records) = read_records_from_path(path, ScanStrictness::default())?;
+    Ok(records)
+}
+
+/// 將已完成的檔案讀取為 seed 與單一連續的特徵值緩衝區
+///
+/// 第 i 筆記錄的特徵值為 `values[i * stride..(i + 1) * stride]`，即 `records × stride` 的列主序矩陣
+/// （亦即每欄一筆記錄的 `stride × records` 行主序矩陣，可直接交給 BLAS 或 `DMatrix::from_vec`）。
+/// 不為每筆記錄配置各自的 `Vec`，記錄順序與 `read_append_file` 相同。
+///
+/// 只支援帶有結束標記的完整檔案：記錄數量與 stride 取自末尾元數據，並驗證 CRC32；
+/// 未完成的檔案返回 `InvalidData` 錯誤，須改用 `read_append_file` 掃描讀取。
+#[allow(dead_code)]
+pub fn read_append_file_flat<P: AsRef<Path>>(path: P) -> FlatReadResult {
+    let file = File::open(&path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::with_capacity(calculate_read_buffer_size(file_len), file);
+
+    let header = read_header(&mut reader)?;
+    let metadata = if file_len < header.data_offset + METADATA_SIZE {
+        None
+    } else {
+        read_file_metadata(&mut reader, file_len)?
+    };
+    let Some((total_count, stride)) = metadata else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Flat read requires a finished file (EOF marker missing); use read_append_file for partial files",
+        ));
+    };
+
+    reader.seek(SeekFrom::Start(header.data_offset))?;
+    let timestamps = header.has_timestamps();
+    if !header.has_checksum() {
+        return read_records_flat(&mut reader, total_count, stride, timestamps);
+    }
+
+    let mut checked = ChecksumReader::new(&mut reader);
+    let (seeds, values, stride) = read_records_flat(&mut checked, total_count, stride, timestamps)?;
+    let computed = checked.checksum();
+    verify_stored_checksum(&mut reader, file_len, computed)?;
+    Ok((seeds, values, stride))
+}
+
+/// 依序讀取指定數量的記錄至平坦緩衝區，每筆記錄的特徵值數量須等於 `stride`
+fn read_records_flat<R: Read>(
+    reader: &mut R,
+    total_count: usize,
+    stride: usize,
+    timestamps: bool,
+) -> FlatReadResult {
+    let mut seeds = Vec::with_capacity(total_count);
+    let mut values = Vec::with_capacity(total_count * stride);
+    let mut val_buf = vec![0u8; stride * 8];
+
+    for _ in 0..total_count {
+        seeds.push(read_uleb128(reader)?);
+
+        if timestamps {
+            let mut time_buf = [0u8; TIMESTAMP_SIZE as usize];
+            reader.read_exact(&mut time_buf)?;
+        }
+
+        let mut count_buf = [0u8; 1];
+        reader.read_exact(&mut count_buf)?;
+        let eigenvalue_count = count_buf[0] as usize;
+        if eigenvalue_count == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid eigenvalue count: cannot be zero",
+            ));
+        }
+        if eigenvalue_count != stride {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Eigenvalue count mismatch: expected {}, actual {}",
+                    format_number_with_commas(stride),
+                    format_number_with_commas(eigenvalue_count)
+                ),
+            ));
+        }
+
+        reader.read_exact(&mut val_buf)?;
+        values.extend(
+            val_buf
+                .chunks_exact(8)
+                .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunk has 8 bytes"))),
+        );
+    }
+
+    Ok((seeds, values, stride))
+}
+
+/// 比對資料區段之後儲存的 CRC32 與讀取記錄時計算的值
+fn verify_stored_checksum(
+    reader: &mut BufReader<File>,
+    file_len: u64,
+    computed: u32,
+) -> std::io::Result<()> {
+    reader.seek(SeekFrom::Start(file_len - METADATA_SIZE - CRC32_SIZE))?;
+    let mut crc_buf = [0u8; 4];
+    reader.read_exact(&mut crc_buf)?;
+    let stored = u32::from_le_bytes(crc_buf);
+
+    if stored != computed {
+        return Err(ChecksumError { stored, computed }.into());
+    }
+    Ok(())
+}
+
+/// 讀取標頭與所有記錄（含時間戳記）
+fn read_records_from_path<P: AsRef<Path>>(
+    path: P,
+    strictness: ScanStrictness,
+) -> std::io::Result<(FileHeader, Records)> {
+    let file = File::open(&path)?;
+    let file_size = file.metadata()?.len();
+
+    // 根據檔案大小計算最佳緩衝區大小
+    let buffer_size = calculate_read_buffer_size(file_size);
+    let mut reader = BufReader::with_capacity(buffer_size, file);
+
+    let header = read_header(&mut reader)?;
 
     // 嘗試從檔案末尾讀取元數據
     let file_len = reader.get_ref().metadata()?.len();
-    if file_len < 18 + 8 + 8 + 1 {
-        // magic(12) + model(1) + dim(1) + steps(4) + eof_marker(8) + count(8) + eigenvalues_per_run(1)
-        return Ok((Vec::new(), model, dim, steps)); // 檔案太小，可能是空檔案
+    if file_len < header.data_offset + METADATA_SIZE {
+        // 標頭 + eof_marker(8) + count(8) + eigenvalues_per_run(1)
+        return Ok((header, (Vec::new(), Vec::new()))); // 檔案太小，可能是空檔案
     }
 
     // 檢查是否有完整的結束標記
@@ -63,13 +560,19 @@ pub fn read_append_file<P: AsRef<Path>>(path: P) -> FileReadResult {
 
     let data = if let Some((total_count, eigenvalues_per_run)) = metadata {
         // 有完整的結束標記，使用快速讀取
-        read_with_metadata(&mut reader, total_count, eigenvalues_per_run)?
+        read_with_metadata(
+            &mut reader,
+            &header,
+            file_len,
+            total_count,
+            eigenvalues_per_run,
+        )?
     } else {
         // 沒有結束標記，掃描式讀取（用於未完成的檔案）
-        scan_read_data(&mut reader)?
+        scan_read_data(&mut reader, &header, strictness)?
     };
 
-    Ok((data, model, dim, steps))
+    Ok((header, data))
 }
 
 /// 嘗試從檔案末尾讀取元數據
@@ -104,18 +607,48 @@ fn read_file_metadata(
 /// 使用元數據快速讀取
 fn read_with_metadata(
     reader: &mut BufReader<File>,
+    header: &FileHeader,
+    file_len: u64,
     total_count: usize,
     eigenvalues_per_run: usize,
-) -> std::io::Result<Vec<(u32, Vec<f64>)>> {
-    // 回到數據開始位置
-    reader.seek(SeekFrom::Start(18))?; // 跳過魔術標頭(12) + model(1) + dim(1) + steps(4)
+) -> std::io::Result<Records> {
+    // 回到數據開始位置（跳過標頭）
+    reader.seek(SeekFrom::Start(header.data_offset))?;
+    let timestamps = header.has_timestamps();
+
+    if !header.has_checksum() {
+        return read_records(reader, total_count, eigenvalues_per_run, timestamps);
+    }
+
+    // 讀取記錄的同時計算 CRC32，再與資料區段之後儲存的值比對
+    let mut checked = ChecksumReader::new(&mut *reader);
+    let data = read_records(&mut checked, total_count, eigenvalues_per_run, timestamps)?;
+    let computed = checked.checksum();
+    verify_stored_checksum(reader, file_len, computed)?;
 
+    Ok(data)
+}
+
+/// 依序讀取指定數量的記錄
+fn read_records<R: Read>(
+    reader: &mut R,
+    total_count: usize,
+    eigenvalues_per_run: usize,
+    timestamps: bool,
+) -> std::io::Result<Records> {
     let mut data = Vec::with_capacity(total_count);
+    let mut times = Vec::with_capacity(if timestamps { total_count } else { 0 });
 
     for _ in 0..total_count {
         // 讀取 ULEB128 編碼的 seed
         let seed = read_uleb128(reader)?;
 
+        if timestamps {
+            let mut time_buf = [0u8; 4];
+            reader.read_exact(&mut time_buf)?;
+            times.push(u32::from_le_bytes(time_buf));
+        }
+
         let mut count_buf = [0u8; 1]; // 1 byte (u8)
         reader.read_exact(&mut count_buf)?;
         let eigenvalue_count_u8 = u8::from_le_bytes(count_buf);
@@ -150,15 +683,23 @@ fn read_with_metadata(
         data.push((seed, eigenvalues));
     }
 
-    Ok(data)
+    Ok((data, times))
 }
 
 /// 掃描式讀取（用於沒有結束標記的檔案）
-fn scan_read_data(reader: &mut BufReader<File>) -> std::io::Result<Vec<(u32, Vec<f64>)>> {
-    // 回到數據開始位置
-    reader.seek(SeekFrom::Start(18))?; // 跳過魔術標頭(12) + model(1) + dim(1) + steps(4)
+///
+/// 所有記錄的特徵值數量須與第一筆記錄相同，不一致時依 `strictness` 返回錯誤或略過該筆記錄。
+fn scan_read_data(
+    reader: &mut BufReader<File>,
+    header: &FileHeader,
+    strictness: ScanStrictness,
+) -> std::io::Result<Records> {
+    // 回到數據開始位置（跳過標頭）
+    reader.seek(SeekFrom::Start(header.data_offset))?;
 
     let mut data = Vec::new();
+    let mut times = Vec::new();
+    let mut eigenvalues_per_run = None;
 
     #[allow(clippy::while_let_loop)]
     loop {
@@ -168,6 +709,11 @@ fn scan_read_data(reader: &mut BufReader<File>) -> std::io::Result<Vec<(u32, Vec
             Err(_) => break, // 到達檔案末尾或遇到錯誤
         };
 
+        let mut time_buf = [0u8; 4];
+        if header.has_timestamps() && reader.read_exact(&mut time_buf).is_err() {
+            break; // 不完整的數據塊
+        }
+
         // 檢查是否遇到 EOF 標記
         // 由於使用 ULEB128，需要在讀取 eigenvalue count 後檢查 EOF
         let mut count_buf = [0u8; 1]; // 1 byte (u8)
@@ -209,8 +755,31 @@ fn scan_read_data(reader: &mut BufReader<File>) -> std::io::Result<Vec<(u32, Vec
             break; // 不完整的特徵值數據
         }
 
+        // 以第一筆記錄的數量為準，檢查後續記錄是否一致
+        let expected = *eigenvalues_per_run.get_or_insert(eigenvalue_count);
+        if eigenvalue_count != expected {
+            let message = format!(
+                "Inconsistent eigenvalue count for seed {seed}: expected {expected}, found {eigenvalue_count}"
+            );
+            match strictness {
+                ScanStrictness::Strict => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        message,
+                    ));
+                }
+                ScanStrictness::Lenient => {
+                    eprintln!("WARNING: {message}; skipping record");
+                    continue;
+                }
+            }
+        }
+
         data.push((seed, eigenvalues));
+        if header.has_timestamps() {
+            times.push(u32::from_le_bytes(time_buf));
+        }
     }
 
-    Ok(data)
+    Ok((data, times))
 }
@@ -0,0 +1,146 @@
+//! 檔案修復 - 移除中斷寫入留下的不完整記錄
+//!
+//! 程式在寫入記錄途中中斷時，檔案末尾可能只有 seed 與部分特徵值。
+//! 掃描式讀取會忽略這些位元組，但續寫時新記錄會接在其後，使檔案無法解析。
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::file_format::{EOF_MARKER, METADATA_SIZE, TIMESTAMP_SIZE};
+use super::reader::read_file_header;
+use super::uleb128::{self, Uleb128Error};
+
+/// 將檔案截斷至最後一筆完整記錄之後，返回保留的記錄數量
+///
+/// 已寫入結束標記的完整檔案不會被修改。未完成的檔案會從資料區段開頭掃描，
+/// 只截斷在檔案末尾中斷、讀到檔案結尾仍不完整的最後一筆記錄，使下次續寫從乾淨的記錄邊界開始。
+///
+/// 資料區段中間完整但特徵值數量為 0 或與第一筆記錄不一致的記錄不是中斷寫入造成的，
+/// 截斷會一併刪除其後所有有效記錄，因此與嚴格的掃描式讀取相同返回 `InvalidData` 錯誤，檔案不變。
+pub fn truncate_to_last_valid_record<P: AsRef<Path>>(path: P) -> std::io::Result<usize> {
+    let path = path.as_ref();
+    let header = read_file_header(path)?;
+
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    // 完整檔案：直接使用末尾元數據中的總數
+    if file_len >= header.data_offset + METADATA_SIZE {
+        reader.seek(SeekFrom::Start(file_len - METADATA_SIZE))?;
+        let mut eof_buf = [0u8; 8];
+        reader.read_exact(&mut eof_buf)?;
+        if eof_buf == EOF_MARKER {
+            let mut count_buf = [0u8; 8];
+            reader.read_exact(&mut count_buf)?;
+            return Ok(u64::from_le_bytes(count_buf) as usize);
+        }
+    }
+
+    reader.seek(SeekFrom::Start(header.data_offset))?;
+    let mut valid_end = header.data_offset;
+    let mut record_count = 0;
+    let mut eigenvalues_per_run = None;
+
+    while let Some(record) = read_record(&mut reader, eigenvalues_per_run, header.has_timestamps())?
+    {
+        eigenvalues_per_run = Some(record.eigenvalues.len());
+        valid_end = reader.stream_position()?;
+        record_count += 1;
+    }
+
+    if valid_end < file_len {
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(valid_end)?;
+    }
+
+    Ok(record_count)
+}
+
+/// 掃描時讀到的一筆完整記錄
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ScannedRecord {
+    pub seed: u32,
+    /// 計算時間（未設定 `FLAG_TIMESTAMPS` 時為 0）
+    pub minutes: u32,
+    pub eigenvalues: Vec<f64>,
+}
+
+/// 讀取一筆記錄
+///
+/// 讀到檔案結尾時（包括記錄只寫了一部分）返回 `Ok(None)`。完整記錄的特徵值數量為 0
+/// 或與 `eigenvalues_per_run` 不一致、或 seed 編碼無效時返回 `InvalidData` 錯誤，
+/// 訊息與嚴格的掃描式讀取相同。
+pub(super) fn read_record<R: Read>(
+    reader: &mut R,
+    eigenvalues_per_run: Option<usize>,
+    timestamps: bool,
+) -> std::io::Result<Option<ScannedRecord>> {
+    let seed = match uleb128::read_from_reader(reader) {
+        Ok(seed) => seed,
+        Err(Uleb128Error::IoError(_) | Uleb128Error::IncompleteEncoding) => return Ok(None),
+        Err(e) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e.to_string(),
+            ));
+        }
+    };
+
+    let mut minutes = 0;
+    if timestamps {
+        let mut time_buf = [0u8; TIMESTAMP_SIZE as usize];
+        if !read_to_end_of_record(reader, &mut time_buf)? {
+            return Ok(None);
+        }
+        minutes = u32::from_le_bytes(time_buf);
+    }
+
+    let mut count_buf = [0u8; 1];
+    if !read_to_end_of_record(reader, &mut count_buf)? {
+        return Ok(None);
+    }
+    let eigenvalue_count = count_buf[0] as usize;
+
+    let mut values_buf = vec![0u8; eigenvalue_count * 8];
+    if !read_to_end_of_record(reader, &mut values_buf)? {
+        return Ok(None);
+    }
+
+    if eigenvalue_count == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid eigenvalue count for seed {seed}: cannot be zero"),
+        ));
+    }
+    if let Some(expected) = eigenvalues_per_run.filter(|&n| n != eigenvalue_count) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Inconsistent eigenvalue count for seed {seed}: expected {expected}, found {eigenvalue_count}"
+            ),
+        ));
+    }
+
+    let eigenvalues = values_buf
+        .chunks_exact(8)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunk has 8 bytes")))
+        .collect();
+    Ok(Some(ScannedRecord {
+        seed,
+        minutes,
+        eigenvalues,
+    }))
+}
+
+/// 讀滿 `buf`；先讀到檔案結尾時返回 `false`（不完整的記錄）
+fn read_to_end_of_record<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
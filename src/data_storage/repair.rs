@@ -0,0 +1,214 @@
+//! 檔案整體修復 - 將受損的資料檔案整理為可快速讀取的完整檔案（`--repair`）
+//!
+//! 結合個別的修復工具：捨棄最後一筆完整記錄之後的位元組（同 `recovery`）、
+//! 略過資料區段中間多餘的結束標記（例如完成後又被直接追加記錄的檔案）、
+//! 移除重複與超出 `1..=num_runs` 的 seed（同 `prune`），最後重寫檔案並寫入新的結束標記。
+//! 檔案以串流方式掃描，記憶體用量與記錄數量無關（重複 seed 的檢查只需 `num_runs` 位元）。
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::checksum::ChecksumError;
+use super::file_format::{CRC32_SIZE, EOF_MARKER, FileHeader};
+use super::lock::WriteLock;
+use super::prune::rewrite_with;
+use super::reader::{read_file_header, read_file_summary};
+use super::recovery::{ScannedRecord, read_record};
+
+/// 修復一個資料檔案的結果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// 修復後的記錄數量
+    pub records: usize,
+    /// 最後一筆完整記錄之後被捨棄的位元組數（不完整的記錄或損壞的尾端）
+    pub truncated_bytes: u64,
+    /// 移除的資料區段中間多餘的結束標記數量
+    pub stray_footers: usize,
+    /// 移除的重複 seed 記錄數量（保留第一次出現的記錄）
+    pub duplicates: usize,
+    /// 移除的 seed 不在 `1..=num_runs` 的記錄數量
+    pub out_of_range: usize,
+    /// 是否重寫了檔案；為 false 時檔案原本即為乾淨的完整檔案，未被修改
+    pub rewritten: bool,
+}
+
+/// 掃描用的讀取器：記錄目前位置，並累加第一個結束區段之前資料的 CRC32
+struct ScanReader {
+    inner: BufReader<File>,
+    position: u64,
+    file_len: u64,
+    /// 尚未遇到結束區段且檔案啟用 CRC32 時存在
+    hasher: Option<crc32fast::Hasher>,
+}
+
+impl ScanReader {
+    /// 目前位置是否為完整的結束區段（[CRC32] + 結束標記 + 元數據），不改變讀取位置
+    fn at_footer(&mut self, header: FileHeader) -> std::io::Result<bool> {
+        if self.file_len - self.position < header.trailer_size() {
+            return Ok(false);
+        }
+        let checksum_len = if header.has_checksum() {
+            CRC32_SIZE as usize
+        } else {
+            0
+        };
+        let mut buf = [0u8; CRC32_SIZE as usize + EOF_MARKER.len()];
+        let peek = &mut buf[..checksum_len + EOF_MARKER.len()];
+        self.inner.read_exact(peek)?;
+        self.inner.seek_relative(-(peek.len() as i64))?;
+        Ok(&peek[checksum_len..] == EOF_MARKER)
+    }
+
+    /// 讀過目前位置的結束區段；第一個結束區段儲存的 CRC32 與其前的資料不符時返回校驗錯誤
+    fn skip_footer(&mut self, header: FileHeader) -> std::io::Result<()> {
+        let mut footer = vec![0u8; header.trailer_size() as usize];
+        self.inner.read_exact(&mut footer)?;
+        self.position += footer.len() as u64;
+
+        if let Some(hasher) = self.hasher.take() {
+            let stored = u32::from_le_bytes(
+                footer[..CRC32_SIZE as usize]
+                    .try_into()
+                    .expect("4 bytes of CRC32"),
+            );
+            let computed = hasher.finalize();
+            if stored != computed {
+                return Err(ChecksumError { stored, computed }.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for ScanReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// 掃描結果
+struct Scan {
+    /// `rewritten` 尚未設定的修復報告
+    report: RepairReport,
+    /// 每筆記錄的特徵值數量（沒有記錄時為 `None`）
+    eigenvalues_per_run: Option<usize>,
+    /// 檔案是否以結束區段結尾
+    finished: bool,
+}
+
+/// 從資料區段開頭逐筆掃描 `path`，將要保留的記錄依序交給 `keep`
+///
+/// 略過中間多餘的結束標記，遇到只寫了一部分的記錄即停止；同一 seed 出現多次時保留第一筆。
+/// 無效的記錄（如特徵值數量不一致）與讀取錯誤返回 `InvalidData` 或原本的 I/O 錯誤，
+/// 第一個結束區段儲存的 CRC32 與其前的資料不符時返回校驗錯誤。
+fn scan_records(
+    path: &Path,
+    header: FileHeader,
+    num_runs: usize,
+    mut keep: impl FnMut(&ScannedRecord) -> std::io::Result<()>,
+) -> std::io::Result<Scan> {
+    let mut inner = BufReader::new(File::open(path)?);
+    let file_len = inner.get_ref().metadata()?.len();
+    let start = header.data_offset.min(file_len);
+    inner.seek(SeekFrom::Start(start))?;
+    let mut reader = ScanReader {
+        inner,
+        position: start,
+        file_len,
+        hasher: header.has_checksum().then(crc32fast::Hasher::new),
+    };
+
+    let timestamps = header.has_timestamps();
+    let mut report = RepairReport::default();
+    let mut seen = vec![0u64; num_runs.div_ceil(64)];
+    let mut eigenvalues_per_run = None;
+    let mut finished = false;
+    let mut pos = start;
+    loop {
+        if reader.at_footer(header)? {
+            reader.skip_footer(header)?;
+            pos = reader.position;
+            if pos == file_len {
+                finished = true;
+                break;
+            }
+            report.stray_footers += 1;
+            continue;
+        }
+
+        // 只有讀到檔案結尾（包括只寫了一部分的記錄）才視為資料結束；
+        // 無效的記錄與讀取錯誤直接返回，不可當作尾端捨棄
+        let Some(record) = read_record(&mut reader, eigenvalues_per_run, timestamps)? else {
+            break;
+        };
+        eigenvalues_per_run = Some(record.eigenvalues.len());
+        pos = reader.position;
+
+        let seed = record.seed as usize;
+        if !(1..=num_runs).contains(&seed) {
+            report.out_of_range += 1;
+            continue;
+        }
+        let (word, bit) = ((seed - 1) / 64, 1u64 << ((seed - 1) % 64));
+        if seen[word] & bit != 0 {
+            report.duplicates += 1;
+            continue;
+        }
+        seen[word] |= bit;
+        report.records += 1;
+        keep(&record)?;
+    }
+    report.truncated_bytes = file_len - pos;
+
+    Ok(Scan {
+        report,
+        eigenvalues_per_run,
+        finished,
+    })
+}
+
+/// 修復 `path`，使其成為只含 `1..=num_runs` 中不重複 seed 的完整檔案
+///
+/// 從資料區段開頭逐筆掃描，略過中間多餘的結束標記，遇到只寫了一部分的記錄即停止並捨棄其後的位元組；
+/// 同一 seed 出現多次時保留第一筆。需要修改時以原本的標頭與時間戳記重寫檔案（見 `rewrite_with`），
+/// 重寫後帶有新的 CRC32 與結束標記。
+///
+/// 重寫前先比對第一個結束區段儲存的 CRC32（已完成的檔案即為其結束區段，完成後又被追加的檔案則為多餘的結束標記），
+/// 不符時返回校驗錯誤且不修改檔案，因為資料本身已損壞，重寫只會讓錯誤的數值帶著新的校驗碼。
+/// 同樣地，無效的記錄與讀取錯誤直接返回且不修改檔案，而非將其後的資料當作損壞的尾端捨棄。
+/// 修復期間持有寫入鎖，其他程序正在寫入同一檔案時返回錯誤。
+pub fn repair_file<P: AsRef<Path>>(path: P, num_runs: usize) -> std::io::Result<RepairReport> {
+    let path = path.as_ref();
+    let _lock = WriteLock::acquire(path)?;
+
+    let header = read_file_header(path)?;
+    let scan = scan_records(path, header, num_runs, |_| Ok(()))?;
+    let mut report = scan.report;
+
+    // 結構完整且沒有多餘記錄的檔案，結束標記中的數量也一致時不需重寫
+    if scan.finished
+        && report.stray_footers == 0
+        && report.duplicates == 0
+        && report.out_of_range == 0
+    {
+        let expected = (report.records, scan.eigenvalues_per_run.unwrap_or(0));
+        if read_file_summary(path)?.metadata == Some(expected) {
+            return Ok(report);
+        }
+    }
+
+    rewrite_with(path, ".repair.tmp", header, |writer| {
+        scan_records(path, header, num_runs, |record| {
+            writer.append_with_timestamp(record.seed, &record.eigenvalues, record.minutes)
+        })
+        .map(|_| ())
+    })?;
+    report.rewritten = true;
+    Ok(report)
+}
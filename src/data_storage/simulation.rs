@@ -2,13 +2,55 @@
 //!
 //! 提供 `EigenvalueSimulation` 結構體，這是整個模組的主要入口點。
 
-use super::parallel_compute::run_model_simulation;
-use super::reader::read_append_file;
+use super::csv_export::{CsvExportOptions, export_csv};
+use super::csv_import::import_csv_to_dat;
+use super::file_format::{DEFAULT_FLAGS, FileHeader, MAX_EIGENVALUES_PER_RUN, MAX_STEPS};
+use super::parallel_compute::{
+    fold_model_runs, run_model_simulation, run_model_with_sink, run_shared_path_simulation,
+};
+use super::progress::{count_append_progress_with_header, get_remaining_seeds};
+use super::prune::prune_out_of_range;
+use super::reader::{read_append_file, read_file_header};
+use super::repair::{RepairReport, repair_file};
+use super::sink::{MemorySink, ResultSink, trace_file_path};
+use super::writer::{AppendOnlyWriter, write_buffer_capacity};
+use crate::display_utils::format_number_with_commas;
 use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::{AggregateMode, EigenvalueOptions, SortOrder};
+use crate::rng_matrix::SeedHashing;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// `EigenvalueSimulation::benchmark` 的量測結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkResult {
+    /// 完成的模擬次數
+    pub runs: usize,
+    /// 經過的實際時間
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    /// 每秒完成的模擬次數
+    pub fn runs_per_second(&self) -> f64 {
+        self.runs as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// 每次模擬的平均耗時（微秒）
+    pub fn micros_per_run(&self) -> f64 {
+        self.elapsed.as_secs_f64() * 1e6 / self.runs as f64
+    }
+}
 
 /// 特徵值模擬配置結構體
 /// 封裝所有模擬參數，提供統一的運算和讀取接口
+///
+/// 啟用 `serde` feature 時可序列化：反序列化時只有 `model`、`dim`、`steps`、`num_runs`
+/// 為必填，其餘欄位省略時與 `new` 的預設值相同；`deadline` 不會被序列化。
+/// 反序列化的配置會經過 `validate` 檢查，無效時反序列化失敗。
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawEigenvalueSimulation"))]
 pub struct EigenvalueSimulation {
     /// 約翰森模型
     pub model: JohansenModel,
@@ -18,35 +60,644 @@ pub struct EigenvalueSimulation {
     pub steps: usize,
     /// 模擬運行次數
     pub num_runs: usize,
+    /// 特徵值儲存順序（預設降序）
+    pub sort_order: SortOrder,
+    /// RNG 子串流編號（0 為預設串流）
+    pub stream_id: u32,
+    /// 與 seed 混合的主種子（`None` 為直接以 seed 推導 RNG 種子）
+    ///
+    /// 記錄在檔案標頭，且資料存放於帶 `_master{M}` 後綴的檔案。
+    pub master_seed: Option<u64>,
+    /// 子串流 0 的 seed 轉換為 RNG 種子的方式（預設以 SplitMix64 混合）
+    ///
+    /// 記錄在檔案標頭的 `FLAG_MIXED_SEEDS`，檔名不變；與既有檔案的設定不同時無法續寫。
+    pub seed_hashing: SeedHashing,
+    /// 每筆記錄只保留最大的 K 個特徵值（`None` 為保留全部）
+    ///
+    /// 截斷後跡統計量（`calculate_trace_percentiles`）為保留的 K 個特徵值之和，
+    /// 而非全部特徵值之和；最大特徵值統計量不受影響。
+    pub top_k: Option<usize>,
+    /// 絕對值低於此下限的特徵值設為 0（`None` 為保留原值）
+    ///
+    /// 記錄在檔案標頭，且資料存放於帶 `_floor{eps}` 後綴的檔案。
+    pub eigenvalue_floor: Option<f64>,
+    /// 每筆記錄儲存全部特徵值或只存 `[跡, 最大特徵值]`（預設為全部特徵值）
+    ///
+    /// 記錄在檔案標頭的 `FLAG_AGGREGATES`，且資料存放於帶 `_agg` 後綴的檔案；
+    /// `read_data` 返回的每筆記錄即為這兩個值。
+    pub aggregate: AggregateMode,
+    /// 條件數檢查門檻：`∫F F' dt` 的倒數條件數低於此值的 seed 會被回報（`None` 為不檢查）
+    ///
+    /// 只影響診斷輸出，不改變計算結果與檔案內容。
+    pub min_condition: Option<f64>,
+    /// 回報求解結果含有不可忽略虛部的 seed（預設為 false）
+    ///
+    /// 與 `min_condition` 相同，只影響診斷輸出，不改變計算結果與檔案內容。
+    pub diagnose_complex: bool,
+    /// 不使用 rayon 並行，依 seed 順序逐一計算（預設為 false）
+    ///
+    /// 用於排查非決定性問題，或不宜產生大量執行緒的環境；檔案中的記錄順序也因此固定。
+    pub single_thread: bool,
+    /// 寫入時依經過時間刷新緩衝區的間隔（`None` 為只依記錄數量刷新）
+    ///
+    /// 計算緩慢的模型可能很久才累積到刷新所需的記錄數量，設定後可限制中斷時遺失的資料量。
+    pub checkpoint_every: Option<Duration>,
+    /// 寫入緩衝區大小（bytes，`None` 為預設的 `WRITE_BUFFER_CAPACITY`）
+    ///
+    /// 只影響寫入時的系統呼叫次數，不影響檔案內容，因此不記錄在標頭或檔名中。
+    pub write_buffer: Option<usize>,
+    /// 同時將每次模擬的跡追加至資料檔案旁的 `.trace` 檔案（預設為 false）
+    ///
+    /// 一次計算即同時得到完整特徵值與精簡的跡統計量，見 `TraceFileSink`。
+    pub trace_file: bool,
+    /// 只計算這些 seed（`None` 為計算 1..=num_runs）
+    ///
+    /// 已存在於資料檔案中的 seed 會被略過，新結果追加至同一個檔案。
+    pub seeds: Option<Vec<u32>>,
+    /// 捨棄布朗運動路徑的前 W 個時間步（預設為 0，不捨棄）
+    ///
+    /// 記錄在檔案標頭，且資料存放於帶 `_warmup{W}` 後綴的檔案；實際使用的步數為 `steps - W`。
+    pub warmup: usize,
+    /// 在每筆記錄中存放計算時間（Unix epoch 分鐘數，預設為 false）
+    ///
+    /// 記錄在檔案標頭的 `FLAG_TIMESTAMPS`，每筆記錄多 4 bytes；
+    /// 與既有檔案的設定不同時無法續寫。
+    pub timestamps: bool,
+    /// 資料檔案存放的資料夾（預設為 `data`）
+    pub data_dir: PathBuf,
+    /// 取代 `model` 預設檔名的完整路徑（`None` 為依 `data_dir` 與參數命名）
+    ///
+    /// 只套用於 `model` 本身；查詢其他模型的檔名時仍使用預設命名。
+    pub output_file: Option<PathBuf>,
+    /// 停止派送新 seed 的截止時間（`None` 為計算全部 seed）
+    ///
+    /// 已開始的 seed 仍會完成並寫入，檔案正常結束，之後可續傳其餘的 seed。
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub deadline: Option<Instant>,
+}
+
+/// 反序列化時尚未檢查的配置，經 `validate` 檢查後才轉換為 `EigenvalueSimulation`
+///
+/// 欄位與 `EigenvalueSimulation` 相同（`deadline` 除外）。
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawEigenvalueSimulation {
+    model: JohansenModel,
+    dim: usize,
+    steps: usize,
+    num_runs: usize,
+    #[serde(default)]
+    sort_order: SortOrder,
+    #[serde(default)]
+    stream_id: u32,
+    #[serde(default)]
+    master_seed: Option<u64>,
+    #[serde(default)]
+    seed_hashing: SeedHashing,
+    #[serde(default)]
+    top_k: Option<usize>,
+    #[serde(default)]
+    eigenvalue_floor: Option<f64>,
+    #[serde(default)]
+    aggregate: AggregateMode,
+    #[serde(default)]
+    min_condition: Option<f64>,
+    #[serde(default)]
+    diagnose_complex: bool,
+    #[serde(default)]
+    single_thread: bool,
+    #[serde(default)]
+    checkpoint_every: Option<Duration>,
+    #[serde(default)]
+    write_buffer: Option<usize>,
+    #[serde(default)]
+    trace_file: bool,
+    #[serde(default)]
+    seeds: Option<Vec<u32>>,
+    #[serde(default)]
+    warmup: usize,
+    #[serde(default)]
+    timestamps: bool,
+    #[serde(default = "default_data_dir")]
+    data_dir: PathBuf,
+    #[serde(default)]
+    output_file: Option<PathBuf>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawEigenvalueSimulation> for EigenvalueSimulation {
+    type Error = std::io::Error;
+
+    fn try_from(raw: RawEigenvalueSimulation) -> std::io::Result<Self> {
+        let simulation = Self {
+            model: raw.model,
+            dim: raw.dim,
+            steps: raw.steps,
+            num_runs: raw.num_runs,
+            sort_order: raw.sort_order,
+            stream_id: raw.stream_id,
+            master_seed: raw.master_seed,
+            seed_hashing: raw.seed_hashing,
+            top_k: raw.top_k,
+            eigenvalue_floor: raw.eigenvalue_floor,
+            aggregate: raw.aggregate,
+            min_condition: raw.min_condition,
+            diagnose_complex: raw.diagnose_complex,
+            single_thread: raw.single_thread,
+            checkpoint_every: raw.checkpoint_every,
+            write_buffer: raw.write_buffer,
+            trace_file: raw.trace_file,
+            seeds: raw.seeds,
+            warmup: raw.warmup,
+            timestamps: raw.timestamps,
+            data_dir: raw.data_dir,
+            output_file: raw.output_file,
+            deadline: None,
+        };
+        simulation.validate()?;
+        Ok(simulation)
+    }
+}
+
+/// 反序列化時未指定 `data_dir` 的預設值（與 `EigenvalueSimulation::new` 相同）
+#[cfg(feature = "serde")]
+fn default_data_dir() -> PathBuf {
+    PathBuf::from("data")
 }
 
 impl EigenvalueSimulation {
     /// 創建新的特徵值模擬配置
+    ///
+    /// 每筆記錄的特徵值數量上限等條件由 `validate` 檢查，運行前即拒絕無效的配置。
     pub fn new(model: JohansenModel, dim: usize, steps: usize, num_runs: usize) -> Self {
         Self {
             model,
             dim,
             steps,
             num_runs,
+            sort_order: SortOrder::default(),
+            stream_id: 0,
+            master_seed: None,
+            seed_hashing: SeedHashing::default(),
+            top_k: None,
+            eigenvalue_floor: None,
+            aggregate: AggregateMode::default(),
+            min_condition: None,
+            diagnose_complex: false,
+            single_thread: false,
+            checkpoint_every: None,
+            write_buffer: None,
+            trace_file: false,
+            seeds: None,
+            warmup: 0,
+            timestamps: false,
+            data_dir: PathBuf::from("data"),
+            output_file: None,
+            deadline: None,
+        }
+    }
+
+    /// 設定特徵值的儲存順序
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
+    /// 設定 RNG 子串流編號
+    ///
+    /// 同一 seed 在不同串流會得到不同且各自可重現的結果，
+    /// 可用於估計 Monte Carlo 誤差。不同串流的資料存放於不同檔案。
+    pub fn with_stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = stream_id;
+        self
+    }
+
+    /// 設定與 seed 混合的主種子
+    ///
+    /// seed 仍只是記錄編號；更換主種子即可為同一段 seed 範圍產生另一組獨立的資料集，
+    /// 每個 seed 在同一主種子下的結果仍可重現。
+    pub fn with_master_seed(mut self, master_seed: u64) -> Self {
+        self.master_seed = Some(master_seed);
+        self
+    }
+
+    /// 設定子串流 0 的 seed 轉換為 RNG 種子的方式
+    ///
+    /// `SeedHashing::Legacy` 與先前版本相同，直接以 seed 作為種子，用於續寫先前版本產生的檔案；
+    /// 常態矩陣的 chunk 切分已改變，因此新的記錄不會逐位元重現舊資料。
+    pub fn with_seed_hashing(mut self, seed_hashing: SeedHashing) -> Self {
+        self.seed_hashing = seed_hashing;
+        self
+    }
+
+    /// 只保留最大的 K 個特徵值
+    ///
+    /// 截斷會記錄在檔案標頭，且資料存放於帶 `_top{K}` 後綴的檔案。
+    /// `top_k` 須介於 1 與 `dim` 之間，否則運行時返回錯誤（見 `validate`）。
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// 將絕對值低於 `floor` 的特徵值設為 0
+    ///
+    /// F 矩陣秩不足時，求解可能產生接近 0 的數值雜訊；這些值設為 0 後不再貢獻跡統計量。
+    /// 下限會記錄在檔案標頭，且資料存放於帶 `_floor{eps}` 後綴的檔案。
+    /// `floor` 須為正的有限數，否則 `validate` 返回錯誤。
+    pub fn with_eigenvalue_floor(mut self, floor: f64) -> Self {
+        self.eigenvalue_floor = Some(floor);
+        self
+    }
+
+    /// 設定每筆記錄儲存的內容
+    ///
+    /// `AggregateMode::Both` 每次模擬只存跡與最大特徵值兩個 `f64`，維度高時大幅減少檔案大小，
+    /// 仍可分析兩種統計量；但無法再由檔案計算需要個別特徵值的統計量（例如 `TraceStatisticAggregator`）。
+    /// 檔案已存有跡，因此不需搭配 `with_trace_file`。
+    pub fn with_aggregate(mut self, aggregate: AggregateMode) -> Self {
+        self.aggregate = aggregate;
+        self
+    }
+
+    /// 設定條件數檢查門檻
+    ///
+    /// 計算結束後，倒數條件數低於 `min_condition` 的 seed 會以警告列出。
+    pub fn with_min_condition(mut self, min_condition: f64) -> Self {
+        self.min_condition = Some(min_condition);
+        self
+    }
+
+    /// 設定是否檢查複數特徵值
+    ///
+    /// 計算結束後，求解結果含有虛部不可忽略之特徵值的 seed 會以警告列出，
+    /// 並附上各 seed 的複數特徵值數量。
+    pub fn with_diagnose_complex(mut self, diagnose_complex: bool) -> Self {
+        self.diagnose_complex = diagnose_complex;
+        self
+    }
+
+    /// 設定是否以單執行緒依序計算
+    pub fn with_single_thread(mut self, single_thread: bool) -> Self {
+        self.single_thread = single_thread;
+        self
+    }
+
+    /// 設定依經過時間刷新寫入緩衝區的間隔
+    pub fn with_checkpoint_every(mut self, interval: Duration) -> Self {
+        self.checkpoint_every = Some(interval);
+        self
+    }
+
+    /// 設定寫入緩衝區大小（bytes）
+    ///
+    /// 須在 `MIN_WRITE_BUFFER_CAPACITY..=MAX_WRITE_BUFFER_CAPACITY` 範圍內，
+    /// 否則運行時返回錯誤（見 `validate`）。
+    pub fn with_write_buffer(mut self, capacity: usize) -> Self {
+        self.write_buffer = Some(capacity);
+        self
+    }
+
+    /// 設定是否同時寫入 `.trace` 跡檔案
+    ///
+    /// 不可與 `AggregateMode::Both` 併用，否則 `validate` 返回錯誤。
+    pub fn with_trace_file(mut self, trace_file: bool) -> Self {
+        self.trace_file = trace_file;
+        self
+    }
+
+    /// 跡檔案的路徑（資料檔案路徑的副檔名改為 `.trace`）
+    #[allow(dead_code)]
+    pub fn trace_filename(&self) -> std::io::Result<String> {
+        let data_path = self.try_get_filename(self.model)?;
+        Ok(trace_file_path(Path::new(&data_path))
+            .to_string_lossy()
+            .to_string())
+    }
+
+    /// 只計算指定的 seed，而非 1..=num_runs
+    ///
+    /// 用於重現或除錯特定的模擬；`read_data` 仍只檢查 1..=num_runs 的記錄。
+    pub fn with_seeds(mut self, seeds: Vec<u32>) -> Self {
+        self.seeds = Some(seeds);
+        self
+    }
+
+    /// 捨棄布朗運動路徑的前 `warmup` 個時間步（burn-in）
+    ///
+    /// `warmup` 須小於 `steps`，否則運行時返回錯誤（見 `validate`）。
+    pub fn with_warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// 設定是否在每筆記錄中存放計算時間
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// 設定資料檔案存放的資料夾
+    #[allow(dead_code)]
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
+    /// 將 `model` 的資料檔案指定為 `path`，取代預設的檔名與資料夾
+    ///
+    /// 不會創建 `path` 的上層資料夾；既有檔案的標頭須與目前的參數相符才能續寫，
+    /// 不符時計算返回錯誤，不會像預設路徑的檔案一樣被刪除重建。
+    pub fn with_output_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_file = Some(path.into());
+        self
+    }
+
+    /// 設定停止派送新 seed 的截止時間，用於在固定的時間預算內計算
+    ///
+    /// 截止後不再開始新的 seed，已寫入的結果可於下次執行時續傳。
+    /// `run_with_sink` 與 `fold_runs` 等不寫入檔案的模式不受影響。
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// 檢查配置是否有效，無效時返回 `ErrorKind::InvalidInput` 錯誤
+    ///
+    /// 各個 `with_*` 不會檢查參數，所有運行模擬的方法都會先呼叫此方法。
+    pub fn validate(&self) -> std::io::Result<()> {
+        let invalid = |message: String| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                message,
+            ))
+        };
+
+        if self.steps > MAX_STEPS {
+            return invalid(format!(
+                "steps ({}) must not exceed {MAX_STEPS}",
+                self.steps
+            ));
+        }
+        if let Some(top_k) = self.top_k {
+            if !(1..=self.dim).contains(&top_k) {
+                return invalid(format!(
+                    "top-k ({top_k}) must be between 1 and dim ({})",
+                    self.dim
+                ));
+            }
+        }
+        // 每筆記錄以 u8 儲存特徵值數量，在計算開始前即拒絕，而非寫入第一筆記錄時才失敗
+        let eigenvalues_per_run = self.num_eigenvalues();
+        if eigenvalues_per_run > MAX_EIGENVALUES_PER_RUN {
+            return invalid(format!(
+                "dim ({}) needs {eigenvalues_per_run} eigenvalues per run for model {}, which must not exceed {MAX_EIGENVALUES_PER_RUN}",
+                self.dim,
+                self.model.to_number()
+            ));
+        }
+        if let Some(floor) = self.eigenvalue_floor {
+            if !(floor > 0.0 && floor.is_finite()) {
+                return invalid(format!(
+                    "eigenvalue floor ({floor}) must be a positive number"
+                ));
+            }
+        }
+        // 只存聚合值的資料檔案已含有跡，跡檔案的加總則會誤將最大特徵值計入
+        if self.trace_file && self.aggregate == AggregateMode::Both {
+            return invalid(
+                "a trace file cannot be combined with aggregate files (the data file already stores the trace)"
+                    .to_string(),
+            );
+        }
+        if self.write_buffer.is_some() {
+            write_buffer_capacity(self.write_buffer)?;
+        }
+        if self.warmup >= self.steps {
+            return invalid(format!(
+                "warmup ({}) must be less than steps ({})",
+                self.warmup, self.steps
+            ));
         }
+        Ok(())
+    }
+
+    /// 特徵值計算所用的選項
+    pub(crate) fn eigenvalue_options(&self) -> EigenvalueOptions {
+        EigenvalueOptions {
+            sort_order: self.sort_order,
+            stream_id: self.stream_id,
+            master_seed: self.master_seed,
+            seed_hashing: self.seed_hashing,
+            top_k: self.top_k,
+            eigenvalue_floor: self.eigenvalue_floor,
+            aggregate: self.aggregate,
+            min_condition: self.min_condition,
+            diagnose_complex: self.diagnose_complex,
+            warmup: self.warmup,
+        }
+    }
+
+    /// 每筆記錄的特徵值數量
+    ///
+    /// 未截斷時為 `model.eigenvalues_per_run(dim)`；設定 `with_top_k` 時為 K；
+    /// `AggregateMode::Both` 時為 2（跡與最大特徵值）。
+    /// 可用於預先配置緩衝區或檢查讀回的資料。
+    pub fn num_eigenvalues(&self) -> usize {
+        match self.aggregate {
+            AggregateMode::Both => 2,
+            AggregateMode::Full => self
+                .top_k
+                .unwrap_or_else(|| self.model.eigenvalues_per_run(self.dim)),
+        }
+    }
+
+    /// 資料檔案應有的標頭
+    ///
+    /// 先呼叫 `validate`，配置無效（例如 `steps` 超過標頭的 u32 寬度）時返回錯誤。
+    pub(crate) fn file_header(&self) -> std::io::Result<FileHeader> {
+        self.validate()?;
+        Ok(FileHeader::new(
+            DEFAULT_FLAGS,
+            self.model.to_number(),
+            self.dim as u8,
+            self.steps as u32, // `validate` 已確保不超過 MAX_STEPS
+        )
+        .with_stream_id(self.stream_id)
+        .with_top_k(self.top_k.map_or(0, |k| k as u8))
+        .with_warmup(self.warmup as u32)
+        .with_master_seed(self.master_seed)
+        .with_eigenvalue_floor(self.eigenvalue_floor)
+        .with_timestamps(self.timestamps)
+        .with_mixed_seeds(self.seed_hashing.differs_from_legacy(self.stream_id))
+        .with_aggregates(self.aggregate == AggregateMode::Both))
     }
 
     /// 運行支援斷點續傳的大規模特徵值計算並保存結果
     /// 這是主要的模擬運算接口，針對單一模型進行計算
+    ///
+    /// `try_run_simulation` 的便利版本：讀取進度或寫入失敗時 panic。
+    #[allow(dead_code)]
     pub fn run_simulation(&self) {
-        run_model_simulation(self, false);
+        self.try_run_simulation()
+            .unwrap_or_else(|e| panic!("Simulation failed: {e}"));
     }
 
     /// 運行模擬（安靜模式）
     /// 不輸出進度信息，適合在批量處理或測試環境中使用
+    #[allow(dead_code)]
     pub fn run_simulation_quiet(&self) {
-        run_model_simulation(self, true);
+        self.try_run_simulation_quiet()
+            .unwrap_or_else(|e| panic!("Simulation failed: {e}"));
+    }
+
+    /// 運行模擬，讀取進度、寫入執行緒失敗或檔案格式不相容時返回錯誤
+    pub fn try_run_simulation(&self) -> std::io::Result<()> {
+        run_model_simulation(self, false)
+    }
+
+    /// 運行模擬（安靜模式），失敗時返回錯誤
+    pub fn try_run_simulation_quiet(&self) -> std::io::Result<()> {
+        run_model_simulation(self, true)
+    }
+
+    /// 以共用布朗運動路徑運行 `models` 的模擬，每個模型寫入各自的資料檔案
+    ///
+    /// 除模型外沿用此配置的參數。每個 seed 的路徑只生成一次，再求解所有模型，
+    /// 省去重複生成路徑的時間；結果與逐一對各模型呼叫 `try_run_simulation` 相同。
+    /// `with_output_file` 的路徑只套用於 `self.model`。
+    pub fn try_run_shared_path(&self, models: &[JohansenModel]) -> std::io::Result<()> {
+        run_shared_path_simulation(&self.for_models(models), false)
+    }
+
+    /// 以共用布朗運動路徑運行模擬（安靜模式），失敗時返回錯誤
+    pub fn try_run_shared_path_quiet(&self, models: &[JohansenModel]) -> std::io::Result<()> {
+        run_shared_path_simulation(&self.for_models(models), true)
+    }
+
+    /// 為每個模型建立沿用此配置的模擬（指定輸出路徑只保留給 `self.model`）
+    fn for_models(&self, models: &[JohansenModel]) -> Vec<Self> {
+        models
+            .iter()
+            .map(|&model| Self {
+                model,
+                output_file: self.output_override(model).cloned(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// 運行模擬並將結果送至自訂的接收端，不讀寫資料檔案
+    ///
+    /// `make_sink` 在寫入執行緒中呼叫一次以建立接收端，例如 `MemorySink::new`。
+    /// 此模式不支援斷點續傳，每次都計算 seed 1..=num_runs。
+    pub fn run_with_sink<S, F>(&self, make_sink: F) -> std::io::Result<S::Output>
+    where
+        S: ResultSink + 'static,
+        S::Output: 'static,
+        F: FnOnce() -> std::io::Result<S> + Send + 'static,
+    {
+        run_model_with_sink(self, make_sink)
+    }
+
+    /// 計算 seed 1..=num_runs 並以資料檔案格式（標頭、記錄、結束標記）寫入 `out`
+    ///
+    /// 不讀寫資料檔案也不支援斷點續傳，例如將結果直接寫至標準輸出供管線使用；
+    /// 輸出可存成檔案後以 `read_append_file` 讀取。
+    pub fn stream_to<W: std::io::Write + Send + 'static>(&self, out: W) -> std::io::Result<()> {
+        let header = self.file_header()?;
+        let write_buffer = self.write_buffer;
+        self.run_with_sink(move || AppendOnlyWriter::for_stream(out, header, write_buffer, true))
+    }
+
+    /// 在記憶體中計算 `num_runs` 次模擬並量測經過時間，不讀寫資料檔案
+    ///
+    /// 計時涵蓋特徵值計算與結果收集，包含寫入執行緒的啟動與結束。
+    pub fn benchmark(&self) -> std::io::Result<BenchmarkResult> {
+        let start = Instant::now();
+        let data = self.run_with_sink(|| Ok(MemorySink::new()))?;
+        let elapsed = start.elapsed();
+
+        if data.len() != self.num_runs {
+            return Err(std::io::Error::other(format!(
+                "Benchmark produced {} results, expected {}",
+                data.len(),
+                self.num_runs
+            )));
+        }
+        Ok(BenchmarkResult {
+            runs: data.len(),
+            elapsed,
+        })
+    }
+
+    /// 計算所有模擬並以 `fold` 歸約每次模擬的特徵值，不保存資料也不寫入檔案
+    ///
+    /// `fold(acc, seed, eigenvalues)` 在計算執行緒中累加，各執行緒的結果再以 `combine` 合併，
+    /// 記憶體用量與 `num_runs` 無關，適合計算總和、最大值、直方圖等串流統計量。
+    /// `init` 須為 `combine` 的單位元素（例如加總時為 0），且 `combine` 須滿足結合律；
+    /// 合併順序不固定，浮點數加總的結果可能有捨入誤差層級的差異。配置無效時返回錯誤（見 `validate`）。
+    #[allow(dead_code)]
+    pub fn fold_runs<T, F, C>(&self, init: T, fold: F, combine: C) -> std::io::Result<T>
+    where
+        T: Clone + Send + Sync,
+        F: Fn(T, u32, &[f64]) -> T + Sync + Send,
+        C: Fn(T, T) -> T + Sync + Send,
+    {
+        self.validate()?;
+        Ok(fold_model_runs(self, init, fold, combine))
+    }
+
+    /// 列出指定模型的資料檔案在 1..=num_runs 中缺少的 seed（由小到大）
+    ///
+    /// 用於檢查續算後的檔案是否完整：檔案不存在時返回全部 seed；
+    /// 檔案無法讀取或標頭參數不符時返回錯誤，而不像續算時視為空檔案。
+    #[allow(dead_code)]
+    pub fn seed_gaps(&self, model: JohansenModel) -> std::io::Result<Vec<u32>> {
+        let filename = self.try_get_filename(model)?;
+        if !std::path::Path::new(&filename).exists() {
+            return Ok(get_remaining_seeds(self.num_runs, &[]));
+        }
+
+        let (data, _model, _dim, _steps) = read_append_file(&filename)?;
+        let expected = FileHeader {
+            model: model.to_number(),
+            ..self.file_header()?
+        };
+        read_file_header(&filename)?.check_matches(expected)?;
+
+        let completed_seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
+        Ok(get_remaining_seeds(self.num_runs, &completed_seeds))
+    }
+
+    /// 移除資料檔案中 seed 不在 `1..=num_runs` 的記錄，返回移除的記錄數量
+    ///
+    /// 檔案不存在或沒有範圍外的 seed 時不修改檔案；標頭參數不符時返回錯誤。
+    pub fn prune_out_of_range(&self) -> std::io::Result<usize> {
+        let filename = self.try_get_filename(self.model)?;
+        if !std::path::Path::new(&filename).exists() {
+            return Ok(0);
+        }
+        read_file_header(&filename)?.check_matches(self.file_header()?)?;
+        prune_out_of_range(&filename, self.num_runs)
+    }
+
+    /// 修復資料檔案，使其成為只含 `1..=num_runs` 中不重複 seed 的完整檔案
+    ///
+    /// 捨棄不完整的末尾、多餘的結束標記、重複與範圍外的 seed 後重寫並寫入新的結束標記，見 `repair_file`。
+    /// 檔案不存在時返回 `None`；標頭參數不符時返回錯誤。
+    pub fn repair(&self) -> std::io::Result<Option<RepairReport>> {
+        let filename = self.try_get_filename(self.model)?;
+        if !std::path::Path::new(&filename).exists() {
+            return Ok(None);
+        }
+        read_file_header(&filename)?.check_matches(self.file_header()?)?;
+        repair_file(&filename, self.num_runs).map(Some)
     }
 
     /// 從追加格式讀取指定模型的所有特徵值數據（包含seed）
     /// 注意：返回的數據可能無序，如需有序請自行排序
     pub fn read_all_data(&self) -> std::io::Result<Vec<(u32, Vec<f64>)>> {
-        let filename = self.get_filename(self.model);
+        let filename = self.try_get_filename(self.model)?;
         read_append_file(&filename).map(|(data, _model, _dim, _steps)| data)
     }
 
@@ -54,6 +705,9 @@ impl EigenvalueSimulation {
     /// 只返回 seed <= num_runs 的記錄，符合當前模擬配置的預期範圍
     /// 注意：返回的數據可能無序，如需有序請自行排序
     ///
+    /// `AggregateMode::Both` 時每筆記錄為 `[跡, 最大特徵值]`
+    /// （見 `AGGREGATE_TRACE_INDEX` 與 `AGGREGATE_MAX_INDEX`），而非全部特徵值。
+    ///
     /// # 錯誤處理
     /// 當可用數據數量不等於 num_runs 時返回錯誤，建議：
     /// - 使用 `read_all_data()` 讀取所有可用數據，或
@@ -86,38 +740,198 @@ impl EigenvalueSimulation {
         Ok(filtered_data)
     }
 
-    /// 獲取當前模型的檔案名稱
+    /// 與 `read_data` 相同，但記錄依 seed 升序排列
     ///
-    /// 這是唯一的檔案命名入口點。所有內部檔案操作都通過此方法獲取檔案名稱，
-    /// 確保檔案命名邏輯的一致性。如果需要自定義檔案命名規則，
-    /// 可以繼承此 struct 並重寫此方法。
+    /// 單執行緒寫入的檔案本來就依 seed 排列，此時不需重新排序；
+    /// 否則以不穩定排序處理（每個 seed 只出現一次，結果與穩定排序相同）。
+    pub fn read_data_sorted(&self) -> std::io::Result<Vec<(u32, Vec<f64>)>> {
+        let mut data = self.read_data()?;
+        if !data.is_sorted_by_key(|(seed, _)| *seed) {
+            data.sort_unstable_by_key(|(seed, _)| *seed);
+        }
+        Ok(data)
+    }
+
+    /// 將 `seed,eigenvalue_1,...` 格式的 CSV 匯入為目前配置的資料檔案，返回匯入的記錄數量
+    ///
+    /// 模型、維度、步數與其他標頭欄位取自目前的配置；資料檔案已存在時返回錯誤。
+    /// 每列的特徵值數量須等於 `num_eigenvalues()`，否則返回錯誤。
+    pub fn import_csv<P: AsRef<std::path::Path>>(&self, csv_path: P) -> std::io::Result<usize> {
+        let filename = self.try_get_filename(self.model)?;
+        import_csv_to_dat(
+            csv_path,
+            filename,
+            self.file_header()?,
+            self.num_eigenvalues(),
+        )
+    }
+
+    /// 將目前配置的資料依 seed 排序後匯出為 CSV，返回匯出的記錄數量
     ///
-    /// 檔案會自動存放在 data/ 資料夾中，如果資料夾不存在會自動創建。
+    /// 欄位名稱與數字格式由 `options` 決定，見 `CsvExportOptions`；彙總檔案的欄位為 `trace,max`。
+    #[allow(dead_code)]
+    pub fn export_csv<P: AsRef<std::path::Path>>(
+        &self,
+        csv_path: P,
+        options: &CsvExportOptions,
+    ) -> std::io::Result<usize> {
+        let data = self.read_data_sorted()?;
+        export_csv(
+            csv_path,
+            &data,
+            self.aggregate == AggregateMode::Both,
+            options,
+        )?;
+        Ok(data.len())
+    }
+
+    /// 將目前配置的資料依 seed 排序後匯出為 Parquet，返回匯出的記錄數量
+    ///
+    /// 欄位為 `seed` 與每個特徵值索引一欄（彙總檔案為 `trace` 與 `max`），見 `parquet_export`。
+    #[cfg(feature = "parquet")]
+    pub fn export_parquet<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<usize> {
+        let data = self.read_data_sorted()?;
+        let aggregates = self.aggregate == AggregateMode::Both;
+        super::parquet_export::export_parquet(path, &data, aggregates)?;
+        Ok(data.len())
+    }
+
+    /// 取得目前已完成的記錄數量
+    ///
+    /// 已完成的檔案只讀取末尾元數據，不掃描全部資料；檔案不存在時返回 0。
+    pub fn completed_count(&self) -> std::io::Result<usize> {
+        count_append_progress_with_header(self.try_get_filename(self.model)?, self.file_header()?)
+    }
+
+    /// 獲取當前模型的檔案名稱
+    ///
+    /// `try_get_filename` 的便利版本，供二進位程式使用：
     /// 如果創建資料夾失敗，程式會 panic，因為沒有資料夾就無法儲存檔案。
-    /// 使用 PathBuf 確保跨平台路徑分隔符的正確性。
+    /// 函式庫使用者應改用 `try_get_filename` 自行處理錯誤。
     pub fn get_filename(&self, model: JohansenModel) -> String {
-        use std::path::PathBuf;
-
-        // 確保 data 資料夾存在，失敗時應該 panic 而不是繼續
-        let data_dir = PathBuf::from("data");
-        std::fs::create_dir_all(&data_dir).unwrap_or_else(|e| {
+        self.try_get_filename(model).unwrap_or_else(|e| {
             panic!(
                 "Failed to create data directory '{}': {}. \
                  This is required for storing simulation results. \
                  Please check file system permissions.",
-                data_dir.display(),
+                self.data_dir.display(),
                 e
             );
-        });
+        })
+    }
+
+    /// 獲取當前模型的檔案名稱，資料夾無法創建時返回錯誤
+    ///
+    /// 這是唯一的檔案命名入口點。所有內部檔案操作都通過此方法獲取檔案名稱，
+    /// 確保檔案命名邏輯的一致性。如果需要自定義檔案命名規則，
+    /// 可以繼承此 struct 並重寫此方法。
+    ///
+    /// 升序儲存的資料會加上 `_asc` 後綴，非預設的 RNG 子串流會加上 `_stream{n}` 後綴，
+    /// 截斷為最大 K 個特徵值的資料會加上 `_top{K}` 後綴，捨棄起始時間步的資料會加上
+    /// `_warmup{W}` 後綴，只存跡與最大特徵值的資料會加上 `_agg` 後綴，避免與預設設定的檔案混用。
+    ///
+    /// 檔案會自動存放在 `data_dir`（預設 data/）資料夾中，如果資料夾不存在會自動創建；
+    /// 創建失敗（例如權限不足，或同名檔案已存在）時返回該 IO 錯誤。
+    /// 使用 PathBuf 確保跨平台路徑分隔符的正確性。
+    ///
+    /// 以 `with_output_file` 指定路徑時，`model` 本身直接使用該路徑，不創建資料夾。
+    pub fn try_get_filename(&self, model: JohansenModel) -> std::io::Result<String> {
+        if let Some(path) = self.output_override(model) {
+            return Ok(path.to_string_lossy().to_string());
+        }
+
+        // 確保資料夾存在
+        std::fs::create_dir_all(&self.data_dir)?;
 
+        Ok(self.file_path(model).to_string_lossy().to_string())
+    }
+
+    /// 檢查指定模型的資料檔案是否存在
+    ///
+    /// 只組合檔案路徑並檢查是否存在，不會創建資料夾。
+    #[allow(dead_code)]
+    pub fn data_exists(&self, model: JohansenModel) -> bool {
+        match self.output_override(model) {
+            Some(path) => path.exists(),
+            None => self.file_path(model).exists(),
+        }
+    }
+
+    /// 檢查此配置下是否有任何模型的資料檔案存在
+    #[allow(dead_code)]
+    pub fn any_data_exists(&self) -> bool {
+        JohansenModel::all_models()
+            .into_iter()
+            .any(|model| self.data_exists(model))
+    }
+
+    /// `model` 的指定輸出路徑（只有模擬本身的模型會被取代）
+    fn output_override(&self, model: JohansenModel) -> Option<&PathBuf> {
+        self.output_file.as_ref().filter(|_| model == self.model)
+    }
+
+    /// 組合指定模型的資料檔案路徑（不檢查或創建資料夾）
+    fn file_path(&self, model: JohansenModel) -> PathBuf {
         // 使用 PathBuf 構建跨平台的檔案路徑，使用新的檔案擴展名
+        let order_suffix = match self.sort_order {
+            SortOrder::Descending => "",
+            SortOrder::Ascending => "_asc",
+        };
+        let stream_suffix = match self.stream_id {
+            0 => String::new(),
+            id => format!("_stream{id}"),
+        };
+        let master_suffix = match self.master_seed {
+            None => String::new(),
+            Some(master_seed) => format!("_master{master_seed}"),
+        };
+        let top_k_suffix = match self.top_k {
+            None => String::new(),
+            Some(k) => format!("_top{k}"),
+        };
+        let warmup_suffix = match self.warmup {
+            0 => String::new(),
+            w => format!("_warmup{w}"),
+        };
+        let floor_suffix = match self.eigenvalue_floor {
+            None => String::new(),
+            Some(floor) => format!("_floor{floor:e}"),
+        };
+        let aggregate_suffix = match self.aggregate {
+            AggregateMode::Full => "",
+            AggregateMode::Both => "_agg",
+        };
+        // 兩種記錄格式無法混用，以檔名區分，切換設定時不會覆蓋既有檔案
+        let timestamps_suffix = if self.timestamps { "_ts" } else { "" };
         let filename = format!(
-            "eigenvalues_model{}_dim{}_steps{}.dat",
+            "eigenvalues_model{}_dim{}_steps{}{}{}{}{}{}{}{}{}.dat",
             &model.to_number(),
             self.dim,
-            self.steps
+            self.steps,
+            warmup_suffix,
+            stream_suffix,
+            master_suffix,
+            top_k_suffix,
+            floor_suffix,
+            aggregate_suffix,
+            timestamps_suffix,
+            order_suffix
         );
 
-        data_dir.join(filename).to_string_lossy().to_string()
+        self.data_dir.join(filename)
+    }
+}
+
+/// 簡短的配置摘要，例如 `Model 2, dim=5, steps=10000, runs=10,000,000`
+impl std::fmt::Display for EigenvalueSimulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Model {}, dim={}, steps={}, runs={}",
+            self.model.to_number(),
+            self.dim,
+            self.steps,
+            format_number_with_commas(self.num_runs)
+        )
     }
 }
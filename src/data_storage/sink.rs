@@ -0,0 +1,231 @@
+//! 結果接收端 - 可替換的模擬結果輸出後端
+//!
+//! 寫入執行緒只透過 `ResultSink` 輸出結果，檔案寫入器（`AppendOnlyWriter`）是預設實作；
+//! 其他後端（記憶體、網路、資料庫）只需實作此 trait 即可接上同一套並行計算流程。
+//! `TeeSink` 將同一份結果同時送往兩個接收端，例如完整資料檔案與 `.trace` 跡檔案。
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::reader::stream_append_file;
+use super::writer::AppendOnlyWriter;
+
+/// 模擬結果的接收端
+///
+/// 所有方法都在寫入執行緒中呼叫，因此實作只需 `Send`，不需要內部同步。
+pub trait ResultSink: Send {
+    /// 完成後返回的結果
+    type Output: Send;
+
+    /// 接收一次模擬的特徵值（送達順序不一定依照 seed）
+    fn append(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()>;
+
+    /// 將已接收的結果寫出至儲存裝置（預設不做任何事）
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// 上次呼叫之後是否已到達檢查點（已將結果寫出），並重設此狀態
+    fn take_checkpoint(&mut self) -> bool {
+        false
+    }
+
+    /// 所有結果送達後呼叫，完成輸出並返回結果
+    fn finish(self) -> std::io::Result<Self::Output>;
+}
+
+impl ResultSink for AppendOnlyWriter {
+    type Output = ();
+
+    fn append(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        self.append_eigenvalues(seed, eigenvalues)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buffer()
+    }
+
+    fn take_checkpoint(&mut self) -> bool {
+        AppendOnlyWriter::take_checkpoint(self)
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        AppendOnlyWriter::finish(self)
+    }
+}
+
+/// 將結果收集在記憶體中的接收端，完成時返回所有 `(seed, eigenvalues)`
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    results: Vec<(u32, Vec<f64>)>,
+}
+
+impl MemorySink {
+    /// 創建空的記憶體接收端
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// 同時輸出至兩個接收端，完成時返回 `primary` 的結果
+///
+/// `secondary` 為附帶的輸出（例如跡檔案），任一接收端失敗即返回錯誤。
+/// `primary` 每到達檢查點即刷新 `secondary`，使附帶輸出不落後於已寫出的主要結果。
+pub struct TeeSink<A, B> {
+    primary: A,
+    secondary: B,
+}
+
+impl<A, B> TeeSink<A, B> {
+    /// 以主要接收端與附帶接收端創建
+    pub fn new(primary: A, secondary: B) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl<A: ResultSink, B: ResultSink<Output = ()>> ResultSink for TeeSink<A, B> {
+    type Output = A::Output;
+
+    fn append(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        self.primary.append(seed, eigenvalues)?;
+        self.secondary.append(seed, eigenvalues)?;
+        if self.primary.take_checkpoint() {
+            self.secondary.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.primary.flush()?;
+        self.secondary.flush()
+    }
+
+    fn finish(self) -> std::io::Result<Self::Output> {
+        self.secondary.finish()?;
+        self.primary.finish()
+    }
+}
+
+/// 資料檔案對應的跡檔案路徑（副檔名改為 `.trace`）
+pub fn trace_file_path(data_path: &Path) -> PathBuf {
+    data_path.with_extension("trace")
+}
+
+/// 將每次模擬的跡（特徵值總和）以 `seed,trace` 文字行追加至檔案的接收端
+///
+/// 新檔案先寫入 `seed,trace` 標題列。跡檔案的資料列與資料檔案的記錄順序相同，
+/// 開啟時先與資料檔案對齊，因此續傳後仍包含資料檔案中的每個 seed。
+/// 數值以 `f64` 的最短精確表示輸出，讀回後與資料檔案中特徵值的總和完全相同。
+pub struct TraceFileSink {
+    writer: BufWriter<File>,
+}
+
+impl TraceFileSink {
+    /// 開啟 `path` 並與資料檔案 `data_path` 對齊後追加記錄，檔案不存在時創建並寫入標題列
+    ///
+    /// 中斷的執行可能使兩個檔案的末尾不一致：超出資料檔案的資料列與不完整的末行會被截斷，
+    /// 跡檔案缺少的記錄（例如中斷前尚未刷新，或對既有資料檔案才啟用跡檔案）則從資料檔案補齊。
+    /// 資料列的 seed 與資料檔案的記錄不一致時返回 `InvalidData` 錯誤。
+    /// 呼叫者須先取得資料檔案的寫入鎖，且資料檔案已寫入標頭。
+    pub fn append_to(path: impl AsRef<Path>, data_path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let (_, mut records) = stream_append_file(data_path.as_ref())?;
+        let mismatch = |detail: String| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "{} does not match {}: {detail}; delete it to rebuild from the data file",
+                    path.display(),
+                    data_path.as_ref().display()
+                ),
+            )
+        };
+
+        // 逐行比對跡檔案與資料檔案的記錄，`aligned_len` 為最後一個一致的完整行之後的位置
+        let mut reader = BufReader::new(&mut file);
+        let mut line = String::new();
+        let mut aligned_len = 0;
+        let mut has_header = false;
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            if !has_header {
+                if line != "seed,trace\n" {
+                    return Err(mismatch("missing the seed,trace header".to_string()));
+                }
+                has_header = true;
+            } else {
+                let seed = line
+                    .split_once(',')
+                    .and_then(|(seed, _)| seed.parse::<u32>().ok());
+                match (seed, records.next().transpose()?) {
+                    (_, None) => break,
+                    (Some(seed), Some((data_seed, _))) if seed == data_seed => {}
+                    (_, Some((data_seed, _))) => {
+                        return Err(mismatch(format!(
+                            "expected seed {data_seed}, found line {:?}",
+                            line.trim_end()
+                        )));
+                    }
+                }
+            }
+            aligned_len += read as u64;
+        }
+        drop(reader);
+
+        file.set_len(aligned_len)?;
+        file.seek(SeekFrom::End(0))?;
+        let mut sink = Self {
+            writer: BufWriter::new(file),
+        };
+        if !has_header {
+            writeln!(sink.writer, "seed,trace")?;
+        }
+        for record in records {
+            let (seed, eigenvalues) = record?;
+            sink.append(seed, &eigenvalues)?;
+        }
+        sink.writer.flush()?;
+        Ok(sink)
+    }
+}
+
+impl ResultSink for TraceFileSink {
+    type Output = ();
+
+    fn append(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        writeln!(self.writer, "{seed},{}", eigenvalues.iter().sum::<f64>())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl ResultSink for MemorySink {
+    type Output = Vec<(u32, Vec<f64>)>;
+
+    fn append(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        self.results.push((seed, eigenvalues.to_vec()));
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<Self::Output> {
+        Ok(self.results)
+    }
+}
@@ -1,63 +1,110 @@
 //! 執行緒管理器 - 負責寫入執行緒的配置和管理
 //!
-//! 提供了寫入執行緒的配置結構體和生成函數
+//! 提供了寫入執行緒的配置結構體和生成函數；寫入執行緒透過 `ResultSink` 輸出結果
 
-use crate::display_utils::{format_number_with_commas, format_remaining_time};
+use crate::display_utils::{format_number_with_commas, format_resumed_remaining_time};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::thread;
+use std::time::Duration;
 
 use super::config::PROGRESS_REPORT_INTERVAL;
-use super::file_format::calculate_expected_file_size;
+use super::file_format::{FileHeader, calculate_expected_file_size};
+use super::sink::{ResultSink, TeeSink, TraceFileSink};
 use super::writer::AppendOnlyWriter;
 
+/// 寫入執行緒的名稱，便於在效能剖析工具或執行緒傾印中辨識
+pub const WRITER_THREAD_NAME: &str = "johansen-writer";
+
+/// 寫入執行緒的返回值
+pub type WriterResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
 /// 寫入執行緒配置
 pub struct WriterConfig {
     pub filename: String,
     pub total_runs: usize,
     pub completed_runs: usize,
     pub dim: usize,
-    pub steps: usize,
     pub model: crate::johansen_models::JohansenModel,
+    /// 寫入新檔案或驗證既有檔案所用的標頭
+    pub header: FileHeader,
     pub quiet: bool,
+    /// 依經過時間刷新寫入緩衝區的間隔（`None` 為只依記錄數量刷新）
+    pub checkpoint_every: Option<Duration>,
+    /// 寫入緩衝區大小（`None` 為預設的 `WRITE_BUFFER_CAPACITY`）
+    pub write_buffer: Option<usize>,
+    /// 同時將每次模擬的跡追加至此 `.trace` 檔案（`None` 為只寫入資料檔案）
+    pub trace_file: Option<PathBuf>,
 }
 
 /// 啟動追加寫入執行緒
 pub fn spawn_append_writer_thread(
     config: WriterConfig,
     receiver: mpsc::Receiver<(u32, Vec<f64>)>,
-) -> thread::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>> {
-    thread::spawn(move || {
-        let WriterConfig {
-            filename,
-            total_runs,
-            completed_runs,
-            dim,
-            steps,
-            model,
-            quiet,
-        } = config;
+) -> thread::JoinHandle<WriterResult<()>> {
+    let WriterConfig {
+        filename,
+        total_runs,
+        completed_runs,
+        dim,
+        model,
+        header,
+        quiet,
+        checkpoint_every,
+        write_buffer,
+        trace_file,
+    } = config;
+
+    let eigenvalues_per_run = match header.top_k {
+        _ if header.has_aggregates() => 2, // [跡, 最大特徵值]
+        0 => model.eigenvalues_per_run(dim),
+        top_k => top_k as usize,
+    };
 
-        let eigenvalues_per_run = match model {
-            crate::johansen_models::JohansenModel::InterceptNoTrendWithInterceptInCoint
-            | crate::johansen_models::JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend => dim + 1,
-            _ => dim,
-        };
+    let expected_size = calculate_expected_file_size(total_runs, eigenvalues_per_run);
 
-        let expected_size = calculate_expected_file_size(total_runs, eigenvalues_per_run);
+    let data_path = PathBuf::from(&filename);
+    let make_writer = move || {
+        AppendOnlyWriter::with_header(&filename, Some(expected_size), header, write_buffer, quiet)
+            .map(|writer| writer.with_checkpoint_every(checkpoint_every))
+    };
+    match trace_file {
+        Some(trace_path) => {
+            // 資料檔案取得寫入鎖並完成續寫準備後才開啟跡檔案，避免與其他程序交錯寫入
+            let make_tee = move || {
+                let writer = make_writer()?;
+                let trace = TraceFileSink::append_to(trace_path, data_path)?;
+                Ok(TeeSink::new(writer, trace))
+            };
+            spawn_sink_thread(make_tee, total_runs, completed_runs, quiet, receiver)
+        }
+        None => spawn_sink_thread(make_writer, total_runs, completed_runs, quiet, receiver),
+    }
+}
 
-        let mut writer = AppendOnlyWriter::with_expected_size(
-            &filename,
-            Some(expected_size),
-            model.to_number(),
-            dim as u8,
-            steps as u32,
-            quiet,
-        )?;
+/// 啟動寫入執行緒，在執行緒中以 `make_sink` 建立接收端並依序輸出收到的結果
+///
+/// `total_runs` 與 `completed_runs` 只用於進度輸出。
+pub fn spawn_sink_thread<S, F>(
+    make_sink: F,
+    total_runs: usize,
+    completed_runs: usize,
+    quiet: bool,
+    receiver: mpsc::Receiver<(u32, Vec<f64>)>,
+) -> thread::JoinHandle<WriterResult<S::Output>>
+where
+    S: ResultSink + 'static,
+    S::Output: 'static,
+    F: FnOnce() -> std::io::Result<S> + Send + 'static,
+{
+    let builder = thread::Builder::new().name(WRITER_THREAD_NAME.to_string());
+    let spawned = builder.spawn(move || {
+        let mut sink = make_sink()?;
         let mut count = 0;
         let start_time = std::time::Instant::now();
 
         while let Ok((seed, eigenvalues)) = receiver.recv() {
-            writer.append_eigenvalues(seed, &eigenvalues)?;
+            sink.append(seed, &eigenvalues)?;
             count += 1;
 
             let current_total = completed_runs + count;
@@ -66,14 +113,13 @@ pub fn spawn_append_writer_thread(
                 let elapsed = start_time.elapsed();
 
                 if progress_ratio > 0.0 {
-                    // 計算剩餘時間時，只使用當前執行的進度和時間
-                    let remaining_runs = total_runs - completed_runs;
+                    // 速率只使用當前執行的進度和時間，剩餘工作量扣除先前執行已完成的部分
                     println!(
                         "Simulation progress: {}/{} ({:.2}%) - {}",
                         format_number_with_commas(current_total),
                         format_number_with_commas(total_runs),
                         progress_ratio * 100.0,
-                        format_remaining_time(elapsed, count, remaining_runs)
+                        format_resumed_remaining_time(elapsed, count, completed_runs, total_runs)
                     );
                 } else {
                     println!(
@@ -86,7 +132,7 @@ pub fn spawn_append_writer_thread(
             }
         }
 
-        writer.finish()?;
-        Ok(())
-    })
+        Ok(sink.finish()?)
+    });
+    spawned.expect("Failed to spawn writer thread")
 }
@@ -12,12 +12,12 @@
 //! 使用 encode 和 decode 函數進行 ULEB128 編碼和解碼：
 //! - encode(300) 將返回 [0xAC, 0x02]
 //! - decode([0xAC, 0x02]) 將返回 (300, 2)
+//! - decode_all([0x01, 0xAC, 0x02]) 將返回 [1, 300]
 
 // 這是一個內部模塊，僅供 crate 內部使用
 #![allow(dead_code)]
 
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 /// ULEB128 編碼錯誤類型
 #[derive(Debug, Clone, PartialEq)]
@@ -137,6 +137,31 @@ pub fn decode(bytes: &[u8]) -> Result<(u32, usize), Uleb128Error> {
     Err(Uleb128Error::IncompleteEncoding)
 }
 
+/// ULEB128 解碼，依序讀取位元組切片中的所有 u32 值
+///
+/// # 參數
+/// * `bytes` - 由連續 ULEB128 編碼組成的位元組切片
+///
+/// # 返回值
+/// `Ok(解碼的值)`；空切片返回空向量。
+/// 切片末尾有不完整的編碼時返回 `Err(Uleb128Error::IncompleteEncoding)`。
+///
+/// # 範例
+/// - decode_all([0x01, 0xAC, 0x02]) -> [1, 300]
+/// - decode_all([0x01, 0x80]) -> Err(IncompleteEncoding)
+pub fn decode_all(bytes: &[u8]) -> Result<Vec<u32>, Uleb128Error> {
+    let mut values = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let (value, bytes_read) = decode(&bytes[offset..])?;
+        values.push(value);
+        offset += bytes_read;
+    }
+
+    Ok(values)
+}
+
 /// 計算 ULEB128 編碼後的大小（位元組數）
 ///
 /// # 參數
@@ -169,7 +194,7 @@ pub fn encoded_size(value: u32) -> usize {
 /// 從讀取器中讀取 ULEB128 編碼的值
 ///
 /// # 參數
-/// * `reader` - 包含 ULEB128 編碼數據的檔案讀取器
+/// * `reader` - 包含 ULEB128 編碼數據的讀取器
 ///
 /// # 返回值
 /// `Ok(解碼的值)` 或 `Err(Uleb128Error)`
@@ -182,7 +207,7 @@ pub fn encoded_size(value: u32) -> usize {
 /// // let mut reader = BufReader::new(file);
 /// // let value = read_from_reader(&mut reader)?;
 /// ```
-pub fn read_from_reader(reader: &mut BufReader<File>) -> Result<u32, Uleb128Error> {
+pub fn read_from_reader<R: Read>(reader: &mut R) -> Result<u32, Uleb128Error> {
     let mut result = 0u32;
     let mut shift = 0;
     let mut bytes_read = 0;
@@ -4,50 +4,149 @@
 
 use crate::display_utils::format_number_with_commas;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
-
-use super::config::{FLUSH_INTERVAL, WRITE_BUFFER_CAPACITY};
-use super::file_format::{EOF_MARKER, MAGIC_HEADER};
-use super::reader::read_append_file;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::checksum::{ChecksumReader, is_checksum_error};
+use super::config::{
+    FLUSH_INTERVAL, IO_RETRY_ATTEMPTS, IO_RETRY_BASE_DELAY_MS, MAX_WRITE_BUFFER_CAPACITY,
+    MIN_WRITE_BUFFER_CAPACITY, WRITE_BUFFER_CAPACITY,
+};
+use super::file_format::{CRC32_SIZE, DEFAULT_FLAGS, EOF_MARKER, FileHeader, METADATA_SIZE};
+use super::lock::WriteLock;
+use super::reader::{read_append_file, read_file_header, read_file_summary};
+use super::recovery::truncate_to_last_valid_record;
 use super::uleb128;
 
+/// 是否為可重試的暫時性 I/O 錯誤（例如網路檔案系統短暫無回應）
+fn is_transient(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::Interrupted
+            | std::io::ErrorKind::WouldBlock
+            | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// 執行 I/O 操作，遇到暫時性錯誤時以指數退避重試
+///
+/// 最多重試 `IO_RETRY_ATTEMPTS` 次，等待時間自 `IO_RETRY_BASE_DELAY_MS` 起每次加倍；
+/// 非暫時性錯誤或重試次數用盡時返回最後一次的錯誤。
+/// `op` 須可安全重複執行：`BufWriter` 的 `flush` 只會送出尚未寫入的位元組，
+/// 小於緩衝區容量的 `write_all` 在失敗時不會放入部分資料。
+pub(crate) fn retry_transient<T>(mut op: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+    let mut delay = Duration::from_millis(IO_RETRY_BASE_DELAY_MS);
+    let mut retries = 0;
+    loop {
+        match op() {
+            Err(e) if is_transient(&e) && retries < IO_RETRY_ATTEMPTS => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// 追加寫入器 - 支援高效的數據追加和斷點續傳
 pub struct AppendOnlyWriter {
-    writer: BufWriter<File>,
+    /// 資料檔案，或 `for_stream` 指定的任意輸出
+    writer: BufWriter<Box<dyn Write + Send>>,
     written_count: usize,
     eigenvalues_per_run: Option<usize>,
+    /// 資料區段的 CRC32 計算器（僅在檔案標頭啟用 CRC32 時存在）
+    checksum: Option<crc32fast::Hasher>,
     model: u8,
     dim: u8,
     steps: u32,
     quiet: bool,
+    /// 距上次刷新超過此時間即刷新緩衝區（`None` 為只依記錄數量刷新）
+    checkpoint_every: Option<Duration>,
+    last_flush: Instant,
+    /// 上次呼叫 `take_checkpoint` 之後是否已刷新緩衝區
+    checkpointed: bool,
+    /// 是否在每筆記錄的 seed 之後寫入計算時間（`FLAG_TIMESTAMPS`）
+    timestamps: bool,
+    /// 防止其他程序同時寫入同一檔案的鎖（以已開啟檔案創建時為 `None`）
+    ///
+    /// 置於最後，使丟棄時先刷新緩衝區再釋放鎖。
+    lock: Option<WriteLock>,
+}
+
+/// 續寫前從既有檔案取得的狀態
+struct ExistingProgress {
+    written_count: usize,
+    eigenvalues_per_run: Option<usize>,
+    /// 已完成檔案儲存的資料區段 CRC32（檔案未啟用 CRC32 或未完成時為 `None`）
+    checksum: Option<crc32fast::Hasher>,
+}
+
+/// 寫入緩衝區大小（`None` 為預設的 `WRITE_BUFFER_CAPACITY`），超出允許範圍時返回 `InvalidInput` 錯誤
+pub fn write_buffer_capacity(write_buffer: Option<usize>) -> std::io::Result<usize> {
+    let capacity = write_buffer.unwrap_or(WRITE_BUFFER_CAPACITY);
+    if !(MIN_WRITE_BUFFER_CAPACITY..=MAX_WRITE_BUFFER_CAPACITY).contains(&capacity) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "write buffer of {capacity} bytes is outside the allowed range of {MIN_WRITE_BUFFER_CAPACITY} to {MAX_WRITE_BUFFER_CAPACITY}"
+            ),
+        ));
+    }
+    Ok(capacity)
+}
+
+/// 目前時間的 Unix epoch 分鐘數（系統時間早於 1970 年時為 0）
+pub fn current_epoch_minutes() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| (elapsed.as_secs() / 60) as u32)
 }
 
 impl AppendOnlyWriter {
     /// 創建新的追加寫入器，並可選擇預先配置檔案大小
+    ///
+    /// `write_buffer` 為寫入緩衝區大小（bytes，`None` 為預設的 `WRITE_BUFFER_CAPACITY`），
+    /// 見 `with_header`。
+    #[allow(dead_code)]
     pub fn with_expected_size<P: AsRef<Path>>(
         path: P,
         expected_size: Option<u64>,
         model: u8,
         dim: u8,
         steps: u32,
+        write_buffer: Option<usize>,
+        quiet: bool,
+    ) -> std::io::Result<Self> {
+        let header = FileHeader::new(DEFAULT_FLAGS, model, dim, steps);
+        Self::with_header(path, expected_size, header, write_buffer, quiet)
+    }
+
+    /// 以完整的檔案標頭（含延伸欄位）創建追加寫入器
+    ///
+    /// 既有檔案的標頭參數必須與 `header` 相符才能續寫。寫入鎖在讀取或修改檔案前取得，
+    /// 另一個程序正在寫入同一檔案時返回 `ResourceBusy` 錯誤。
+    /// `write_buffer` 不在 `MIN_WRITE_BUFFER_CAPACITY..=MAX_WRITE_BUFFER_CAPACITY`
+    /// 範圍內時，在開啟檔案前返回 `InvalidInput` 錯誤。
+    pub fn with_header<P: AsRef<Path>>(
+        path: P,
+        expected_size: Option<u64>,
+        header: FileHeader,
+        write_buffer: Option<usize>,
         quiet: bool,
     ) -> std::io::Result<Self> {
+        let capacity = write_buffer_capacity(write_buffer)?;
         let path_ref = path.as_ref();
+        let lock = WriteLock::acquire(path_ref)?;
         let is_new_file = !path_ref.exists();
 
         let mut written_count = 0;
         let mut eigenvalues_per_run = None;
+        let mut stored_checksum = None;
 
         if is_new_file {
             // 新檔案：直接創建並寫入魔術標頭和元數據
-            let file = OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .read(true)
-                .write(true)
-                .open(path_ref)?;
-
             // 如果指定了預期大小，預先分配檔案空間（暫時禁用以避免影響斷點續傳）
             if let Some(_size) = expected_size {
                 if !quiet {
@@ -57,55 +156,18 @@ impl AppendOnlyWriter {
                 }
             }
 
-            let mut writer = BufWriter::with_capacity(WRITE_BUFFER_CAPACITY, file);
-            writer.write_all(MAGIC_HEADER)?;
-            writer.write_all(&model.to_le_bytes())?;
-            writer.write_all(&dim.to_le_bytes())?;
-            writer.write_all(&steps.to_le_bytes())?;
-            writer.flush()?;
-
-            Ok(Self {
-                writer,
-                written_count: 0,
-                eigenvalues_per_run: None,
-                model,
-                dim,
-                steps,
-                quiet,
-            })
+            Self::create_new(path_ref, header, capacity, quiet, lock)
         } else {
             // 既有檔案：檢查數據並移除 EOF 標記
-            // 先讀取檔案內容來獲取計數 (保持原始容錯邏輯)
-            match read_append_file(&path) {
-                Ok((existing_data, file_model, file_dim, file_steps)) => {
+            // 先取得記錄數量 (保持原始容錯邏輯)
+            match Self::read_existing_progress(path_ref) {
+                Ok(existing) => {
                     // 驗證參數是否匹配
-                    if file_model != model {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!(
-                                "Model mismatch: file has model {file_model}, expected {model}"
-                            ),
-                        ));
-                    }
-                    if file_dim != dim {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Dimension mismatch: file has dim {file_dim}, expected {dim}"),
-                        ));
-                    }
-                    if file_steps != steps {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!(
-                                "Steps mismatch: file has steps {file_steps}, expected {steps}"
-                            ),
-                        ));
-                    }
+                    read_file_header(path_ref)?.check_matches(header)?;
 
-                    written_count = existing_data.len();
-                    if let Some((_, eigenvalues)) = existing_data.first() {
-                        eigenvalues_per_run = Some(eigenvalues.len());
-                    }
+                    written_count = existing.written_count;
+                    eigenvalues_per_run = existing.eigenvalues_per_run;
+                    stored_checksum = existing.checksum;
                     if !quiet {
                         println!(
                             "Detected existing file with {} data records",
@@ -124,29 +186,15 @@ impl AppendOnlyWriter {
                     std::fs::remove_file(&path)?;
 
                     // 重新創建新文件
-                    let file = OpenOptions::new()
-                        .create(true)
-                        .truncate(true)
-                        .read(true)
-                        .write(true)
-                        .open(path_ref)?;
-
-                    let mut writer = BufWriter::with_capacity(WRITE_BUFFER_CAPACITY, file);
-                    writer.write_all(MAGIC_HEADER)?;
-                    writer.write_all(&model.to_le_bytes())?;
-                    writer.write_all(&dim.to_le_bytes())?;
-                    writer.write_all(&steps.to_le_bytes())?;
-                    writer.flush()?;
-
-                    return Ok(Self {
-                        writer,
-                        written_count: 0,
-                        eigenvalues_per_run: None,
-                        model,
-                        dim,
-                        steps,
-                        quiet,
-                    });
+                    return Self::create_new(path_ref, header, capacity, quiet, lock);
+                }
+                Err(e) if is_checksum_error(&e) => {
+                    // 校驗失敗代表資料已損壞，不可在其後追加
+                    return Err(e);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    // 含有不認得的選用區段，續寫會破壞較新版本的檔案
+                    return Err(e);
                 }
                 Err(_) => {
                     // 其他讀取錯誤，採用容錯策略
@@ -158,52 +206,282 @@ impl AppendOnlyWriter {
                 }
             }
 
+            // 無法解析標頭時視為沒有任何選用區段
+            let file_header = read_file_header(path_ref).ok();
+            let trailer_size = file_header.map_or(METADATA_SIZE, |h| h.trailer_size());
+
             // 然後移除 EOF 標記：打開檔案並截斷到數據結束位置
-            Self::remove_eof_marker(path_ref, quiet)?;
+            let eof_removed = Self::remove_eof_marker(path_ref, trailer_size, quiet)?;
+
+            // 未完成的檔案可能留有中斷寫入的不完整記錄，先截斷至最後一筆完整記錄
+            if !eof_removed && file_header.is_some() {
+                written_count = truncate_to_last_valid_record(path_ref)?;
+            }
+
+            // 延續既有資料的 CRC32（已完成的檔案沿用結束標記前儲存的值）
+            let checksum = match (file_header, stored_checksum) {
+                (Some(h), Some(hasher)) if h.has_checksum() && eof_removed => Some(hasher),
+                (Some(h), _) if h.has_checksum() => {
+                    Some(Self::checksum_existing_data(path_ref, h.data_offset)?)
+                }
+                _ => None,
+            };
 
             // 設置為追加模式
             let file = OpenOptions::new().append(true).open(path_ref)?;
-            let writer = BufWriter::with_capacity(WRITE_BUFFER_CAPACITY, file);
+            let writer = BufWriter::with_capacity(capacity, Box::new(file) as Box<_>);
 
             Ok(Self {
                 writer,
                 written_count,
                 eigenvalues_per_run,
-                model,
-                dim,
-                steps,
+                checksum,
+                model: header.model,
+                dim: header.dim,
+                steps: header.steps,
                 quiet,
+                checkpoint_every: None,
+                last_flush: Instant::now(),
+                checkpointed: false,
+                timestamps: header.has_timestamps(),
+                lock: Some(lock),
             })
         }
     }
 
-    /// 移除 EOF 標記以啟用追加模式
-    fn remove_eof_marker<P: AsRef<Path>>(path: P, quiet: bool) -> std::io::Result<()> {
-        use std::io::Read;
+    /// 創建新檔案並寫入 V7 標頭
+    fn create_new(
+        path: &Path,
+        header: FileHeader,
+        capacity: usize,
+        quiet: bool,
+        lock: WriteLock,
+    ) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        let mut writer = Self::for_stream(file, header, Some(capacity), quiet)?;
+        writer.lock = Some(lock);
+        Ok(writer)
+    }
+
+    /// 以已開啟的檔案創建追加寫入器（建立模式）
+    ///
+    /// 供測試或自訂路徑（例如暫存檔）使用：不檢查既有內容、不執行續寫邏輯，
+    /// 直接從檔案目前位置寫入 `header` 的標頭。呼叫者須確保檔案為空且可寫入。
+    pub fn from_existing_handle(
+        file: File,
+        header: FileHeader,
+        quiet: bool,
+    ) -> std::io::Result<Self> {
+        Self::for_stream(file, header, None, quiet)
+    }
+
+    /// 以任意輸出（例如標準輸出）創建寫入器，依序寫出標頭、記錄與結束標記
+    ///
+    /// 輸出內容與資料檔案完全相同，但不取得寫入鎖、不續寫也不預先配置空間；
+    /// 輸出至標準輸出時 `quiet` 必須為 `true`，否則完成訊息會混入資料中。
+    /// `write_buffer` 的意義與檢查同 `with_header`。
+    pub fn for_stream(
+        out: impl Write + Send + 'static,
+        header: FileHeader,
+        write_buffer: Option<usize>,
+        quiet: bool,
+    ) -> std::io::Result<Self> {
+        let capacity = write_buffer_capacity(write_buffer)?;
+        let mut writer = BufWriter::with_capacity(capacity, Box::new(out) as Box<_>);
+        writer.write_all(&header.to_bytes())?;
+        writer.flush()?;
+
+        Ok(Self {
+            writer,
+            written_count: 0,
+            eigenvalues_per_run: None,
+            checksum: header.has_checksum().then(crc32fast::Hasher::new),
+            model: header.model,
+            dim: header.dim,
+            steps: header.steps,
+            quiet,
+            checkpoint_every: None,
+            last_flush: Instant::now(),
+            checkpointed: false,
+            timestamps: header.has_timestamps(),
+            lock: None,
+        })
+    }
+
+    /// 設定依經過時間刷新緩衝區的間隔
+    ///
+    /// 記錄數量刷新（`FLUSH_INTERVAL`）之外，距上次刷新超過 `interval` 時也會刷新，
+    /// 使計算緩慢的模型在中斷時遺失的資料不超過這段時間。
+    pub fn with_checkpoint_every(mut self, interval: Option<Duration>) -> Self {
+        self.checkpoint_every = interval;
+        self
+    }
+
+    /// 取得既有檔案的記錄數量與每筆特徵值數量
+    ///
+    /// 已完成的檔案只讀取標頭與末尾元數據（啟用 CRC32 時沿用儲存的值，不讀取資料區段）；
+    /// 沒有結束標記的未完成檔案才退回讀取所有記錄。
+    fn read_existing_progress(path: &Path) -> std::io::Result<ExistingProgress> {
+        let summary = read_file_summary(path)?;
+        if let Some((written_count, eigenvalues_per_run)) = summary.metadata {
+            let checksum = if summary.header.has_checksum() {
+                Some(Self::stored_checksum(path, summary.header)?)
+            } else {
+                None
+            };
+            return Ok(ExistingProgress {
+                written_count,
+                eigenvalues_per_run: (written_count > 0).then_some(eigenvalues_per_run),
+                checksum,
+            });
+        }
+
+        let (existing_data, _model, _dim, _steps) = read_append_file(path)?;
+        Ok(ExistingProgress {
+            written_count: existing_data.len(),
+            eigenvalues_per_run: existing_data
+                .first()
+                .map(|(_, eigenvalues)| eigenvalues.len()),
+            checksum: None,
+        })
+    }
+
+    /// 以已完成檔案結束標記前儲存的 CRC32 作為續寫的起點
+    ///
+    /// 不重新雜湊資料區段，因此續寫的成本與檔案大小無關。既有資料若已損壞，
+    /// 延續的 CRC32 仍與實際內容不符，讀取時同樣會回報校驗錯誤。
+    fn stored_checksum(path: &Path, header: FileHeader) -> std::io::Result<crc32fast::Hasher> {
+        let mut file = File::open(path)?;
+        let data_end = file.metadata()?.len() - header.trailer_size();
+        let data_len = data_end.checked_sub(header.data_offset).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "File too short for its checksum trailer",
+            )
+        })?;
+
+        file.seek(SeekFrom::Start(data_end))?;
+        let mut crc_buf = [0u8; CRC32_SIZE as usize];
+        file.read_exact(&mut crc_buf)?;
+        let stored = u32::from_le_bytes(crc_buf);
+        Ok(crc32fast::Hasher::new_with_initial_len(stored, data_len))
+    }
+
+    /// 計算既有資料區段的 CRC32，供續寫時延續使用
+    fn checksum_existing_data(path: &Path, data_offset: u64) -> std::io::Result<crc32fast::Hasher> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(data_offset))?;
+
+        let mut reader = ChecksumReader::new(std::io::BufReader::new(file));
+        std::io::copy(&mut reader, &mut std::io::sink())?;
+        Ok(reader.into_hasher())
+    }
 
+    /// 移除 EOF 標記（以及其前的 CRC32）以啟用追加模式，返回是否找到並移除了標記
+    fn remove_eof_marker<P: AsRef<Path>>(
+        path: P,
+        trailer_size: u64,
+        quiet: bool,
+    ) -> std::io::Result<bool> {
         let mut file = OpenOptions::new().read(true).write(true).open(path)?;
         let file_len = file.metadata()?.len();
 
         // 檢查檔案結尾是否真的包含 EOF 標記
-        if file_len >= 18 + 17 {
-            // magic(12) + model(1) + dim(1) + steps(4) + eof_marker(8) + count(8) + eigenvalues_per_run(1) = 35
-            file.seek(SeekFrom::End(-17))?; // eof_marker(8) + count(8) + eigenvalues_per_run(1) = 17
+        if file_len >= 18 + trailer_size {
+            // 最小標頭(18) + [crc32(4)] + eof_marker(8) + count(8) + eigenvalues_per_run(1)
+            file.seek(SeekFrom::End(-(METADATA_SIZE as i64)))?; // eof_marker(8) + count(8) + eigenvalues_per_run(1) = 17
             let mut eof_buf = [0u8; 8];
             if let Ok(()) = file.read_exact(&mut eof_buf) {
                 if eof_buf == EOF_MARKER {
-                    let new_len = file_len - 17;
+                    let new_len = file_len - trailer_size;
                     file.set_len(new_len)?;
                     if !quiet {
                         println!("Removed EOF marker to enable append mode");
                     }
+                    return Ok(true);
                 }
             }
         }
-        Ok(())
+        Ok(false)
     }
 
     /// 追加特徵值數據
     pub fn append_eigenvalues(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        self.write_record(seed, eigenvalues)?;
+
+        // 定期刷新緩衝區：依記錄數量，或距上次刷新已超過檢查點間隔
+        let checkpoint_due = self
+            .checkpoint_every
+            .is_some_and(|interval| self.last_flush.elapsed() >= interval);
+        if self.written_count % FLUSH_INTERVAL == 0 || checkpoint_due {
+            self.flush_buffer()?;
+        }
+
+        Ok(())
+    }
+
+    /// 批次追加多筆記錄，全部寫入後才刷新一次緩衝區，返回寫入的記錄數量
+    ///
+    /// 適合匯入預先計算好的資料。每筆記錄的特徵值數量檢查與 `append_eigenvalues` 相同；
+    /// 遇到錯誤時立即返回，之前的記錄已寫入緩衝區。
+    pub fn append_all<I: IntoIterator<Item = (u32, Vec<f64>)>>(
+        &mut self,
+        records: I,
+    ) -> std::io::Result<usize> {
+        let mut count = 0;
+        for (seed, eigenvalues) in records {
+            self.write_record(seed, &eigenvalues)?;
+            count += 1;
+        }
+        self.flush_buffer()?;
+        Ok(count)
+    }
+
+    /// 以指定的計算時間追加一筆記錄（不刷新緩衝區）
+    ///
+    /// 供重寫既有檔案時保留原本的時間戳記；檔案未啟用時間戳記時忽略 `epoch_minutes`。
+    pub(crate) fn append_with_timestamp(
+        &mut self,
+        seed: u32,
+        eigenvalues: &[f64],
+        epoch_minutes: u32,
+    ) -> std::io::Result<()> {
+        self.write_record_at(seed, eigenvalues, epoch_minutes)
+    }
+
+    /// 刷新寫入緩衝區並重設檢查點計時
+    pub(super) fn flush_buffer(&mut self) -> std::io::Result<()> {
+        retry_transient(|| self.writer.flush())?;
+        self.last_flush = Instant::now();
+        self.checkpointed = true;
+        Ok(())
+    }
+
+    /// 上次呼叫之後是否已將緩衝區刷新至檔案，並重設此狀態
+    ///
+    /// 供附帶輸出（例如跡檔案）在資料檔案的每個檢查點同步刷新。
+    pub fn take_checkpoint(&mut self) -> bool {
+        std::mem::take(&mut self.checkpointed)
+    }
+
+    /// 檢查並寫入一筆記錄至緩衝區（不刷新），時間戳記為目前時間
+    fn write_record(&mut self, seed: u32, eigenvalues: &[f64]) -> std::io::Result<()> {
+        self.write_record_at(seed, eigenvalues, current_epoch_minutes())
+    }
+
+    /// 檢查並寫入一筆記錄至緩衝區（不刷新）
+    fn write_record_at(
+        &mut self,
+        seed: u32,
+        eigenvalues: &[f64],
+        epoch_minutes: u32,
+    ) -> std::io::Result<()> {
         // 檢查特徵值數量是否在 u8 範圍內
         if eigenvalues.len() > u8::MAX as usize {
             return Err(std::io::Error::new(
@@ -238,35 +516,42 @@ impl AppendOnlyWriter {
             }
         }
 
-        // 寫入數據塊：[seed: ULEB128] [eigenvalue_count: 1 byte] [eigenvalues: count * 8 bytes]
-        let seed_bytes = uleb128::encode(seed);
-        self.writer.write_all(&seed_bytes)?;
-        self.writer
-            .write_all(&(eigenvalues.len() as u8).to_le_bytes())?;
-
+        // 寫入數據塊：[seed: ULEB128] [timestamp: u32]? [eigenvalue_count: 1 byte] [eigenvalues: count * 8 bytes]
+        let mut record = Vec::with_capacity(5 + 4 + 1 + eigenvalues.len() * 8);
+        record.extend_from_slice(&uleb128::encode(seed));
+        if self.timestamps {
+            record.extend_from_slice(&epoch_minutes.to_le_bytes());
+        }
+        record.push(eigenvalues.len() as u8);
         for &val in eigenvalues {
-            self.writer.write_all(&val.to_le_bytes())?;
+            record.extend_from_slice(&val.to_le_bytes());
         }
+        retry_transient(|| self.writer.write_all(&record))?;
 
-        self.written_count += 1;
-
-        // 定期刷新緩衝區
-        if self.written_count % FLUSH_INTERVAL == 0 {
-            self.writer.flush()?;
+        if let Some(hasher) = self.checksum.as_mut() {
+            hasher.update(&record);
         }
 
+        self.written_count += 1;
         Ok(())
     }
 
     /// 完成寫入，添加結束標記
     pub fn finish(mut self) -> std::io::Result<()> {
         // 刷新所有緩衝的數據
-        self.writer.flush()?;
+        retry_transient(|| self.writer.flush())?;
+
+        // 組合尾端後一次寫入，暫時性錯誤時可整段重試
+        let mut trailer = Vec::with_capacity(METADATA_SIZE as usize + 4);
+
+        // 寫入資料區段的 CRC32（位於結束標記之前）
+        if let Some(hasher) = self.checksum.take() {
+            trailer.extend_from_slice(&hasher.finalize().to_le_bytes());
+        }
 
         // 寫入結束標記和總數
-        self.writer.write_all(EOF_MARKER)?;
-        self.writer
-            .write_all(&(self.written_count as u64).to_le_bytes())?;
+        trailer.extend_from_slice(EOF_MARKER);
+        trailer.extend_from_slice(&(self.written_count as u64).to_le_bytes());
 
         if let Some(eigenvalues_per_run) = self.eigenvalues_per_run {
             // 檢查 eigenvalues_per_run 是否在 u8 範圍內
@@ -280,13 +565,13 @@ impl AppendOnlyWriter {
                     ),
                 ));
             }
-            self.writer
-                .write_all(&(eigenvalues_per_run as u8).to_le_bytes())?;
+            trailer.push(eigenvalues_per_run as u8);
         } else {
-            self.writer.write_all(&0u8.to_le_bytes())?;
+            trailer.push(0);
         }
 
-        self.writer.flush()?;
+        retry_transient(|| self.writer.write_all(&trailer))?;
+        retry_transient(|| self.writer.flush())?;
 
         if !self.quiet {
             println!(
@@ -2,6 +2,7 @@
 //!
 //! 提供各種用於格式化顯示的實用函數，包括時間格式化、數字格式化等。
 
+use std::io::Write;
 use std::time::Duration;
 
 /// 條件性輸出宏，根據 quiet 參數決定是否輸出
@@ -100,6 +101,97 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// 多維度批次中各維度的耗時紀錄
+///
+/// 依執行順序累計 `(dim, Duration)`，於批次結束時輸出比較各維度的摘要表，
+/// 方便估算更大規模執行所需的時間。
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default)]
+pub struct DimensionTimings {
+    entries: Vec<(usize, Duration)>,
+}
+
+#[allow(dead_code)]
+impl DimensionTimings {
+    /// 建立空的耗時紀錄
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 記錄一個維度的耗時
+    pub fn record(&mut self, dim: usize, duration: Duration) {
+        self.entries.push((dim, duration));
+    }
+
+    /// 已記錄的維度數量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否尚未記錄任何維度
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 所有維度的總耗時
+    pub fn total(&self) -> Duration {
+        self.entries.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// 耗時最短的維度（相同耗時取先執行者）
+    pub fn fastest(&self) -> Option<(usize, Duration)> {
+        self.entries
+            .iter()
+            .copied()
+            .reduce(|best, entry| if entry.1 < best.1 { entry } else { best })
+    }
+
+    /// 耗時最長的維度（相同耗時取先執行者）
+    pub fn slowest(&self) -> Option<(usize, Duration)> {
+        self.entries
+            .iter()
+            .copied()
+            .reduce(|best, entry| if entry.1 > best.1 { entry } else { best })
+    }
+
+    /// 格式化各維度耗時摘要表，包含最快、最慢維度與總耗時
+    pub fn format_summary(&self) -> String {
+        let dim_width = self
+            .entries
+            .iter()
+            .map(|(dim, _)| dim.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max("Dim".len());
+
+        let mut summary = String::from("=== Per-dimension Timing ===\n");
+        summary.push_str(&format!("{:>dim_width$}  Duration\n", "Dim"));
+        summary.push_str(&format!(
+            "{}\n",
+            "-".repeat(dim_width + 2 + "Duration".len())
+        ));
+        for (dim, duration) in &self.entries {
+            summary.push_str(&format!(
+                "{dim:>dim_width$}  {}\n",
+                format_duration(*duration)
+            ));
+        }
+
+        if let (Some((fast_dim, fast)), Some((slow_dim, slow))) = (self.fastest(), self.slowest()) {
+            summary.push_str(&format!(
+                "Fastest: dim {fast_dim} ({})\n",
+                format_duration(fast)
+            ));
+            summary.push_str(&format!(
+                "Slowest: dim {slow_dim} ({})\n",
+                format_duration(slow)
+            ));
+        }
+        summary.push_str(&format!("Total: {}\n", format_duration(self.total())));
+        summary
+    }
+}
+
 /// 格式化數字，添加千位分隔符
 ///
 /// # 參數
@@ -129,43 +221,72 @@ pub fn format_number_with_commas(n: usize) -> String {
     result
 }
 
-/// 格式化剩餘時間估算
+/// 格式化檔案大小，以 1024 進位的單位顯示
 ///
 /// # 參數
-/// * `elapsed` - 已經過的時間
-/// * `completed` - 已完成的工作量
-/// * `total` - 總工作量
+/// * `n` - 位元組數
 ///
 /// # 返回值
-/// 剩餘時間的格式化字串，如果無法估算則返回 "unknown"
+/// 小於 1 KB 時為整數位元組，否則取最大且不小於 1 的單位並保留兩位小數
+/// （KB = 1024 bytes、MB = 1024 KB，依此類推）
 ///
 /// # 範例
 /// ```text
-/// use std::time::Duration;
-///
-/// let elapsed = Duration::from_secs(60); // 已經過 1 分鐘
-/// let remaining = format_remaining_time(elapsed, 100, 1000); // 完成了 100/1000
-/// // 輸出類似: "estimated remaining: 9 minutes 0.00 seconds"
+/// assert_eq!(format_bytes(1023), "1023 B");
+/// assert_eq!(format_bytes(1024), "1.00 KB");
+/// assert_eq!(format_bytes(1_610_612_736), "1.50 GB");
 /// ```
-pub fn format_remaining_time(elapsed: Duration, completed: usize, total: usize) -> String {
-    if completed == 0 || completed >= total {
-        return "unknown".to_string();
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 6] = ["KB", "MB", "GB", "TB", "PB", "EB"];
+
+    if n < 1024 {
+        return format!("{n} B");
     }
 
-    let elapsed_seconds = elapsed.as_secs_f64();
-    let progress_ratio = completed as f64 / total as f64;
-    let estimated_total_seconds = elapsed_seconds / progress_ratio;
-    let remaining_seconds = estimated_total_seconds - elapsed_seconds;
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    // 四捨五入至兩位小數後達到 1024 時改用下一個單位，避免出現 "1024.00 KB"
+    while value >= 1023.995 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
 
-    if remaining_seconds <= 0.0 {
-        return "completing soon".to_string();
+/// 估算續算時的剩餘時間
+///
+/// 速率只取本次執行的 `session_completed` 筆與 `elapsed`，先前執行已完成的
+/// `previously_completed` 筆只從剩餘工作量中扣除：剩餘 = `total - (previously_completed + session_completed)`。
+/// 本次尚未完成任何工作或已無剩餘工作時返回 `None`。
+pub fn estimate_remaining(
+    elapsed: Duration,
+    session_completed: usize,
+    previously_completed: usize,
+    total: usize,
+) -> Option<Duration> {
+    let remaining = total.saturating_sub(previously_completed + session_completed);
+    if session_completed == 0 || remaining == 0 {
+        return None;
     }
 
-    let remaining_duration = Duration::from_secs_f64(remaining_seconds);
-    format!(
-        "estimated remaining: {}",
-        format_duration(remaining_duration)
-    )
+    let seconds_per_run = elapsed.as_secs_f64() / session_completed as f64;
+    Some(Duration::from_secs_f64(seconds_per_run * remaining as f64))
+}
+
+/// 格式化續算時的剩餘時間估算（見 `estimate_remaining`）
+///
+/// 無法估算時返回 "unknown"，估算值為零時返回 "completing soon"。
+pub fn format_resumed_remaining_time(
+    elapsed: Duration,
+    session_completed: usize,
+    previously_completed: usize,
+    total: usize,
+) -> String {
+    match estimate_remaining(elapsed, session_completed, previously_completed, total) {
+        None => "unknown".to_string(),
+        Some(remaining) if remaining.is_zero() => "completing soon".to_string(),
+        Some(remaining) => format!("estimated remaining: {}", format_duration(remaining)),
+    }
 }
 
 /// 格式化百分比
@@ -238,11 +359,53 @@ pub fn display_percentiles_table(
     values: &[f64],
     total_count: usize,
 ) {
-    println!("{statistic_name} for model {model_name}:");
-    println!(
+    write_percentiles_table(
+        &mut std::io::stdout().lock(),
+        model_name,
+        statistic_name,
+        percentiles,
+        values,
+        total_count,
+    )
+    .expect("Failed to write to stdout");
+}
+
+/// 將百分位數結果的表格寫入 `w`
+pub fn write_percentiles_table(
+    w: &mut dyn Write,
+    model_name: &str,
+    statistic_name: &str,
+    percentiles: &[f64],
+    values: &[f64],
+    total_count: usize,
+) -> std::io::Result<()> {
+    write_percentiles_table_with_intervals(
+        w,
+        model_name,
+        statistic_name,
+        percentiles,
+        values,
+        None,
+        total_count,
+    )
+}
+
+/// 將百分位數結果的表格寫入 `w`，`intervals` 有值時每個值後附上 `[lo, hi]` 信賴區間
+pub fn write_percentiles_table_with_intervals(
+    w: &mut dyn Write,
+    model_name: &str,
+    statistic_name: &str,
+    percentiles: &[f64],
+    values: &[f64],
+    intervals: Option<&[(f64, f64)]>,
+    total_count: usize,
+) -> std::io::Result<()> {
+    writeln!(w, "{statistic_name} for model {model_name}:")?;
+    writeln!(
+        w,
         "Total calculated {} values",
         format_number_with_commas(total_count)
-    );
+    )?;
 
     // 計算百分位數列的實際顯示寬度（包含 "th" 後綴）
     let percentile_display_width = percentiles
@@ -254,26 +417,46 @@ pub fn display_percentiles_table(
     // 確保標題列寬度至少和內容一樣寬
     let percentile_col_width = percentile_display_width.max("Percentile".len());
 
-    let value_width = values
+    let value_strs: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(
+            |(i, v)| match intervals.and_then(|intervals| intervals.get(i)) {
+                Some((lo, hi)) => format!("{v:.6} [{lo:.6}, {hi:.6}]"),
+                None => format!("{v:.6}"),
+            },
+        )
+        .collect();
+    let value_header = if intervals.is_some() {
+        "Value [lo, hi]"
+    } else {
+        "Value"
+    };
+    let value_width = value_strs
         .iter()
-        .map(|v| format!("{v:.6}").len())
+        .map(String::len)
         .max()
         .unwrap_or(12)
-        .max("Value".len());
+        .max(value_header.len());
 
     // 表格標題
-    println!(
+    writeln!(
+        w,
         "{:<width1$} {:>width2$}",
         "Percentile",
-        "Value",
+        value_header,
         width1 = percentile_col_width,
         width2 = value_width
-    );
-    println!("{}", "-".repeat(percentile_col_width + value_width + 1));
+    )?;
+    writeln!(w, "{}", "-".repeat(percentile_col_width + value_width + 1))?;
 
     // 表格內容
-    for (percentile, value) in percentiles.iter().zip(values.iter()) {
+    for (percentile, value_str) in percentiles.iter().zip(value_strs.iter()) {
         let percentile_str = format!("{:.1}th", percentile * 100.0);
-        println!("{percentile_str:<percentile_col_width$} {value:>value_width$.6}");
+        writeln!(
+            w,
+            "{percentile_str:<percentile_col_width$} {value_str:>value_width$}"
+        )?;
     }
+    Ok(())
 }
@@ -0,0 +1,157 @@
+//! 特徵值快取（`cache` feature）
+//!
+//! 互動式探索時常以相同的小型配置重複計算。啟用 `cache` feature 後，
+//! `calculate_eigenvalues` 會先查詢以 `(dim, steps, seed, model)` 為鍵的 LRU 快取，
+//! 命中時返回快取結果的複本。計算結果與未啟用時完全相同。
+
+use crate::johansen_models::JohansenModel;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+/// 全域快取預設保留的結果數量
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// 快取鍵：`(dim, steps, seed, model)`
+pub type CacheKey = (usize, usize, u32, u8);
+
+/// 建立快取鍵
+pub fn cache_key(dim: usize, steps: usize, seed: u32, model: JohansenModel) -> CacheKey {
+    (dim, steps, seed, model.to_number())
+}
+
+/// 固定容量的 LRU 快取
+///
+/// 以遞增的使用序號記錄存取順序，超過容量時移除序號最小的項目。
+/// 容量通常不大，線性搜尋最舊項目的成本遠低於一次特徵值計算。
+#[derive(Debug)]
+pub struct EigenvalueCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, (Vec<f64>, u64)>,
+    tick: u64,
+}
+
+impl EigenvalueCache {
+    /// 創建指定容量的快取（容量為 0 時不保存任何結果）
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// 查詢快取，命中時更新使用順序並返回複本
+    pub fn get(&mut self, key: &CacheKey) -> Option<Vec<f64>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(values, last_used)| {
+            *last_used = tick;
+            values.clone()
+        })
+    }
+
+    /// 加入結果，超過容量時移除最久未使用的項目
+    pub fn insert(&mut self, key: CacheKey, values: Vec<f64>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        self.entries.insert(key, (values, self.tick));
+        while self.entries.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// 調整容量，必要時移除最久未使用的項目
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// 目前保存的結果數量
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否沒有保存任何結果
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 清空快取
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// 快取命中與未命中次數
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+static CACHE: LazyLock<Mutex<EigenvalueCache>> =
+    LazyLock::new(|| Mutex::new(EigenvalueCache::new(DEFAULT_CACHE_CAPACITY)));
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// 查詢全域快取，未命中時以 `compute` 計算並存入
+///
+/// 計算期間不持有鎖，並行計算不同的鍵不會互相阻塞；
+/// 同一個鍵同時未命中時可能重複計算，但結果相同。
+pub fn get_or_compute(key: CacheKey, compute: impl FnOnce() -> Vec<f64>) -> Vec<f64> {
+    if let Some(values) = lock_cache().get(&key) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        return values;
+    }
+
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let values = compute();
+    lock_cache().insert(key, values.clone());
+    values
+}
+
+/// 清空全域快取（不重設命中統計）
+#[allow(dead_code)]
+pub fn clear_cache() {
+    lock_cache().clear();
+}
+
+/// 設定全域快取的容量上限
+#[allow(dead_code)]
+pub fn set_cache_capacity(capacity: usize) {
+    lock_cache().set_capacity(capacity);
+}
+
+/// 取得全域快取累計的命中與未命中次數
+#[allow(dead_code)]
+pub fn cache_stats() -> CacheStats {
+    CacheStats {
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+    }
+}
+
+fn lock_cache() -> std::sync::MutexGuard<'static, EigenvalueCache> {
+    // 快取只保存計算結果，持有鎖的執行緒 panic 不會留下不一致的狀態
+    CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
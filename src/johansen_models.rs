@@ -39,15 +39,10 @@ impl JohansenModel {
     }
 
     /// 從數字標識符創建模型
+    ///
+    /// `TryFrom<u8>` 的便利版本，需要錯誤資訊時請改用 `JohansenModel::try_from`。
     pub fn from_number(n: u8) -> Option<Self> {
-        match n {
-            0 => Some(JohansenModel::NoInterceptNoTrend),
-            1 => Some(JohansenModel::InterceptNoTrendWithInterceptInCoint),
-            2 => Some(JohansenModel::InterceptNoTrendUnrestrictedIntercept),
-            3 => Some(JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend),
-            4 => Some(JohansenModel::InterceptTrendUnrestrictedBoth),
-            _ => None,
-        }
+        Self::try_from(n).ok()
     }
 
     /// 返回模型的描述名稱
@@ -115,6 +110,19 @@ impl JohansenModel {
         }
     }
 
+    /// 維度為 `dim` 時每次模擬的特徵值數量（即 F 矩陣的列數）
+    ///
+    /// Model 1 與 Model 3 在 F 矩陣中多一列受限的常數或趨勢項，特徵值為 `dim + 1` 個。
+    pub fn eigenvalues_per_run(self, dim: usize) -> usize {
+        match self {
+            JohansenModel::InterceptNoTrendWithInterceptInCoint
+            | JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend => dim + 1,
+            JohansenModel::NoInterceptNoTrend
+            | JohansenModel::InterceptNoTrendUnrestrictedIntercept
+            | JohansenModel::InterceptTrendUnrestrictedBoth => dim,
+        }
+    }
+
     /// 返回所有可用的模型
     pub fn all_models() -> [JohansenModel; 5] {
         [
@@ -125,6 +133,60 @@ impl JohansenModel {
             JohansenModel::InterceptTrendUnrestrictedBoth,
         ]
     }
+
+    /// 返回所有模型的數字標識符（依 `all_models` 的順序）
+    ///
+    /// 有效編號範圍的唯一來源；命令行解析與錯誤訊息皆由此取得，新增模型時不需另外修改。
+    pub fn all_model_numbers() -> [u8; 5] {
+        Self::all_models().map(Self::to_number)
+    }
+
+    /// 數字是否為有效的模型標識符
+    pub fn is_valid_number(n: u8) -> bool {
+        Self::all_model_numbers().contains(&n)
+    }
+
+    /// 有效模型編號範圍的顯示文字，例如 `0-4`
+    fn number_range() -> String {
+        let numbers = Self::all_model_numbers();
+        format!("{}-{}", numbers[0], numbers[numbers.len() - 1])
+    }
+}
+
+/// 模型轉換錯誤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelError {
+    /// 模型編號不在 `all_model_numbers` 之中
+    InvalidNumber(u8),
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelError::InvalidNumber(n) => {
+                let range = JohansenModel::number_range();
+                write!(f, "invalid model number: {n} (expected {range})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+impl TryFrom<u8> for JohansenModel {
+    type Error = ModelError;
+
+    /// 從數字標識符（0-4）轉換為模型
+    fn try_from(n: u8) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(JohansenModel::NoInterceptNoTrend),
+            1 => Ok(JohansenModel::InterceptNoTrendWithInterceptInCoint),
+            2 => Ok(JohansenModel::InterceptNoTrendUnrestrictedIntercept),
+            3 => Ok(JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend),
+            4 => Ok(JohansenModel::InterceptTrendUnrestrictedBoth),
+            _ => Err(ModelError::InvalidNumber(n)),
+        }
+    }
 }
 
 impl Default for JohansenModel {
@@ -134,8 +196,75 @@ impl Default for JohansenModel {
     }
 }
 
+impl PartialOrd for JohansenModel {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JohansenModel {
+    /// 依模型編號（0-4）排序，可作為 `BTreeMap` 的鍵
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.to_number().cmp(&other.to_number())
+    }
+}
+
 impl std::fmt::Display for JohansenModel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Model {}: {}", self.to_number(), self.description())
     }
 }
+
+/// 以模型編號（0-4）序列化
+#[cfg(feature = "serde")]
+impl serde::Serialize for JohansenModel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.to_number())
+    }
+}
+
+/// 接受模型編號（`2` 或 `"2"`）或變體名稱（如 `"InterceptNoTrendUnrestrictedIntercept"`）
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JohansenModel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected, Visitor};
+
+        struct ModelVisitor;
+
+        impl Visitor<'_> for ModelVisitor {
+            type Value = JohansenModel;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let range = JohansenModel::number_range();
+                write!(f, "a model number ({range}) or model name")
+            }
+
+            fn visit_u64<E: Error>(self, n: u64) -> Result<Self::Value, E> {
+                u8::try_from(n)
+                    .ok()
+                    .and_then(JohansenModel::from_number)
+                    .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(n), &self))
+            }
+
+            fn visit_i64<E: Error>(self, n: i64) -> Result<Self::Value, E> {
+                match u64::try_from(n) {
+                    Ok(n) => self.visit_u64(n),
+                    Err(_) => Err(E::invalid_value(Unexpected::Signed(n), &self)),
+                }
+            }
+
+            fn visit_str<E: Error>(self, s: &str) -> Result<Self::Value, E> {
+                if let Ok(n) = s.parse::<u64>() {
+                    return self.visit_u64(n);
+                }
+                // 變體名稱與 Debug 輸出相同
+                JohansenModel::all_models()
+                    .into_iter()
+                    .find(|model| format!("{model:?}") == s)
+                    .ok_or_else(|| E::invalid_value(Unexpected::Str(s), &self))
+            }
+        }
+
+        deserializer.deserialize_any(ModelVisitor)
+    }
+}
@@ -7,9 +7,202 @@
 //! - 廣義特徵值問題求解
 
 use crate::johansen_models::JohansenModel;
-use crate::matrix_utils::sum_of_outer_products;
+use crate::matrix_utils::{gram_matrix, reciprocal_condition, sum_of_outer_products_into};
+use crate::profiling::{self, Stage};
+use crate::rng_matrix::SeedHashing;
+use crate::stats::total_cmp_f64;
 use nalgebra::DMatrix;
+#[cfg(feature = "lapack")]
 use nalgebra_lapack::GeneralizedEigen;
+use std::cell::RefCell;
+
+/// 特徵值的排列順序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortOrder {
+    /// 由小到大
+    Ascending,
+    /// 由大到小（最大特徵值統計量的慣例）
+    #[default]
+    Descending,
+}
+
+/// 每筆記錄儲存的內容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AggregateMode {
+    /// 全部特徵值（依 `SortOrder` 排列）
+    #[default]
+    Full,
+    /// 只存 `[跡, 最大特徵值]` 兩個值，見 `aggregate_both`
+    Both,
+}
+
+impl std::fmt::Display for AggregateMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AggregateMode::Full => "full",
+            AggregateMode::Both => "both",
+        })
+    }
+}
+
+impl std::str::FromStr for AggregateMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "full" => Ok(AggregateMode::Full),
+            "both" => Ok(AggregateMode::Both),
+            other => Err(format!(
+                "invalid aggregate mode: {other} (expected full or both)"
+            )),
+        }
+    }
+}
+
+/// `AggregateMode::Both` 記錄中跡（特徵值總和）的位置
+pub const AGGREGATE_TRACE_INDEX: usize = 0;
+/// `AggregateMode::Both` 記錄中最大特徵值的位置
+pub const AGGREGATE_MAX_INDEX: usize = 1;
+
+/// 將特徵值聚合為 `[跡, 最大特徵值]`
+///
+/// 與 `SumAggregator`、`MaxAggregator` 的定義相同：跡為全部特徵值的總和，
+/// 最大值取真正的最大值，不受排列順序影響。
+pub fn aggregate_both(eigenvalues: &[f64]) -> Vec<f64> {
+    let trace = eigenvalues.iter().sum();
+    let max = eigenvalues.iter().copied().fold(f64::MIN, f64::max);
+    vec![trace, max]
+}
+
+impl SortOrder {
+    /// 依此順序就地排序特徵值（NaN 不會 panic，升冪時排在最後、降冪時排在最前）
+    pub fn sort(self, values: &mut [f64]) {
+        match self {
+            SortOrder::Ascending => values.sort_by(total_cmp_f64),
+            SortOrder::Descending => values.sort_by(|a, b| total_cmp_f64(b, a)),
+        }
+    }
+}
+
+/// 特徵值計算的選項
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EigenvalueOptions {
+    /// 特徵值排列順序
+    pub sort_order: SortOrder,
+    /// RNG 子串流編號（0 為預設串流）
+    pub stream_id: u32,
+    /// 與 seed 混合的主種子（`None` 為直接以 seed 推導 RNG 種子）
+    pub master_seed: Option<u64>,
+    /// 子串流 0 的 seed 轉換為 RNG 種子的方式
+    pub seed_hashing: SeedHashing,
+    /// 只保留最大的 K 個特徵值（`None` 為保留全部）
+    pub top_k: Option<usize>,
+    /// 絕對值低於此下限的特徵值設為 0（`None` 為保留原值），見 `apply_eigenvalue_floor`
+    pub eigenvalue_floor: Option<f64>,
+    /// 返回全部特徵值或只返回 `[跡, 最大特徵值]`（在截斷與下限處理之後聚合）
+    pub aggregate: AggregateMode,
+    /// `∫F F' dt` 的倒數條件數下限，低於此值的 seed 會被標記為病態（`None` 為不檢查）
+    pub min_condition: Option<f64>,
+    /// 標記求解結果含有不可忽略虛部的 seed（預設為 false）
+    pub diagnose_complex: bool,
+    /// 捨棄布朗運動路徑的前 W 個時間步（burn-in），須小於 `steps`（0 為不捨棄）
+    ///
+    /// 路徑仍以 `steps` 步、`delta_t = 1 / steps` 生成，之後只使用時間 `W / steps` 之後的
+    /// `steps - W` 個增量構造 `bm_previous` 與 `dbm`。
+    pub warmup: usize,
+}
+
+impl EigenvalueOptions {
+    /// 是否需要計算 `SolveDiagnostics`
+    pub fn diagnoses(&self) -> bool {
+        self.min_condition.is_some() || self.diagnose_complex
+    }
+
+    /// 依啟用的檢查判斷此 seed 是否應被回報
+    pub fn flags(&self, diagnostics: SolveDiagnostics) -> bool {
+        self.min_condition
+            .is_some_and(|min_condition| diagnostics.is_ill_conditioned(min_condition))
+            || (self.diagnose_complex && diagnostics.has_complex_eigenvalues())
+    }
+}
+
+/// 虛部絕對值超過特徵值模長的此比例時，視為不可忽略的複數特徵值
+///
+/// 問題對稱時廣義特徵值理論上皆為實數，LAPACK 的虛部只來自捨入誤差（約 1e-15 的相對量）。
+#[cfg(feature = "lapack")]
+pub const IMAGINARY_TOLERANCE: f64 = 1e-8;
+
+/// 廣義特徵值求解的診斷資訊
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveDiagnostics {
+    /// 求解前 `∫F F' dt`（廣義特徵值問題右側矩陣）的倒數條件數
+    pub reciprocal_condition: f64,
+    /// 虛部超過 `IMAGINARY_TOLERANCE` 的特徵值數量
+    ///
+    /// 特徵值以模長儲存，因此虛部不會出現在結果中；不為 0 代表數值求解出了問題。
+    /// 不使用 LAPACK 的對稱求解器只產生實數特徵值，此值恆為 0。
+    pub complex_eigenvalues: usize,
+}
+
+impl SolveDiagnostics {
+    /// 倒數條件數是否低於門檻（NaN 亦視為病態）
+    pub fn is_ill_conditioned(self, min_condition: f64) -> bool {
+        self.reciprocal_condition.is_nan() || self.reciprocal_condition < min_condition
+    }
+
+    /// 是否有虛部不可忽略的特徵值
+    pub fn has_complex_eigenvalues(self) -> bool {
+        self.complex_eigenvalues > 0
+    }
+}
+
+/// `calculate_eigenvalues_from_matrices` 的輸入矩陣形狀不相容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixShapeError {
+    /// 矩陣沒有任何列或欄
+    Empty { rows: usize, cols: usize },
+    /// `dbm` 與 `bm_previous` 的列數（維度）不同
+    RowMismatch { dbm: usize, bm_previous: usize },
+    /// `dbm` 與 `bm_previous` 的欄數（時間步數）不同，無法逐欄做外積
+    ColumnMismatch { dbm: usize, bm_previous: usize },
+}
+
+impl std::fmt::Display for MatrixShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixShapeError::Empty { rows, cols } => {
+                write!(f, "Brownian motion matrix is empty ({rows}x{cols})")
+            }
+            MatrixShapeError::RowMismatch { dbm, bm_previous } => write!(
+                f,
+                "dbm has {dbm} rows but bm_previous has {bm_previous} (dimensions must match)"
+            ),
+            MatrixShapeError::ColumnMismatch { dbm, bm_previous } => write!(
+                f,
+                "dbm has {dbm} columns but bm_previous has {bm_previous} \
+                 (each time step needs one column in both)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MatrixShapeError {}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "asc" | "ascending" => Ok(SortOrder::Ascending),
+            "desc" | "descending" => Ok(SortOrder::Descending),
+            other => Err(format!(
+                "invalid sort order: {other} (expected asc or desc)"
+            )),
+        }
+    }
+}
 
 /// 計算 Johansen 測試在指定模型下的特徵值
 ///
@@ -18,32 +211,233 @@ use nalgebra_lapack::GeneralizedEigen;
 /// * `dbm` - 布朗運動的差分矩陣
 /// * `delta_t` - 時間間隔
 /// * `model` - Johansen 模型類型
+/// * `order` - 特徵值排列順序
+/// * `diagnose` - 是否在求解前估計右側矩陣的條件數
 ///
 /// # 返回值
-/// 按降序排列的特徵值向量
-fn calculate_eigenvalues_from_matrices(
+/// 按 `order` 排列的特徵值向量，以及 `diagnose` 時的診斷資訊
+///
+/// # 錯誤
+/// 兩個矩陣為空或列數、欄數不同時，在計算前返回 `MatrixShapeError`，
+/// 而不是在外積累加或 LAPACK 求解時 panic。
+pub(crate) fn calculate_eigenvalues_from_matrices(
     bm_previous: &DMatrix<f64>,
     dbm: &DMatrix<f64>,
     delta_t: f64,
     model: JohansenModel,
-) -> Vec<f64> {
-    let fm = construct_f_matrix(bm_previous, model);
+    order: SortOrder,
+    diagnose: bool,
+) -> Result<(Vec<f64>, Option<SolveDiagnostics>), MatrixShapeError> {
+    check_matrix_shapes(bm_previous, dbm)?;
+
+    let (sum_dbm_fm_outer_products, sum_fm_fm_outer_products) =
+        profiling::time(Stage::OuterProducts, || {
+            let fm = construct_f_matrix(bm_previous, model);
 
-    let sum_dbm_fm_outer_products = sum_of_outer_products(dbm, &fm);
-    let sum_fm_fm_outer_products = sum_of_outer_products(&fm, &fm) * delta_t;
+            // 以預先配置的累加矩陣做 rank-1 更新，避免每一欄配置暫存矩陣
+            let mut sum_dbm_fm = DMatrix::<f64>::zeros(dbm.nrows(), fm.nrows());
+            sum_of_outer_products_into(&mut sum_dbm_fm, dbm, &fm);
+            let mut sum_fm_fm = DMatrix::<f64>::zeros(fm.nrows(), fm.nrows());
+            sum_of_outer_products_into(&mut sum_fm_fm, &fm, &fm);
+            (sum_dbm_fm, sum_fm_fm * delta_t)
+        });
 
-    let ge = GeneralizedEigen::new(
-        sum_dbm_fm_outer_products.transpose() * sum_dbm_fm_outer_products,
+    Ok(solve_generalized_eigen(
+        &sum_dbm_fm_outer_products,
         sum_fm_fm_outer_products,
-    );
+        order,
+        diagnose,
+    ))
+}
+
+/// 檢查 `bm_previous` 與 `dbm` 非空且形狀相同（F 矩陣的欄數與 `bm_previous` 相同）
+fn check_matrix_shapes(
+    bm_previous: &DMatrix<f64>,
+    dbm: &DMatrix<f64>,
+) -> Result<(), MatrixShapeError> {
+    for matrix in [bm_previous, dbm] {
+        let (rows, cols) = matrix.shape();
+        if rows == 0 || cols == 0 {
+            return Err(MatrixShapeError::Empty { rows, cols });
+        }
+    }
+    if dbm.nrows() != bm_previous.nrows() {
+        return Err(MatrixShapeError::RowMismatch {
+            dbm: dbm.nrows(),
+            bm_previous: bm_previous.nrows(),
+        });
+    }
+    if dbm.ncols() != bm_previous.ncols() {
+        return Err(MatrixShapeError::ColumnMismatch {
+            dbm: dbm.ncols(),
+            bm_previous: bm_previous.ncols(),
+        });
+    }
+    Ok(())
+}
 
-    let mut eigenvalues_real: Vec<f64> = ge
-        .raw_eigenvalues()
+/// 以 `scratch` 中的 `bm_previous`、`dbm` 計算特徵值，重複使用 F 矩陣與累加矩陣的緩衝區
+///
+/// 結果與 `calculate_eigenvalues_from_matrices` 逐位元相同。
+fn calculate_eigenvalues_pooled(
+    scratch: &mut Scratch,
+    delta_t: f64,
+    model: JohansenModel,
+    order: SortOrder,
+    diagnose: bool,
+) -> (Vec<f64>, Option<SolveDiagnostics>) {
+    let Scratch {
+        bm_previous,
+        dbm,
+        fm,
+        sum_dbm_fm,
+    } = scratch;
+
+    let sum_fm_fm_outer_products = profiling::time(Stage::OuterProducts, || {
+        construct_f_matrix_into(bm_previous, model, fm);
+
+        reshape(sum_dbm_fm, dbm.nrows(), fm.nrows());
+        sum_dbm_fm.fill(0.0);
+        sum_of_outer_products_into(sum_dbm_fm, dbm, fm);
+        // 求解時會取得右側矩陣的所有權，且大小只有 F 列數的平方，不放入緩衝池
+        let mut sum_fm_fm = DMatrix::<f64>::zeros(fm.nrows(), fm.nrows());
+        sum_of_outer_products_into(&mut sum_fm_fm, fm, fm);
+        sum_fm_fm * delta_t
+    });
+
+    solve_generalized_eigen(sum_dbm_fm, sum_fm_fm_outer_products, order, diagnose)
+}
+
+/// 求解 `(S_dbm_fm' S_dbm_fm) v = λ (∫F F' dt) v`，返回按 `order` 排列的特徵值
+fn solve_generalized_eigen(
+    sum_dbm_fm_outer_products: &DMatrix<f64>,
+    sum_fm_fm_outer_products: DMatrix<f64>,
+    order: SortOrder,
+    diagnose: bool,
+) -> (Vec<f64>, Option<SolveDiagnostics>) {
+    profiling::time(Stage::EigenSolve, || {
+        // 步數少於 F 的列數等情況下右側矩陣接近奇異，求解結果不可靠
+        let reciprocal_condition =
+            diagnose.then(|| reciprocal_condition(&sum_fm_fm_outer_products));
+
+        let (mut eigenvalues_real, complex_eigenvalues) = generalized_eigenvalues(
+            gram_matrix(sum_dbm_fm_outer_products),
+            sum_fm_fm_outer_products,
+        );
+        order.sort(&mut eigenvalues_real);
+        let diagnostics = reciprocal_condition.map(|reciprocal_condition| SolveDiagnostics {
+            reciprocal_condition,
+            complex_eigenvalues,
+        });
+        (eigenvalues_real, diagnostics)
+    })
+}
+
+/// 求解 `A v = λ B v` 的特徵值（取絕對值，未排序）與虛部不可忽略的特徵值數量
+#[cfg(all(feature = "lapack", not(feature = "pure-rust")))]
+fn generalized_eigenvalues(a: DMatrix<f64>, b: DMatrix<f64>) -> (Vec<f64>, usize) {
+    generalized_eigenvalues_lapack_counted(a, b)
+}
+
+/// 求解 `A v = λ B v` 的特徵值（取絕對值，未排序）與虛部不可忽略的特徵值數量
+#[cfg(not(all(feature = "lapack", not(feature = "pure-rust"))))]
+fn generalized_eigenvalues(a: DMatrix<f64>, b: DMatrix<f64>) -> (Vec<f64>, usize) {
+    (generalized_eigenvalues_cholesky(a, b), 0)
+}
+
+/// 以 LAPACK (`dggev`) 求解一般的廣義特徵值問題
+#[cfg(feature = "lapack")]
+#[allow(dead_code)]
+pub(crate) fn generalized_eigenvalues_lapack(a: DMatrix<f64>, b: DMatrix<f64>) -> Vec<f64> {
+    generalized_eigenvalues_lapack_counted(a, b).0
+}
+
+/// 同 `generalized_eigenvalues_lapack`，另外返回虛部超過 `IMAGINARY_TOLERANCE` 的特徵值數量
+#[cfg(feature = "lapack")]
+pub(crate) fn generalized_eigenvalues_lapack_counted(
+    a: DMatrix<f64>,
+    b: DMatrix<f64>,
+) -> (Vec<f64>, usize) {
+    let raw = GeneralizedEigen::new(a, b).raw_eigenvalues();
+    let complex = raw
+        .iter()
+        .filter(|val| val.0.im.abs() > IMAGINARY_TOLERANCE * val.0.norm())
+        .count();
+    let eigenvalues = raw.iter().map(|val| val.0.norm() / val.1).collect();
+    (eigenvalues, complex)
+}
+
+/// 不依賴 LAPACK 的對稱廣義特徵值求解
+///
+/// `A` 對稱且 `B` 對稱正定時，以 `B = L L'` 將問題化為對稱矩陣 `L⁻¹ A L⁻ᵀ` 的特徵值問題，
+/// 再以 nalgebra 的 `SymmetricEigen` 求解。`B` 無法做 Cholesky 分解（奇異或非正定）時
+/// 返回 NaN，與 LAPACK 在奇異右側矩陣下得到的不可靠結果同樣會被條件數檢查標記。
+#[allow(dead_code)]
+pub(crate) fn generalized_eigenvalues_cholesky(a: DMatrix<f64>, b: DMatrix<f64>) -> Vec<f64> {
+    let n = a.nrows();
+    let Some(cholesky) = b.cholesky() else {
+        return vec![f64::NAN; n];
+    };
+    let l = cholesky.l();
+
+    // L⁻¹ A，再由 A 的對稱性得到 L⁻¹ (L⁻¹ A)' = L⁻¹ A L⁻ᵀ
+    let reduced = l
+        .solve_lower_triangular(&a)
+        .and_then(|l_inv_a| l.solve_lower_triangular(&l_inv_a.transpose()));
+    let Some(reduced) = reduced else {
+        return vec![f64::NAN; n];
+    };
+
+    // 消除捨入誤差造成的不對稱
+    let symmetric = (&reduced + reduced.transpose()) * 0.5;
+    symmetric
+        .symmetric_eigenvalues()
+        .iter()
+        .map(|value| value.abs())
+        .collect()
+}
+
+/// 單一特徵值的概似比項 `-T·ln(1-λ)`，其中 `λ = ρ / T`
+///
+/// 模擬得到的特徵值 `ρ` 是 `T·λ̂` 的漸進極限，因此先換算回樣本特徵值 `λ = ρ / T`。
+/// `ρ` 遠小於 `T` 時此項約等於 `ρ + ρ²/(2T)`；`ρ >= T` 時結果為無窮大或 NaN。
+fn likelihood_ratio_term(eigenvalue: f64, steps: usize) -> f64 {
+    let t = steps as f64;
+    -t * (-eigenvalue / t).ln_1p()
+}
+
+/// 由大到小排序的特徵值複本（不受儲存順序影響）
+fn descending(eigenvalues: &[f64]) -> Vec<f64> {
+    let mut sorted = eigenvalues.to_vec();
+    SortOrder::Descending.sort(&mut sorted);
+    sorted
+}
+
+/// 秩為 `rank` 時的 Johansen 跡統計量 `-T·Σ_{i>rank} ln(1-λ_i)`，`T = steps`
+///
+/// 與跡統計量的原始聚合（特徵值之和，`SumAggregator`）不同，
+/// 這裡套用有限樣本的 `-T·ln(1-λ)` 轉換，且只加總第 `rank` 個之後（由大到小）的特徵值。
+/// `T` 趨於無窮大時兩者一致。
+#[allow(dead_code)]
+pub fn trace_statistic(eigenvalues: &[f64], steps: usize, rank: usize) -> f64 {
+    descending(eigenvalues)
         .iter()
-        .map(|val| val.0.norm() / val.1)
-        .collect();
-    eigenvalues_real.sort_by(|a, b| b.partial_cmp(a).unwrap());
-    eigenvalues_real
+        .skip(rank)
+        .map(|&eigenvalue| likelihood_ratio_term(eigenvalue, steps))
+        .sum()
+}
+
+/// 秩為 `rank` 時的 Johansen 最大特徵值統計量 `-T·ln(1-λ_{rank+1})`，`T = steps`
+///
+/// 與最大特徵值的原始聚合（`MaxAggregator`）不同，這裡取第 `rank + 1` 大的特徵值並套用
+/// `-T·ln(1-λ)` 轉換。`rank` 不小於特徵值數量時返回 NaN。
+#[allow(dead_code)]
+pub fn max_eigen_statistic(eigenvalues: &[f64], steps: usize, rank: usize) -> f64 {
+    descending(eigenvalues)
+        .get(rank)
+        .map_or(f64::NAN, |&eigenvalue| {
+            likelihood_ratio_term(eigenvalue, steps)
+        })
 }
 
 /// 計算 Johansen 測試在指定模型下的特徵值（從完整布朗運動矩陣）
@@ -56,32 +450,405 @@ fn calculate_eigenvalues_from_matrices(
 ///
 /// # 返回值
 /// 按降序排列的特徵值向量
+///
+/// 啟用 `cache` feature 時，相同參數的重複呼叫會直接返回快取結果的複本。
+#[allow(dead_code)]
 pub fn calculate_eigenvalues(
     dim: usize,
     steps: usize,
     seed: u32,
     model: JohansenModel,
 ) -> Vec<f64> {
-    use crate::rng_matrix::{TimeAxisDirection, brownian_motion_matrix};
+    let compute = || {
+        calculate_eigenvalues_with_options(dim, steps, seed, model, EigenvalueOptions::default())
+    };
+
+    #[cfg(feature = "cache")]
+    {
+        use crate::eigenvalue_cache::{cache_key, get_or_compute};
+        get_or_compute(cache_key(dim, steps, seed, model), compute)
+    }
 
-    // 將 u32 seed 轉換為 u64 以兼容底層 RNG
-    let seed_u64 = seed as u64;
+    #[cfg(not(feature = "cache"))]
+    compute()
+}
+
+/// 計算 Johansen 測試在指定模型下的特徵值，並套用計算選項
+///
+/// # 參數
+/// * `dim` - 維度
+/// * `steps` - 時間步數
+/// * `seed` - 隨機種子
+/// * `model` - Johansen 模型類型
+/// * `options` - 排列順序、RNG 子串流等選項
+///
+/// # 返回值
+/// 按 `options.sort_order` 排列的特徵值向量；設定 `options.top_k` 時只含最大的 K 個，
+/// `options.aggregate` 為 `AggregateMode::Both` 時為 `[跡, 最大特徵值]`
+pub fn calculate_eigenvalues_with_options(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    model: JohansenModel,
+    options: EigenvalueOptions,
+) -> Vec<f64> {
+    let delta_t = 1.0 / (steps as f64);
+    calculate_eigenvalues_core(dim, steps, seed, model, delta_t, options, false).0
+}
 
+/// 計算特徵值，並返回求解前的條件數診斷資訊
+///
+/// 與 `calculate_eigenvalues_with_options` 的結果相同，另外估計 `∫F F' dt` 的倒數條件數。
+/// 可搭配 `SolveDiagnostics::is_ill_conditioned` 標記不可靠的 seed。
+pub fn calculate_eigenvalues_with_diagnostics(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    model: JohansenModel,
+    options: EigenvalueOptions,
+) -> (Vec<f64>, SolveDiagnostics) {
     let delta_t = 1.0 / (steps as f64);
-    let bm = brownian_motion_matrix(
+    let (eigenvalues, diagnostics) =
+        calculate_eigenvalues_core(dim, steps, seed, model, delta_t, options, true);
+    (
+        eigenvalues,
+        diagnostics.expect("diagnostics are computed when requested"),
+    )
+}
+
+/// 以指定的時間間隔 `delta_t` 計算 Johansen 測試的特徵值
+///
+/// `calculate_eigenvalues` 等同於 `delta_t = 1 / steps`（單位時間區間）。
+///
+/// `delta_t` 同時決定布朗運動增量的尺度（標準差 `sqrt(delta_t)`）與
+/// `sum_fm_fm_outer_products * delta_t` 中對 ∫F F' dt 的黎曼和近似。
+/// 增量與 F 的隨機列都與 `sqrt(delta_t)` 成正比，常數與趨勢列則不變，
+/// 相當於對 F 做列縮放 `D`：廣義特徵值問題的兩側分別變為
+/// `delta_t · D A D` 與 `delta_t · D B D`，特徵值因此與 `delta_t` 無關。
+/// 改變 `delta_t` 只影響浮點數值的尺度與條件數，可用於檢查數值誤差。
+///
+/// # 參數
+/// * `dim` - 維度
+/// * `steps` - 時間步數
+/// * `seed` - 隨機種子
+/// * `model` - Johansen 模型類型
+/// * `delta_t` - 時間間隔（須為正數）
+///
+/// # 返回值
+/// 按降序排列的特徵值向量
+#[allow(dead_code)]
+pub fn calculate_eigenvalues_with_dt(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    model: JohansenModel,
+    delta_t: f64,
+) -> Vec<f64> {
+    debug_assert!(delta_t > 0.0, "delta_t must be positive");
+    calculate_eigenvalues_core(
         dim,
         steps,
+        seed,
+        model,
         delta_t,
-        TimeAxisDirection::AlongColumns,
-        DMatrix::<f64>::zeros(dim, 1),
-        seed_u64,
+        EigenvalueOptions::default(),
+        false,
+    )
+    .0
+}
+
+/// 每個執行緒跨 seed 重複使用的計算緩衝區
+///
+/// 同一批次中 `dim`、`steps` 與模型都不變，除第一個 seed 外只需覆寫內容而不必重新配置
+/// `dim x steps` 大小的矩陣。形狀改變時（例如換到下一個維度）才重新配置。
+struct Scratch {
+    bm_previous: DMatrix<f64>,
+    dbm: DMatrix<f64>,
+    fm: DMatrix<f64>,
+    sum_dbm_fm: DMatrix<f64>,
+}
+
+impl Default for Scratch {
+    fn default() -> Self {
+        Self {
+            bm_previous: DMatrix::zeros(0, 0),
+            dbm: DMatrix::zeros(0, 0),
+            fm: DMatrix::zeros(0, 0),
+            sum_dbm_fm: DMatrix::zeros(0, 0),
+        }
+    }
+}
+
+impl Scratch {
+    /// 由完整布朗運動矩陣載入捨棄前 `warmup` 個時間步後的 `bm_previous` 與 `dbm`
+    fn load_increments(&mut self, bm: &DMatrix<f64>, warmup: usize) {
+        let (dim, cols) = (bm.nrows(), bm.ncols() - 1 - warmup);
+
+        reshape(&mut self.bm_previous, dim, cols);
+        self.bm_previous.copy_from(&bm.columns(warmup, cols));
+        reshape(&mut self.dbm, dim, cols);
+        self.dbm.copy_from(&bm.columns(warmup + 1, cols));
+        self.dbm -= &self.bm_previous;
+    }
+}
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+}
+
+/// 以目前執行緒的緩衝區執行 `f`
+///
+/// 同一執行緒上已有計算持有緩衝區時（例如 rayon 在等待中竊取了另一個 seed），
+/// 改用臨時緩衝區，不會 panic。
+fn with_scratch<T>(f: impl FnOnce(&mut Scratch) -> T) -> T {
+    SCRATCH.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut scratch) => f(&mut scratch),
+        Err(_) => f(&mut Scratch::default()),
+    })
+}
+
+/// 形狀不同時重新配置為零矩陣，形狀相同時保留原有配置（內容由呼叫者覆寫）
+fn reshape(matrix: &mut DMatrix<f64>, nrows: usize, ncols: usize) {
+    if matrix.shape() != (nrows, ncols) {
+        *matrix = DMatrix::zeros(nrows, ncols);
+    }
+}
+
+/// 生成 `dim x (steps + 1)` 的布朗運動矩陣（時間軸沿欄方向，起點為 0）
+fn generate_brownian_motion(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    delta_t: f64,
+    options: EigenvalueOptions,
+) -> DMatrix<f64> {
+    use crate::rng_matrix::{TimeAxisDirection, brownian_motion_matrix, derive_rng_seed};
+
+    // 由 seed、子串流編號與主種子推導底層 RNG 的 u64 種子
+    let seed_u64 = derive_rng_seed(
+        seed,
+        options.stream_id,
+        options.master_seed,
+        options.seed_hashing,
     );
 
-    let bm_current = bm.columns(1, steps);
-    let bm_previous = bm.columns(0, steps);
+    profiling::time(Stage::BrownianMotion, || {
+        brownian_motion_matrix(
+            dim,
+            steps,
+            delta_t,
+            TimeAxisDirection::AlongColumns,
+            DMatrix::<f64>::zeros(dim, 1),
+            seed_u64,
+        )
+    })
+}
+
+/// 求解時使用的排列順序：設定 `top_k` 時先以降序取最大的 K 個
+fn solve_order(options: EigenvalueOptions) -> SortOrder {
+    match options.top_k {
+        Some(_) => SortOrder::Descending,
+        None => options.sort_order,
+    }
+}
+
+/// 截斷為最大的 K 個後依指定順序排列（未設定 `top_k` 時不變）
+fn apply_top_k(eigenvalues: &mut Vec<f64>, options: EigenvalueOptions) {
+    if let Some(top_k) = options.top_k {
+        eigenvalues.truncate(top_k);
+        options.sort_order.sort(eigenvalues);
+    }
+}
+
+/// 將絕對值低於 `floor` 的特徵值設為 0，其餘特徵值不變
+///
+/// 秩不足的 F 矩陣會產生接近 0 的數值雜訊（可能為微小的負數）。設為 0 等同從統計量中移除：
+/// 跡統計量 `-T Σ ln(1 - λ)` 中 λ = 0 的項為 0，最大特徵值也不受影響。
+/// 為維持每筆記錄固定的特徵值數量，這些值保留為 0 而非自向量中刪除；
+/// 下限應遠小於真實特徵值的尺度，否則會系統性地低估統計量的分布。
+pub fn apply_eigenvalue_floor(eigenvalues: &mut [f64], floor: f64) {
+    for value in eigenvalues.iter_mut() {
+        if value.abs() < floor {
+            *value = 0.0;
+        }
+    }
+}
+
+/// 截斷、下限處理與聚合後的特徵值（未設定 `top_k`、`eigenvalue_floor` 與 `aggregate` 時不變）
+fn finish_eigenvalues(eigenvalues: &mut Vec<f64>, options: EigenvalueOptions) {
+    apply_top_k(eigenvalues, options);
+    if let Some(floor) = options.eigenvalue_floor {
+        apply_eigenvalue_floor(eigenvalues, floor);
+    }
+    if options.aggregate == AggregateMode::Both {
+        *eigenvalues = aggregate_both(eigenvalues);
+    }
+}
+
+/// 特徵值計算的共用實作：生成布朗運動後求解廣義特徵值問題
+///
+/// 布朗運動以外的大型暫存矩陣取自執行緒區域的緩衝池，見 `Scratch`。
+fn calculate_eigenvalues_core(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    model: JohansenModel,
+    delta_t: f64,
+    options: EigenvalueOptions,
+    diagnose: bool,
+) -> (Vec<f64>, Option<SolveDiagnostics>) {
+    calculate_eigenvalues_shared_core(dim, steps, seed, &[model], delta_t, options, diagnose)
+        .pop()
+        .expect("one result per model")
+}
+
+/// 以同一條布朗運動路徑計算多個模型的特徵值，結果依 `models` 的順序排列
+///
+/// 各模型的 F 矩陣都由同一個 seed 的路徑構造，路徑只生成一次。
+/// 每個模型的結果與分別呼叫 `calculate_eigenvalues_with_options`
+/// （或 `diagnose` 時的 `calculate_eigenvalues_with_diagnostics`）逐位元相同。
+pub fn calculate_eigenvalues_shared_path(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    models: &[JohansenModel],
+    options: EigenvalueOptions,
+    diagnose: bool,
+) -> Vec<(Vec<f64>, Option<SolveDiagnostics>)> {
+    let delta_t = 1.0 / (steps as f64);
+    calculate_eigenvalues_shared_core(dim, steps, seed, models, delta_t, options, diagnose)
+}
+
+/// 生成一次布朗運動並依序求解各模型的廣義特徵值問題
+///
+/// 增量只載入緩衝區一次；求解各模型時不會修改 `bm_previous` 與 `dbm`。
+fn calculate_eigenvalues_shared_core(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    models: &[JohansenModel],
+    delta_t: f64,
+    options: EigenvalueOptions,
+    diagnose: bool,
+) -> Vec<(Vec<f64>, Option<SolveDiagnostics>)> {
+    debug_assert!(options.warmup < steps, "warmup must be less than steps");
+
+    // 生成布朗運動時可能進入 rayon，不可在此期間持有緩衝區
+    let bm = generate_brownian_motion(dim, steps, seed, delta_t, options);
+
+    with_scratch(|scratch| {
+        // 捨棄前 warmup 個時間步，從較晚的時間點開始計算泛函
+        profiling::time(Stage::BrownianMotion, || {
+            scratch.load_increments(&bm, options.warmup)
+        });
+        models
+            .iter()
+            .map(|&model| {
+                let (mut eigenvalues, diagnostics) = calculate_eigenvalues_pooled(
+                    scratch,
+                    delta_t,
+                    model,
+                    solve_order(options),
+                    diagnose,
+                );
+                finish_eigenvalues(&mut eigenvalues, options);
+                (eigenvalues, diagnostics)
+            })
+            .collect()
+    })
+}
+
+/// 不使用緩衝池的參考實作，每次呼叫都配置新的暫存矩陣
+///
+/// 結果與 `calculate_eigenvalues_with_options` 相同，用於驗證緩衝池不影響數值。
+#[allow(dead_code)]
+pub(crate) fn calculate_eigenvalues_unpooled(
+    dim: usize,
+    steps: usize,
+    seed: u32,
+    model: JohansenModel,
+    options: EigenvalueOptions,
+) -> Vec<f64> {
+    debug_assert!(options.warmup < steps, "warmup must be less than steps");
+
+    let delta_t = 1.0 / (steps as f64);
+    let bm = generate_brownian_motion(dim, steps, seed, delta_t, options);
+
+    let warmup = options.warmup;
+    let bm_current = bm.columns(warmup + 1, steps - warmup);
+    let bm_previous = bm.columns(warmup, steps - warmup);
     let dbm = bm_current - bm_previous;
 
-    calculate_eigenvalues_from_matrices(&bm_previous.into_owned(), &dbm, delta_t, model)
+    let (mut eigenvalues, _) = calculate_eigenvalues_from_matrices(
+        &bm_previous.into_owned(),
+        &dbm,
+        delta_t,
+        model,
+        solve_order(options),
+        false,
+    )
+    .expect("dbm and bm_previous are slices of the same Brownian path");
+    finish_eigenvalues(&mut eigenvalues, options);
+    eigenvalues
+}
+
+/// 將 F 矩陣構造至預先配置的 `fm`，形狀相同時不重新配置
+///
+/// 數值與 `construct_f_matrix` 逐位元相同。`InterceptTrendUnrestrictedBoth`
+/// 的投影需要多個暫存矩陣，直接沿用 `construct_f_matrix` 的結果。
+fn construct_f_matrix_into(
+    bm_previous: &DMatrix<f64>,
+    model: JohansenModel,
+    fm: &mut DMatrix<f64>,
+) {
+    let (rows, cols) = bm_previous.shape();
+    let t = cols as f64;
+
+    match model {
+        JohansenModel::NoInterceptNoTrend => {
+            reshape(fm, rows, cols);
+            fm.copy_from(bm_previous);
+        }
+
+        JohansenModel::InterceptNoTrendWithInterceptInCoint => {
+            reshape(fm, rows + 1, cols);
+            fm.rows_mut(0, rows).copy_from(bm_previous);
+            fm.rows_mut(rows, 1).fill(1.0);
+        }
+
+        JohansenModel::InterceptNoTrendUnrestrictedIntercept => {
+            reshape(fm, rows, cols);
+            fm.rows_mut(0, rows - 1)
+                .copy_from(&bm_previous.rows(0, rows - 1));
+            for mut row in fm.rows_mut(0, rows - 1).row_iter_mut() {
+                let mean = row.mean();
+                for val in row.iter_mut() {
+                    *val -= mean;
+                }
+            }
+            for (i, val) in fm.row_mut(rows - 1).iter_mut().enumerate() {
+                *val = (i + 1) as f64 / t - 0.5;
+            }
+        }
+
+        JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend => {
+            reshape(fm, rows + 1, cols);
+            fm.rows_mut(0, rows).copy_from(bm_previous);
+            for mut row in fm.rows_mut(0, rows).row_iter_mut() {
+                let mean = row.mean();
+                for val in row.iter_mut() {
+                    *val -= mean;
+                }
+            }
+            for (i, val) in fm.row_mut(rows).iter_mut().enumerate() {
+                *val = (i + 1) as f64 / t - 0.5;
+            }
+        }
+
+        JohansenModel::InterceptTrendUnrestrictedBoth => {
+            *fm = construct_f_matrix(bm_previous, model);
+        }
+    }
 }
 
 /// 根據指定的 Johansen 模型構造 F 矩陣
@@ -1,11 +1,37 @@
+pub(crate) mod adaptive;
+#[cfg(feature = "serde")]
+pub(crate) mod batch_config;
 pub(crate) mod data_storage;
 pub(crate) mod display_utils;
+#[cfg(feature = "cache")]
+pub(crate) mod eigenvalue_cache;
 pub(crate) mod johansen_models;
 pub(crate) mod johansen_statistics;
 pub(crate) mod matrix_utils;
+pub(crate) mod profiling;
 pub(crate) mod rng_matrix;
 mod simulation_analyzers;
+pub(crate) mod stats;
+pub(crate) mod validation;
 
 // Re-export the main API
+pub use adaptive::{AdaptiveResult, AdaptiveTarget, DEFAULT_CHECK_EVERY};
+#[cfg(feature = "serde")]
+pub use batch_config::read_batch_config;
 pub use data_storage::EigenvalueSimulation;
-pub use johansen_models::JohansenModel;
+pub use data_storage::checksum::ChecksumError;
+pub use data_storage::csv_export::{CsvExportOptions, NumberFormat};
+pub use data_storage::reader::read_append_file_flat;
+pub use data_storage::recovery::truncate_to_last_valid_record;
+pub use data_storage::sink::{MemorySink, ResultSink};
+#[cfg(feature = "cache")]
+pub use eigenvalue_cache::{CacheStats, cache_stats, clear_cache, set_cache_capacity};
+pub use johansen_models::{JohansenModel, ModelError};
+pub use johansen_statistics::{AggregateMode, SortOrder};
+pub use simulation_analyzers::{
+    CriticalValueRow, PercentilePairs, REPORT_PERCENTILES, TAIL_PERCENTILES,
+    write_critical_value_table,
+};
+pub use validation::{
+    ReferenceValue, ValidationRow, all_passed, read_reference_csv, write_validation_table,
+};
@@ -1,62 +1,922 @@
+mod adaptive;
+#[cfg(feature = "serde")]
+mod batch_config;
 mod cli;
 mod data_storage;
 mod display_utils;
+#[cfg(feature = "cache")]
+mod eigenvalue_cache;
 mod johansen_models;
 mod johansen_statistics;
 mod matrix_utils;
+mod profiling;
 mod rng_matrix;
 mod simulation_analyzers;
+mod stats;
+mod validation;
 
 #[cfg(test)]
 mod tests;
 
-use cli::CliArgs;
-use data_storage::EigenvalueSimulation;
-use display_utils::{display_percentiles_table, format_duration, format_number_with_commas};
+use cli::{CliArgs, CliError, ExportFormat};
+use data_storage::csv_export::AGGREGATE_COLUMNS;
+use data_storage::{EigenvalueSimulation, disk_space};
+use display_utils::{DimensionTimings, format_bytes, format_duration, format_number_with_commas};
 use johansen_models::JohansenModel;
-use std::time::Instant;
+use johansen_statistics::{AggregateMode, EigenvalueOptions};
+use simulation_analyzers::{
+    Aggregator, MaxAggregator, REPORT_PERCENTILES, SumAggregator, TAIL_PERCENTILES,
+    write_critical_value_table,
+};
+use std::io::Write;
+use std::time::{Duration, Instant};
 
-/// 計算並顯示百分位數統計
-fn display_simulation_statistics(
+/// 依命令行參數建立指定模型與維度的模擬配置
+fn configured_simulation(args: &CliArgs, model: JohansenModel, dim: usize) -> EigenvalueSimulation {
+    let mut simulation = EigenvalueSimulation::new(model, dim, args.steps, args.num_runs)
+        .with_sort_order(args.sort_order)
+        .with_stream_id(args.stream_id)
+        .with_single_thread(args.single_thread)
+        .with_diagnose_complex(args.diagnose_complex)
+        .with_timestamps(args.timestamps)
+        .with_trace_file(args.trace_file)
+        .with_seed_hashing(args.seed_hashing)
+        .with_aggregate(args.aggregate);
+    if let Some(master_seed) = args.master_seed {
+        simulation = simulation.with_master_seed(master_seed);
+    }
+    if let Some(top_k) = args.top_k {
+        simulation = simulation.with_top_k(top_k);
+    }
+    if let Some(floor) = args.eigenvalue_floor {
+        simulation = simulation.with_eigenvalue_floor(floor);
+    }
+    if args.warmup > 0 {
+        simulation = simulation.with_warmup(args.warmup);
+    }
+    if let Some(min_condition) = args.min_condition {
+        simulation = simulation.with_min_condition(min_condition);
+    }
+    if let Some(secs) = args.checkpoint_every {
+        simulation = simulation.with_checkpoint_every(Duration::from_secs(secs as u64));
+    }
+    if let Some(bytes) = args.write_buffer {
+        simulation = simulation.with_write_buffer(bytes);
+    }
+    if let Some(seeds) = &args.seeds {
+        simulation = simulation.with_seeds(seeds.clone());
+    }
+    if let Some(path) = &args.output_file {
+        simulation = simulation.with_output_file(path);
+    }
+    simulation
+}
+
+/// 輸出每個模型與維度目前已完成的模擬次數（只讀取標頭與末尾元數據，不進行計算）
+fn write_completion_counts(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "model, dim, steps, completed/num_runs")?;
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            match simulation.completed_count() {
+                Ok(completed) => writeln!(
+                    out,
+                    "{}, {}, {}, {}/{}",
+                    model.to_number(),
+                    dim,
+                    args.steps,
+                    completed,
+                    args.num_runs
+                )?,
+                Err(e) => writeln!(
+                    out,
+                    "{}, {}, {}, error: {}",
+                    model.to_number(),
+                    dim,
+                    args.steps,
+                    e
+                )?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 輸出批次的檔案數量、總模擬次數與預估輸出大小（`--dry-run`，不進行計算）
+fn write_dry_run(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let dims = args.dimensions();
+    let simulations: Vec<EigenvalueSimulation> = dims
+        .iter()
+        .flat_map(|&dim| {
+            models
+                .iter()
+                .map(move |&model| configured_simulation(args, model, dim))
+        })
+        .collect();
+    let plan = disk_space::plan_batch(&simulations)?;
+    writeln!(out, "Dry run (nothing will be computed):")?;
+    writeln!(
+        out,
+        "  Files: {} ({} model(s) x {} dimension(s))",
+        plan.files,
+        models.len(),
+        dims.len()
+    )?;
+    writeln!(
+        out,
+        "  Runs per file: {}",
+        format_number_with_commas(args.num_runs)
+    )?;
+    writeln!(
+        out,
+        "  Total runs: {}",
+        format_number_with_commas(plan.total_runs as usize)
+    )?;
+    writeln!(
+        out,
+        "  Estimated total size: {}",
+        format_bytes(plan.total_bytes)
+    )?;
+    Ok(())
+}
+
+/// 移除每個模型與維度資料檔案中 seed 超出 `1..=num_runs` 的記錄，並輸出移除的數量
+fn write_prune_report(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "model, dim, steps, pruned")?;
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            match simulation.prune_out_of_range() {
+                Ok(pruned) => writeln!(
+                    out,
+                    "{}, {}, {}, {}",
+                    model.to_number(),
+                    dim,
+                    args.steps,
+                    pruned
+                )?,
+                Err(e) => writeln!(
+                    out,
+                    "{}, {}, {}, error: {}",
+                    model.to_number(),
+                    dim,
+                    args.steps,
+                    e
+                )?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 修復每個模型與維度的資料檔案，並輸出各檔案修改的內容
+fn write_repair_report(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "model, dim, steps, records, truncated_bytes, stray_footers, duplicates, out_of_range, status"
+    )?;
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            let prefix = format!("{}, {}, {}", model.to_number(), dim, args.steps);
+            match simulation.repair() {
+                Ok(Some(report)) => writeln!(
+                    out,
+                    "{prefix}, {}, {}, {}, {}, {}, {}",
+                    report.records,
+                    report.truncated_bytes,
+                    report.stray_footers,
+                    report.duplicates,
+                    report.out_of_range,
+                    if report.rewritten {
+                        "repaired"
+                    } else {
+                        "clean"
+                    }
+                )?,
+                Ok(None) => writeln!(out, "{prefix}, -, -, -, -, -, missing")?,
+                Err(e) => writeln!(out, "{prefix}, -, -, -, -, -, error: {e}")?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 計算開始前確認資料檔案所在的磁碟放得下預估的輸出
+fn check_disk_space(args: &CliArgs, models: &[JohansenModel]) -> std::io::Result<()> {
+    let mut simulations = Vec::new();
+    for dim in args.dimensions() {
+        for &model in models {
+            simulations.push(configured_simulation(args, model, dim));
+        }
+    }
+    disk_space::check_projected_space(&simulations, disk_space::available_space)
+}
+
+/// 以串流方式估計每個模型與維度既有資料的百分位數（`--summary-only`，不進行計算）
+///
+/// 無法讀取的檔案（例如尚未計算）輸出錯誤訊息後略過。
+fn write_streaming_summaries(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            writeln!(out, "\n=== {model}, dim {dim} ===")?;
+            if let Err(e) = simulation.summarize_to_writer(out, &REPORT_PERCENTILES) {
+                writeln!(out, "Error: failed to read data: {e}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 輸出資料檔案的標頭與末尾元數據（不讀取資料記錄）
+fn write_header_info(path: &str, out: &mut impl Write) -> std::io::Result<()> {
+    let summary = data_storage::reader::read_file_summary(path)?;
+    let header = summary.header;
+    let model = match JohansenModel::from_number(header.model) {
+        Some(model) => model.description(),
+        None => "unknown model",
+    };
+
+    writeln!(out, "File: {path}")?;
+    writeln!(out, "Format version: V{}", summary.version)?;
+    writeln!(out, "Model: {} ({})", header.model, model)?;
+    writeln!(out, "Dimension: {}", header.dim)?;
+    writeln!(out, "Steps: {}", header.steps)?;
+    if header.warmup > 0 {
+        writeln!(
+            out,
+            "Warmup: {} (effective steps: {})",
+            header.warmup,
+            header.steps.saturating_sub(header.warmup)
+        )?;
+    }
+    if let Some(master_seed) = header.master_seed {
+        writeln!(out, "Master seed: {master_seed}")?;
+    }
+    if header.has_mixed_seeds() {
+        writeln!(out, "Seed hashing: splitmix")?;
+    }
+    if let Some(floor) = header.eigenvalue_floor {
+        writeln!(out, "Eigenvalue floor: {floor:e}")?;
+    }
+    if header.has_aggregates() {
+        writeln!(out, "Aggregates: trace and max only")?;
+    }
+    if header.has_timestamps() {
+        writeln!(out, "Timestamps: per record (epoch minutes)")?;
+    }
+    match summary.metadata {
+        Some((total_count, eigenvalues_per_run)) => {
+            writeln!(out, "EOF marker: present")?;
+            writeln!(out, "Total count: {total_count}")?;
+            writeln!(out, "Eigenvalues per run: {eigenvalues_per_run}")?;
+        }
+        None => {
+            writeln!(out, "EOF marker: missing (incomplete file)")?;
+            writeln!(out, "Total count: unknown")?;
+            writeln!(out, "Eigenvalues per run: unknown")?;
+        }
+    }
+    Ok(())
+}
+
+/// 以 NDJSON 格式輸出模擬資料：每行一筆 `{"seed":N,"eigenvalues":[...]}`，依 seed 排序
+///
+/// 彙總檔案（`--aggregate both`）的記錄只有跡與最大特徵值，輸出為 `{"seed":N,"trace":x,"max":y}`。
+/// 非有限值（NaN、無窮大）在 JSON 中沒有對應的數字，輸出為 `null`。
+fn write_ndjson(simulation: &EigenvalueSimulation, out: &mut impl Write) -> std::io::Result<()> {
+    let data = simulation.read_data_sorted()?;
+    let aggregates = simulation.aggregate == AggregateMode::Both;
+
+    for (seed, eigenvalues) in data {
+        let values: Vec<String> = eigenvalues
+            .iter()
+            .map(|value| {
+                if value.is_finite() {
+                    value.to_string()
+                } else {
+                    "null".to_string()
+                }
+            })
+            .collect();
+        if aggregates {
+            let fields: Vec<String> = AGGREGATE_COLUMNS
+                .iter()
+                .zip(&values)
+                .map(|(name, value)| format!("\"{name}\":{value}"))
+                .collect();
+            writeln!(out, "{{\"seed\":{},{}}}", seed, fields.join(","))?;
+        } else {
+            writeln!(
+                out,
+                "{{\"seed\":{},\"eigenvalues\":[{}]}}",
+                seed,
+                values.join(",")
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 以 CSV 輸出布朗運動累加前的標準常態抽樣：每列一個時間步，每欄一個維度
+///
+/// 與模擬使用相同的 `gen_normal_matrix(dim, steps, seed)`，可單獨比對 RNG 與參考實作的差異。
+fn write_normals_csv(
+    dim: usize,
+    steps: usize,
+    seed: u64,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let normals = rng_matrix::gen_normal_matrix(dim, steps, seed);
+
+    let header: Vec<String> = (1..=dim).map(|i| format!("x{i}")).collect();
+    writeln!(out, "{}", header.join(","))?;
+    for step in normals.column_iter() {
+        let row: Vec<String> = step.iter().map(|value| value.to_string()).collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// 重新計算單一 seed 並輸出其全部特徵值、跡（總和）與最大特徵值，不讀寫資料檔案
+///
+/// 使用與模擬相同的選項（子串流、主種子、warmup、top-k 與排列順序），
+/// 因此輸出與資料檔案中該 seed 的記錄相同，可用於檢查離群值。
+/// `--aggregate both` 時仍輸出全部特徵值，其跡與最大值即為檔案中的兩個值。
+fn write_rerun_seed(
     simulation: &EigenvalueSimulation,
-    percentiles: &[f64],
-    statistic_type: &str,
-    calculate_fn: impl Fn(&EigenvalueSimulation, &[f64]) -> Result<Vec<f64>, Box<dyn std::error::Error>>,
-) {
-    match calculate_fn(simulation, percentiles) {
-        Ok(values) => {
-            if let Ok(data) = simulation.read_data() {
-                display_percentiles_table(
-                    &simulation.model.to_string(),
-                    statistic_type,
-                    percentiles,
-                    &values,
-                    data.len(),
+    seed: u32,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let options = EigenvalueOptions {
+        aggregate: AggregateMode::Full,
+        ..simulation.eigenvalue_options()
+    };
+    let eigenvalues = johansen_statistics::calculate_eigenvalues_with_options(
+        simulation.dim,
+        simulation.steps,
+        seed,
+        simulation.model,
+        options,
+    );
+
+    writeln!(
+        out,
+        "{}, dim {}, steps {}, seed {seed}",
+        simulation.model,
+        simulation.dim,
+        format_number_with_commas(simulation.steps)
+    )?;
+    writeln!(out, "Eigenvalues ({}):", eigenvalues.len())?;
+    for (i, value) in eigenvalues.iter().enumerate() {
+        writeln!(out, "  {:>3}: {value}", i + 1)?;
+    }
+    let trace = SumAggregator.aggregate(&eigenvalues);
+    let max = MaxAggregator.aggregate(&eigenvalues);
+    writeln!(out, "Trace (sum): {trace}")?;
+    writeln!(out, "Max eigenvalue: {max}")?;
+    Ok(())
+}
+
+/// `--benchmark` 每個模型與維度的模擬次數上限
+const BENCHMARK_RUNS: usize = 1_000;
+
+/// 量測每個模型與維度的吞吐量（在記憶體中計算，不寫入檔案）
+///
+/// 模擬次數為 `--runs` 與 `BENCHMARK_RUNS` 中較小者，以 seed 1..=N 計算。
+fn write_benchmark_report(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "model, dim, steps, runs, elapsed, runs/sec, us/run")?;
+    for dim in args.dimensions() {
+        for &model in models {
+            let mut simulation = configured_simulation(args, model, dim);
+            simulation.num_runs = args.num_runs.min(BENCHMARK_RUNS);
+            let result = simulation.benchmark()?;
+            writeln!(
+                out,
+                "{}, {}, {}, {}, {}, {:.1}, {:.1}",
+                model.to_number(),
+                dim,
+                args.steps,
+                result.runs,
+                format_duration(result.elapsed),
+                result.runs_per_second(),
+                result.micros_per_run()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 比對每個維度的跡統計量臨界值與參考表，輸出比對表格並返回是否全部通過
+fn validate_against_reference(
+    args: &CliArgs,
+    model: JohansenModel,
+    path: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let reference = validation::read_reference_csv(path)?;
+    let mut rows = Vec::new();
+    for dim in args.dimensions() {
+        let simulation = configured_simulation(args, model, dim);
+        rows.extend(simulation.validate_against(&reference, args.tolerance)?);
+    }
+
+    println!();
+    validation::write_validation_table(&rows, args.tolerance, &mut std::io::stdout().lock())?;
+    Ok(validation::all_passed(&rows))
+}
+
+/// 將所有選定模型與維度的臨界值寫入單一 CSV，返回資料列數
+///
+/// 百分位數與分析報告相同（`--tail-only` 時為上尾百分位數）；沒有資料的模型會被略過。
+fn write_table_out(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    path: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let percentiles: &[f64] = if args.tail_only {
+        &TAIL_PERCENTILES
+    } else {
+        &REPORT_PERCENTILES
+    };
+
+    let mut rows = Vec::new();
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            match simulation.critical_values(percentiles) {
+                Ok(values) => rows.extend(values),
+                Err(e) => eprintln!("Warning: skipping {model}, dim {dim} in {path}: {e}"),
+            }
+        }
+    }
+
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_critical_value_table(&rows, &mut out)?;
+    out.flush()?;
+    Ok(rows.len())
+}
+
+/// 決定指定維度要處理的模型
+///
+/// 啟用 `--only-missing-models` 時，以只讀取末尾元數據的計數跳過已完成的模型，
+/// 避免對已完成的模型做完整的進度檢查與輸出。
+fn models_to_process(args: &CliArgs, models: &[JohansenModel], dim: usize) -> Vec<JohansenModel> {
+    if !args.only_missing_models {
+        return models.to_vec();
+    }
+
+    models
+        .iter()
+        .copied()
+        .filter(|&model| {
+            let simulation = configured_simulation(args, model, dim);
+            !matches!(simulation.completed_count(), Ok(completed) if completed >= args.num_runs)
+        })
+        .collect()
+}
+
+/// 依序執行指定維度下各模型的模擬
+///
+/// 模擬失敗時返回帶有模型與維度的錯誤；啟用 `--continue-on-error` 時改為輸出錯誤、
+/// 記錄於 `failures` 並繼續處理下一個模型。
+///
+/// `--shared-paths` 時先以共用布朗運動路徑一次計算所有模型，再逐一分析；
+/// 失敗時整個維度視為失敗。
+///
+/// 設定 `deadline`（`--time-budget`）時，超過截止時間後不再開始新的 seed；
+/// 未完成的模型不做分析，其餘模型也不再處理，留待下次執行續傳。
+fn run_dimension(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    dim: usize,
+    deadline: Option<Instant>,
+    failures: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let models = models_to_process(args, models, dim);
+    let configured = |model| {
+        let simulation = configured_simulation(args, model, dim);
+        match deadline {
+            Some(deadline) => simulation.with_deadline(deadline),
+            None => simulation,
+        }
+    };
+    let budget_exhausted = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    if let (true, Some(&first)) = (args.shared_paths, models.first()) {
+        let simulation = configured(first);
+        let result = if args.quiet {
+            simulation.try_run_shared_path_quiet(&models)
+        } else {
+            simulation.try_run_shared_path(&models)
+        };
+        if let Err(e) = result {
+            let e = std::io::Error::new(e.kind(), format!("dim {dim}: {e}"));
+            if !args.continue_on_error {
+                return Err(e);
+            }
+            eprintln!("Error: {e} (continuing with the next dimension)");
+            failures.push(e.to_string());
+            return Ok(());
+        }
+        if budget_exhausted() {
+            return Ok(());
+        }
+    }
+
+    for model in models {
+        if budget_exhausted() {
+            return Ok(());
+        }
+        let mut simulation = configured(model);
+        let result = if args.shared_paths {
+            Ok(()) // 已在上方計算
+        } else if let Some(target) = args.adaptive_target() {
+            // 之後的分析只使用實際完成的次數
+            simulation.run_adaptive(target, args.quiet).map(|adaptive| {
+                conditional_println!(
+                    args.quiet,
+                    "Adaptive run stopped after {} runs ({})",
+                    format_number_with_commas(adaptive.runs),
+                    if adaptive.converged {
+                        "target precision reached"
+                    } else {
+                        "--runs limit reached before the target precision"
+                    }
                 );
+                simulation.num_runs = adaptive.runs;
+            })
+        } else if args.quiet {
+            simulation.try_run_simulation_quiet()
+        } else {
+            simulation.try_run_simulation()
+        };
+        if let Err(e) = result {
+            let e = std::io::Error::new(e.kind(), format!("{model}, dim {dim}: {e}"));
+            if !args.continue_on_error {
+                return Err(e);
             }
+            eprintln!("Error: {e} (continuing with the next model)");
+            failures.push(e.to_string());
+            continue;
+        }
+        // 截止時間已過時此模型可能只完成一部分，不做分析
+        if budget_exhausted() {
+            return Ok(());
         }
-        Err(_) => {
-            // 如果讀取失敗，忽略這個模型
+
+        report_simulation(args, &simulation);
+    }
+    Ok(())
+}
+
+/// 輸出一個完成的模擬：安靜模式下依 `--stdout-ndjson` 輸出記錄，否則顯示分析結果
+fn report_simulation(args: &CliArgs, simulation: &EigenvalueSimulation) {
+    if args.quiet {
+        if args.stdout_ndjson {
+            if let Err(e) = write_ndjson(simulation, &mut std::io::stdout().lock()) {
+                eprintln!(
+                    "Error: failed to write NDJSON for {}, dim {}: {e}",
+                    simulation.model, simulation.dim
+                );
+            }
         }
+    } else {
+        // 收集並顯示統計數據（在每個模型運行完後立即分析）；讀取失敗時忽略這個模型
+        let _ = write_analysis(args, simulation, &mut std::io::stdout().lock());
     }
 }
 
+/// 依 `--tail-only`、`--order-stats` 與 `--bootstrap` 分析跡統計量與最大特徵值統計量並寫入 `out`
+fn write_analysis(
+    args: &CliArgs,
+    simulation: &EigenvalueSimulation,
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(resamples) = args.bootstrap {
+        // 點估計附上固定種子的 bootstrap 信賴區間
+        let percentiles: &[f64] = if args.tail_only {
+            &TAIL_PERCENTILES
+        } else {
+            &REPORT_PERCENTILES
+        };
+        simulation.analyze_with_bootstrap_to_writer(out, SumAggregator, percentiles, resamples)?;
+        writeln!(out)?;
+        simulation.analyze_with_bootstrap_to_writer(out, MaxAggregator, percentiles, resamples)
+    } else if args.tail_only {
+        // 臨界值所在的上尾以較密的百分位數報告
+        simulation.analyze_tail_to_writer(out, SumAggregator, args.order_stats)?;
+        writeln!(out)?;
+        simulation.analyze_tail_to_writer(out, MaxAggregator, args.order_stats)
+    } else {
+        simulation.analyze_to_writer(out, SumAggregator, &REPORT_PERCENTILES)?;
+        writeln!(out)?;
+        simulation.analyze_to_writer(out, MaxAggregator, &REPORT_PERCENTILES)
+    }
+}
+
+/// 重新分析每個模型與維度既有的資料檔案（`--analyze-only`，不進行計算也不修改檔案）
+///
+/// 與計算完成後的分析相同須有 `1..=num_runs` 的完整數據；無法讀取的檔案輸出錯誤訊息後略過。
+fn write_analysis_only(
+    args: &CliArgs,
+    models: &[JohansenModel],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    for dim in args.dimensions() {
+        for &model in models {
+            let simulation = configured_simulation(args, model, dim);
+            writeln!(out, "\n=== {model}, dim {dim} ===")?;
+            if let Err(e) = write_analysis(args, &simulation, out) {
+                writeln!(out, "Error: failed to analyze data: {e}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 依序執行 `--config` 批次配置檔案中的工作並輸出各自的分析結果
+///
+/// 模擬參數皆取自配置檔案；`--quiet`、`--continue-on-error` 與輸出相關的選項仍依命令行設定。
+/// 失敗時返回帶有工作編號的錯誤，或在 `--continue-on-error` 時記錄於 `failures` 並繼續。
+#[cfg(feature = "serde")]
+fn run_batch(
+    args: &CliArgs,
+    jobs: &[EigenvalueSimulation],
+    failures: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for (index, simulation) in jobs.iter().enumerate() {
+        let job = index + 1;
+        conditional_println!(
+            args.quiet,
+            "\n=== Job {job}/{}: {simulation} ===",
+            jobs.len()
+        );
+        let result = if args.quiet {
+            simulation.try_run_simulation_quiet()
+        } else {
+            simulation.try_run_simulation()
+        };
+        if let Err(e) = result {
+            let e = std::io::Error::new(
+                e.kind(),
+                format!(
+                    "job {job} ({}, dim {}): {e}",
+                    simulation.model, simulation.dim
+                ),
+            );
+            if !args.continue_on_error {
+                return Err(e);
+            }
+            eprintln!("Error: {e} (continuing with the next job)");
+            failures.push(e.to_string());
+            continue;
+        }
+        report_simulation(args, simulation);
+    }
+    Ok(())
+}
+
 fn main() {
     // 解析命令行參數
     let args = match CliArgs::parse() {
-        Some(args) => args,
-        None => return, // 顯示幫助信息或解析錯誤
+        Ok(Some(args)) => args,
+        Ok(None) => return, // 已顯示幫助或版本信息
+        Err(e) => {
+            eprintln!("Error: {e}");
+            if matches!(e, CliError::UnknownArgument(_)) {
+                eprintln!("Use --help to see available options");
+            }
+            std::process::exit(e.exit_code());
+        }
     };
 
-    // 配置 Rayon 線程池
-    args.configure_rayon();
-
     // 將 models_vec 提升到 main 函式較外層作用域
     let models_vec = args
         .models
         .clone()
         .unwrap_or_else(|| JohansenModel::all_models().to_vec());
 
+    // 只回報完成進度，不觸發任何計算
+    if args.count {
+        if let Err(e) = write_completion_counts(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to report completion counts: {e}");
+        }
+        return;
+    }
+
+    // 只輸出批次規劃，不觸發任何計算
+    if args.dry_run {
+        if let Err(e) = write_dry_run(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to print the batch plan: {e}");
+        }
+        return;
+    }
+
+    // 修復既有檔案的損壞，不觸發任何計算
+    if args.repair {
+        if let Err(e) = write_repair_report(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to repair data files: {e}");
+        }
+        return;
+    }
+
+    // 清理既有檔案中超出 1..=num_runs 的 seed，不觸發任何計算
+    if args.prune_out_of_range {
+        if let Err(e) = write_prune_report(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to prune data files: {e}");
+        }
+        return;
+    }
+
+    // 以完整數據重新分析既有檔案，不觸發任何計算
+    if args.analyze_only {
+        if let Err(e) = write_analysis_only(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to analyze data files: {e}");
+        }
+        return;
+    }
+
+    // 只讀取既有檔案並輸出百分位數估計，不觸發任何計算
+    if args.summary_only {
+        if let Err(e) = write_streaming_summaries(&args, &models_vec, &mut std::io::stdout().lock())
+        {
+            eprintln!("Error: failed to summarize data files: {e}");
+        }
+        return;
+    }
+
+    // 只輸出檔案的元數據
+    if let Some(path) = &args.header_path {
+        if let Err(e) = write_header_info(path, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to read header of {path}: {e}");
+        }
+        return;
+    }
+
+    // 將外部 CSV 轉換為資料檔案後結束
+    if let Some(csv_path) = &args.import_csv {
+        let simulation = configured_simulation(&args, models_vec[0], args.dim_start);
+        match simulation.import_csv(csv_path) {
+            Ok(count) => println!(
+                "Imported {} records into {}",
+                format_number_with_commas(count),
+                simulation.get_filename(simulation.model)
+            ),
+            Err(e) => eprintln!("Error: failed to import {csv_path}: {e}"),
+        }
+        return;
+    }
+
+    // 將資料檔案匯出為 CSV 或 Parquet 後結束
+    if let Some((format, path)) = &args.export {
+        let simulation = configured_simulation(&args, models_vec[0], args.dim_start);
+        let result = match format {
+            ExportFormat::Csv => simulation.export_csv(path, &Default::default()),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => simulation.export_parquet(path),
+            #[cfg(not(feature = "parquet"))]
+            ExportFormat::Parquet => unreachable!("rejected during argument validation"),
+        };
+        match result {
+            Ok(count) => conditional_println!(
+                args.quiet,
+                "Exported {} records to {path}",
+                format_number_with_commas(count)
+            ),
+            Err(e) => {
+                eprintln!("Error: failed to export {path}: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // 重新計算單一 seed 並輸出特徵值後結束
+    if let Some(seed) = args.rerun_seed {
+        let simulation = configured_simulation(&args, models_vec[0], args.dim_start);
+        if let Err(e) = write_rerun_seed(&simulation, seed, &mut std::io::stdout().lock()) {
+            eprintln!("Error: failed to print seed {seed}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // 計算單一模型與維度並將資料檔案格式串流至標準輸出，不使用 data/ 目錄
+    if args.no_data_dir {
+        let simulation = configured_simulation(&args, models_vec[0], args.dim_start);
+        if let Err(e) = simulation.stream_to(std::io::stdout()) {
+            eprintln!("Error: failed to stream records to stdout: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // 輸出指定 seed 的常態抽樣後結束（使用起始維度、目前的子串流與主種子）
+    if let Some(seed) = args.dump_normals {
+        let path = format!(
+            "normals_dim{}_steps{}_seed{}.csv",
+            args.dim_start, args.steps, seed
+        );
+        let seed_u64 =
+            rng_matrix::derive_rng_seed(seed, args.stream_id, args.master_seed, args.seed_hashing);
+        let result = std::fs::File::create(&path).and_then(|file| {
+            let mut out = std::io::BufWriter::new(file);
+            write_normals_csv(args.dim_start, args.steps, seed_u64, &mut out)?;
+            out.flush()
+        });
+        match result {
+            Ok(()) => println!("Normal draws written to {path}"),
+            Err(e) => eprintln!("Error: failed to write {path}: {e}"),
+        }
+        return;
+    }
+
+    // 配置 Rayon 線程池
+    args.configure_rayon();
+
+    // 依批次配置檔案執行工作後結束（步數等參數取自檔案，不檢查命令行的 --steps）
+    #[cfg(feature = "serde")]
+    if let Some(path) = &args.config {
+        let jobs = match batch_config::read_batch_config(path) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                let e = CliError::InvalidValue(format!("failed to read config file {path}: {e}"));
+                eprintln!("Error: {e}");
+                std::process::exit(e.exit_code());
+            }
+        };
+        if !args.ignore_space {
+            if let Err(e) = disk_space::check_projected_space(&jobs, disk_space::available_space) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+        let mut failures = Vec::new();
+        if let Err(e) = run_batch(&args, &jobs, &mut failures) {
+            eprintln!("Error: batch failed at {e}");
+            std::process::exit(1);
+        }
+        if !failures.is_empty() {
+            eprintln!("\n{} job(s) failed:", failures.len());
+            for failure in &failures {
+                eprintln!("  {failure}");
+            }
+            std::process::exit(1);
+        }
+        conditional_println!(args.quiet, "\nBatch completed: {} job(s)", jobs.len());
+        return;
+    }
+
+    // 步數過少時提醒離散化偏誤（不中止執行）
+    if let Some(warning) = args.steps_warning() {
+        eprintln!("Warning: {warning}");
+    }
+
+    // 只量測吞吐量，不讀寫資料檔案
+    if args.benchmark {
+        if let Err(e) = write_benchmark_report(&args, &models_vec, &mut std::io::stdout().lock()) {
+            eprintln!("Error: benchmark failed: {e}");
+        }
+        return;
+    }
+
+    // 預估輸出超過可用空間時，在開始長時間計算前中止
+    if !args.ignore_space {
+        if let Err(e) = check_disk_space(&args, &models_vec) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if args.profile {
+        profiling::enable();
+    }
+
     conditional_println!(args.quiet, "=== Large-scale Simulation Demo ===");
     conditional_println!(
         args.quiet,
@@ -73,16 +933,34 @@ fn main() {
         "  Runs: {}",
         format_number_with_commas(args.num_runs)
     );
-    conditional_println!(
-        args.quiet,
-        "  Dimension range: {} - {}",
-        args.dim_start,
-        args.dim_end
-    );
+    match &args.dim_list {
+        Some(dims) => conditional_println!(
+            args.quiet,
+            "  Dimensions: {}",
+            dims.iter()
+                .map(|dim| dim.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => conditional_println!(
+            args.quiet,
+            "  Dimension range: {} - {}",
+            args.dim_start,
+            args.dim_end
+        ),
+    }
     conditional_println!(args.quiet, "  Threads: {}", rayon::current_num_threads());
     conditional_println_empty!(args.quiet);
 
-    for dim in args.dim_start..=args.dim_end {
+    let mut timings = DimensionTimings::new();
+    let mut failures = Vec::new();
+    let deadline = args
+        .time_budget
+        .map(|secs| Instant::now() + Duration::from_secs(secs as u64));
+    for dim in args.dimensions() {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
         let start_time = Instant::now();
         conditional_println!(
             args.quiet,
@@ -106,28 +984,13 @@ fn main() {
         }
 
         // 對每個模型運行模擬
-        for &model in &models_vec {
-            let simulation = EigenvalueSimulation::new(model, dim, args.steps, args.num_runs);
-            if args.quiet {
-                simulation.run_simulation_quiet();
-            } else {
-                simulation.run_simulation();
-
-                // 定義要計算的百分位數
-                let percentiles = vec![0.5, 0.75, 0.8, 0.85, 0.9, 0.95, 0.975, 0.99];
-
-                // 收集並顯示統計數據（在每個模型運行完後立即分析）
-                display_simulation_statistics(&simulation, &percentiles, "Trace", |sim, p| {
-                    sim.calculate_trace_percentiles(p)
-                });
-                println!();
-                display_simulation_statistics(&simulation, &percentiles, "MaxEig", |sim, p| {
-                    sim.calculate_maxeig_percentiles(p)
-                });
-            }
+        if let Err(e) = run_dimension(&args, &models_vec, dim, deadline, &mut failures) {
+            eprintln!("Error: simulation failed for {e}");
+            std::process::exit(1);
         }
 
         let elapsed_time = start_time.elapsed();
+        timings.record(dim, elapsed_time);
         conditional_println!(
             args.quiet,
             "Simulation completed! Duration: {}",
@@ -135,12 +998,62 @@ fn main() {
         );
     }
 
+    // 多維度批次結束時比較各維度耗時
+    if timings.len() > 1 && !args.quiet {
+        println!();
+        print!("{}", timings.format_summary());
+    }
+
+    // --continue-on-error 時彙總失敗的模型
+    if !failures.is_empty() {
+        eprintln!("\n{} model run(s) failed:", failures.len());
+        for failure in &failures {
+            eprintln!("  {failure}");
+        }
+    }
+
+    // 時間預算用盡時數據尚未完整，略過臨界值表、比對與讀取示範
+    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        conditional_println!(
+            args.quiet,
+            "Time budget exhausted; rerun the same command to resume the remaining work"
+        );
+        std::process::exit(if failures.is_empty() { 0 } else { 1 });
+    }
+
+    // 將臨界值寫入單一表格檔案
+    if let Some(path) = &args.table_out {
+        match write_table_out(&args, &models_vec, path) {
+            Ok(rows) => conditional_println!(
+                args.quiet,
+                "Critical value table written to {path} ({} rows)",
+                format_number_with_commas(rows)
+            ),
+            Err(e) => {
+                eprintln!("Error: failed to write {path}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // 與參考臨界值表比對，超出容許誤差時以非零狀態結束
+    if let Some(path) = &args.validate_against {
+        match validate_against_reference(&args, models_vec[0], path) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Error: failed to validate against {path}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     conditional_println!(args.quiet, "\n=== Result Reading Demo ===");
 
     // 讀取特定模型的數據
     conditional_println!(args.quiet, "Starting to read model data...");
     let model = models_vec[0]; // 使用第一個模型作為範例
-    let simulation = EigenvalueSimulation::new(model, args.dim_start, args.steps, args.num_runs);
+    let simulation = configured_simulation(&args, model, args.dim_start);
     conditional_println!(
         args.quiet,
         "Simulation config: {} dimensions, {} steps, {} runs",
@@ -213,19 +1126,33 @@ fn main() {
 
     // 展示所有模型的狀態
     conditional_println!(args.quiet, "\n=== All Models Status ===");
-    // 使用 for-loop 搭配 simulation.read_data
+    // 使用 completed_count 只讀取檔案末尾的記錄數，避免掃描整個檔案
     for &model in &models_vec {
-        let sim = EigenvalueSimulation::new(model, args.dim_start, args.steps, args.num_runs);
-        match sim.read_data() {
-            Ok(data) => conditional_println!(
+        let sim = configured_simulation(&args, model, args.dim_start);
+        match sim.completed_count() {
+            Ok(count) if count > 0 => conditional_println!(
                 args.quiet,
                 "  {}: {} data records",
                 model,
-                format_number_with_commas(data.len())
+                format_number_with_commas(count)
             ),
-            Err(_) => conditional_println!(args.quiet, "  {}: No data or read failed", model),
+            _ => conditional_println!(args.quiet, "  {}: No data or read failed", model),
         }
     }
 
     conditional_println!(args.quiet, "\nDemo completed!");
+
+    if args.profile {
+        // NDJSON 模式下標準輸出只留給資料，剖析報告改寫至標準錯誤
+        if args.stdout_ndjson {
+            eprint!("{}", profiling::format_report());
+        } else {
+            println!();
+            print!("{}", profiling::format_report());
+        }
+    }
+
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
 }
@@ -10,6 +10,7 @@ pub enum CumsumOrder {
     RowWise,    // 每一列各自累加
 }
 
+#[allow(dead_code)]
 pub fn dmatrix_cumsum(matrix: &DMatrix<f64>, order: CumsumOrder) -> DMatrix<f64> {
     match order {
         CumsumOrder::ColumnMajor => {
@@ -64,6 +65,50 @@ pub fn dmatrix_cumsum(matrix: &DMatrix<f64>, order: CumsumOrder) -> DMatrix<f64>
     }
 }
 
+/// `dmatrix_cumsum` 的就地版本，直接以累加結果覆寫 `matrix`
+///
+/// 累加順序與 `dmatrix_cumsum` 相同，結果逐位元相同，但不配置新的矩陣。
+pub fn dmatrix_cumsum_in_place(matrix: &mut DMatrix<f64>, order: CumsumOrder) {
+    let (nrows, ncols) = matrix.shape();
+    match order {
+        CumsumOrder::ColumnMajor => {
+            let mut acc = 0.0;
+            for v in matrix.iter_mut() {
+                acc += *v;
+                *v = acc;
+            }
+        }
+        CumsumOrder::RowMajor => {
+            let mut acc = 0.0;
+            for row in 0..nrows {
+                for col in 0..ncols {
+                    acc += matrix[(row, col)];
+                    matrix[(row, col)] = acc;
+                }
+            }
+        }
+        CumsumOrder::ColumnWise => {
+            for mut column in matrix.column_iter_mut() {
+                let mut acc = 0.0;
+                for v in column.iter_mut() {
+                    acc += *v;
+                    *v = acc;
+                }
+            }
+        }
+        CumsumOrder::RowWise => {
+            for mut row in matrix.row_iter_mut() {
+                let mut acc = 0.0;
+                for v in row.iter_mut() {
+                    acc += *v;
+                    *v = acc;
+                }
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
 pub fn sum_of_outer_products(a: &DMatrix<f64>, b: &DMatrix<f64>) -> DMatrix<f64> {
     use rayon::prelude::*;
 
@@ -83,3 +128,62 @@ pub fn sum_of_outer_products(a: &DMatrix<f64>, b: &DMatrix<f64>) -> DMatrix<f64>
             |acc, outer_product| acc + outer_product,   // 累加操作
         )
 }
+
+/// 將 `a` 與 `b` 每一欄的外積累加到預先配置的 `acc`
+///
+/// `sum_of_outer_products` 的另一種實作：以 rank-1 更新（`ger`）依欄的順序就地累加，
+/// 不會為每一欄配置暫存矩陣。`sum_of_outer_products` 以 rayon 平行歸約，加總順序隨執行緒切分而變；
+/// 這裡的順序固定，結果與執行緒數無關，但與 `sum_of_outer_products` 只在數值可精確相加時逐位元相同，
+/// 一般情況下會在最後幾個位元不同。特徵值計算使用此版本，使相同 seed 的結果在任何執行緒數下都相同。
+/// 呼叫者須確保 `acc` 為 `a.nrows() x b.nrows()`，若只要外積和本身，請先將 `acc` 清為零。
+pub fn sum_of_outer_products_into(acc: &mut DMatrix<f64>, a: &DMatrix<f64>, b: &DMatrix<f64>) {
+    let n_samples = a.ncols();
+    debug_assert_eq!(b.ncols(), n_samples);
+    debug_assert_eq!(acc.shape(), (a.nrows(), b.nrows()));
+
+    for i in 0..n_samples {
+        acc.ger(1.0, &a.column(i), &b.column(i), 1.0);
+    }
+}
+
+/// 計算 Gram 矩陣 `AᵀA`（以一般矩陣乘法 `a.transpose() * a`）
+pub fn gram_matrix(a: &DMatrix<f64>) -> DMatrix<f64> {
+    a.transpose() * a
+}
+
+/// `gram_matrix` 的另一種實作：只計算上三角再鏡射至下三角
+///
+/// 以 `a` 各欄的內積計算，不需配置 `a.transpose()` 暫存矩陣，內積次數約為完整矩陣乘法的一半。
+/// 數值上與 `gram_matrix` 不保證逐位元相同：後者對較大的矩陣交由 matrixmultiply 計算，
+/// 其分塊方式與執行期選用的 FMA 核心會改變捨入。這裡每個內積依列的順序逐項累加，結果完全對稱，
+/// 與 `gram_matrix` 只在數值可精確相加時逐位元相同，因此特徵值計算仍使用 `gram_matrix`。
+#[allow(dead_code)]
+pub fn symmetric_gram_matrix(a: &DMatrix<f64>) -> DMatrix<f64> {
+    let (nrows, n) = a.shape();
+    let data = a.as_slice();
+    // column-major：第 k 欄為 data[k * nrows..(k + 1) * nrows]
+    let column = |k: usize| &data[k * nrows..(k + 1) * nrows];
+
+    let mut gram = vec![0.0; n * n];
+    for j in 0..n {
+        let column_j = column(j);
+        for i in 0..=j {
+            let value: f64 = column(i).iter().zip(column_j).map(|(x, y)| x * y).sum();
+            gram[j * n + i] = value;
+            gram[i * n + j] = value;
+        }
+    }
+    DMatrix::from_vec(n, n, gram)
+}
+
+/// 對稱半正定矩陣的倒數條件數（2-範數）：最小特徵值 / 最大特徵值
+///
+/// 結果介於 0 與 1 之間，越接近 0 表示矩陣越接近奇異；零矩陣返回 0。
+pub fn reciprocal_condition(matrix: &DMatrix<f64>) -> f64 {
+    let eigenvalues = matrix.clone().symmetric_eigenvalues();
+    let max = eigenvalues.max();
+    if max <= 0.0 {
+        return 0.0;
+    }
+    eigenvalues.min().max(0.0) / max
+}
@@ -0,0 +1,151 @@
+//! 效能剖析模組
+//!
+//! 在 `--profile` 模式下累計 `calculate_eigenvalues` 各階段的耗時（奈秒），
+//! 以原子計數器跨執行緒彙總，並於執行結束時輸出各階段的時間分佈。
+//! 未啟用時僅多一次 relaxed 原子讀取，不會呼叫 `Instant::now()`。
+
+use crate::display_utils::format_duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 特徵值計算的階段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// 布朗運動矩陣生成
+    BrownianMotion,
+    /// F 矩陣構造與外積累加
+    OuterProducts,
+    /// 廣義特徵值求解（LAPACK 或 `pure-rust` 的 Cholesky 求解器）
+    EigenSolve,
+}
+
+impl Stage {
+    /// 所有階段（依計算順序）
+    pub const ALL: [Stage; 3] = [
+        Stage::BrownianMotion,
+        Stage::OuterProducts,
+        Stage::EigenSolve,
+    ];
+
+    /// 階段名稱
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::BrownianMotion => "Brownian motion generation",
+            Stage::OuterProducts => "Outer products",
+            Stage::EigenSolve => "Eigen solve",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STAGE_NANOS: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// 啟用效能剖析
+#[allow(dead_code)]
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// 是否已啟用效能剖析
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) || thread_enabled()
+}
+
+#[cfg(test)]
+thread_local! {
+    static THREAD_ENABLED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+#[cfg(test)]
+fn thread_enabled() -> bool {
+    THREAD_ENABLED.with(std::cell::Cell::get)
+}
+
+#[cfg(not(test))]
+#[inline]
+fn thread_enabled() -> bool {
+    false
+}
+
+/// 只在目前執行緒啟用效能剖析的範圍，離開範圍時恢復
+///
+/// 測試用：全域的 `enable` 會影響同一測試執行檔中並行的其他測試。
+#[cfg(test)]
+#[allow(dead_code)]
+pub struct ThreadProfilingGuard {
+    previous: bool,
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+impl ThreadProfilingGuard {
+    pub fn enable() -> Self {
+        let previous = THREAD_ENABLED.with(|enabled| enabled.replace(true));
+        Self { previous }
+    }
+}
+
+#[cfg(test)]
+impl Drop for ThreadProfilingGuard {
+    fn drop(&mut self) {
+        THREAD_ENABLED.with(|enabled| enabled.set(self.previous));
+    }
+}
+
+/// 執行閉包並將耗時累計至指定階段（未啟用時直接執行）
+#[inline]
+pub fn time<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed().as_nanos() as u64;
+    STAGE_NANOS[stage.index()].fetch_add(elapsed, Ordering::Relaxed);
+    result
+}
+
+/// 取得各階段目前累計的耗時
+#[allow(dead_code)]
+pub fn stage_durations() -> [(Stage, Duration); 3] {
+    Stage::ALL.map(|stage| {
+        let nanos = STAGE_NANOS[stage.index()].load(Ordering::Relaxed);
+        (stage, Duration::from_nanos(nanos))
+    })
+}
+
+/// 格式化各階段耗時分佈
+///
+/// 耗時為所有執行緒的加總，因此可能大於實際經過的時間。
+#[allow(dead_code)]
+pub fn format_report() -> String {
+    let durations = stage_durations();
+    let total_nanos: u128 = durations.iter().map(|(_, d)| d.as_nanos()).sum();
+    let name_width = Stage::ALL
+        .iter()
+        .map(|stage| stage.name().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut report = String::from("=== Profile (CPU time summed across threads) ===\n");
+    for (stage, duration) in durations {
+        let share = if total_nanos == 0 {
+            0.0
+        } else {
+            duration.as_nanos() as f64 / total_nanos as f64 * 100.0
+        };
+        report.push_str(&format!(
+            "  {:<name_width$}  {:>6.2}%  {}\n",
+            stage.name(),
+            share,
+            format_duration(duration)
+        ));
+    }
+    report
+}
@@ -1,4 +1,4 @@
-use crate::matrix_utils::{CumsumOrder, dmatrix_cumsum};
+use crate::matrix_utils::{CumsumOrder, dmatrix_cumsum_in_place};
 
 use nalgebra::DMatrix;
 use rand::Rng;
@@ -8,16 +8,102 @@ use rand_distr::StandardNormal;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use rayon::prelude::*;
 
+/// 子串流 0 的 seed 轉換為 RNG 種子的方式
+///
+/// 非 0 子串流一律以 SplitMix64 混合 `(stream_id, seed)`，兩種設定的結果相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeedHashing {
+    /// 直接以 `seed as u64` 作為種子，用於續寫先前版本產生的檔案
+    ///
+    /// 只沿用先前版本的種子推導；常態矩陣的 chunk 切分已改變，因此不會逐位元重現舊資料。
+    Legacy,
+    /// 先以 SplitMix64 混合，使相鄰 seed 的初始狀態沒有共同的位元樣式
+    #[default]
+    SplitMix,
+}
+
+impl SeedHashing {
+    /// 此設定下子串流 `stream_id` 的種子是否與 `Legacy` 不同（記錄於檔案標頭）
+    pub fn differs_from_legacy(self, stream_id: u32) -> bool {
+        self == SeedHashing::SplitMix && stream_id == 0
+    }
+}
+
+impl std::fmt::Display for SeedHashing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SeedHashing::Legacy => "legacy",
+            SeedHashing::SplitMix => "splitmix",
+        })
+    }
+}
+
+impl std::str::FromStr for SeedHashing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "legacy" => Ok(SeedHashing::Legacy),
+            "splitmix" | "splitmix64" => Ok(SeedHashing::SplitMix),
+            other => Err(format!(
+                "invalid seed hashing: {other} (expected splitmix or legacy)"
+            )),
+        }
+    }
+}
+
+/// 由 seed 與子串流編號推導 Xoshiro 的 u64 種子
+///
+/// 以 SplitMix64 混合 `(stream_id, seed)`，使各串流與相鄰 seed 彼此獨立；
+/// `SeedHashing::Legacy` 時串流 0 沿用原本的 `seed as u64`。
+pub fn derive_stream_seed(seed: u32, stream_id: u32, hashing: SeedHashing) -> u64 {
+    if stream_id == 0 && hashing == SeedHashing::Legacy {
+        return seed as u64;
+    }
+
+    splitmix64((stream_id as u64) << 32 | seed as u64)
+}
+
+/// 由 seed、子串流編號與主種子推導 Xoshiro 的 u64 種子
+///
+/// 未指定主種子時與 `derive_stream_seed` 相同；指定時再以 SplitMix64 混合主種子，
+/// 使同一段 seed 範圍在不同主種子下得到互相獨立的資料集，而 seed 仍只作為記錄編號。
+pub fn derive_rng_seed(
+    seed: u32,
+    stream_id: u32,
+    master_seed: Option<u64>,
+    hashing: SeedHashing,
+) -> u64 {
+    let stream_seed = derive_stream_seed(seed, stream_id, hashing);
+    match master_seed {
+        None => stream_seed,
+        Some(master_seed) => splitmix64(splitmix64(master_seed) ^ stream_seed),
+    }
+}
+
+/// SplitMix64 的單步輸出（雙射，不同輸入必得不同輸出）
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// 每個 chunk 的最小元素數量
+const MIN_CHUNK_LEN: usize = 10_000;
+
+/// 產生標準常態分佈的隨機矩陣
+///
+/// 資料依元素位置切成 chunk 並行產生，第 i 個 chunk 使用主 RNG 的第 i 個輸出作為種子。
+/// chunk 的切分只取決於矩陣大小，因此相同 seed 的結果與 CPU 核心數及 rayon 執行緒數無關。
 pub fn gen_normal_matrix(nrows: usize, ncols: usize, seed: u64) -> DMatrix<f64> {
     let total = nrows.checked_mul(ncols).expect("Matrix too large");
     let mut data = vec![0.0; total];
 
-    // 取得邏輯核心數
-    let n_cpus = num_cpus::get_physical();
-    // 讓每個核心有多一點工作量，避免太多小 chunk
-    let min_chunk = 10_000;
-    let chunk_count = (total / min_chunk).max(n_cpus).min(total);
-    let chunk_size = total.div_ceil(chunk_count);
+    // 不可依賴核心數切分，否則不同機器會得到不同的隨機數
+    let chunk_count = (total / MIN_CHUNK_LEN).max(1);
+    let chunk_size = total.div_ceil(chunk_count).max(1);
 
     // 建立主 RNG 產生每個 chunk 專用的 seed
     let mut base_rng = Xoshiro256PlusPlus::seed_from_u64(seed);
@@ -134,8 +220,10 @@ pub fn brownian_motion_matrix(
         check_start_shape(dim, time_axis, &start);
     }
 
-    let z = make_z_matrix(dim, steps, time_axis, &start, seed);
+    // 縮放與累加都就地進行，每次模擬只配置一個 dim × (steps + 1) 矩陣
+    let mut path = make_z_matrix(dim, steps, time_axis, &start, seed);
     let sqrt_dt = delta_t.sqrt();
-    let scaled = z.map(|v| v * sqrt_dt);
-    dmatrix_cumsum(&scaled, time_axis.to_cumsum_order())
+    path.iter_mut().for_each(|v| *v *= sqrt_dt);
+    dmatrix_cumsum_in_place(&mut path, time_axis.to_cumsum_order());
+    path
 }
@@ -1,25 +1,50 @@
 use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::reader::stream_append_file;
+use crate::display_utils::{write_percentiles_table, write_percentiles_table_with_intervals};
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::{
+    AGGREGATE_MAX_INDEX, AGGREGATE_TRACE_INDEX, AggregateMode, max_eigen_statistic, trace_statistic,
+};
+use crate::stats::bootstrap::{BOOTSTRAP_SEED, bootstrap_percentile_intervals};
+use crate::stats::p2_quantile::P2Quantile;
+use crate::stats::percentile::{nth_percentile, percentile_sorted};
+use crate::stats::total_cmp_f64;
+use rayon::prelude::*;
+use std::io::Write;
 
-/// 輸出百分位數統計資訊，使用內插法計算百分位值
-fn get_percentile_value(sorted_values: &[f64], percentile: f64) -> f64 {
-    let n = sorted_values.len();
-    if n == 0 {
-        return f64::NAN;
-    }
-    let rank = percentile * (n - 1) as f64;
-    let lower_index = rank.floor() as usize;
-    let upper_index = rank.ceil() as usize;
-    if lower_index == upper_index {
-        sorted_values[lower_index]
+/// 每個模型完成後分析報告使用的百分位數
+pub const REPORT_PERCENTILES: [f64; 8] = [0.5, 0.75, 0.8, 0.85, 0.9, 0.95, 0.975, 0.99];
+
+/// `--tail-only` 報告的上尾百分位數，臨界值通常落在此區間
+pub const TAIL_PERCENTILES: [f64; 6] = [0.90, 0.95, 0.975, 0.99, 0.995, 0.999];
+
+/// 超過此數量的樣本改用平行排序，小樣本的執行緒調度成本高於收益
+pub const PARALLEL_SORT_THRESHOLD: usize = 1 << 16;
+
+/// 由小到大排序統計量，樣本數達 `PARALLEL_SORT_THRESHOLD` 時使用 rayon 平行排序
+///
+/// 兩種排序都使用全序比較 `total_cmp_f64`，排序結果一致；NaN 排在最後。
+pub fn sort_values(values: &mut [f64]) {
+    if values.len() >= PARALLEL_SORT_THRESHOLD {
+        values.par_sort_unstable_by(total_cmp_f64);
     } else {
-        let weight = rank - lower_index as f64;
-        sorted_values[lower_index] * (1.0 - weight) + sorted_values[upper_index] * weight
+        values.sort_by(total_cmp_f64);
     }
 }
 
 /// 聚合函數 trait
 pub trait Aggregator {
     fn aggregate(&self, eigenvalues: &[f64]) -> f64;
+
+    /// 統計量名稱，用於分析報告的標題
+    fn name(&self) -> &'static str;
+
+    /// 由 `AggregateMode::Both` 的記錄 `[跡, 最大特徵值]` 取得統計量
+    ///
+    /// 預設返回 `None`，代表此統計量需要個別特徵值，無法由這兩個值計算。
+    fn aggregate_stored(&self, _aggregates: &[f64]) -> Option<f64> {
+        None
+    }
 }
 
 /// 聚合函數實作：計算總和
@@ -28,43 +53,220 @@ impl Aggregator for SumAggregator {
     fn aggregate(&self, eigenvalues: &[f64]) -> f64 {
         eigenvalues.iter().sum()
     }
+
+    fn name(&self) -> &'static str {
+        "Trace"
+    }
+
+    fn aggregate_stored(&self, aggregates: &[f64]) -> Option<f64> {
+        aggregates.get(AGGREGATE_TRACE_INDEX).copied()
+    }
 }
 
 /// 聚合函數實作：計算最大值
+///
+/// 取真正的最大值而非第一個元素，因此不受儲存順序影響
 pub struct MaxAggregator;
 impl Aggregator for MaxAggregator {
     fn aggregate(&self, eigenvalues: &[f64]) -> f64 {
         eigenvalues.iter().cloned().fold(f64::MIN, f64::max)
     }
+
+    fn name(&self) -> &'static str {
+        "MaxEig"
+    }
+
+    fn aggregate_stored(&self, aggregates: &[f64]) -> Option<f64> {
+        aggregates.get(AGGREGATE_MAX_INDEX).copied()
+    }
+}
+
+/// 聚合函數實作：秩為 0 時的 Johansen 跡統計量 `-T·Σln(1-λ)`
+///
+/// `SumAggregator` 是特徵值的原始總和（漸進分布）；此聚合以 `T = steps`
+/// 套用有限樣本轉換，見 `johansen_statistics::trace_statistic`。
+pub struct TraceStatisticAggregator {
+    pub steps: usize,
+}
+impl Aggregator for TraceStatisticAggregator {
+    fn aggregate(&self, eigenvalues: &[f64]) -> f64 {
+        trace_statistic(eigenvalues, self.steps, 0)
+    }
+
+    fn name(&self) -> &'static str {
+        "Trace statistic"
+    }
+}
+
+/// 聚合函數實作：秩為 0 時的 Johansen 最大特徵值統計量 `-T·ln(1-λ_max)`
+///
+/// `MaxAggregator` 是最大特徵值本身；此聚合以 `T = steps` 套用有限樣本轉換。
+pub struct MaxEigenStatisticAggregator {
+    pub steps: usize,
+}
+impl Aggregator for MaxEigenStatisticAggregator {
+    fn aggregate(&self, eigenvalues: &[f64]) -> f64 {
+        max_eigen_statistic(eigenvalues, self.steps, 0)
+    }
+
+    fn name(&self) -> &'static str {
+        "Max-eigen statistic"
+    }
+
+    fn aggregate_stored(&self, aggregates: &[f64]) -> Option<f64> {
+        aggregates
+            .get(AGGREGATE_MAX_INDEX)
+            .map(|&max| max_eigen_statistic(&[max], self.steps, 0))
+    }
+}
+
+/// 以聚合函數計算每筆記錄的統計量，見 `EigenvalueSimulation::record_statistic`
+fn statistic_values<A: Aggregator + ?Sized>(
+    simulation: &EigenvalueSimulation,
+    data: &[(u32, Vec<f64>)],
+    aggregator: &A,
+) -> std::io::Result<Vec<f64>> {
+    data.iter()
+        .map(|(_, eigenvalues)| simulation.record_statistic(aggregator, eigenvalues))
+        .collect()
+}
+
+/// 讀取模擬數據並依聚合函數轉換為排序後的統計量
+fn aggregated_sorted_values<A: Aggregator>(
+    simulation: &EigenvalueSimulation,
+    aggregator: &A,
+) -> std::io::Result<Vec<f64>> {
+    let data = simulation.read_data()?;
+    let mut values = statistic_values(simulation, &data, aggregator)?;
+    sort_values(&mut values);
+    Ok(values)
+}
+
+/// 臨界值表中的一列：同一模型、維度與百分位數下兩種統計量的值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CriticalValueRow {
+    pub model: JohansenModel,
+    pub dim: usize,
+    pub percentile: f64,
+    /// 跡統計量（特徵值總和，`SumAggregator`）
+    pub trace: f64,
+    /// 最大特徵值統計量（`MaxAggregator`）
+    pub maxeig: f64,
+}
+
+/// 以 `model,dim,percentile,trace_value,maxeig_value` 欄位寫出臨界值表 CSV
+pub fn write_critical_value_table(
+    rows: &[CriticalValueRow],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "model,dim,percentile,trace_value,maxeig_value")?;
+    for row in rows {
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            row.model.to_number(),
+            row.dim,
+            row.percentile,
+            row.trace,
+            row.maxeig
+        )?;
+    }
+    Ok(())
+}
+
+/// 取已排序樣本中最大的 `k` 個順序統計量，由大到小排列
+///
+/// 樣本數不足 `k` 時返回全部樣本。
+pub fn top_order_statistics(values_sorted: &[f64], k: usize) -> Vec<f64> {
+    values_sorted.iter().rev().take(k).copied().collect()
+}
+
+/// 計算兩組已排序樣本的雙樣本 Kolmogorov–Smirnov 統計量
+///
+/// 同時掃描兩組樣本，於每個相異值之後比較兩者的經驗分布函數，
+/// 返回最大差距 `sup |F1(x) - F2(x)|`。任一樣本為空時返回 NaN。
+#[allow(dead_code)]
+pub fn ks_statistic_sorted(a_sorted: &[f64], b_sorted: &[f64]) -> f64 {
+    let (n, m) = (a_sorted.len(), b_sorted.len());
+    if n == 0 || m == 0 {
+        return f64::NAN;
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut max_diff: f64 = 0.0;
+    while i < n && j < m {
+        // 取下一個最小值，並跳過兩組樣本中所有等於此值的元素
+        let x = a_sorted[i].min(b_sorted[j]);
+        while i < n && a_sorted[i] <= x {
+            i += 1;
+        }
+        while j < m && b_sorted[j] <= x {
+            j += 1;
+        }
+        let diff = (i as f64 / n as f64 - j as f64 / m as f64).abs();
+        max_diff = max_diff.max(diff);
+    }
+    max_diff
 }
 
 /// 計算指定百分位數的值
+///
+/// 只查詢單一百分位數時以 quickselect 取代完整排序。
 pub fn calculate_percentiles<A: Aggregator>(
     simulation: &EigenvalueSimulation,
     aggregator: A,
     percentiles: &[f64],
 ) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
     let data = simulation.read_data()?;
-    if data.is_empty() {
-        return Ok(vec![]);
-    }
+    let values = statistic_values(simulation, &data, &aggregator)?;
+    Ok(percentiles_of_values(values, percentiles))
+}
 
-    let values: Vec<f64> = data
-        .iter()
-        .map(|(_, eigenvalues)| aggregator.aggregate(eigenvalues))
-        .collect();
-    let mut sorted_values = values;
-    sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+/// 計算統計量樣本的百分位數；樣本為空時返回空向量
+fn percentiles_of_values(mut values: Vec<f64>, percentiles: &[f64]) -> Vec<f64> {
+    if values.is_empty() {
+        return vec![];
+    }
+    if let [percentile] = percentiles {
+        return vec![nth_percentile(&mut values, *percentile)];
+    }
 
-    let results = percentiles
+    sort_values(&mut values);
+    percentiles
         .iter()
-        .map(|&percentile| get_percentile_value(&sorted_values, percentile))
-        .collect();
-
-    Ok(results)
+        .map(|&percentile| percentile_sorted(&values, percentile))
+        .collect()
 }
 
+/// 跡與最大特徵值的 `(百分位數, 值)` 配對（`run_and_analyze` 的結果）
+pub type PercentilePairs = (Vec<(f64, f64)>, Vec<(f64, f64)>);
+
 impl EigenvalueSimulation {
+    /// 以聚合函數計算一筆記錄的統計量
+    ///
+    /// `AggregateMode::Full` 的記錄為全部特徵值，使用 `Aggregator::aggregate`；
+    /// `AggregateMode::Both` 的記錄為 `[跡, 最大特徵值]`，改用 `Aggregator::aggregate_stored`，
+    /// 無法由這兩個值計算的統計量返回 `ErrorKind::Unsupported` 錯誤。
+    pub fn record_statistic<A: Aggregator + ?Sized>(
+        &self,
+        aggregator: &A,
+        record: &[f64],
+    ) -> std::io::Result<f64> {
+        match self.aggregate {
+            AggregateMode::Full => Ok(aggregator.aggregate(record)),
+            AggregateMode::Both => aggregator.aggregate_stored(record).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!(
+                        "{} cannot be computed from data that stores only the trace and max (--aggregate both)",
+                        aggregator.name()
+                    ),
+                )
+            }),
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn calculate_trace_percentiles(
         &self,
         percentiles: &[f64],
@@ -72,10 +274,258 @@ impl EigenvalueSimulation {
         calculate_percentiles(self, SumAggregator, percentiles)
     }
 
+    #[allow(dead_code)]
     pub fn calculate_maxeig_percentiles(
         &self,
         percentiles: &[f64],
     ) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
         calculate_percentiles(self, MaxAggregator, percentiles)
     }
+
+    /// 運行模擬（安靜模式）並返回跡與最大特徵值的 `(百分位數, 值)` 配對
+    ///
+    /// 已有部分結果時從中斷處續算，已完成時直接讀取。兩個向量的長度與順序皆與 `percentiles` 相同，
+    /// 數值同 `calculate_trace_percentiles` 與 `calculate_maxeig_percentiles`；沒有數據時值為 NaN。
+    #[allow(dead_code)]
+    pub fn run_and_analyze(&self, percentiles: &[f64]) -> std::io::Result<PercentilePairs> {
+        self.try_run_simulation_quiet()?;
+        let data = self.read_data()?;
+
+        let pairs = |aggregator: &dyn Aggregator| -> std::io::Result<Vec<(f64, f64)>> {
+            let mut values = statistic_values(self, &data, aggregator)?;
+            sort_values(&mut values);
+            Ok(percentiles
+                .iter()
+                .map(|&q| (q, percentile_sorted(&values, q)))
+                .collect())
+        };
+        Ok((pairs(&SumAggregator)?, pairs(&MaxAggregator)?))
+    }
+
+    /// 計算跡統計量 `-T·Σln(1-λ)`（`T = steps`）的百分位數
+    ///
+    /// `calculate_trace_percentiles` 回報特徵值的原始總和；此方法套用有限樣本轉換。
+    #[allow(dead_code)]
+    pub fn calculate_trace_statistic_percentiles(
+        &self,
+        percentiles: &[f64],
+    ) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+        let aggregator = TraceStatisticAggregator { steps: self.steps };
+        calculate_percentiles(self, aggregator, percentiles)
+    }
+
+    /// 計算最大特徵值統計量 `-T·ln(1-λ_max)`（`T = steps`）的百分位數
+    ///
+    /// `calculate_maxeig_percentiles` 回報最大特徵值本身；此方法套用有限樣本轉換。
+    #[allow(dead_code)]
+    pub fn calculate_max_eigen_statistic_percentiles(
+        &self,
+        percentiles: &[f64],
+    ) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+        let aggregator = MaxEigenStatisticAggregator { steps: self.steps };
+        calculate_percentiles(self, aggregator, percentiles)
+    }
+
+    /// 計算統計量的百分位數並以表格寫入 `w`
+    ///
+    /// 表格標題使用 `Aggregator::name`，可將分析結果寫入標準輸出、檔案或記憶體緩衝區。
+    pub fn analyze_to_writer<A: Aggregator>(
+        &self,
+        w: &mut dyn Write,
+        aggregator: A,
+        percentiles: &[f64],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.read_data()?;
+        let values = statistic_values(self, &data, &aggregator)?;
+        let results = percentiles_of_values(values, percentiles);
+
+        write_percentiles_table(
+            w,
+            &self.model.to_string(),
+            aggregator.name(),
+            percentiles,
+            &results,
+            data.len(),
+        )?;
+        Ok(())
+    }
+
+    /// 計算統計量的百分位數與 bootstrap 信賴區間，以 `value [lo, hi]` 表格寫入 `w`
+    ///
+    /// 信賴區間以固定種子 `BOOTSTRAP_SEED` 重抽 `resamples` 次計算（見 `bootstrap_percentile_intervals`），
+    /// 相同數據的輸出可重現。
+    pub fn analyze_with_bootstrap_to_writer<A: Aggregator>(
+        &self,
+        w: &mut dyn Write,
+        aggregator: A,
+        percentiles: &[f64],
+        resamples: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let values = aggregated_sorted_values(self, &aggregator)?;
+        let results: Vec<f64> = percentiles
+            .iter()
+            .map(|&percentile| percentile_sorted(&values, percentile))
+            .collect();
+        let intervals =
+            bootstrap_percentile_intervals(&values, percentiles, resamples, BOOTSTRAP_SEED);
+
+        write_percentiles_table_with_intervals(
+            w,
+            &self.model.to_string(),
+            aggregator.name(),
+            percentiles,
+            &results,
+            Some(&intervals),
+            values.len(),
+        )?;
+        Ok(())
+    }
+
+    /// 以串流方式讀取數據，估計多個統計量在各百分位數的值
+    ///
+    /// 每筆記錄讀取後立即聚合並送入 P² 估計器（見 `P2Quantile`），不保存原始數據，
+    /// 記憶體用量與檔案大小無關；結果為近似值，各百分位數分別估計，
+    /// 樣本少時可能不單調。與 `read_data` 相同只使用
+    /// seed <= num_runs 的記錄，但不要求數據完整。返回每個統計量的結果與使用的記錄數量。
+    pub fn streaming_percentiles(
+        &self,
+        aggregators: &[&dyn Aggregator],
+        percentiles: &[f64],
+    ) -> std::io::Result<(Vec<Vec<f64>>, usize)> {
+        let (_, records) = stream_append_file(self.try_get_filename(self.model)?)?;
+        let mut estimators: Vec<Vec<P2Quantile>> = aggregators
+            .iter()
+            .map(|_| percentiles.iter().map(|&q| P2Quantile::new(q)).collect())
+            .collect();
+        let mut count = 0;
+
+        for record in records {
+            let (seed, eigenvalues) = record?;
+            if seed > self.num_runs as u32 {
+                continue;
+            }
+            for (aggregator, row) in aggregators.iter().zip(&mut estimators) {
+                let value = self.record_statistic(*aggregator, &eigenvalues)?;
+                row.iter_mut().for_each(|estimator| estimator.push(value));
+            }
+            count += 1;
+        }
+
+        let results = estimators
+            .iter()
+            .map(|row| row.iter().map(P2Quantile::estimate).collect())
+            .collect();
+        Ok((results, count))
+    }
+
+    /// 以串流方式估計跡與最大特徵值的百分位數並以表格寫入 `w`（`--summary-only`）
+    ///
+    /// 兩個統計量只需讀取檔案一次，見 `streaming_percentiles`。
+    pub fn summarize_to_writer(
+        &self,
+        w: &mut dyn Write,
+        percentiles: &[f64],
+    ) -> std::io::Result<()> {
+        let aggregators: [&dyn Aggregator; 2] = [&SumAggregator, &MaxAggregator];
+        let (results, count) = self.streaming_percentiles(&aggregators, percentiles)?;
+
+        for (i, (aggregator, values)) in aggregators.iter().zip(&results).enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            // 沒有數據時與 `analyze_to_writer` 相同輸出空白表格
+            let values: &[f64] = if count == 0 { &[] } else { values };
+            write_percentiles_table(
+                w,
+                &self.model.to_string(),
+                &format!("{} (streaming estimate)", aggregator.name()),
+                percentiles,
+                values,
+                count,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 計算跡統計量與最大特徵值統計量在各百分位數的臨界值
+    ///
+    /// 資料只讀取一次；沒有資料時返回空向量。
+    pub fn critical_values(
+        &self,
+        percentiles: &[f64],
+    ) -> Result<Vec<CriticalValueRow>, Box<dyn std::error::Error>> {
+        let data = self.read_data()?;
+        let trace =
+            percentiles_of_values(statistic_values(self, &data, &SumAggregator)?, percentiles);
+        let maxeig =
+            percentiles_of_values(statistic_values(self, &data, &MaxAggregator)?, percentiles);
+
+        Ok(percentiles
+            .iter()
+            .zip(trace.iter().zip(&maxeig))
+            .map(|(&percentile, (&trace, &maxeig))| CriticalValueRow {
+                model: self.model,
+                dim: self.dim,
+                percentile,
+                trace,
+                maxeig,
+            })
+            .collect())
+    }
+
+    /// 以較密的上尾百分位數（`TAIL_PERCENTILES`）分析統計量並寫入 `w`
+    ///
+    /// `order_stats` 大於 0 時，另外列出最大的 `order_stats` 個樣本值。
+    pub fn analyze_tail_to_writer<A: Aggregator>(
+        &self,
+        w: &mut dyn Write,
+        aggregator: A,
+        order_stats: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let values = aggregated_sorted_values(self, &aggregator)?;
+        let results: Vec<f64> = if values.is_empty() {
+            vec![]
+        } else {
+            TAIL_PERCENTILES
+                .iter()
+                .map(|&percentile| percentile_sorted(&values, percentile))
+                .collect()
+        };
+
+        write_percentiles_table(
+            w,
+            &self.model.to_string(),
+            aggregator.name(),
+            &TAIL_PERCENTILES,
+            &results,
+            values.len(),
+        )?;
+
+        if order_stats > 0 {
+            writeln!(w, "Largest {order_stats} values:")?;
+            for (rank, value) in top_order_statistics(&values, order_stats)
+                .iter()
+                .enumerate()
+            {
+                writeln!(w, "  {:>4}  {value:.6}", rank + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 計算與另一組模擬數據之間的雙樣本 Kolmogorov–Smirnov 統計量
+    ///
+    /// 兩組數據先以 `aggregator` 聚合（例如 `SumAggregator` 為跡統計量），
+    /// 再比較其經驗分布。不同 seed 或子串流的數據無法逐點比較時，
+    /// 可用此統計量檢驗兩者是否來自相同分布。
+    #[allow(dead_code)]
+    pub fn ks_statistic<A: Aggregator>(
+        &self,
+        other: &EigenvalueSimulation,
+        aggregator: A,
+    ) -> std::io::Result<f64> {
+        let a = aggregated_sorted_values(self, &aggregator)?;
+        let b = aggregated_sorted_values(other, &aggregator)?;
+        Ok(ks_statistic_sorted(&a, &b))
+    }
 }
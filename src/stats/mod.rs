@@ -0,0 +1,18 @@
+//! 統計輔助函數
+//!
+//! 提供所有浮點數排序共用的比較函數、百分位數計算（`percentile`）、
+//! 固定記憶體的串流分位數估計（`p2_quantile`）與百分位數的 bootstrap 信賴區間（`bootstrap`）。
+
+pub mod bootstrap;
+pub mod p2_quantile;
+pub mod percentile;
+
+use std::cmp::Ordering;
+
+/// 浮點數的全序比較，供所有排序使用
+///
+/// 以 `f64::total_cmp` 取代 `partial_cmp(..).unwrap()`，遇到 NaN 不會 panic：
+/// 升冪排序時（正號）NaN 固定排在最後，降冪排序時排在最前。
+pub fn total_cmp_f64(a: &f64, b: &f64) -> Ordering {
+    a.total_cmp(b)
+}
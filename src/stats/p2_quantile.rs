@@ -0,0 +1,126 @@
+//! 串流分位數估計（P² 演算法，Jain & Chlamtac, 1985）
+//!
+//! 只以五個標記的高度與位置追蹤目標分位數，記憶體用量固定，不需保存樣本。
+//! 每個新樣本到來時調整標記位置，偏離理想位置的中間標記以分段拋物線（必要時改用線性）
+//! 內插修正高度。樣本少於五個時直接以 `percentile_sorted` 計算精確值。
+
+use super::percentile::percentile_sorted;
+use super::total_cmp_f64;
+
+/// 單一分位數的 P² 串流估計器
+#[derive(Debug, Clone)]
+pub struct P2Quantile {
+    q: f64,
+    count: usize,
+    /// 標記高度（前五個樣本到齊前為依序收到的樣本）
+    heights: [f64; 5],
+    /// 標記的實際位置（0 起算）
+    positions: [f64; 5],
+    /// 標記的理想位置
+    desired: [f64; 5],
+    /// 每個樣本使理想位置增加的量
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    /// 建立估計 `q` 分位數（0 與 1 之間）的估計器
+    pub fn new(q: f64) -> Self {
+        Self {
+            q,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [0.0, 1.0, 2.0, 3.0, 4.0],
+            desired: [0.0, 2.0 * q, 4.0 * q, 2.0 + 2.0 * q, 4.0],
+            increments: [0.0, q / 2.0, q, (1.0 + q) / 2.0, 1.0],
+        }
+    }
+
+    /// 目標分位數
+    #[allow(dead_code)]
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+
+    /// 已加入的樣本數量
+    #[allow(dead_code)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 加入一個樣本；NaN 會被略過
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(total_cmp_f64);
+            }
+            return;
+        }
+        self.count += 1;
+
+        // 找出樣本所在的區間，必要時更新端點標記
+        let h = &mut self.heights;
+        let k = if x < h[0] {
+            h[0] = x;
+            0
+        } else if x >= h[4] {
+            h[4] = x;
+            3
+        } else {
+            (0..4).rfind(|&i| h[i] <= x).unwrap_or(0)
+        };
+
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            let room_right = self.positions[i + 1] - self.positions[i] > 1.0;
+            let room_left = self.positions[i - 1] - self.positions[i] < -1.0;
+            if (offset >= 1.0 && room_right) || (offset <= -1.0 && room_left) {
+                let d = offset.signum();
+                let parabolic = self.parabolic(i, d);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, d)
+                    };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    /// 目前的分位數估計值；沒有樣本時返回 NaN
+    pub fn estimate(&self) -> f64 {
+        if self.count < 5 {
+            let mut values = self.heights[..self.count].to_vec();
+            values.sort_by(total_cmp_f64);
+            return percentile_sorted(&values, self.q);
+        }
+        self.heights[2]
+    }
+
+    /// 分段拋物線內插的標記高度
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (h, n) = (&self.heights, &self.positions);
+        h[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (h[i + 1] - h[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (h[i] - h[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    /// 線性內插的標記高度
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        let (h, n) = (&self.heights, &self.positions);
+        h[i] + d * (h[j] - h[i]) / (n[j] - n[i])
+    }
+}
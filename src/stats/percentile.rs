@@ -0,0 +1,96 @@
+//! 百分位數計算
+//!
+//! 所有百分位數都以線性內插計算：排序後第 `q·(n-1)` 個位置（0 起算）落在兩個順序統計量之間時，
+//! 依距離加權平均。`percentile_sorted` 用於已排序的樣本；只需要單一分位數時，
+//! `nth_percentile` 以 quickselect（`select_nth_unstable_by`）取代完整排序，平均 O(n) 即可得到相同結果。
+//! 兩個順序統計量相等（重複值）時直接返回該值，不經內插，因此不會出現捨入誤差。
+
+use super::total_cmp_f64;
+
+/// 在相鄰兩個順序統計量之間線性內插
+///
+/// 兩者相等時直接返回，避免 `a·(1-w) + a·w` 與 `a` 相差一個 ulp。
+fn interpolate(lower: f64, upper: f64, weight: f64) -> f64 {
+    if lower == upper {
+        lower
+    } else {
+        lower * (1.0 - weight) + upper * weight
+    }
+}
+
+/// 計算已由小到大排序樣本的百分位數（線性內插）
+///
+/// `q` 介於 0 與 1 之間。樣本為空或 `q` 超出範圍時返回 NaN。
+pub fn percentile_sorted(sorted_values: &[f64], q: f64) -> f64 {
+    let n = sorted_values.len();
+    if n == 0 || !(0.0..=1.0).contains(&q) {
+        return f64::NAN;
+    }
+    if q == 1.0 {
+        return sorted_values[n - 1];
+    }
+
+    let rank = q * (n - 1) as f64;
+    let lower_index = rank.floor() as usize;
+    let upper_index = (rank.ceil() as usize).min(n - 1);
+    let weight = rank - lower_index as f64;
+    interpolate(
+        sorted_values[lower_index],
+        sorted_values[upper_index],
+        weight,
+    )
+}
+
+/// 以 quickselect 計算單一百分位數，內插方式與 `percentile_sorted` 相同
+///
+/// `q` 介於 0 與 1 之間。計算過程會重新排列 `values` 的順序。
+/// 樣本為空、含有 NaN 或 `q` 超出範圍時返回 NaN。
+pub fn nth_percentile(values: &mut [f64], q: f64) -> f64 {
+    let n = values.len();
+    if n == 0 || !(0.0..=1.0).contains(&q) || values.iter().any(|value| value.is_nan()) {
+        return f64::NAN;
+    }
+    if q == 1.0 {
+        return values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    }
+
+    let rank = q * (n - 1) as f64;
+    let lower_index = rank.floor() as usize;
+    let upper_index = (rank.ceil() as usize).min(n - 1);
+
+    let (_, lower, upper_part) = values.select_nth_unstable_by(lower_index, total_cmp_f64);
+    let lower = *lower;
+    if lower_index == upper_index {
+        return lower;
+    }
+
+    // 下一個順序統計量即右側分區的最小值
+    let upper = upper_part.iter().copied().fold(f64::INFINITY, f64::min);
+    let weight = rank - lower_index as f64;
+    interpolate(lower, upper, weight)
+}
+
+/// 以批次法估計樣本 `q` 分位數的標準誤
+///
+/// 依原順序將樣本分為 `batches` 個等長批次（無法整除的末尾樣本不使用），分別計算 `q` 分位數，
+/// 以批次分位數的樣本標準差除以 `sqrt(batches)` 近似全樣本分位數估計量的標準誤。
+/// 樣本須為獨立抽樣（例如依 seed 排列的模擬結果）。批次少於 2 個或任一批次為空時返回 NaN。
+pub fn batch_quantile_standard_error(values: &[f64], q: f64, batches: usize) -> f64 {
+    let batch_len = values.len() / batches.max(1);
+    if batches < 2 || batch_len == 0 {
+        return f64::NAN;
+    }
+
+    let quantiles: Vec<f64> = values
+        .chunks_exact(batch_len)
+        .take(batches)
+        .map(|batch| nth_percentile(&mut batch.to_vec(), q))
+        .collect();
+    let mean = quantiles.iter().sum::<f64>() / batches as f64;
+    let variance = quantiles
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (batches - 1) as f64;
+    (variance / batches as f64).sqrt()
+}
@@ -0,0 +1,71 @@
+use crate::adaptive::AdaptiveTarget;
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+
+#[test]
+fn test_adaptive_stops_once_precise() {
+    let data_dir = "data/test_adaptive";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let upper_bound = 50_000;
+    let simulation =
+        EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 1, 352, upper_bound)
+            .with_data_dir(data_dir);
+    let target = AdaptiveTarget {
+        quantile: 0.5,
+        precision: 0.1,
+        check_every: 200,
+    };
+
+    let result = simulation.run_adaptive(target, true).unwrap();
+    assert!(result.converged, "{result:?}");
+    assert!(result.runs < upper_bound, "{result:?}");
+    assert!(result.relative_half_width() <= target.precision);
+    assert!(result.estimate > 0.0);
+
+    // 檔案中恰好有 seed 1..=runs
+    assert_eq!(simulation.completed_count().unwrap(), result.runs);
+
+    // 既有結果已達精度時不再計算
+    let again = simulation.run_adaptive(target, true).unwrap();
+    assert_eq!(again, result);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_adaptive_stops_at_upper_bound() {
+    let data_dir = "data/test_adaptive_bound";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 1, 353, 300)
+        .with_data_dir(data_dir);
+    let target = AdaptiveTarget {
+        quantile: 0.95,
+        precision: 1e-9,
+        check_every: 100,
+    };
+
+    let result = simulation.run_adaptive(target, true).unwrap();
+    assert!(!result.converged);
+    assert_eq!(result.runs, 300);
+    assert_eq!(simulation.completed_count().unwrap(), 300);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_adaptive_rejects_zero_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    let simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 1, 372, 0)
+        .with_data_dir(dir.path());
+    let target = AdaptiveTarget {
+        quantile: 0.5,
+        precision: 0.1,
+        check_every: 100,
+    };
+
+    let error = simulation.run_adaptive(target, true).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!simulation.data_exists(JohansenModel::NoInterceptNoTrend));
+}
@@ -0,0 +1,128 @@
+use crate::batch_config::read_batch_config;
+use crate::cli::CliArgs;
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+use std::path::Path;
+
+fn parse(args: &[&str]) -> CliArgs {
+    let args: Vec<String> = std::iter::once("johansen-null-eigenspectra")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+    CliArgs::parse_from(&args).unwrap().unwrap()
+}
+
+fn write_config(dir: &Path, contents: &str) -> String {
+    let path = dir.join("batch.json");
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_config_batch_produces_expected_files() {
+    use crate::run_batch;
+
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = serde_json::to_string(dir.path()).unwrap();
+    let config = write_config(
+        dir.path(),
+        &format!(
+            r#"[
+                {{"model": 0, "dim": 2, "steps": 355, "num_runs": 4, "data_dir": {data_dir}}},
+                {{"model": "InterceptTrendUnrestrictedBoth", "dim": 3, "steps": 355, "num_runs": 3,
+                  "data_dir": {data_dir}}}
+            ]"#
+        ),
+    );
+
+    let jobs = read_batch_config(&config).unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[1].model, JohansenModel::InterceptTrendUnrestrictedBoth);
+
+    let args = parse(&["--quiet", "--config", &config]);
+    assert_eq!(args.config.as_deref(), Some(config.as_str()));
+    let mut failures = Vec::new();
+    run_batch(&args, &jobs, &mut failures).unwrap();
+    assert!(failures.is_empty());
+
+    let mut produced: Vec<String> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".dat"))
+        .collect();
+    produced.sort();
+    let mut expected: Vec<String> = jobs
+        .iter()
+        .map(|job| {
+            Path::new(&job.get_filename(job.model))
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    expected.sort();
+    assert_eq!(produced, expected);
+
+    for job in &jobs {
+        assert_eq!(job.completed_count().unwrap(), job.num_runs);
+    }
+    let first = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 2, 355, 4)
+        .with_data_dir(dir.path());
+    assert_eq!(first.read_data().unwrap().len(), 4);
+}
+
+#[test]
+fn test_config_rejects_invalid_jobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let cases = [
+        ("[]", "no jobs"),
+        (
+            r#"[{"model": 2, "dim": 0, "steps": 100, "num_runs": 1}]"#,
+            "job 1",
+        ),
+        (
+            r#"[{"model": 2, "dim": 2, "steps": 100, "num_runs": 1},
+                {"model": 2, "dim": 2, "steps": 100, "num_runs": 1, "warmup": 100}]"#,
+            "job 2: warmup",
+        ),
+        (
+            r#"[{"model": 1, "dim": 2, "steps": 100, "num_runs": 1, "top_k": 3}]"#,
+            "job 1: top-k",
+        ),
+        (
+            r#"[{"model": 2, "dim": 260, "steps": 100, "num_runs": 1}]"#,
+            "job 1: dim (260) needs 260 eigenvalues per run",
+        ),
+        (
+            r#"[{"model": 7, "dim": 2, "steps": 100, "num_runs": 1}]"#,
+            "model",
+        ),
+        (r#"{"model": 2}"#, "batch config"),
+    ];
+    for (contents, expected) in cases {
+        let config = write_config(dir.path(), contents);
+        let err = read_batch_config(&config).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().contains(expected),
+            "{contents}: unexpected error {err}"
+        );
+    }
+}
+
+#[test]
+fn test_config_conflicts_with_per_run_options() {
+    let args: Vec<String> = [
+        "johansen-null-eigenspectra",
+        "--config",
+        "batch.json",
+        "--shared-paths",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    let err = CliArgs::parse_from(&args).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("--shared-paths"));
+}
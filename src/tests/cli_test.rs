@@ -0,0 +1,997 @@
+use crate::cli::{CliArgs, CliError, ExportFormat};
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::AggregateMode;
+use crate::{write_analysis_only, write_completion_counts, write_dry_run};
+
+fn try_parse(args: &[&str]) -> Result<Option<CliArgs>, CliError> {
+    let args: Vec<String> = std::iter::once("johansen-null-eigenspectra")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+    CliArgs::parse_from(&args)
+}
+
+fn parse(args: &[&str]) -> Result<CliArgs, CliError> {
+    try_parse(args).map(|args| args.expect("help or version was not requested"))
+}
+
+#[test]
+fn test_parse_distinguishes_help_from_errors() {
+    assert_eq!(try_parse(&["--help"]).unwrap().map(|_| ()), None);
+    assert_eq!(try_parse(&["-v"]).unwrap().map(|_| ()), None);
+    assert!(try_parse(&["--dim", "3"]).unwrap().is_some());
+
+    let unknown = try_parse(&["--no-such-flag"]).unwrap_err();
+    assert_eq!(
+        unknown,
+        CliError::UnknownArgument("--no-such-flag".to_string())
+    );
+    let missing = try_parse(&["--runs"]).unwrap_err();
+    assert_eq!(missing, CliError::MissingValue("--runs".to_string()));
+    let invalid_value = try_parse(&["--runs", "many"]).unwrap_err();
+    assert!(matches!(invalid_value, CliError::InvalidValue(_)));
+    let invalid = try_parse(&["--dim-start", "5", "--dim-end", "2"]).unwrap_err();
+    assert!(matches!(invalid, CliError::Invalid(_)));
+
+    // 每類錯誤有各自的非零結束代碼
+    let codes = [
+        &unknown,
+        &missing,
+        &invalid_value,
+        &invalid,
+        &CliError::Cancelled,
+    ]
+    .map(CliError::exit_code);
+    assert!(codes.iter().all(|&code| code != 0));
+    for (i, code) in codes.iter().enumerate() {
+        assert!(!codes[i + 1..].contains(code));
+    }
+}
+
+#[test]
+fn test_parse_count_flag() {
+    let args = parse(&["--count", "--dim", "3", "--model", "1"]).unwrap();
+    assert!(args.count);
+    assert_eq!((args.dim_start, args.dim_end), (3, 3));
+    assert_eq!(
+        args.models,
+        Some(vec![JohansenModel::InterceptNoTrendWithInterceptInCoint])
+    );
+
+    assert!(!parse(&[]).unwrap().count);
+}
+
+#[test]
+fn test_parse_single_thread_conflicts_with_threads() {
+    assert!(parse(&["--single-thread"]).unwrap().single_thread);
+    assert!(parse(&["--single-thread", "--threads", "1"]).is_ok());
+    assert!(parse(&["--single-thread", "--threads", "2"]).is_err());
+}
+
+#[test]
+fn test_parse_top_k_validates_against_dimension() {
+    let args = parse(&["--top-k", "2", "--dim-start", "3", "--dim-end", "5"]).unwrap();
+    assert_eq!(args.top_k, Some(2));
+    assert_eq!(parse(&[]).unwrap().top_k, None);
+
+    // K 須介於 1 與最小維度之間
+    assert!(parse(&["--top-k", "4", "--dim-start", "3", "--dim-end", "5"]).is_err());
+    assert!(parse(&["--top-k", "0", "--dim", "3"]).is_err());
+}
+
+#[test]
+fn test_dry_run_reports_batch_totals() {
+    let args = parse(&[
+        "--dry-run",
+        "--dim-start",
+        "1",
+        "--dim-end",
+        "3",
+        "--runs",
+        "1e6",
+    ])
+    .unwrap();
+    assert!(args.dry_run);
+    assert!(!parse(&[]).unwrap().dry_run);
+
+    let mut out = Vec::new();
+    write_dry_run(&args, &JohansenModel::all_models(), &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+    assert!(report.contains("Files: 15 (5 model(s) x 3 dimension(s))"));
+    assert!(report.contains("Runs per file: 1,000,000"));
+    assert!(report.contains("Total runs: 15,000,000"));
+    assert!(report.contains("Estimated total size: "));
+}
+
+#[test]
+fn test_analyze_only_reads_existing_file_without_modifying_it() {
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, 365, 20);
+    let filename = simulation.get_filename(model);
+    let missing = EigenvalueSimulation::new(model, 3, 365, 20).get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+    let _ = std::fs::remove_file(&missing);
+
+    simulation.run_simulation_quiet();
+    let before = std::fs::read(&filename).unwrap();
+
+    let args = parse(&[
+        "--analyze-only",
+        "--dim-start",
+        "2",
+        "--dim-end",
+        "3",
+        "--steps",
+        "365",
+        "--runs",
+        "20",
+        "--model",
+        "2",
+    ])
+    .unwrap();
+    assert!(args.analyze_only);
+
+    let mut out = Vec::new();
+    write_analysis_only(&args, &[model], &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    // 已完成的維度輸出兩個統計量的百分位數，檔案內容不變
+    let (computed, not_computed) = report
+        .split_once(&format!("=== {model}, dim 3 ==="))
+        .unwrap();
+    assert!(computed.contains(&format!("=== {model}, dim 2 ===")));
+    assert!(computed.contains("Trace"));
+    assert!(computed.contains("MaxEig"));
+    assert!(!computed.contains("Error"));
+    assert_eq!(std::fs::read(&filename).unwrap(), before);
+
+    // 尚未計算的維度只輸出錯誤訊息，不會建立檔案
+    assert!(not_computed.contains("Error: failed to analyze data"));
+    assert!(!std::path::Path::new(&missing).exists());
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_count_reports_partial_completion() {
+    let model = JohansenModel::InterceptTrendUnrestrictedBoth;
+    let partial = EigenvalueSimulation::new(model, 2, 305, 3);
+    let filename = partial.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    // 只完成 3 次，但 --runs 要求 8 次
+    partial.run_simulation_quiet();
+
+    let args = parse(&[
+        "--count", "--dim", "2", "--steps", "305", "--runs", "8", "--model", "4",
+    ])
+    .unwrap();
+    let mut out = Vec::new();
+    write_completion_counts(&args, &[model], &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    assert_eq!(
+        report,
+        "model, dim, steps, completed/num_runs\n4, 2, 305, 3/8\n"
+    );
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_only_missing_models_skips_complete_models() {
+    use crate::models_to_process;
+
+    let complete = JohansenModel::NoInterceptNoTrend;
+    let incomplete = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let untouched = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let models = [complete, incomplete, untouched];
+
+    for model in models {
+        let _ =
+            std::fs::remove_file(EigenvalueSimulation::new(model, 2, 309, 4).get_filename(model));
+    }
+    EigenvalueSimulation::new(complete, 2, 309, 4).run_simulation_quiet();
+    EigenvalueSimulation::new(incomplete, 2, 309, 2).run_simulation_quiet();
+
+    let args = parse(&["--only-missing-models", "--steps", "309", "--runs", "4"]).unwrap();
+    assert_eq!(
+        models_to_process(&args, &models, 2),
+        vec![incomplete, untouched]
+    );
+
+    // 未啟用時處理所有模型
+    let args = parse(&["--steps", "309", "--runs", "4"]).unwrap();
+    assert_eq!(models_to_process(&args, &models, 2), models.to_vec());
+
+    for model in models {
+        let _ =
+            std::fs::remove_file(EigenvalueSimulation::new(model, 2, 309, 4).get_filename(model));
+    }
+}
+
+#[test]
+fn test_stdout_ndjson_one_object_per_run() {
+    use crate::write_ndjson;
+
+    let args = parse(&["--stdout-ndjson"]).unwrap();
+    assert!(args.stdout_ndjson);
+    assert!(args.quiet);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 313, 3);
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+    simulation.run_simulation_quiet();
+
+    let mut out = Vec::new();
+    write_ndjson(&simulation, &mut out).unwrap();
+    let output = String::from_utf8(out).unwrap();
+
+    let mut expected = simulation.read_data().unwrap();
+    expected.sort_unstable_by_key(|(seed, _)| *seed);
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    for (line, (seed, eigenvalues)) in lines.iter().zip(&expected) {
+        // 解析 {"seed":N,"eigenvalues":[...]}
+        let body = line
+            .strip_prefix("{\"seed\":")
+            .and_then(|rest| rest.strip_suffix("]}"))
+            .unwrap_or_else(|| panic!("not a seed/eigenvalues object: {line}"));
+        let (seed_str, values_str) = body.split_once(",\"eigenvalues\":[").unwrap();
+
+        assert_eq!(seed_str.parse::<u32>().unwrap(), *seed);
+        let values: Vec<f64> = values_str
+            .split(',')
+            .map(|value| value.parse().unwrap())
+            .collect();
+        assert_eq!(&values, eigenvalues);
+    }
+
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+}
+
+#[test]
+fn test_stdout_ndjson_names_aggregate_fields() {
+    use crate::write_ndjson;
+
+    let dir = tempfile::tempdir().unwrap();
+    let simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 3, 374, 2)
+        .with_data_dir(dir.path())
+        .with_aggregate(AggregateMode::Both);
+    simulation.run_simulation_quiet();
+
+    let mut out = Vec::new();
+    write_ndjson(&simulation, &mut out).unwrap();
+    let output = String::from_utf8(out).unwrap();
+
+    let expected: Vec<String> = simulation
+        .read_data_sorted()
+        .unwrap()
+        .iter()
+        .map(|(seed, values)| {
+            format!(
+                "{{\"seed\":{seed},\"trace\":{},\"max\":{}}}",
+                values[0], values[1]
+            )
+        })
+        .collect();
+    assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_dump_normals_csv_is_deterministic() {
+    use crate::write_normals_csv;
+
+    let args = parse(&["--dump-normals", "42", "--dim", "3", "--steps", "20,000"]).unwrap();
+    assert_eq!(args.dump_normals, Some(42));
+
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    write_normals_csv(3, 20_000, 42, &mut first).unwrap();
+    write_normals_csv(3, 20_000, 42, &mut second).unwrap();
+    assert_eq!(first, second);
+
+    let csv = String::from_utf8(first).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("x1,x2,x3"));
+
+    let mut sums = [0.0; 3];
+    let mut rows = 0;
+    for line in lines {
+        let values: Vec<f64> = line.split(',').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(values.len(), 3);
+        for (sum, value) in sums.iter_mut().zip(values) {
+            *sum += value;
+        }
+        rows += 1;
+    }
+    assert_eq!(rows, 20_000);
+
+    // 標準常態樣本平均的標準差約為 1/sqrt(20000) ≈ 0.007
+    for sum in sums {
+        assert!((sum / rows as f64).abs() < 0.05);
+    }
+}
+
+#[test]
+fn test_rerun_seed_prints_all_eigenvalues() {
+    use crate::{configured_simulation, write_rerun_seed};
+
+    let args = parse(&[
+        "--rerun-seed",
+        "7",
+        "--model",
+        "2",
+        "--dim",
+        "3",
+        "--steps",
+        "357",
+    ])
+    .unwrap();
+    assert_eq!(args.rerun_seed, Some(7));
+    assert!(try_parse(&["--rerun-seed", "7", "--dim", "3"]).is_err());
+
+    let simulation = configured_simulation(&args, JohansenModel::try_from(2).unwrap(), 3);
+    let mut out = Vec::new();
+    write_rerun_seed(&simulation, 7, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+
+    let printed = text
+        .lines()
+        .filter(|line| {
+            line.trim_start()
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        })
+        .count();
+    assert_eq!(printed, simulation.num_eigenvalues());
+    assert!(text.contains("Trace (sum): "));
+    assert!(text.contains("Max eigenvalue: "));
+}
+
+#[test]
+fn test_header_reports_model_and_count() {
+    use crate::data_storage::writer::AppendOnlyWriter;
+    use crate::write_header_info;
+
+    let filename = "test_cli_header_info.dat";
+    let _ = std::fs::remove_file(filename);
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 2, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let args = parse(&["--header", filename]).unwrap();
+    assert_eq!(args.header_path.as_deref(), Some(filename));
+
+    let mut out = Vec::new();
+    write_header_info(filename, &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    let description = JohansenModel::InterceptNoTrendUnrestrictedIntercept.description();
+    assert!(report.contains(&format!("Model: 2 ({description})")));
+    assert!(report.contains("Format version: V7"));
+    assert!(report.contains("Dimension: 2"));
+    assert!(report.contains("Steps: 100"));
+    assert!(report.contains("EOF marker: present"));
+    assert!(report.contains("Total count: 2"));
+    assert!(report.contains("Eigenvalues per run: 2"));
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_parse_dim_list() {
+    let args = parse(&["--dim-list", "10, 2,5,2"]).unwrap();
+    assert_eq!(args.dim_list, Some(vec![2, 5, 10]));
+    assert_eq!(args.dimensions(), vec![2, 5, 10]);
+    assert_eq!((args.dim_start, args.dim_end), (2, 10));
+
+    // 不使用列表時沿用連續範圍
+    let args = parse(&["--dim-start", "2", "--dim-end", "4"]).unwrap();
+    assert_eq!(args.dimensions(), vec![2, 3, 4]);
+
+    assert!(parse(&["--dim-list", "2,0,5"]).is_err());
+    assert!(parse(&["--dim-list", "2,x"]).is_err());
+    assert!(parse(&["--dim-list", ","]).is_err());
+    assert!(parse(&["--dim-list", "2,5", "--dim", "3"]).is_err());
+    assert!(parse(&["--dim-end", "6", "--dim-list", "2,5"]).is_err());
+}
+
+#[test]
+fn test_dim_list_only_computes_listed_dimensions() {
+    use crate::{configured_simulation, models_to_process};
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let args = parse(&["--dim-list", "1,3", "--steps", "323", "--runs", "2"]).unwrap();
+    let filenames: Vec<String> = (1..=3)
+        .map(|dim| configured_simulation(&args, model, dim).get_filename(model))
+        .collect();
+    for filename in &filenames {
+        let _ = std::fs::remove_file(filename);
+    }
+
+    // 與 main 相同的迴圈
+    for dim in args.dimensions() {
+        for model in models_to_process(&args, &[model], dim) {
+            configured_simulation(&args, model, dim).run_simulation_quiet();
+        }
+    }
+
+    assert!(std::path::Path::new(&filenames[0]).exists());
+    assert!(!std::path::Path::new(&filenames[1]).exists());
+    assert!(std::path::Path::new(&filenames[2]).exists());
+
+    for filename in &filenames {
+        let _ = std::fs::remove_file(filename);
+    }
+}
+
+#[test]
+fn test_import_csv_requires_single_model_and_dim() {
+    let args = parse(&["--import-csv", "in.csv", "--model", "1", "--dim", "3"]).unwrap();
+    assert_eq!(args.import_csv.as_deref(), Some("in.csv"));
+
+    assert!(parse(&["--import-csv", "in.csv", "--dim", "3"]).is_err());
+    assert!(parse(&["--import-csv", "in.csv", "--model", "1,2", "--dim", "3"]).is_err());
+    assert!(parse(&["--import-csv", "in.csv", "--model", "1"]).is_err());
+}
+
+#[test]
+fn test_parse_seed_file() {
+    let path = "test_cli_seed_file.txt";
+    std::fs::write(path, "# seeds to reproduce\n42\n\n 7 \n").unwrap();
+    let args = parse(&["--seed-file", path]).unwrap();
+    assert_eq!(args.seeds, Some(vec![42, 7]));
+
+    std::fs::write(path, "42\nabc\n").unwrap();
+    assert!(parse(&["--seed-file", path]).is_err());
+    assert!(parse(&["--seed-file", "missing_seed_file.txt"]).is_err());
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn test_benchmark_reports_positive_rate_without_writing() {
+    use crate::write_benchmark_report;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let args = parse(&["--benchmark", "--dim", "2", "--steps", "333", "--runs", "5"]).unwrap();
+    assert!(args.benchmark);
+
+    let filename = EigenvalueSimulation::new(model, 2, 333, 5).get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    let mut out = Vec::new();
+    write_benchmark_report(&args, &[model], &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("0, 2, 333, 5, "));
+
+    let fields: Vec<&str> = lines[1].split(", ").collect();
+    let runs_per_second: f64 = fields[5].parse().unwrap();
+    let micros_per_run: f64 = fields[6].parse().unwrap();
+    assert!(runs_per_second > 0.0);
+    assert!(micros_per_run > 0.0);
+
+    // 量測模式不產生資料檔案
+    assert!(!std::path::Path::new(&filename).exists());
+}
+
+#[test]
+fn test_output_file_requires_single_model_and_dim() {
+    let args = parse(&["--output-file", "out.dat", "--model", "1", "--dim", "3"]).unwrap();
+    assert_eq!(args.output_file.as_deref(), Some("out.dat"));
+
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = crate::configured_simulation(&args, model, 3);
+    assert_eq!(simulation.get_filename(model), "out.dat");
+
+    assert!(parse(&["--output-file", "out.dat", "--dim", "3"]).is_err());
+    assert!(parse(&["--output-file", "out.dat", "--model", "0,1", "--dim", "3"]).is_err());
+    let dim_list = [
+        "--output-file",
+        "out.dat",
+        "--model",
+        "1",
+        "--dim-list",
+        "2,3",
+    ];
+    assert!(parse(&dim_list).is_err());
+}
+
+#[test]
+fn test_parse_warmup_must_be_less_than_steps() {
+    let args = parse(&["--warmup", "100", "--steps", "500", "--dim", "2"]).unwrap();
+    assert_eq!(args.warmup, 100);
+    assert_eq!(parse(&[]).unwrap().warmup, 0);
+
+    let simulation = crate::configured_simulation(&args, JohansenModel::NoInterceptNoTrend, 2);
+    assert_eq!(simulation.warmup, 100);
+
+    assert!(parse(&["--warmup", "500", "--steps", "500"]).is_err());
+}
+
+#[test]
+fn test_parse_validate_against_and_tolerance() {
+    let args = parse(&["--validate-against", "ref.csv", "--model", "2"]).unwrap();
+    assert_eq!(args.validate_against.as_deref(), Some("ref.csv"));
+    assert_eq!(args.tolerance, 0.01);
+
+    assert_eq!(parse(&["--tolerance", "0.05"]).unwrap().tolerance, 0.05);
+
+    // 參考表只對應單一模型
+    assert!(parse(&["--validate-against", "ref.csv"]).is_err());
+    assert!(parse(&["--tolerance", "-0.1"]).is_err());
+    assert!(matches!(
+        parse(&["--tolerance", "abc"]),
+        Err(CliError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn test_parse_runs_scientific_notation_and_suffixes() {
+    assert_eq!(parse(&["--runs", "1e7"]).unwrap().num_runs, 10_000_000);
+    assert_eq!(parse(&["--runs", "2.5e3"]).unwrap().num_runs, 2_500);
+    assert_eq!(parse(&["--runs", "10M"]).unwrap().num_runs, 10_000_000);
+    assert_eq!(parse(&["--runs", "5k"]).unwrap().num_runs, 5_000);
+    assert_eq!(parse(&["--runs", "1.5K"]).unwrap().num_runs, 1_500);
+    assert_eq!(parse(&["--runs", "1,000"]).unwrap().num_runs, 1_000);
+
+    // 溢位、非整數與無法解析的值都被拒絕
+    for value in ["1e99", "1.5", "2.5e0", "10X", "inf", "-5k"] {
+        assert!(
+            matches!(parse(&["--runs", value]), Err(CliError::InvalidValue(_))),
+            "{value} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_continue_on_error_completes_remaining_models() {
+    use crate::run_dimension;
+
+    let broken = JohansenModel::NoInterceptNoTrend;
+    let healthy = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let models = [broken, healthy];
+    let broken_path = EigenvalueSimulation::new(broken, 2, 345, 3).get_filename(broken);
+    let healthy_path = EigenvalueSimulation::new(healthy, 2, 345, 3).get_filename(healthy);
+    let _ = std::fs::remove_file(&healthy_path);
+
+    // 以同名目錄佔用輸出路徑，使該模型無法寫入
+    let _ = std::fs::remove_file(&broken_path);
+    std::fs::create_dir_all(&broken_path).unwrap();
+
+    let args = parse(&["--quiet", "--steps", "345", "--runs", "3"]).unwrap();
+    assert!(!args.continue_on_error);
+    let mut failures = Vec::new();
+    let err = run_dimension(&args, &models, 2, None, &mut failures).unwrap_err();
+    assert!(err.to_string().contains("dim 2"), "unexpected error: {err}");
+    assert!(failures.is_empty());
+
+    let args = parse(&[
+        "--quiet",
+        "--continue-on-error",
+        "--steps",
+        "345",
+        "--runs",
+        "3",
+    ])
+    .unwrap();
+    assert!(args.continue_on_error);
+    let mut failures = Vec::new();
+    run_dimension(&args, &models, 2, None, &mut failures).unwrap();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with(&broken.to_string()));
+
+    let completed = EigenvalueSimulation::new(healthy, 2, 345, 3).completed_count();
+    assert_eq!(completed.unwrap(), 3);
+
+    let _ = std::fs::remove_dir_all(&broken_path);
+    let _ = std::fs::remove_file(&healthy_path);
+}
+
+#[test]
+fn test_order_stats_requires_tail_only() {
+    let args = parse(&["--tail-only", "--order-stats", "5"]).unwrap();
+    assert!(args.tail_only);
+    assert_eq!(args.order_stats, 5);
+
+    let err = parse(&["--order-stats", "5"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+}
+
+#[test]
+fn test_no_data_dir_option() {
+    let args = parse(&["--no-data-dir", "--model", "1", "--dim", "3"]).unwrap();
+    assert!(args.no_data_dir);
+    assert!(args.quiet);
+
+    let err = parse(&["--no-data-dir", "--model", "1"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    let err = parse(&[
+        "--no-data-dir",
+        "--model",
+        "1",
+        "--dim",
+        "3",
+        "--output-file",
+        "out.dat",
+    ])
+    .unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("--output-file"));
+}
+
+#[test]
+fn test_seed_hashing_option() {
+    use crate::rng_matrix::SeedHashing;
+
+    assert_eq!(parse(&[]).unwrap().seed_hashing, SeedHashing::SplitMix);
+    assert_eq!(
+        parse(&["--seed-hashing", "legacy"]).unwrap().seed_hashing,
+        SeedHashing::Legacy
+    );
+    assert_eq!(
+        parse(&["--seed-hashing", "md5"]).unwrap_err().exit_code(),
+        4
+    );
+}
+
+#[test]
+fn test_repair_flag() {
+    assert!(!parse(&[]).unwrap().repair);
+    assert!(parse(&["--repair", "--model", "0"]).unwrap().repair);
+}
+
+#[test]
+fn test_aggregate_option() {
+    assert_eq!(parse(&[]).unwrap().aggregate, AggregateMode::Full);
+    assert_eq!(
+        parse(&["--aggregate", "both"]).unwrap().aggregate,
+        AggregateMode::Both
+    );
+    assert_eq!(parse(&["--aggregate", "mean"]).unwrap_err().exit_code(), 4);
+
+    // 聚合檔案已含有跡，不需另外寫入跡檔案
+    let err = parse(&["--aggregate", "both", "--trace-file"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("--trace-file"));
+}
+
+#[test]
+fn test_trace_file_option() {
+    assert!(!parse(&[]).unwrap().trace_file);
+    assert!(parse(&["--trace-file"]).unwrap().trace_file);
+
+    let err = parse(&[
+        "--no-data-dir",
+        "--model",
+        "1",
+        "--dim",
+        "3",
+        "--trace-file",
+    ])
+    .unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("--trace-file"));
+}
+
+#[test]
+fn test_bootstrap_option() {
+    assert_eq!(parse(&[]).unwrap().bootstrap, None);
+    let args = parse(&["--bootstrap", "200", "--tail-only"]).unwrap();
+    assert_eq!(args.bootstrap, Some(200));
+
+    assert_eq!(parse(&["--bootstrap", "0"]).unwrap_err().exit_code(), 5);
+    assert_eq!(parse(&["--bootstrap", "x"]).unwrap_err().exit_code(), 4);
+    let err = parse(&["--bootstrap", "200", "--tail-only", "--order-stats", "3"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+}
+
+#[test]
+fn test_eigenvalue_floor_option() {
+    assert_eq!(parse(&[]).unwrap().eigenvalue_floor, None);
+    let args = parse(&["--eigenvalue-floor", "1e-12"]).unwrap();
+    assert_eq!(args.eigenvalue_floor, Some(1e-12));
+
+    assert_eq!(
+        parse(&["--eigenvalue-floor", "tiny"])
+            .unwrap_err()
+            .exit_code(),
+        4
+    );
+    assert_eq!(
+        parse(&["--eigenvalue-floor", "0"]).unwrap_err().exit_code(),
+        5
+    );
+    assert_eq!(
+        parse(&["--eigenvalue-floor", "-1e-9"])
+            .unwrap_err()
+            .exit_code(),
+        5
+    );
+}
+
+#[test]
+fn test_master_seed_option() {
+    let args = parse(&["--master-seed", "18446744073709551615"]).unwrap();
+    assert_eq!(args.master_seed, Some(u64::MAX));
+    assert_eq!(parse(&[]).unwrap().master_seed, None);
+    assert_eq!(parse(&["--master-seed", "-1"]).unwrap_err().exit_code(), 4);
+}
+
+#[test]
+fn test_exclude_models() {
+    use JohansenModel::*;
+
+    let args = parse(&["--exclude-models", "4"]).unwrap();
+    assert_eq!(
+        args.models,
+        Some(vec![
+            NoInterceptNoTrend,
+            InterceptNoTrendWithInterceptInCoint,
+            InterceptNoTrendUnrestrictedIntercept,
+            InterceptTrendUnrestrictedInterceptRestrictedTrend,
+        ])
+    );
+
+    let args = parse(&["--exclude-models", "0, 2,3"]).unwrap();
+    assert_eq!(
+        args.models,
+        Some(vec![
+            InterceptNoTrendWithInterceptInCoint,
+            InterceptTrendUnrestrictedBoth
+        ])
+    );
+
+    let err = parse(&["--exclude-models", "0,1,2,3,4"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    let err = parse(&["--model", "1", "--exclude-models", "0"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    let err = parse(&["--exclude-models", "9"]).unwrap_err();
+    assert_eq!(err.exit_code(), 4);
+
+    // 模型編號的邊界值
+    assert_eq!(
+        parse(&["--model", "4"]).unwrap().models,
+        Some(vec![InterceptTrendUnrestrictedBoth])
+    );
+    let err = parse(&["--model", "5"]).unwrap_err();
+    assert_eq!(err.exit_code(), 4);
+    assert!(err.to_string().contains("expected 0-4"));
+}
+
+#[test]
+fn test_steps_limited_to_header_width() {
+    let max = u32::MAX.to_string();
+    assert_eq!(parse(&["--steps", &max]).unwrap().steps, u32::MAX as usize);
+
+    // 超過 u32 的步數不可被標頭截斷
+    let over = (u32::MAX as u64 + 1).to_string();
+    let err = parse(&["--steps", &over]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("--steps"), "{err}");
+}
+
+#[test]
+fn test_dimension_limited_to_record_width() {
+    // 有截距於協整關係的模型每次模擬有 dim + 1 個特徵值
+    assert_eq!(parse(&["--dim", "254"]).unwrap().dim_end, 254);
+    assert_eq!(
+        parse(&["--dim", "255", "--model", "0"]).unwrap().dim_end,
+        255
+    );
+
+    // 計算開始前即拒絕，不必等到寫入第一筆記錄
+    let err = parse(&["--dim", "260"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("storage limit of 255"), "{err}");
+
+    let err = parse(&["--dim", "255", "--model", "1"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+    assert!(err.to_string().contains("model 1"), "{err}");
+
+    let err = parse(&["--dim-list", "2,260"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+
+    // 只儲存部分數值時依實際儲存的數量檢查
+    let args = parse(&["--dim", "260", "--aggregate", "both"]).unwrap();
+    assert_eq!(args.dim_end, 260);
+    assert!(parse(&["--dim", "260", "--top-k", "10"]).is_ok());
+}
+
+#[test]
+fn test_low_steps_warning() {
+    let warning = parse(&["--steps", "500"]).unwrap().steps_warning().unwrap();
+    assert!(warning.contains("--steps 500 is below 1000"), "{warning}");
+
+    assert_eq!(parse(&["--steps", "5000"]).unwrap().steps_warning(), None);
+    assert_eq!(parse(&["--steps", "1000"]).unwrap().steps_warning(), None);
+
+    // 可調整門檻、以 0 關閉，安靜模式下不警告
+    let args = parse(&["--steps", "500", "--min-steps-warning", "100"]).unwrap();
+    assert_eq!(args.steps_warning(), None);
+    let args = parse(&["--steps", "5000", "--min-steps-warning", "10k"]).unwrap();
+    assert!(args.steps_warning().is_some());
+    let args = parse(&["--steps", "500", "--min-steps-warning", "0"]).unwrap();
+    assert_eq!(args.steps_warning(), None);
+    assert_eq!(
+        parse(&["--steps", "500", "--quiet"])
+            .unwrap()
+            .steps_warning(),
+        None
+    );
+}
+
+#[test]
+fn test_table_out_row_count() {
+    use crate::simulation_analyzers::REPORT_PERCENTILES;
+    use crate::write_table_out;
+
+    let models = [
+        JohansenModel::NoInterceptNoTrend,
+        JohansenModel::InterceptTrendUnrestrictedBoth,
+    ];
+    let path = "test_table_out.csv";
+    let args = parse(&[
+        "--dim-list",
+        "2,3",
+        "--steps",
+        "349",
+        "--runs",
+        "5",
+        "--table-out",
+        path,
+    ])
+    .unwrap();
+    assert_eq!(args.table_out.as_deref(), Some(path));
+
+    for dim in [2, 3] {
+        for model in models {
+            let simulation = EigenvalueSimulation::new(model, dim, 349, 5);
+            let _ = std::fs::remove_file(simulation.get_filename(model));
+            simulation.run_simulation_quiet();
+        }
+    }
+
+    let rows = write_table_out(&args, &models, path).unwrap();
+    assert_eq!(rows, models.len() * 2 * REPORT_PERCENTILES.len());
+
+    let table = std::fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "model,dim,percentile,trace_value,maxeig_value");
+    assert_eq!(lines.len(), rows + 1);
+    assert!(lines[1].starts_with("0,2,0.5,"), "{}", lines[1]);
+    // 跡統計量不小於最大特徵值
+    for line in &lines[1..] {
+        let fields: Vec<f64> = line.split(',').map(|f| f.parse().unwrap()).collect();
+        assert!(fields[3] >= fields[4], "{line}");
+    }
+
+    let _ = std::fs::remove_file(path);
+    for dim in [2, 3] {
+        for model in models {
+            let simulation = EigenvalueSimulation::new(model, dim, 349, 5);
+            let _ = std::fs::remove_file(simulation.get_filename(model));
+        }
+    }
+}
+
+#[test]
+fn test_adaptive_arguments() {
+    let target = parse(&["--adaptive"]).unwrap().adaptive_target().unwrap();
+    assert_eq!((target.quantile, target.precision), (0.95, 0.001));
+    assert_eq!(parse(&[]).unwrap().adaptive_target(), None);
+
+    let args = parse(&[
+        "--adaptive",
+        "--target-quantile",
+        "0.99",
+        "--target-precision",
+        "0.01",
+    ])
+    .unwrap();
+    let target = args.adaptive_target().unwrap();
+    assert_eq!((target.quantile, target.precision), (0.99, 0.01));
+
+    for args in [
+        &["--target-quantile", "0.9"][..],
+        &["--adaptive", "--target-quantile", "1"],
+        &["--adaptive", "--target-precision", "0"],
+        &["--adaptive", "--shared-paths"],
+        &["--adaptive", "--table-out", "table.csv"],
+    ] {
+        assert_eq!(parse(args).unwrap_err().exit_code(), 5, "{args:?}");
+    }
+    assert_eq!(
+        parse(&["--adaptive", "--target-precision", "tight"])
+            .unwrap_err()
+            .exit_code(),
+        4
+    );
+}
+
+#[test]
+fn test_time_budget_argument() {
+    assert_eq!(parse(&[]).unwrap().time_budget, None);
+    assert_eq!(
+        parse(&["--time-budget", "3600"]).unwrap().time_budget,
+        Some(3600)
+    );
+    assert_eq!(parse(&["--time-budget", "0"]).unwrap_err().exit_code(), 5);
+    assert_eq!(parse(&["--time-budget", "1h"]).unwrap_err().exit_code(), 4);
+    assert_eq!(
+        parse(&["--time-budget", "60", "--adaptive"])
+            .unwrap_err()
+            .exit_code(),
+        5
+    );
+}
+
+#[test]
+fn test_write_buffer_argument() {
+    assert_eq!(parse(&[]).unwrap().write_buffer, None);
+    assert_eq!(
+        parse(&["--write-buffer", "8M"]).unwrap().write_buffer,
+        Some(8_000_000)
+    );
+    assert_eq!(
+        parse(&["--write-buffer", "4096"]).unwrap().write_buffer,
+        Some(4096)
+    );
+    assert_eq!(
+        parse(&["--write-buffer", "512"]).unwrap_err().exit_code(),
+        5
+    );
+    assert_eq!(parse(&["--write-buffer", "1G"]).unwrap_err().exit_code(), 5);
+    assert_eq!(
+        parse(&["--write-buffer", "big"]).unwrap_err().exit_code(),
+        4
+    );
+}
+
+#[test]
+fn test_export_argument() {
+    let args = parse(&["--model", "2", "--dim", "3", "--export", "csv", "out.csv"]).unwrap();
+    assert_eq!(
+        args.export,
+        Some((ExportFormat::Csv, "out.csv".to_string()))
+    );
+
+    let err = parse(&["--model", "2", "--dim", "3", "--export", "xlsx", "out.xlsx"]).unwrap_err();
+    assert_eq!(err.exit_code(), 4);
+    let err = parse(&["--model", "2", "--dim", "3", "--export", "csv"]).unwrap_err();
+    assert_eq!(err.exit_code(), 3);
+    let err = parse(&["--dim", "3", "--export", "csv", "out.csv"]).unwrap_err();
+    assert_eq!(err.exit_code(), 5);
+
+    let parquet = parse(&[
+        "--model",
+        "2",
+        "--dim",
+        "3",
+        "--export",
+        "parquet",
+        "out.parquet",
+    ]);
+    assert_eq!(parquet.is_ok(), cfg!(feature = "parquet"));
+}
+
+#[test]
+fn test_diagnose_complex_flag() {
+    assert!(!parse(&[]).unwrap().diagnose_complex);
+    let args = parse(&["--diagnose-complex"]).unwrap();
+    assert!(args.diagnose_complex);
+
+    let simulation = crate::configured_simulation(&args, JohansenModel::default(), 2);
+    assert!(simulation.diagnose_complex);
+    assert!(simulation.eigenvalue_options().diagnoses());
+}
@@ -1,6 +1,9 @@
 use crate::data_storage::{
-    progress::check_append_progress, reader::read_append_file, writer::AppendOnlyWriter,
+    progress::{check_append_progress, count_append_progress},
+    reader::{ScanStrictness, read_append_file, read_append_file_with_strictness},
+    writer::{AppendOnlyWriter, retry_transient},
 };
+use std::io::{self, Write};
 
 #[test]
 fn test_append_writer_basic() {
@@ -12,7 +15,7 @@ fn test_append_writer_basic() {
     // 測試寫入
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
         writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
         writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
         writer.finish().unwrap();
@@ -46,7 +49,7 @@ fn test_append_writer_progress() {
     // 寫入一些數據
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 1, 2, 100, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 2, 100, None, true).unwrap();
         writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
         writer.append_eigenvalues(3, &[5.0, 6.0]).unwrap();
         writer.finish().unwrap();
@@ -72,7 +75,7 @@ fn test_append_writer_incomplete_file() {
     // 創建不完整的檔案（沒有結束標記）
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 2, 2, 150, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 2, 2, 150, None, true).unwrap();
         writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
         writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
         // 故意不調用 finish()
@@ -98,7 +101,7 @@ fn test_append_writer_large_dataset() {
     // 寫入大量數據
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 3, 2, 500, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 3, 2, 500, None, true).unwrap();
         for i in 1..=1000 {
             let eigenvalues = vec![i as f64 * 0.1, i as f64 * 0.2];
             writer.append_eigenvalues(i, &eigenvalues).unwrap();
@@ -127,7 +130,7 @@ fn test_append_writer_eigenvalue_consistency() {
 
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
         writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
 
         // 嘗試寫入不一致的特徵值數量，應該失敗
@@ -149,7 +152,7 @@ fn test_eigenvalue_count_overflow_protection() {
 
     // 這應該失敗
     let mut writer =
-        AppendOnlyWriter::with_expected_size(filename, None, 0, 255, 1000, true).unwrap();
+        AppendOnlyWriter::with_expected_size(filename, None, 0, 255, 1000, None, true).unwrap();
     let result = writer.append_eigenvalues(1, &large_eigenvalues);
 
     match result {
@@ -175,7 +178,7 @@ fn test_valid_eigenvalue_count_boundary() {
     // 這應該成功
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 4, 255, 1000, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 4, 255, 1000, None, true).unwrap();
         let result = writer.append_eigenvalues(1, &boundary_eigenvalues);
         assert!(
             result.is_ok(),
@@ -203,25 +206,654 @@ fn test_parameter_validation() {
     // 先創建一個檔案
     {
         let mut writer =
-            AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 100, true).unwrap();
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 100, None, true).unwrap();
         writer.append_eigenvalues(1, &[1.0, 2.0, 3.0]).unwrap();
         writer.finish().unwrap();
     }
 
     // 嘗試用不同的參數打開，應該失敗
-    let result = AppendOnlyWriter::with_expected_size(filename, None, 2, 3, 100, true); // 不同的 model
+    let result = AppendOnlyWriter::with_expected_size(filename, None, 2, 3, 100, None, true); // 不同的 model
     assert!(result.is_err());
 
-    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 4, 100, true); // 不同的 dim  
+    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 4, 100, None, true); // 不同的 dim  
     assert!(result.is_err());
 
-    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 200, true); // 不同的 steps
+    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 200, None, true); // 不同的 steps
     assert!(result.is_err());
 
     // 用正確的參數應該成功
-    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 100, true);
+    let result = AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 100, None, true);
     assert!(result.is_ok());
 
     // 清理
     let _ = std::fs::remove_file(filename);
 }
+
+#[test]
+fn test_count_progress_uses_trailing_metadata() {
+    let filename = "test_count_trailer.dat";
+    let _ = std::fs::remove_file(filename);
+
+    // 未完成的檔案（沒有結束標記）退回掃描式計數
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 120, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0, 3.0]).unwrap();
+        writer.append_eigenvalues(2, &[4.0, 5.0, 6.0]).unwrap();
+    }
+    assert_eq!(count_append_progress(filename, 1, 3, 120).unwrap(), 2);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 3, 120, None, true).unwrap();
+        writer.append_eigenvalues(3, &[7.0, 8.0, 9.0]).unwrap();
+        writer.finish().unwrap();
+    }
+    assert_eq!(count_append_progress(filename, 1, 3, 120).unwrap(), 3);
+
+    // 將末尾元數據中的總數改成遠大於實際記錄數的值：
+    // 若計數有掃描記錄就只會得到 3，讀取末尾元數據則直接得到此值
+    let mut bytes = std::fs::read(filename).unwrap();
+    let count_offset = bytes.len() - 9; // count(8) + eigenvalues_per_run(1)
+    bytes[count_offset..count_offset + 8].copy_from_slice(&1_000_000u64.to_le_bytes());
+    std::fs::write(filename, &bytes).unwrap();
+    assert_eq!(
+        count_append_progress(filename, 1, 3, 120).unwrap(),
+        1_000_000
+    );
+
+    // 參數不符時仍須回報錯誤
+    assert!(count_append_progress(filename, 2, 3, 120).is_err());
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_resume_uses_trailing_metadata() {
+    use crate::data_storage::reader::read_file_summary;
+
+    let filename = "test_resume_trailer.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 2, 120, None, true).unwrap();
+        for seed in 1..=3 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, 0.5])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    // 將末尾元數據中的總數改成 1,000（CRC32 只涵蓋資料區段，不受影響）：
+    // 續寫若讀取所有記錄只會得到 3，讀取末尾元數據則從 1,000 繼續計數
+    let mut bytes = std::fs::read(filename).unwrap();
+    let count_offset = bytes.len() - 9; // count(8) + eigenvalues_per_run(1)
+    bytes[count_offset..count_offset + 8].copy_from_slice(&1_000u64.to_le_bytes());
+    std::fs::write(filename, &bytes).unwrap();
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 2, 120, None, true).unwrap();
+        writer.append_eigenvalues(4, &[4.0, 0.5]).unwrap();
+        writer.finish().unwrap();
+    }
+    let summary = read_file_summary(filename).unwrap();
+    assert_eq!(summary.metadata, Some((1_001, 2)));
+
+    // 資料區段的 CRC32 與結束標記前儲存的值是否一致
+    let data_offset = summary.header.data_offset as usize;
+    let checksum_matches = || {
+        let bytes = std::fs::read(filename).unwrap();
+        let data_end = bytes.len() - summary.header.trailer_size() as usize;
+        let stored = u32::from_le_bytes(bytes[data_end..data_end + 4].try_into().unwrap());
+        crc32fast::hash(&bytes[data_offset..data_end]) == stored
+    };
+    assert!(checksum_matches());
+
+    // 續寫不重新讀取資料區段；已完成檔案的資料損壞在續寫後仍與 CRC32 不符
+    let mut bytes = std::fs::read(filename).unwrap();
+    bytes[data_offset + 2] ^= 0xFF;
+    std::fs::write(filename, &bytes).unwrap();
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 1, 2, 120, None, true).unwrap();
+        writer.append_eigenvalues(5, &[5.0, 0.5]).unwrap();
+        writer.finish().unwrap();
+    }
+    assert!(!checksum_matches());
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_legacy_v5_file_is_read_and_resumed() {
+    let filename = "test_legacy_v5.dat";
+    let _ = std::fs::remove_file(filename);
+
+    // V5 標頭：magic(12) + model(1) + dim(1) + steps(4)，沒有 flags 與 CRC32
+    let mut bytes = b"EIGENVALS_V5".to_vec();
+    bytes.extend_from_slice(&[0, 2]);
+    bytes.extend_from_slice(&100u32.to_le_bytes());
+    bytes.extend_from_slice(&[1, 2]); // seed(ULEB128) + count
+    bytes.extend_from_slice(&1.5f64.to_le_bytes());
+    bytes.extend_from_slice(&2.5f64.to_le_bytes());
+    bytes.extend_from_slice(b"EOF_MARK");
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    bytes.push(2);
+    std::fs::write(filename, &bytes).unwrap();
+
+    let (data, model, dim, steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.5, 2.5])]);
+    assert_eq!((model, dim, steps), (0, 2, 100));
+
+    // 續寫不會因魔術標頭而刪除檔案，並保留 V5 標頭
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(2, &[3.5, 4.5]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.5, 2.5]), (2, vec![3.5, 4.5])]);
+    assert!(
+        std::fs::read(filename)
+            .unwrap()
+            .starts_with(b"EIGENVALS_V5")
+    );
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_checkpoint_every_flushes_before_finish() {
+    use std::time::Duration;
+
+    let filename = "test_append_checkpoint.dat";
+    let _ = std::fs::remove_file(filename);
+
+    // 未設定檢查點時，少量記錄仍留在寫入緩衝區中
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+        assert!(data.is_empty());
+    }
+    let _ = std::fs::remove_file(filename);
+
+    let mut writer = AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true)
+        .unwrap()
+        .with_checkpoint_every(Some(Duration::from_millis(1)));
+    for seed in 1..=3 {
+        // 模擬計算緩慢的模型
+        std::thread::sleep(Duration::from_millis(5));
+        writer
+            .append_eigenvalues(seed, &[seed as f64, seed as f64])
+            .unwrap();
+
+        // 尚未 finish，以掃描式讀取取得已刷新的記錄
+        let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+        assert_eq!(data.len(), seed as usize);
+    }
+    writer.finish().unwrap();
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data.len(), 3);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_custom_write_buffer_produces_same_file() {
+    use crate::data_storage::config::{MAX_WRITE_BUFFER_CAPACITY, MIN_WRITE_BUFFER_CAPACITY};
+
+    let filename = "test_append_write_buffer.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let records: Vec<(u32, Vec<f64>)> = (1..=2_000)
+        .map(|seed| (seed, vec![seed as f64, seed as f64 * 0.5]))
+        .collect();
+
+    // 最小緩衝區會在寫入期間多次填滿並刷新
+    let mut writer = AppendOnlyWriter::with_expected_size(
+        filename,
+        None,
+        0,
+        2,
+        100,
+        Some(MIN_WRITE_BUFFER_CAPACITY),
+        true,
+    )
+    .unwrap();
+    writer.append_all(records[..1_500].iter().cloned()).unwrap();
+    writer.finish().unwrap();
+
+    // 續寫時同樣可以指定緩衝區大小
+    let mut writer =
+        AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, Some(64 * 1024), true)
+            .unwrap();
+    writer.append_all(records[1_500..].iter().cloned()).unwrap();
+    writer.finish().unwrap();
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, records);
+    assert_eq!(count_append_progress(filename, 0, 2, 100).unwrap(), 2_000);
+
+    // 超出範圍的緩衝區在開啟檔案前即被拒絕
+    for capacity in [MIN_WRITE_BUFFER_CAPACITY - 1, MAX_WRITE_BUFFER_CAPACITY + 1] {
+        let error = match AppendOnlyWriter::with_expected_size(
+            filename,
+            None,
+            0,
+            2,
+            100,
+            Some(capacity),
+            true,
+        ) {
+            Ok(_) => panic!("buffer of {capacity} bytes should be rejected"),
+            Err(e) => e,
+        };
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+    assert_eq!(count_append_progress(filename, 0, 2, 100).unwrap(), 2_000);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_scan_read_detects_ragged_record() {
+    let filename = "test_scan_ragged_record.dat";
+    let _ = std::fs::remove_file(filename);
+
+    // V7 標頭（無旗標）後接三筆記錄，第二筆的特徵值數量與其他不同，且沒有結束標記
+    let mut bytes = b"EIGENVALS_V7".to_vec();
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&[0, 2]);
+    bytes.extend_from_slice(&100u32.to_le_bytes());
+    for (seed, values) in [
+        (1u8, &[1.0f64, 2.0][..]),
+        (2, &[3.0, 4.0, 5.0]),
+        (3, &[6.0, 7.0]),
+    ] {
+        bytes.extend_from_slice(&[seed, values.len() as u8]);
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    std::fs::write(filename, &bytes).unwrap();
+
+    // 預設為嚴格模式
+    let err = read_append_file(filename).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        err.to_string().contains("seed 2"),
+        "unexpected error: {err}"
+    );
+
+    let (data, _model, _dim, _steps) =
+        read_append_file_with_strictness(filename, ScanStrictness::Lenient).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 2.0]), (3, vec![6.0, 7.0])]);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_append_all_bulk_records() {
+    let filename = "test_append_all_bulk.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let records: Vec<(u32, Vec<f64>)> = (1..=1000)
+        .map(|seed| (seed, vec![seed as f64, seed as f64 * 0.5]))
+        .collect();
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        assert_eq!(writer.append_all(records.clone()).unwrap(), 1000);
+
+        // 批次寫入同樣檢查特徵值數量是否一致
+        assert!(writer.append_all(vec![(1001, vec![1.0])]).is_err());
+        writer.finish().unwrap();
+    }
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, records);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_header_flags_round_trip() {
+    use crate::data_storage::file_format::{
+        DEFAULT_FLAGS, FLAG_CRC32, FLAG_STREAM_ID, FLAG_TOP_K, FileHeader,
+    };
+    use crate::data_storage::reader::read_file_header;
+
+    let filename = "test_header_flags_round_trip.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let header = FileHeader::new(DEFAULT_FLAGS, 1, 3, 100)
+        .with_stream_id(9)
+        .with_top_k(2);
+    {
+        let mut writer = AppendOnlyWriter::with_header(filename, None, header, None, true).unwrap();
+        writer.append_eigenvalues(1, &[2.0, 1.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let stored = read_file_header(filename).unwrap();
+    assert_eq!(stored.flags, FLAG_CRC32 | FLAG_STREAM_ID | FLAG_TOP_K);
+    assert_eq!((stored.stream_id, stored.top_k), (9, 2));
+    assert_eq!(stored, header);
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![2.0, 1.0])]);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_unknown_header_flag_is_rejected() {
+    let filename = "test_unknown_header_flag.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    // 設定未定義的最高旗標位元 0x8000（位於 magic 之後的 little-endian flags 欄位）
+    let mut bytes = std::fs::read(filename).unwrap();
+    bytes[13] |= 0x80;
+    std::fs::write(filename, &bytes).unwrap();
+
+    let err = read_append_file(filename).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+
+    // 不可續寫或計入進度，檔案保持原狀
+    assert!(AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).is_err());
+    assert!(count_append_progress(filename, 0, 2, 100).is_err());
+    assert_eq!(std::fs::read(filename).unwrap(), bytes);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+/// 前 `failures` 次寫入或刷新以指定錯誤失敗，之後正常寫入的模擬寫入器
+struct FlakyWriter {
+    failures: usize,
+    kind: io::ErrorKind,
+    attempts: usize,
+    data: Vec<u8>,
+}
+
+impl FlakyWriter {
+    fn new(failures: usize, kind: io::ErrorKind) -> Self {
+        Self {
+            failures,
+            kind,
+            attempts: 0,
+            data: Vec::new(),
+        }
+    }
+
+    fn fail_if_pending(&mut self) -> io::Result<()> {
+        self.attempts += 1;
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err(io::Error::new(self.kind, "simulated transient failure"));
+        }
+        Ok(())
+    }
+}
+
+impl Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.fail_if_pending()?;
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.fail_if_pending()
+    }
+}
+
+#[test]
+fn test_retry_transient_recovers_after_failures() {
+    // write_all 本身會吞掉 Interrupted，故寫入以 WouldBlock 測試
+    let mut writer = FlakyWriter::new(2, io::ErrorKind::WouldBlock);
+    retry_transient(|| writer.write_all(b"record")).unwrap();
+    assert_eq!(writer.attempts, 3);
+    assert_eq!(writer.data, b"record");
+
+    let mut writer = FlakyWriter::new(2, io::ErrorKind::Interrupted);
+    retry_transient(|| writer.flush()).unwrap();
+    assert_eq!(writer.attempts, 3);
+}
+
+#[test]
+fn test_retry_transient_gives_up() {
+    // 非暫時性錯誤不重試
+    let mut writer = FlakyWriter::new(1, io::ErrorKind::PermissionDenied);
+    let err = retry_transient(|| writer.flush()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(writer.attempts, 1);
+
+    // 持續失敗時在重試次數用盡後返回錯誤
+    let mut writer = FlakyWriter::new(usize::MAX, io::ErrorKind::TimedOut);
+    let err = retry_transient(|| writer.flush()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(writer.attempts > 1);
+    assert!(writer.data.is_empty());
+}
+
+#[test]
+fn test_record_timestamps_round_trip() {
+    use crate::data_storage::file_format::{DEFAULT_FLAGS, FileHeader};
+    use crate::data_storage::reader::{read_append_file_with_timestamps, read_record_count};
+    use crate::data_storage::writer::current_epoch_minutes;
+
+    let filename = "test_record_timestamps.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let header = FileHeader::new(DEFAULT_FLAGS, 0, 2, 100).with_timestamps(true);
+    let before = current_epoch_minutes();
+    {
+        let mut writer = AppendOnlyWriter::with_header(filename, None, header, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.append_eigenvalues(200, &[3.0, 4.0]).unwrap();
+        // 未寫入結束標記，以掃描式讀取
+    }
+    let after = current_epoch_minutes();
+
+    let check = |expected_len: usize| {
+        let (data, times) = read_append_file_with_timestamps(filename).unwrap();
+        assert_eq!(data.len(), expected_len);
+        assert_eq!(data[1], (200, vec![3.0, 4.0]));
+        assert_eq!(times.len(), expected_len);
+        assert!(times.iter().all(|&t| (before..=after + 1).contains(&t)));
+    };
+    check(2);
+
+    // 續寫後以末尾元數據快速讀取
+    {
+        let mut writer = AppendOnlyWriter::with_header(filename, None, header, None, true).unwrap();
+        writer.append_eigenvalues(3, &[5.0, 6.0]).unwrap();
+        writer.finish().unwrap();
+    }
+    check(3);
+    assert_eq!(read_record_count(filename).unwrap().1, 3);
+
+    // 一般讀取忽略時間戳記
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data[2], (3, vec![5.0, 6.0]));
+
+    // 記錄格式不同，不可以未帶時間戳記的設定續寫
+    let plain = FileHeader::new(DEFAULT_FLAGS, 0, 2, 100);
+    assert!(AppendOnlyWriter::with_header(filename, None, plain, None, true).is_err());
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_files_without_timestamps_return_empty_times() {
+    use crate::data_storage::reader::read_append_file_with_timestamps;
+
+    let filename = "test_record_no_timestamps.dat";
+    let _ = std::fs::remove_file(filename);
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let (data, times) = read_append_file_with_timestamps(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 2.0])]);
+    assert!(times.is_empty());
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_from_existing_handle_round_trip() {
+    use crate::data_storage::file_format::{DEFAULT_FLAGS, FileHeader};
+    use crate::data_storage::reader::read_file_header;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    let header = FileHeader::new(DEFAULT_FLAGS, 2, 3, 100).with_stream_id(4);
+    {
+        let file = temp.reopen().unwrap();
+        let mut writer = AppendOnlyWriter::from_existing_handle(file, header, true).unwrap();
+        writer.append_eigenvalues(1, &[3.0, 2.0, 1.0]).unwrap();
+        writer.append_eigenvalues(2, &[6.0, 5.0, 4.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    assert_eq!(read_file_header(temp.path()).unwrap(), header);
+    let (data, model, dim, steps) = read_append_file(temp.path()).unwrap();
+    assert_eq!(
+        data,
+        vec![(1, vec![3.0, 2.0, 1.0]), (2, vec![6.0, 5.0, 4.0])]
+    );
+    assert_eq!((model, dim, steps), (2, 3, 100));
+
+    // 建立完成的檔案可照常以路徑續寫
+    {
+        let mut writer =
+            AppendOnlyWriter::with_header(temp.path(), None, header, None, true).unwrap();
+        writer.append_eigenvalues(3, &[9.0, 8.0, 7.0]).unwrap();
+        writer.finish().unwrap();
+    }
+    let (data, _model, _dim, _steps) = read_append_file(temp.path()).unwrap();
+    assert_eq!(data.len(), 3);
+}
+
+#[test]
+fn test_stream_matches_full_read() {
+    use crate::data_storage::reader::stream_append_file;
+
+    let filename = "test_stream_records.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let collect = || -> io::Result<Vec<(u32, Vec<f64>)>> {
+        let (_, records) = stream_append_file(filename)?;
+        records.collect()
+    };
+
+    // 未完成的檔案：末尾不完整的記錄會被略過
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 120, None, true).unwrap();
+        for seed in 1..=3 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, -1.0])
+                .unwrap();
+        }
+    }
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(filename)
+        .unwrap()
+        .write_all(&[4, 2, 0, 0])
+        .unwrap();
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(collect().unwrap(), data);
+    assert_eq!(data.len(), 3);
+
+    // 已完成的檔案：依末尾元數據讀取並比對 CRC32
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 120, None, true).unwrap();
+        writer.append_eigenvalues(4, &[4.0, -1.0]).unwrap();
+        writer.finish().unwrap();
+    }
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(collect().unwrap(), data);
+    assert_eq!(data.len(), 4);
+
+    let mut bytes = std::fs::read(filename).unwrap();
+    let data_offset = crate::data_storage::reader::read_file_header(filename)
+        .unwrap()
+        .data_offset as usize;
+    bytes[data_offset + 3] ^= 0xFF;
+    std::fs::write(filename, &bytes).unwrap();
+    let error = collect().unwrap_err();
+    assert!(crate::data_storage::checksum::is_checksum_error(&error));
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_flat_read_matches_record_read() {
+    use crate::data_storage::file_format::{DEFAULT_FLAGS, FileHeader};
+    use crate::data_storage::reader::read_append_file_flat;
+
+    let dir = tempfile::tempdir().unwrap();
+    let filename = dir.path().join("test_flat_read.dat");
+
+    let header = FileHeader::new(DEFAULT_FLAGS, 1, 3, 370).with_timestamps(true);
+    {
+        let mut writer =
+            AppendOnlyWriter::with_header(&filename, None, header, None, true).unwrap();
+        for seed in [7u32, 1, 300, 2] {
+            let base = seed as f64;
+            writer
+                .append_eigenvalues(seed, &[base, base + 0.5, -base])
+                .unwrap();
+        }
+    }
+
+    // 未完成的檔案須改用掃描式讀取
+    let error = read_append_file_flat(&filename).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("finished file"));
+
+    AppendOnlyWriter::with_header(&filename, None, header, None, true)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    let (data, _model, _dim, _steps) = read_append_file(&filename).unwrap();
+    let (seeds, values, stride) = read_append_file_flat(&filename).unwrap();
+    assert_eq!(stride, 3);
+    assert_eq!(values.len(), seeds.len() * stride);
+    let rebuilt: Vec<(u32, Vec<f64>)> = seeds
+        .iter()
+        .zip(values.chunks_exact(stride))
+        .map(|(&seed, row)| (seed, row.to_vec()))
+        .collect();
+    assert_eq!(rebuilt, data);
+
+    // 資料損壞時與一般讀取同樣返回校驗錯誤
+    let mut bytes = std::fs::read(&filename).unwrap();
+    bytes[header.data_offset as usize + 8] ^= 0xFF;
+    std::fs::write(&filename, &bytes).unwrap();
+    let error = read_append_file_flat(&filename).unwrap_err();
+    assert!(crate::data_storage::checksum::is_checksum_error(&error));
+}
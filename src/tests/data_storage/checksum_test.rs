@@ -0,0 +1,80 @@
+use crate::data_storage::checksum::{ChecksumError, is_checksum_error};
+use crate::data_storage::reader::{read_append_file, read_file_header};
+use crate::data_storage::writer::AppendOnlyWriter;
+
+#[test]
+fn test_checksum_round_trip() {
+    let filename = "test_checksum_round_trip.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    // 新檔案預設帶有 CRC32
+    assert!(read_file_header(filename).unwrap().has_checksum());
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 2.0]), (2, vec![3.0, 4.0])]);
+
+    // 續寫後校驗碼仍須涵蓋全部資料
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(3, &[5.0, 6.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[2], (3, vec![5.0, 6.0]));
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_checksum_detects_flipped_data_byte() {
+    let filename = "test_checksum_flipped.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+        writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    // 翻轉第一筆記錄中第一個特徵值的一個位元組：
+    // 標頭之後依序為 seed(1) + count(1) + eigenvalue(8)
+    let header = read_file_header(filename).unwrap();
+    let mut bytes = std::fs::read(filename).unwrap();
+    let target = header.data_offset as usize + 2 + 3;
+    bytes[target] ^= 0x01;
+    std::fs::write(filename, &bytes).unwrap();
+
+    let err = read_append_file(filename).unwrap_err();
+    assert!(is_checksum_error(&err), "unexpected error: {err}");
+
+    let checksum_err = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<ChecksumError>())
+        .unwrap();
+    assert_ne!(checksum_err.stored, checksum_err.computed);
+
+    // 續寫沿用儲存的 CRC32 而不重新讀取資料區段；損壞仍會在讀取時被發現
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(3, &[5.0, 6.0]).unwrap();
+        writer.finish().unwrap();
+    }
+    let err = read_append_file(filename).unwrap_err();
+    assert!(is_checksum_error(&err), "unexpected error: {err}");
+
+    let _ = std::fs::remove_file(filename);
+}
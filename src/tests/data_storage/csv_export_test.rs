@@ -0,0 +1,87 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::csv_export::{CsvExportOptions, NumberFormat, write_eigenvalues_csv};
+use crate::data_storage::csv_import::read_eigenvalues_csv_with_seed;
+use crate::johansen_models::JohansenModel;
+
+fn export_to_string(data: &[(u32, Vec<f64>)], options: &CsvExportOptions) -> String {
+    let mut out = Vec::new();
+    write_eigenvalues_csv(data, false, options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_export_default_format() {
+    let data = vec![(1, vec![2.5, 0.125])];
+    let csv = export_to_string(&data, &CsvExportOptions::default());
+    assert_eq!(
+        csv,
+        "seed,eigenvalue_1,eigenvalue_2\n1,2.500000000000,0.125000000000\n"
+    );
+}
+
+#[test]
+fn test_export_custom_prefix_and_scientific_notation() {
+    let data = vec![(3, vec![12345.678, 0.00042]), (7, vec![-1.5, 0.0])];
+    let options = CsvExportOptions {
+        column_prefix: "lambda".to_string(),
+        format: NumberFormat::Scientific,
+        precision: 3,
+    };
+    let csv = export_to_string(&data, &options);
+    assert_eq!(
+        csv,
+        "seed,lambda1,lambda2\n3,1.235e4,4.200e-4\n7,-1.500e0,0.000e0\n"
+    );
+
+    // 定點格式的小數位數可調整
+    let options = CsvExportOptions {
+        precision: 2,
+        ..CsvExportOptions::default()
+    };
+    assert_eq!(options.format_value(1.23456), "1.23");
+}
+
+#[test]
+fn test_export_aggregate_columns() {
+    let data = vec![(1, vec![4.5, 3.0]), (2, vec![2.25, 2.0])];
+    let mut out = Vec::new();
+    write_eigenvalues_csv(&data, true, &CsvExportOptions::default(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "seed,trace,max\n1,4.500000000000,3.000000000000\n2,2.250000000000,2.000000000000\n"
+    );
+}
+
+#[test]
+fn test_export_csv_round_trips_through_import() {
+    let data_dir = "data/test_export_csv";
+    let csv_path = "test_export_csv_round_trip.csv";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 343, 3).with_data_dir(data_dir);
+    std::fs::create_dir_all(data_dir).unwrap();
+    std::fs::write(csv_path, "3,4.0,-0.125\n1,2.5,0.5\n2,3.25,1.0\n").unwrap();
+    simulation.import_csv(csv_path).unwrap();
+
+    let options = CsvExportOptions {
+        format: NumberFormat::Scientific,
+        precision: 17,
+        ..CsvExportOptions::default()
+    };
+    assert_eq!(simulation.export_csv(csv_path, &options).unwrap(), 3);
+
+    // 依 seed 排序，並可再被匯入
+    let exported = read_eigenvalues_csv_with_seed(csv_path).unwrap();
+    assert_eq!(
+        exported,
+        vec![
+            (1, vec![2.5, 0.5]),
+            (2, vec![3.25, 1.0]),
+            (3, vec![4.0, -0.125])
+        ]
+    );
+
+    let _ = std::fs::remove_file(csv_path);
+    let _ = std::fs::remove_dir_all(data_dir);
+}
@@ -0,0 +1,82 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::csv_import::read_eigenvalues_csv_with_seed;
+use crate::data_storage::reader::{read_append_file, read_file_header};
+use crate::johansen_models::JohansenModel;
+
+#[test]
+fn test_import_csv_round_trip() {
+    let data_dir = "data/test_import_csv";
+    let csv_path = "test_import_csv_round_trip.csv";
+    let _ = std::fs::remove_dir_all(data_dir);
+    std::fs::write(
+        csv_path,
+        "seed,lambda1,lambda2\n1,2.5,0.5\n2, 3.25 , 1.0\n\n3,4.0,-0.125\n",
+    )
+    .unwrap();
+
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, 329, 3).with_data_dir(data_dir);
+    assert_eq!(simulation.import_csv(csv_path).unwrap(), 3);
+
+    let filename = simulation.get_filename(model);
+    let (data, file_model, dim, steps) = read_append_file(&filename).unwrap();
+    assert_eq!(
+        data,
+        vec![
+            (1, vec![2.5, 0.5]),
+            (2, vec![3.25, 1.0]),
+            (3, vec![4.0, -0.125])
+        ]
+    );
+    assert_eq!((file_model, dim, steps), (2, 2, 329));
+    assert_eq!(
+        read_file_header(&filename).unwrap(),
+        simulation.file_header().unwrap()
+    );
+    assert_eq!(simulation.completed_count().unwrap(), 3);
+
+    // 不覆寫既有的資料檔案
+    assert!(simulation.import_csv(csv_path).is_err());
+
+    let _ = std::fs::remove_file(csv_path);
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_import_csv_rejects_wrong_eigenvalue_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv_path = dir.path().join("three_columns.csv");
+    std::fs::write(&csv_path, "1,2.5,0.5,0.25\n2,3.0,1.0,0.5\n").unwrap();
+
+    // 模型 0、維度 2 每筆記錄有 2 個特徵值
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 379, 2).with_data_dir(dir.path());
+    let err = simulation.import_csv(&csv_path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        err.to_string().contains("expected 2"),
+        "unexpected error: {err}"
+    );
+    assert!(!simulation.data_exists(model));
+
+    // 截斷為前 3 個特徵值時相符
+    let top_k = EigenvalueSimulation::new(model, 4, 379, 2)
+        .with_top_k(3)
+        .with_data_dir(dir.path());
+    assert_eq!(top_k.import_csv(&csv_path).unwrap(), 2);
+}
+
+#[test]
+fn test_read_csv_rejects_ragged_rows() {
+    let csv_path = "test_import_csv_ragged.csv";
+    std::fs::write(csv_path, "1,2.5,0.5\n2,3.0\n").unwrap();
+
+    let err = read_eigenvalues_csv_with_seed(csv_path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        err.to_string().contains("line 2"),
+        "unexpected error: {err}"
+    );
+
+    let _ = std::fs::remove_file(csv_path);
+}
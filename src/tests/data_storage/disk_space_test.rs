@@ -0,0 +1,115 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::disk_space::{
+    BatchPlan, check_free_space, check_projected_space, plan_batch,
+};
+use crate::data_storage::file_format::{
+    TIMESTAMP_SIZE, calculate_expected_file_size, extension_size,
+};
+use crate::data_storage::writer::AppendOnlyWriter;
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::AggregateMode;
+use std::path::Path;
+
+#[test]
+fn test_plan_batch_totals_runs_and_size() {
+    let models = [
+        JohansenModel::NoInterceptNoTrend,
+        JohansenModel::InterceptNoTrendWithInterceptInCoint,
+    ];
+    let batch = |configure: fn(EigenvalueSimulation) -> EigenvalueSimulation| {
+        [2, 3, 4]
+            .into_iter()
+            .flat_map(|dim| {
+                models
+                    .iter()
+                    .map(move |&model| configure(EigenvalueSimulation::new(model, dim, 100, 1_000)))
+            })
+            .collect::<Vec<_>>()
+    };
+    let plan = plan_batch(&batch(|simulation| simulation)).unwrap();
+
+    // 3 個維度 × 2 個模型 × 1,000 次；模型 1 每筆多一個特徵值
+    let expected_bytes: u64 = [2, 3, 4]
+        .into_iter()
+        .map(|dim| {
+            calculate_expected_file_size(1_000, dim) + calculate_expected_file_size(1_000, dim + 1)
+        })
+        .sum();
+    assert_eq!(
+        plan,
+        BatchPlan {
+            files: 6,
+            total_runs: 6_000,
+            total_bytes: expected_bytes,
+        }
+    );
+
+    // 只存跡與最大特徵值時每筆記錄固定為 2 個數值，另有時間戳記
+    let aggregated = batch(|simulation| {
+        simulation
+            .with_aggregate(AggregateMode::Both)
+            .with_timestamps(true)
+    });
+    let plan = plan_batch(&aggregated).unwrap();
+    let expected_bytes: u64 = aggregated
+        .iter()
+        .map(|simulation| simulation.projected_file_size().unwrap())
+        .sum();
+    assert_eq!(plan.total_bytes, expected_bytes);
+    assert_eq!(
+        aggregated[0].projected_file_size().unwrap(),
+        calculate_expected_file_size(1_000, 2)
+            + extension_size(aggregated[0].file_header().unwrap().flags)
+            + TIMESTAMP_SIZE * 1_000
+    );
+
+    assert_eq!(plan_batch(&[]).unwrap().total_bytes, 0);
+    assert!(plan_batch(&[EigenvalueSimulation::new(models[0], 2, 100, 10).with_top_k(3)]).is_err());
+}
+
+#[test]
+fn test_projected_size_exceeding_free_space_aborts() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulations: Vec<EigenvalueSimulation> = [2, 3]
+        .into_iter()
+        .map(|dim| EigenvalueSimulation::new(model, dim, 358, 1_000).with_data_dir(dir.path()))
+        .collect();
+
+    // 兩個檔案都在同一目錄，預估寫入量為兩者之和
+    let required: u64 = simulations
+        .iter()
+        .map(|simulation| simulation.projected_remaining_bytes().unwrap())
+        .sum();
+    assert_eq!(
+        required,
+        calculate_expected_file_size(1_000, 2) + calculate_expected_file_size(1_000, 3)
+    );
+
+    assert!(check_projected_space(&simulations, |_| Ok(required)).is_ok());
+    let error = check_projected_space(&simulations, |_| Ok(required - 1)).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::StorageFull);
+    assert!(error.to_string().contains("--ignore-space"));
+
+    // 既有檔案已佔用的部分不再計入
+    let filename = simulations[0].get_filename(model);
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulations[0].file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        writer.append_eigenvalues(1, &[1.0, 0.5]).unwrap();
+    }
+    let existing = std::fs::metadata(&filename).unwrap().len();
+    assert_eq!(
+        simulations[0].projected_remaining_bytes().unwrap(),
+        calculate_expected_file_size(1_000, 2) - existing
+    );
+
+    assert!(check_free_space(Path::new("data"), 10, 10).is_ok());
+    assert!(check_free_space(Path::new("data"), 11, 10).is_err());
+}
@@ -1,18 +1,14 @@
 use super::*;
 
-/// 重寫追加格式檔案的測試輔助函數
-pub fn rewrite_append_file(
-    filename: &str,
-    data: &[(u32, Vec<f64>)],
-    model: u8,
-    dim: u8,
-    steps: u32,
-) -> std::io::Result<()> {
+/// 重寫追加格式檔案的測試輔助函數（保留原本的標頭，包括旗標與延伸欄位）
+pub fn rewrite_append_file(filename: &str, data: &[(u32, Vec<f64>)]) -> std::io::Result<()> {
+    let header = crate::data_storage::reader::read_file_header(filename)?;
+
     // 刪除舊檔案
     let _ = std::fs::remove_file(filename);
 
     // 使用追加寫入器重建檔案
-    let mut writer = AppendOnlyWriter::with_expected_size(filename, None, model, dim, steps, true)?;
+    let mut writer = AppendOnlyWriter::with_header(filename, None, header, None, true)?;
 
     for (seed, eigenvalues) in data {
         writer.append_eigenvalues(*seed, eigenvalues)?;
@@ -55,13 +51,7 @@ pub fn remove_seed_from_file(
         std::fs::copy(&filename, &backup_filename)?;
 
         // 重寫檔案
-        rewrite_append_file(
-            &filename,
-            &filtered_data,
-            model.to_number(),
-            simulation.dim as u8,
-            simulation.steps as u32,
-        )?;
+        rewrite_append_file(&filename, &filtered_data)?;
     }
 
     Ok(removed_count)
@@ -9,6 +9,7 @@ mod filename_consistency;
 mod helpers;
 mod multiple_models;
 mod read_all_data;
+mod repair;
 mod resumable;
 
 pub use helpers::*;
@@ -43,3 +43,46 @@ fn test_multiple_models() {
         let _ = std::fs::remove_file(&filename);
     }
 }
+
+#[test]
+fn test_data_exists_for_each_model() {
+    let dir = tempfile::tempdir().unwrap();
+    let simulation = |model| EigenvalueSimulation::new(model, 2, 381, 2).with_data_dir(dir.path());
+
+    for model in JohansenModel::all_models() {
+        assert!(!simulation(model).data_exists(model));
+    }
+
+    // 逐一計算，只有已計算的模型回報存在，且與檔案路徑是否存在一致
+    let models = JohansenModel::all_models();
+    for (index, &model) in models.iter().enumerate() {
+        simulation(model).run_simulation_quiet();
+        for (other_index, &other) in models.iter().enumerate() {
+            let exists = simulation(other).data_exists(other);
+            assert_eq!(exists, other_index <= index, "{other} after {model}");
+            assert_eq!(
+                exists,
+                std::path::Path::new(&simulation(other).get_filename(other)).exists()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_data_exists_follows_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let output_file = dir.path().join("chosen.dat");
+    let simulation = EigenvalueSimulation::new(model, 2, 382, 2)
+        .with_data_dir(dir.path())
+        .with_output_file(&output_file);
+
+    assert!(!simulation.data_exists(model));
+    simulation.run_simulation_quiet();
+    assert!(simulation.data_exists(model));
+    assert!(output_file.exists());
+
+    // 預設路徑的資料夾中沒有此模型的檔案
+    let default = EigenvalueSimulation::new(model, 2, 382, 2).with_data_dir(dir.path());
+    assert!(!default.data_exists(model));
+}
@@ -0,0 +1,160 @@
+use super::*;
+use crate::data_storage::reader::read_file_summary;
+use std::io::Write;
+
+/// 單一位元組 seed 的記錄位元組（seed + 特徵值數量 + 特徵值）
+fn raw_record(seed: u8, eigenvalues: &[f64]) -> Vec<u8> {
+    let mut bytes = vec![seed, eigenvalues.len() as u8];
+    for value in eigenvalues {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn test_repair_cleans_damaged_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 369, 5).with_data_dir(dir.path());
+    let filename = simulation.get_filename(model);
+
+    // 完成的檔案（seed 1..=4）之後被直接追加記錄，原本的結束標記留在資料區段中間
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in 1..=4 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, 0.5])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let partial = &raw_record(6, &[6.0, 0.5])[..10];
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&filename)
+            .unwrap();
+        // 重複的 seed 2、缺少的 seed 5、範圍外的 seed 9，最後是中斷寫入的不完整記錄
+        file.write_all(&raw_record(2, &[-2.0, 0.5])).unwrap();
+        file.write_all(&raw_record(5, &[5.0, 0.5])).unwrap();
+        file.write_all(&raw_record(9, &[9.0, 0.5])).unwrap();
+        file.write_all(partial).unwrap();
+    }
+    assert!(read_file_summary(&filename).unwrap().metadata.is_none());
+
+    let report = simulation.repair().unwrap().unwrap();
+    assert_eq!(report.records, 5);
+    assert_eq!(report.truncated_bytes, partial.len() as u64);
+    assert_eq!(report.stray_footers, 1);
+    assert_eq!(report.duplicates, 1);
+    assert_eq!(report.out_of_range, 1);
+    assert!(report.rewritten);
+    assert!(!std::path::Path::new(&format!("{filename}.repair.tmp")).exists());
+
+    // 修復後為帶有結束標記的完整檔案，可直接以末尾元數據快速讀取
+    let summary = read_file_summary(&filename).unwrap();
+    assert_eq!(summary.metadata, Some((5, 2)));
+    assert_eq!(summary.header, simulation.file_header().unwrap());
+    let data = simulation.read_data_sorted().unwrap();
+    let expected: Vec<(u32, Vec<f64>)> =
+        (1..=5).map(|seed| (seed, vec![seed as f64, 0.5])).collect();
+    assert_eq!(data, expected);
+
+    // 乾淨的檔案再次修復不會被修改
+    let before = std::fs::read(&filename).unwrap();
+    let report = simulation.repair().unwrap().unwrap();
+    assert!(!report.rewritten);
+    assert_eq!(report.records, 5);
+    assert_eq!(std::fs::read(&filename).unwrap(), before);
+
+    // 不存在的檔案不需修復
+    let missing = EigenvalueSimulation::new(model, 3, 369, 5).with_data_dir(dir.path());
+    assert_eq!(missing.repair().unwrap(), None);
+}
+
+#[test]
+fn test_repair_refuses_corrupted_segment_before_stray_footer() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 375, 5).with_data_dir(dir.path());
+    let filename = simulation.get_filename(model);
+
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in 1..=3 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, 0.5])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    // 結束標記之前的資料被就地改動，之後又被直接追加記錄
+    let mut bytes = std::fs::read(&filename).unwrap();
+    let data_offset = read_file_summary(&filename).unwrap().header.data_offset as usize;
+    bytes[data_offset + 2] ^= 0x01;
+    bytes.extend_from_slice(&raw_record(4, &[4.0, 0.5]));
+    std::fs::write(&filename, &bytes).unwrap();
+
+    // 重寫會讓損壞的數值帶著新的校驗碼，因此返回校驗錯誤且不修改檔案
+    let err = simulation.repair().unwrap_err();
+    assert!(
+        crate::data_storage::checksum::is_checksum_error(&err),
+        "{err}"
+    );
+    assert_eq!(std::fs::read(&filename).unwrap(), bytes);
+    assert!(!std::path::Path::new(&format!("{filename}.repair.tmp")).exists());
+}
+
+#[test]
+fn test_repair_refuses_invalid_record_mid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 378, 5).with_data_dir(dir.path());
+    let filename = simulation.get_filename(model);
+
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in 1..=3 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, 0.5])
+                .unwrap();
+        }
+    }
+
+    // 特徵值數量不一致的記錄之後仍有完整的記錄，不可當作損壞的尾端捨棄
+    let mut bytes = std::fs::read(&filename).unwrap();
+    bytes.extend_from_slice(&raw_record(4, &[4.0]));
+    bytes.extend_from_slice(&raw_record(5, &[5.0, 0.5]));
+    std::fs::write(&filename, &bytes).unwrap();
+
+    let err = simulation.repair().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        err.to_string().contains("Inconsistent eigenvalue count"),
+        "{err}"
+    );
+    assert_eq!(std::fs::read(&filename).unwrap(), bytes);
+    assert!(!std::path::Path::new(&format!("{filename}.repair.tmp")).exists());
+}
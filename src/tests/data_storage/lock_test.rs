@@ -0,0 +1,63 @@
+use crate::data_storage::lock::lock_path;
+use crate::data_storage::reader::read_append_file;
+use crate::data_storage::writer::AppendOnlyWriter;
+use std::path::Path;
+
+#[test]
+fn test_second_writer_on_locked_file_fails_fast() {
+    let filename = "test_lock_second_writer.dat";
+    let lock_file = lock_path(Path::new(filename));
+    let _ = std::fs::remove_file(filename);
+    let _ = std::fs::remove_file(&lock_file);
+
+    let mut first =
+        AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+    first.append_eigenvalues(1, &[1.0, 10.0]).unwrap();
+    assert!(lock_file.exists());
+
+    let error = match AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true) {
+        Ok(_) => panic!("second writer should not acquire the lock"),
+        Err(e) => e,
+    };
+    assert_eq!(error.kind(), std::io::ErrorKind::ResourceBusy);
+    assert!(error.to_string().contains("already in progress"));
+
+    // 失敗的寫入器不會釋放他人的鎖，也不會修改檔案
+    assert!(lock_file.exists());
+    first.append_eigenvalues(2, &[2.0, 20.0]).unwrap();
+    first.finish().unwrap();
+    assert!(!lock_file.exists());
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 10.0]), (2, vec![2.0, 20.0])]);
+
+    // 鎖釋放後可以續寫；寫入器被丟棄時同樣會釋放鎖
+    {
+        let mut resumed =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        resumed.append_eigenvalues(3, &[3.0, 30.0]).unwrap();
+    }
+    assert!(!lock_file.exists());
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_stale_lock_file_does_not_block_writer() {
+    let dir = tempfile::tempdir().unwrap();
+    let filename = dir.path().join("stale_lock.dat");
+    let lock_file = lock_path(&filename);
+
+    // 被強制終止的程序留下的旁檔沒有任何程序持有鎖
+    std::fs::write(&lock_file, "4294967295\n").unwrap();
+
+    let mut writer =
+        AppendOnlyWriter::with_expected_size(&filename, None, 0, 2, 100, None, true).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&lock_file).unwrap(),
+        format!("{}\n", std::process::id())
+    );
+    writer.append_eigenvalues(1, &[1.0, 10.0]).unwrap();
+    writer.finish().unwrap();
+    assert!(!lock_file.exists());
+}
@@ -2,11 +2,30 @@
 //!
 //! 這個模組包含所有與數據存儲相關的測試，包括：
 //! - 追加寫入器測試 (append_writer_test)
+//! - CRC32 校驗測試 (checksum_test)
+//! - CSV 匯出測試 (csv_export_test)
+//! - CSV 匯入測試 (csv_import_test)
+//! - 磁碟空間預檢測試 (disk_space_test)
+//! - Parquet 匯出測試 (parquet_export_test，需要 `parquet` feature)
+//! - 寫入鎖測試 (lock_test)
+//! - 範圍外 seed 清理測試 (prune_test)
+//! - 不完整記錄修復測試 (recovery_test)
 //! - 模擬配置測試 (simulation_test)
+//! - 結果接收端測試 (sink_test)
 //! - 整合測試 (integration)
 
 mod append_writer_test;
+mod checksum_test;
+mod csv_export_test;
+mod csv_import_test;
+mod disk_space_test;
 mod integration;
+mod lock_test;
+#[cfg(feature = "parquet")]
+mod parquet_export_test;
+mod prune_test;
+mod recovery_test;
 mod simulation_test;
+mod sink_test;
 mod uleb128_test;
 mod uleb128_unit_test;
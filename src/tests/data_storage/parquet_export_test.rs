@@ -0,0 +1,129 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::parquet_export::export_parquet;
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::AggregateMode;
+use arrow_array::{Array, Float64Array, RecordBatch, UInt32Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::path::Path;
+
+fn read_parquet(path: &Path) -> Vec<RecordBatch> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+}
+
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> &'a T {
+    batch
+        .column_by_name(name)
+        .unwrap_or_else(|| panic!("missing column {name}"))
+        .as_any()
+        .downcast_ref::<T>()
+        .unwrap()
+}
+
+#[test]
+fn test_parquet_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("eigenvalues.parquet");
+    let data = vec![
+        (1, vec![3.5, 1.25, 0.125]),
+        (2, vec![2.0, 1.0, 0.5]),
+        (7, vec![9.75, 0.0, -0.0]),
+    ];
+    export_parquet(&path, &data, false).unwrap();
+
+    let batches = read_parquet(&path);
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    let names: Vec<&str> = batch
+        .schema_ref()
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["seed", "eigenvalue_1", "eigenvalue_2", "eigenvalue_3"]
+    );
+
+    let seeds = column::<UInt32Array>(batch, "seed");
+    assert_eq!(seeds.values().to_vec(), vec![1, 2, 7]);
+    for i in 0..3 {
+        let values = column::<Float64Array>(batch, &format!("eigenvalue_{}", i + 1));
+        let expected: Vec<f64> = data.iter().map(|(_, eigenvalues)| eigenvalues[i]).collect();
+        assert_eq!(values.values().to_vec(), expected);
+        assert_eq!(values.null_count(), 0);
+    }
+}
+
+#[test]
+fn test_parquet_pads_shorter_records_with_nulls() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mixed.parquet");
+    export_parquet(&path, &[(1, vec![1.0]), (2, vec![2.0, 0.5])], false).unwrap();
+
+    let batch = &read_parquet(&path)[0];
+    assert_eq!(batch.num_columns(), 3);
+    let second = column::<Float64Array>(batch, "eigenvalue_2");
+    assert!(second.is_null(0));
+    assert_eq!(second.value(1), 0.5);
+}
+
+#[test]
+fn test_simulation_export_parquet_has_model_column_count() {
+    let dir = tempfile::tempdir().unwrap();
+    // Model 1 多一列受限常數項，特徵值為 dim + 1 個
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = EigenvalueSimulation::new(model, 2, 356, 5).with_data_dir(dir.path());
+    simulation.try_run_simulation_quiet().unwrap();
+
+    let path = dir.path().join("model1.parquet");
+    assert_eq!(simulation.export_parquet(&path).unwrap(), 5);
+
+    let batches = read_parquet(&path);
+    let batch = &batches[0];
+    assert_eq!(batch.num_columns(), 1 + model.eigenvalues_per_run(2));
+    assert_eq!(batch.num_rows(), 5);
+
+    let records = simulation.read_data_sorted().unwrap();
+    let seeds = column::<UInt32Array>(batch, "seed");
+    let largest = column::<Float64Array>(batch, "eigenvalue_1");
+    for (row, (seed, eigenvalues)) in records.iter().enumerate() {
+        assert_eq!(seeds.value(row), *seed);
+        assert_eq!(largest.value(row), eigenvalues[0]);
+    }
+}
+
+#[test]
+fn test_simulation_export_parquet_aggregate_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 3, 373, 4)
+        .with_data_dir(dir.path())
+        .with_aggregate(AggregateMode::Both);
+    simulation.try_run_simulation_quiet().unwrap();
+
+    let path = dir.path().join("aggregates.parquet");
+    assert_eq!(simulation.export_parquet(&path).unwrap(), 4);
+
+    // 彙總檔案的記錄為 [跡, 最大特徵值]，不可標成特徵值欄位
+    let batch = &read_parquet(&path)[0];
+    let names: Vec<&str> = batch
+        .schema_ref()
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    assert_eq!(names, ["seed", "trace", "max"]);
+
+    let records = simulation.read_data_sorted().unwrap();
+    let trace = column::<Float64Array>(batch, "trace");
+    let max = column::<Float64Array>(batch, "max");
+    for (row, (_, values)) in records.iter().enumerate() {
+        assert_eq!([trace.value(row), max.value(row)], values[..]);
+    }
+}
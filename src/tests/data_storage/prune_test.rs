@@ -0,0 +1,63 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::reader::{read_append_file, read_append_file_with_timestamps};
+use crate::data_storage::writer::AppendOnlyWriter;
+use crate::johansen_models::JohansenModel;
+
+#[test]
+fn test_prune_removes_out_of_range_seeds() {
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 358, 4).with_timestamps(true);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    // 先前以較大 num_runs 計算留下的 seed 5..=6，以及不應存在的 seed 0
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in [1, 5, 2, 0, 3, 6, 4] {
+            writer
+                .append_with_timestamp(seed, &[seed as f64, 0.5], 1_000 + seed)
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    assert_eq!(simulation.prune_out_of_range().unwrap(), 3);
+    let (data, times) = read_append_file_with_timestamps(&filename).unwrap();
+    let seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
+    assert_eq!(seeds, vec![1, 2, 3, 4]);
+    assert_eq!(data[1], (2, vec![2.0, 0.5]));
+    // 保留原本的時間戳記與標頭欄位
+    assert_eq!(times, vec![1_001, 1_002, 1_003, 1_004]);
+    assert_eq!(simulation.read_data().unwrap().len(), 4);
+    assert!(!std::path::Path::new(&format!("{filename}.prune.tmp")).exists());
+
+    // 沒有範圍外的 seed 時不修改檔案
+    let before = std::fs::read(&filename).unwrap();
+    assert_eq!(simulation.prune_out_of_range().unwrap(), 0);
+    assert_eq!(std::fs::read(&filename).unwrap(), before);
+
+    // 重寫後仍可續寫
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        writer.append_eigenvalues(7, &[7.0, 0.5]).unwrap();
+        writer.finish().unwrap();
+    }
+    let (data, _model, _dim, _steps) = read_append_file(&filename).unwrap();
+    assert_eq!(data.len(), 5);
+
+    let _ = std::fs::remove_file(&filename);
+}
@@ -0,0 +1,126 @@
+use crate::data_storage::reader::read_append_file;
+use crate::data_storage::recovery::truncate_to_last_valid_record;
+use crate::data_storage::writer::AppendOnlyWriter;
+use std::io::Write;
+
+#[test]
+fn test_truncate_partial_final_record() {
+    let filename = "test_recovery_partial_record.dat";
+    let _ = std::fs::remove_file(filename);
+
+    // 寫入三筆完整記錄後模擬中斷（未呼叫 finish，沒有結束標記）
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        for seed in 1..=3 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, seed as f64 * 10.0])
+                .unwrap();
+        }
+    }
+    let valid_len = std::fs::metadata(filename).unwrap().len();
+
+    // 追加只寫了 seed、數量與第一個特徵值的不完整記錄
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(filename)
+            .unwrap();
+        file.write_all(&[4, 2]).unwrap();
+        file.write_all(&4.0f64.to_le_bytes()).unwrap();
+    }
+
+    assert_eq!(truncate_to_last_valid_record(filename).unwrap(), 3);
+    assert_eq!(std::fs::metadata(filename).unwrap().len(), valid_len);
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(
+        data,
+        vec![
+            (1, vec![1.0, 10.0]),
+            (2, vec![2.0, 20.0]),
+            (3, vec![3.0, 30.0])
+        ]
+    );
+
+    // 已乾淨的檔案再次修復不會改變內容
+    assert_eq!(truncate_to_last_valid_record(filename).unwrap(), 3);
+    assert_eq!(std::fs::metadata(filename).unwrap().len(), valid_len);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_resume_after_partial_final_record() {
+    let filename = "test_recovery_resume.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+    }
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(filename)
+            .unwrap();
+        file.write_all(&[2, 2, 0xAB, 0xCD]).unwrap();
+    }
+
+    // 續寫時自動截斷不完整記錄，新記錄接在最後一筆完整記錄之後
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(2, &[3.0, 4.0]).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let (data, _model, _dim, _steps) = read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 2.0]), (2, vec![3.0, 4.0])]);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+#[test]
+fn test_ragged_record_in_middle_is_not_truncated() {
+    let filename = "test_recovery_ragged_middle.dat";
+    let _ = std::fs::remove_file(filename);
+
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+        writer.append_eigenvalues(1, &[1.0, 2.0]).unwrap();
+    }
+    // 中間一筆完整但特徵值數量不同的記錄，其後仍有有效記錄與中斷的不完整記錄
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(filename)
+            .unwrap();
+        file.write_all(&[2, 3]).unwrap();
+        for value in [3.0f64, 4.0, 5.0] {
+            file.write_all(&value.to_le_bytes()).unwrap();
+        }
+        file.write_all(&[3, 2]).unwrap();
+        for value in [6.0f64, 7.0] {
+            file.write_all(&value.to_le_bytes()).unwrap();
+        }
+        file.write_all(&[4, 2, 0xAB]).unwrap();
+    }
+    let original = std::fs::read(filename).unwrap();
+
+    // 與嚴格的掃描式讀取相同的錯誤，檔案不被截斷
+    let err = truncate_to_last_valid_record(filename).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("seed 2"), "{err}");
+    let read_err = read_append_file(filename).unwrap_err();
+    assert_eq!(err.to_string(), read_err.to_string());
+    assert_eq!(std::fs::read(filename).unwrap(), original);
+
+    // 續寫同樣失敗，不會刪除其後的有效記錄
+    assert!(AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).is_err());
+    assert_eq!(std::fs::read(filename).unwrap(), original);
+
+    let _ = std::fs::remove_file(filename);
+}
@@ -124,3 +124,946 @@ fn test_read_data_error_handling() {
     // 清理
     let _ = fs::remove_file(&filename);
 }
+
+#[test]
+fn test_ascending_sort_order_storage() {
+    use crate::johansen_statistics::SortOrder;
+
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let desc_sim = EigenvalueSimulation::new(model, 3, 301, 2);
+    let asc_sim = EigenvalueSimulation::new(model, 3, 301, 2).with_sort_order(SortOrder::Ascending);
+
+    // 升序資料使用獨立的檔案，避免與降序檔案混用
+    let desc_file = desc_sim.get_filename(model);
+    let asc_file = asc_sim.get_filename(model);
+    assert_ne!(desc_file, asc_file);
+    assert!(asc_file.ends_with("eigenvalues_model1_dim3_steps301_asc.dat"));
+    let _ = std::fs::remove_file(&desc_file);
+    let _ = std::fs::remove_file(&asc_file);
+
+    desc_sim.run_simulation_quiet();
+    asc_sim.run_simulation_quiet();
+
+    let mut desc_data = desc_sim.read_data().unwrap();
+    let mut asc_data = asc_sim.read_data().unwrap();
+    desc_data.sort_by_key(|(seed, _)| *seed);
+    asc_data.sort_by_key(|(seed, _)| *seed);
+
+    for ((_, desc), (_, asc)) in desc_data.iter().zip(&asc_data) {
+        let mut reversed = asc.clone();
+        reversed.reverse();
+        assert_eq!(desc, &reversed);
+    }
+
+    // 最大特徵值統計量不受儲存順序影響
+    let percentiles = [0.5];
+    assert_eq!(
+        desc_sim.calculate_maxeig_percentiles(&percentiles).unwrap(),
+        asc_sim.calculate_maxeig_percentiles(&percentiles).unwrap()
+    );
+
+    let _ = std::fs::remove_file(&desc_file);
+    let _ = std::fs::remove_file(&asc_file);
+}
+
+#[test]
+fn test_stream_id_separate_files_and_header() {
+    use crate::data_storage::reader::read_file_header;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let stream1 = EigenvalueSimulation::new(model, 2, 303, 2).with_stream_id(1);
+    let stream2 = EigenvalueSimulation::new(model, 2, 303, 2).with_stream_id(2);
+
+    let file1 = stream1.get_filename(model);
+    let file2 = stream2.get_filename(model);
+    assert!(file1.ends_with("eigenvalues_model0_dim2_steps303_stream1.dat"));
+    assert!(file2.ends_with("eigenvalues_model0_dim2_steps303_stream2.dat"));
+    let _ = std::fs::remove_file(&file1);
+    let _ = std::fs::remove_file(&file2);
+
+    stream1.run_simulation_quiet();
+    stream2.run_simulation_quiet();
+
+    // 標頭記錄子串流編號
+    assert_eq!(read_file_header(&file1).unwrap().stream_id, 1);
+    assert_eq!(read_file_header(&file2).unwrap().stream_id, 2);
+
+    let mut data1 = stream1.read_data().unwrap();
+    let mut data2 = stream2.read_data().unwrap();
+    data1.sort_by_key(|(seed, _)| *seed);
+    data2.sort_by_key(|(seed, _)| *seed);
+    assert_eq!(data1.len(), 2);
+    assert_ne!(data1, data2);
+    assert_eq!(stream1.completed_count().unwrap(), 2);
+
+    let _ = std::fs::remove_file(&file1);
+    let _ = std::fs::remove_file(&file2);
+}
+
+#[test]
+fn test_try_get_filename_reports_dir_creation_failure() {
+    // 在資料夾應在的位置放一個同名檔案，使創建資料夾失敗
+    let blocker = "test_data_dir_blocker";
+    let _ = std::fs::remove_dir_all(blocker);
+    std::fs::write(blocker, b"not a directory").unwrap();
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 307, 3).with_data_dir(blocker);
+
+    assert!(simulation.try_get_filename(model).is_err());
+    assert!(simulation.read_all_data().is_err());
+    assert!(simulation.completed_count().is_err());
+
+    // 便利版本仍會 panic
+    let result = std::panic::catch_unwind(|| simulation.get_filename(model));
+    assert!(result.is_err());
+
+    let _ = std::fs::remove_file(blocker);
+}
+
+#[test]
+fn test_data_exists_before_and_after_run() {
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, 315, 2);
+    for other in JohansenModel::all_models() {
+        let _ = std::fs::remove_file(simulation.get_filename(other));
+    }
+
+    assert!(!simulation.data_exists(model));
+    assert!(!simulation.any_data_exists());
+
+    simulation.run_simulation_quiet();
+
+    assert!(simulation.data_exists(model));
+    assert!(simulation.any_data_exists());
+    assert!(!simulation.data_exists(JohansenModel::NoInterceptNoTrend));
+
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+}
+
+#[test]
+fn test_single_thread_matches_parallel_records() {
+    let model = JohansenModel::InterceptTrendUnrestrictedBoth;
+    let parallel = EigenvalueSimulation::new(model, 2, 317, 20);
+    let single = EigenvalueSimulation::new(model, 2, 317, 20)
+        .with_single_thread(true)
+        .with_data_dir("data/test_single_thread");
+    let _ = std::fs::remove_file(parallel.get_filename(model));
+    let _ = std::fs::remove_file(single.get_filename(model));
+
+    parallel.run_simulation_quiet();
+    single.run_simulation_quiet();
+
+    // 單執行緒依 seed 順序寫入
+    let single_data = single.read_data().unwrap();
+    let seeds: Vec<u32> = single_data.iter().map(|(seed, _)| *seed).collect();
+    assert_eq!(seeds, (1..=20).collect::<Vec<u32>>());
+
+    // 兩種模式的記錄集合相同
+    let mut parallel_data = parallel.read_data().unwrap();
+    parallel_data.sort_by_key(|(seed, _)| *seed);
+    assert_eq!(single_data, parallel_data);
+
+    let _ = std::fs::remove_file(parallel.get_filename(model));
+    let _ = std::fs::remove_dir_all("data/test_single_thread");
+}
+
+#[test]
+fn test_fold_runs_matches_stored_data() {
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = EigenvalueSimulation::new(model, 2, 319, 25);
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+
+    let count = simulation
+        .fold_runs(0usize, |acc, _, _| acc + 1, |a, b| a + b)
+        .unwrap();
+    let sum = simulation
+        .fold_runs(
+            0.0,
+            |acc, _, eigenvalues| acc + eigenvalues.iter().sum::<f64>(),
+            |a, b| a + b,
+        )
+        .unwrap();
+
+    // 歸約不寫入檔案
+    assert_eq!(count, 25);
+    assert!(!simulation.data_exists(model));
+
+    simulation.run_simulation_quiet();
+    let expected: f64 = simulation
+        .read_data()
+        .unwrap()
+        .iter()
+        .flat_map(|(_, eigenvalues)| eigenvalues.iter())
+        .sum();
+    assert!((sum - expected).abs() <= 1e-9 * expected.abs());
+
+    // 單執行緒依序歸約得到相同結果
+    let serial_count = simulation
+        .clone()
+        .with_single_thread(true)
+        .fold_runs(0usize, |acc, _, _| acc + 1, |a, b| a + b)
+        .unwrap();
+    assert_eq!(serial_count, 25);
+
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+}
+
+#[test]
+fn test_seed_list_computes_only_listed_seeds() {
+    let data_dir = "data/test_seed_list";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 331, 5).with_data_dir(data_dir);
+    simulation
+        .clone()
+        .with_seeds(vec![12, 3, 7, 7])
+        .run_simulation_quiet();
+
+    let mut seeds: Vec<u32> = simulation
+        .read_all_data()
+        .unwrap()
+        .into_iter()
+        .map(|(seed, _)| seed)
+        .collect();
+    seeds.sort_unstable();
+    assert_eq!(seeds, vec![3, 7, 12]);
+
+    // 已存在的 seed 會被略過，新的 seed 追加至同一個檔案
+    simulation
+        .clone()
+        .with_seeds(vec![3, 4])
+        .run_simulation_quiet();
+    let data = simulation.read_all_data().unwrap();
+    let mut seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
+    seeds.sort_unstable();
+    assert_eq!(seeds, vec![3, 4, 7, 12]);
+
+    // 結果與一般模擬的同一個 seed 相同
+    let expected = crate::johansen_statistics::calculate_eigenvalues(2, 331, 4, model);
+    assert!(data.contains(&(4, expected)));
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_output_file_overrides_default_path() {
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let output_file = "test_output_file_override.dat";
+    let _ = std::fs::remove_file(output_file);
+
+    let default = EigenvalueSimulation::new(model, 2, 337, 4);
+    let default_path = default.get_filename(model);
+    let _ = std::fs::remove_file(&default_path);
+
+    let simulation = default.clone().with_output_file(output_file);
+    assert_eq!(simulation.get_filename(model), output_file);
+    // 其他模型仍使用預設命名
+    let other = JohansenModel::NoInterceptNoTrend;
+    assert_eq!(simulation.get_filename(other), default.get_filename(other));
+
+    simulation.run_simulation_quiet();
+    assert_eq!(simulation.read_data().unwrap().len(), 4);
+    assert_eq!(simulation.completed_count().unwrap(), 4);
+    assert!(std::path::Path::new(output_file).exists());
+    assert!(!std::path::Path::new(&default_path).exists());
+
+    let _ = std::fs::remove_file(output_file);
+}
+
+#[test]
+fn test_mismatched_output_file_is_not_replaced() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let output_file = dir.path().join("chosen.dat");
+
+    let simulation = EigenvalueSimulation::new(model, 2, 377, 3)
+        .with_data_dir(dir.path())
+        .with_output_file(&output_file);
+    simulation.try_run_simulation_quiet().unwrap();
+    let before = std::fs::read(&output_file).unwrap();
+
+    // 維度不同：返回錯誤，檔案保持原樣
+    let mut mismatched = simulation.clone();
+    mismatched.dim = 3;
+    let error = mismatched.try_run_simulation_quiet().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("will not be replaced"));
+    assert_eq!(std::fs::read(&output_file).unwrap(), before);
+
+    // 不是資料檔案：同樣不刪除
+    let other = dir.path().join("notes.dat");
+    std::fs::write(&other, b"not a data file").unwrap();
+    let error = simulation
+        .clone()
+        .with_output_file(&other)
+        .try_run_simulation_quiet()
+        .unwrap_err();
+    assert!(error.to_string().contains("will not be replaced"));
+    assert_eq!(std::fs::read(&other).unwrap(), b"not a data file");
+}
+
+#[test]
+fn test_seed_gaps_lists_missing_seeds() {
+    use crate::data_storage::writer::AppendOnlyWriter;
+
+    let model = JohansenModel::InterceptTrendUnrestrictedBoth;
+    let simulation = EigenvalueSimulation::new(model, 2, 339, 10);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    assert_eq!(
+        simulation.seed_gaps(model).unwrap(),
+        (1..=10).collect::<Vec<u32>>()
+    );
+
+    // 刻意略過 2、5、6、10，並寫入一個超出範圍的 seed
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in [9, 1, 3, 4, 7, 8, 12] {
+            writer.append_eigenvalues(seed, &[1.0, 0.5]).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    assert_eq!(simulation.seed_gaps(model).unwrap(), vec![2, 5, 6, 10]);
+
+    // 標頭參數不符時返回錯誤
+    let mut mismatched = simulation.clone().with_output_file(&filename);
+    mismatched.steps = 340;
+    assert!(mismatched.seed_gaps(model).is_err());
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_warmup_is_recorded_in_header() {
+    use crate::data_storage::file_format::FLAG_WARMUP;
+    use crate::data_storage::reader::read_file_header;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 341, 3).with_warmup(41);
+    let filename = simulation.get_filename(model);
+    assert!(filename.ends_with("eigenvalues_model0_dim2_steps341_warmup41.dat"));
+    let _ = std::fs::remove_file(&filename);
+
+    simulation.run_simulation_quiet();
+    let header = read_file_header(&filename).unwrap();
+    assert_ne!(header.flags & FLAG_WARMUP, 0);
+    assert_eq!((header.steps, header.warmup), (341, 41));
+
+    // 記錄以相同的捨棄步數計算；未捨棄的標頭與此檔案不相符，不可續寫
+    let data = simulation.read_data().unwrap();
+    let options = simulation.eigenvalue_options();
+    let expected =
+        crate::johansen_statistics::calculate_eigenvalues_with_options(2, 341, 1, model, options);
+    assert!(data.contains(&(1, expected)));
+    assert!(
+        read_file_header(&filename)
+            .unwrap()
+            .check_matches(
+                EigenvalueSimulation::new(model, 2, 341, 3)
+                    .file_header()
+                    .unwrap()
+            )
+            .is_err()
+    );
+
+    let _ = std::fs::remove_file(&filename);
+
+    // 捨棄步數須小於總步數：建構時不 panic，運行前返回錯誤
+    let invalid = EigenvalueSimulation::new(model, 2, 341, 3).with_warmup(341);
+    let error = invalid.validate().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("warmup (341)"), "{error}");
+    assert!(invalid.try_run_simulation_quiet().is_err());
+    assert!(!invalid.data_exists(model));
+}
+
+#[test]
+fn test_num_eigenvalues_matches_computed_count() {
+    use crate::johansen_statistics::calculate_eigenvalues_with_options;
+
+    for model in JohansenModel::all_models() {
+        for dim in [2, 5] {
+            let simulation = EigenvalueSimulation::new(model, dim, 100, 1);
+            let expected = match model {
+                JohansenModel::InterceptNoTrendWithInterceptInCoint
+                | JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend => dim + 1,
+                _ => dim,
+            };
+            assert_eq!(simulation.num_eigenvalues(), expected, "{model}, dim {dim}");
+
+            let eigenvalues = calculate_eigenvalues_with_options(
+                dim,
+                100,
+                1,
+                model,
+                simulation.eigenvalue_options(),
+            );
+            assert_eq!(eigenvalues.len(), expected, "{model}, dim {dim}");
+
+            // 截斷後只保留 K 個
+            assert_eq!(simulation.with_top_k(2).num_eigenvalues(), 2);
+        }
+    }
+}
+
+#[test]
+fn test_invalid_top_k_rejected_when_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 3, 371, 2)
+        .with_data_dir(dir.path());
+    assert!(simulation.clone().with_top_k(3).validate().is_ok());
+
+    // 建構時不 panic，運行前返回錯誤且不建立資料檔案
+    for top_k in [0, 4] {
+        let invalid = simulation.clone().with_top_k(top_k);
+        let error = invalid.try_run_simulation_quiet().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("top-k"), "{error}");
+        assert!(invalid.benchmark().is_err());
+        assert!(
+            invalid
+                .fold_runs(0, |acc, _, _| acc + 1, |a, b| a + b)
+                .is_err()
+        );
+        assert!(!invalid.any_data_exists());
+    }
+
+    // 寫入緩衝區大小同樣在運行前檢查
+    let invalid = simulation.clone().with_write_buffer(512);
+    let error = invalid.try_run_simulation_quiet().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!invalid.any_data_exists());
+}
+
+#[test]
+fn test_simulation_display_summary() {
+    let simulation = EigenvalueSimulation::new(
+        JohansenModel::InterceptNoTrendUnrestrictedIntercept,
+        5,
+        10_000,
+        10_000_000,
+    );
+    assert_eq!(
+        simulation.to_string(),
+        "Model 2, dim=5, steps=10000, runs=10,000,000"
+    );
+
+    // 只摘要模型、維度、步數與次數，其他選項不影響輸出
+    let small = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 1, 50, 999)
+        .with_stream_id(3)
+        .with_timestamps(true);
+    assert_eq!(small.to_string(), "Model 0, dim=1, steps=50, runs=999");
+}
+
+#[test]
+fn test_seed_hashing_is_recorded_in_header() {
+    use crate::data_storage::file_format::FLAG_MIXED_SEEDS;
+    use crate::data_storage::reader::read_file_header;
+    use crate::rng_matrix::SeedHashing;
+
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let mixed = EigenvalueSimulation::new(model, 2, 366, 6).with_data_dir(dir.path());
+    let legacy = mixed.clone().with_seed_hashing(SeedHashing::Legacy);
+
+    // 兩種設定使用相同檔名，由標頭旗標區分
+    assert_eq!(mixed.get_filename(model), legacy.get_filename(model));
+    legacy.run_simulation_quiet();
+    let filename = legacy.get_filename(model);
+    assert_eq!(
+        read_file_header(&filename).unwrap().flags & FLAG_MIXED_SEEDS,
+        0
+    );
+    let legacy_data = legacy.read_data().unwrap();
+
+    // 以預設的混合種子續寫 legacy 檔案會被拒絕，既有資料不會被刪除或混用兩種種子
+    let error = mixed.try_run_simulation_quiet().unwrap_err();
+    assert!(error.to_string().contains("Seed hashing mismatch"));
+    assert!(error.to_string().contains("--seed-hashing legacy"));
+    assert_eq!(legacy.read_data().unwrap(), legacy_data);
+
+    std::fs::remove_file(&filename).unwrap();
+    mixed.run_simulation_quiet();
+    assert_ne!(
+        read_file_header(&filename).unwrap().flags & FLAG_MIXED_SEEDS,
+        0
+    );
+    assert_ne!(mixed.read_data().unwrap(), legacy_data);
+
+    // 非 0 子串流不受設定影響，不設定旗標
+    assert_eq!(
+        mixed.clone().with_stream_id(2).file_header().unwrap(),
+        legacy.clone().with_stream_id(2).file_header().unwrap()
+    );
+}
+
+#[test]
+fn test_eigenvalue_floor_zeroes_tiny_eigenvalues() {
+    use crate::data_storage::file_format::FLAG_EIGENVALUE_FLOOR;
+    use crate::data_storage::reader::read_file_header;
+    use crate::johansen_statistics::apply_eigenvalue_floor;
+
+    // 刻意放入的微小特徵值（含數值雜訊造成的負值）設為 0，其餘不變
+    let mut eigenvalues = [3.25, 0.5, 1e-14, -2e-15];
+    apply_eigenvalue_floor(&mut eigenvalues, 1e-10);
+    assert_eq!(eigenvalues, [3.25, 0.5, 0.0, 0.0]);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let plain = EigenvalueSimulation::new(model, 2, 364, 10);
+    let floored = plain.clone().with_eigenvalue_floor(0.5);
+    let floored_file = floored.get_filename(model);
+    assert!(floored_file.ends_with("eigenvalues_model0_dim2_steps364_floor5e-1.dat"));
+    for simulation in [&plain, &floored] {
+        let _ = std::fs::remove_file(simulation.get_filename(model));
+        simulation.run_simulation_quiet();
+    }
+
+    let header = read_file_header(&floored_file).unwrap();
+    assert_ne!(header.flags & FLAG_EIGENVALUE_FLOOR, 0);
+    assert_eq!(header.eigenvalue_floor, Some(0.5));
+    assert!(header.check_matches(plain.file_header().unwrap()).is_err());
+
+    let sorted = |simulation: &EigenvalueSimulation| {
+        let mut data = simulation.read_data().unwrap();
+        data.sort_unstable_by_key(|(seed, _)| *seed);
+        data
+    };
+    let mut zeroed = 0;
+    for ((_, original), (_, stored)) in sorted(&plain).iter().zip(&sorted(&floored)) {
+        assert_eq!(original.len(), stored.len());
+        for (&original, &stored) in original.iter().zip(stored) {
+            if original.abs() < 0.5 {
+                assert_eq!(stored, 0.0);
+                zeroed += 1;
+            } else {
+                assert_eq!(stored, original);
+            }
+        }
+    }
+    assert!(zeroed > 0);
+
+    for simulation in [&plain, &floored] {
+        let _ = std::fs::remove_file(simulation.get_filename(model));
+    }
+
+    // 下限須為正的有限數：建構時不 panic，運行前返回錯誤
+    for floor in [0.0, -1e-9, f64::NAN, f64::INFINITY] {
+        let invalid = plain.clone().with_eigenvalue_floor(floor);
+        let error = invalid.validate().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("eigenvalue floor"), "{error}");
+        assert!(invalid.file_header().is_err());
+    }
+}
+
+#[test]
+fn test_toggling_timestamps_keeps_existing_file() {
+    use crate::data_storage::file_format::is_header_mismatch;
+    use crate::data_storage::reader::read_file_header;
+
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let plain = EigenvalueSimulation::new(model, 2, 376, 5).with_data_dir(dir.path());
+    let stamped = plain.clone().with_timestamps(true);
+    assert!(
+        stamped
+            .get_filename(model)
+            .ends_with("eigenvalues_model0_dim2_steps376_ts.dat")
+    );
+
+    plain.run_simulation_quiet();
+    let plain_data = plain.read_data_sorted().unwrap();
+
+    // 啟用時間戳記時寫入另一個檔案，不會刪除或重算既有的資料
+    stamped.run_simulation_quiet();
+    assert_eq!(plain.read_data_sorted().unwrap(), plain_data);
+    assert_eq!(stamped.read_data_sorted().unwrap(), plain_data);
+
+    // 記錄格式不符是標頭參數不符
+    let err = read_file_header(plain.get_filename(model))
+        .unwrap()
+        .check_matches(stamped.file_header().unwrap())
+        .unwrap_err();
+    assert!(is_header_mismatch(&err), "{err}");
+    assert!(err.to_string().contains("Timestamp mismatch"), "{err}");
+}
+
+#[test]
+fn test_aggregate_both_round_trip() {
+    use crate::data_storage::file_format::FLAG_AGGREGATES;
+    use crate::data_storage::reader::read_file_header;
+    use crate::johansen_statistics::AggregateMode;
+
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = EigenvalueSimulation::new(model, 4, 367, 6)
+        .with_data_dir(dir.path())
+        .with_aggregate(AggregateMode::Both);
+    let filename = simulation.get_filename(model);
+    assert!(filename.ends_with("eigenvalues_model1_dim4_steps367_agg.dat"));
+    assert_eq!(simulation.num_eigenvalues(), 2);
+
+    simulation.run_simulation_quiet();
+    let header = read_file_header(&filename).unwrap();
+    assert_ne!(header.flags & FLAG_AGGREGATES, 0);
+    assert!(
+        header
+            .check_matches(
+                simulation
+                    .clone()
+                    .with_aggregate(AggregateMode::Full)
+                    .file_header()
+                    .unwrap()
+            )
+            .is_err()
+    );
+
+    // 續傳時沿用相同的記錄格式，每筆記錄都是 [跡, 最大特徵值]
+    let extended = EigenvalueSimulation {
+        num_runs: 10,
+        ..simulation.clone()
+    };
+    extended.run_simulation_quiet();
+    let data = extended.read_data_sorted().unwrap();
+    let seeds: Vec<u32> = data.iter().map(|(seed, _)| *seed).collect();
+    assert_eq!(seeds, (1..=10).collect::<Vec<_>>());
+    for (seed, aggregates) in &data {
+        assert_eq!(aggregates.len(), 2, "seed {seed}");
+        assert!(
+            aggregates[0] >= aggregates[1],
+            "seed {seed}: {aggregates:?}"
+        );
+    }
+    assert_eq!(simulation.read_data_sorted().unwrap(), data[..6].to_vec());
+}
+
+#[test]
+fn test_aggregate_both_matches_full_values() {
+    use crate::johansen_statistics::{AggregateMode, aggregate_both};
+
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let full = EigenvalueSimulation::new(model, 3, 368, 20).with_data_dir(dir.path());
+    let aggregated = full.clone().with_aggregate(AggregateMode::Both);
+    assert_ne!(full.get_filename(model), aggregated.get_filename(model));
+    full.run_simulation_quiet();
+    aggregated.run_simulation_quiet();
+
+    let full_data = full.read_data_sorted().unwrap();
+    let aggregated_data = aggregated.read_data_sorted().unwrap();
+    assert_eq!(full_data.len(), aggregated_data.len());
+    for ((seed, eigenvalues), (aggregated_seed, aggregates)) in
+        full_data.iter().zip(&aggregated_data)
+    {
+        assert_eq!(seed, aggregated_seed);
+        assert_eq!(aggregates, &aggregate_both(eigenvalues), "seed {seed}");
+    }
+
+    // 兩種檔案的跡與最大特徵值分析結果相同
+    let percentiles = [0.5, 0.9, 0.95];
+    assert_eq!(
+        aggregated
+            .calculate_trace_percentiles(&percentiles)
+            .unwrap(),
+        full.calculate_trace_percentiles(&percentiles).unwrap()
+    );
+    assert_eq!(
+        aggregated
+            .calculate_maxeig_percentiles(&percentiles)
+            .unwrap(),
+        full.calculate_maxeig_percentiles(&percentiles).unwrap()
+    );
+    assert_eq!(
+        aggregated
+            .calculate_max_eigen_statistic_percentiles(&percentiles)
+            .unwrap(),
+        full.calculate_max_eigen_statistic_percentiles(&percentiles)
+            .unwrap()
+    );
+    assert_eq!(
+        aggregated.critical_values(&percentiles).unwrap(),
+        full.critical_values(&percentiles).unwrap()
+    );
+
+    // 需要個別特徵值的統計量無法由聚合值計算
+    let error = aggregated
+        .calculate_trace_statistic_percentiles(&percentiles)
+        .unwrap_err();
+    assert!(error.to_string().contains("--aggregate both"));
+}
+
+#[test]
+fn test_master_seed_changes_dataset_reproducibly() {
+    use crate::data_storage::file_format::FLAG_MASTER_SEED;
+    use crate::data_storage::reader::read_file_header;
+
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let plain = EigenvalueSimulation::new(model, 2, 347, 4);
+    let first = plain.clone().with_master_seed(7);
+    let second = plain.clone().with_master_seed(8);
+    let first_file = first.get_filename(model);
+    assert!(first_file.ends_with("eigenvalues_model2_dim2_steps347_master7.dat"));
+    for simulation in [&plain, &first, &second] {
+        let _ = std::fs::remove_file(simulation.get_filename(model));
+        simulation.run_simulation_quiet();
+    }
+
+    let header = read_file_header(&first_file).unwrap();
+    assert_ne!(header.flags & FLAG_MASTER_SEED, 0);
+    assert_eq!(header.master_seed, Some(7));
+    assert!(header.check_matches(second.file_header().unwrap()).is_err());
+    assert!(header.check_matches(plain.file_header().unwrap()).is_err());
+
+    let sorted = |simulation: &EigenvalueSimulation| {
+        let mut data = simulation.read_data().unwrap();
+        data.sort_unstable_by_key(|(seed, _)| *seed);
+        data
+    };
+    let (plain_data, first_data, second_data) = (sorted(&plain), sorted(&first), sorted(&second));
+
+    // 同一 seed 在不同主種子下的每個特徵值都不同
+    for ((seed, a), ((_, b), (_, c))) in first_data.iter().zip(second_data.iter().zip(&plain_data))
+    {
+        assert!(a.iter().zip(b).all(|(x, y)| x != y), "seed {seed}");
+        assert!(a.iter().zip(c).all(|(x, y)| x != y), "seed {seed}");
+    }
+
+    // 同一主種子下每個 seed 可重現
+    let options = first.eigenvalue_options();
+    for (seed, eigenvalues) in &first_data {
+        let expected = crate::johansen_statistics::calculate_eigenvalues_with_options(
+            2, 347, *seed, model, options,
+        );
+        assert_eq!(eigenvalues, &expected);
+    }
+
+    for simulation in [&plain, &first, &second] {
+        let _ = std::fs::remove_file(simulation.get_filename(model));
+    }
+}
+
+#[test]
+fn test_storage_summary_matches_file_length() {
+    use crate::data_storage::reader::read_storage_summary;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 348, 200);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+    simulation.run_simulation_quiet();
+
+    let summary = read_storage_summary(&filename).unwrap();
+    let file_size = std::fs::metadata(&filename).unwrap().len();
+    assert_eq!(summary.file_size, file_size);
+    assert_eq!(summary.records, 200);
+    // seed 為連續的 1..=200 時估計值即為實際大小
+    assert_eq!(summary.expected_size, file_size);
+    assert_eq!(summary.bytes_per_record(), file_size as f64 / 200.0);
+
+    let line = summary.to_string();
+    assert!(line.contains(&format!(
+        "for 200 records ({:.2} bytes/record)",
+        file_size as f64 / 200.0
+    )));
+    assert!(line.ends_with("(+0.00%)"), "{line}");
+    assert!(line.starts_with(&format!(
+        "Storage: {} for",
+        crate::display_utils::format_bytes(file_size)
+    )));
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_steps_above_header_width_rejected() {
+    let max = crate::data_storage::file_format::MAX_STEPS;
+    let simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 2, max, 1);
+    assert_eq!(simulation.file_header().unwrap().steps, u32::MAX);
+
+    // 建構時不 panic，取得標頭或運行前返回錯誤
+    let too_many = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 2, max + 1, 1);
+    let error = too_many.validate().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("must not exceed"), "{error}");
+    assert!(too_many.file_header().is_err());
+}
+
+#[test]
+fn test_dimension_above_record_width_rejected() {
+    use crate::johansen_statistics::AggregateMode;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 255, 10, 1);
+    assert_eq!(simulation.num_eigenvalues(), 255);
+    assert!(simulation.validate().is_ok());
+
+    let too_wide = EigenvalueSimulation::new(model, 260, 10, 1);
+    let err = too_wide.validate().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("eigenvalues per run"), "{err}");
+    assert!(too_wide.file_header().is_err());
+
+    // 依實際儲存的數量檢查：截斷或只存聚合值時不受維度限制
+    assert!(too_wide.clone().with_top_k(5).validate().is_ok());
+    let aggregates = too_wide.with_aggregate(AggregateMode::Both);
+    assert_eq!(aggregates.num_eigenvalues(), 2);
+    assert!(aggregates.validate().is_ok());
+}
+
+#[test]
+fn test_shared_path_run_matches_per_model_runs() {
+    let shared_dir = "data/test_shared_path";
+    let reference_dir = "data/test_shared_path_reference";
+    let _ = std::fs::remove_dir_all(shared_dir);
+    let _ = std::fs::remove_dir_all(reference_dir);
+
+    let models = JohansenModel::all_models();
+    let base = EigenvalueSimulation::new(models[0], 3, 350, 6);
+
+    // 第一個模型已有部分資料：共用路徑模式只補齊缺少的 seed
+    let shared = base.clone().with_data_dir(shared_dir);
+    shared.clone().with_seeds(vec![2, 5]).run_simulation_quiet();
+    shared.try_run_shared_path_quiet(&models).unwrap();
+
+    for model in models {
+        let reference = EigenvalueSimulation::new(model, 3, 350, 6).with_data_dir(reference_dir);
+        reference.run_simulation_quiet();
+
+        let mut expected = reference.read_data().unwrap();
+        let mut actual = EigenvalueSimulation::new(model, 3, 350, 6)
+            .with_data_dir(shared_dir)
+            .read_data()
+            .unwrap();
+        expected.sort_by_key(|(seed, _)| *seed);
+        actual.sort_by_key(|(seed, _)| *seed);
+        assert_eq!(actual, expected, "model {model}");
+    }
+
+    // 全部完成後再次執行不會重複寫入
+    shared.try_run_shared_path_quiet(&models).unwrap();
+    assert_eq!(shared.completed_count().unwrap(), 6);
+
+    let _ = std::fs::remove_dir_all(shared_dir);
+    let _ = std::fs::remove_dir_all(reference_dir);
+}
+
+#[test]
+fn test_read_data_sorted() {
+    let data_dir = "data/test_read_data_sorted";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 351, 40).with_data_dir(data_dir);
+    // 先寫入較大的 seed，使檔案中的記錄順序不是升序
+    simulation
+        .clone()
+        .with_seeds((21..=40).rev().collect())
+        .run_simulation_quiet();
+    simulation.run_simulation_quiet();
+
+    let sorted = simulation.read_data_sorted().unwrap();
+    let seeds: Vec<u32> = sorted.iter().map(|(seed, _)| *seed).collect();
+    assert_eq!(seeds, (1..=40).collect::<Vec<u32>>());
+
+    let mut expected = simulation.read_data().unwrap();
+    assert!(!expected.is_sorted_by_key(|(seed, _)| *seed));
+    expected.sort_by_key(|(seed, _)| *seed);
+    assert_eq!(sorted, expected);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_deadline_leaves_resumable_partial_file() {
+    use crate::data_storage::parallel_compute::ManualClockGuard;
+    use crate::data_storage::reader::read_append_file;
+    use std::time::{Duration, Instant};
+
+    let data_dir = "data/test_deadline";
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 3, 354, 2_000).with_data_dir(data_dir);
+    let filename = simulation.get_filename(model);
+
+    // 截止時間已過：不計算任何 seed，但仍留下可讀取的空檔案
+    simulation
+        .clone()
+        .with_deadline(Instant::now())
+        .run_simulation_quiet();
+    assert_eq!(simulation.completed_count().unwrap(), 0);
+
+    // 很短的時間預算：提早停止，已寫入的記錄完整可讀。
+    // 手動時鐘每次檢查前進 1 ms：批次開始檢查一次，之後每個 seed 檢查一次，
+    // 因此 30 ms 的預算恰好完成 29 個 seed
+    let start = Instant::now();
+    {
+        let _clock = ManualClockGuard::start(start, Duration::from_millis(1));
+        simulation
+            .clone()
+            .with_single_thread(true)
+            .with_deadline(start + Duration::from_millis(30))
+            .run_simulation_quiet();
+    }
+    let partial = simulation.completed_count().unwrap();
+    assert_eq!(partial, 29);
+    let (data, _model, _dim, _steps) = read_append_file(&filename).unwrap();
+    assert_eq!(data.len(), partial);
+
+    // 續傳補齊其餘的 seed
+    simulation.run_simulation_quiet();
+    let seeds: Vec<u32> = simulation
+        .read_data_sorted()
+        .unwrap()
+        .into_iter()
+        .map(|(seed, _)| seed)
+        .collect();
+    assert_eq!(seeds, (1..=2_000).collect::<Vec<u32>>());
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_run_and_analyze() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, 370, 12).with_data_dir(dir.path());
+    assert!(!std::path::Path::new(&simulation.get_filename(model)).exists());
+
+    let percentiles = [0.1, 0.5, 0.9, 0.95];
+    let (trace, maxeig) = simulation.run_and_analyze(&percentiles).unwrap();
+    assert_eq!(trace.len(), percentiles.len());
+    assert_eq!(maxeig.len(), percentiles.len());
+    assert_eq!(simulation.read_data().unwrap().len(), 12);
+
+    let values = |pairs: &[(f64, f64)]| pairs.iter().map(|&(_, value)| value).collect::<Vec<_>>();
+    assert!(trace.iter().map(|&(q, _)| q).eq(percentiles));
+    assert_eq!(
+        values(&trace),
+        simulation
+            .calculate_trace_percentiles(&percentiles)
+            .unwrap()
+    );
+    assert_eq!(
+        values(&maxeig),
+        simulation
+            .calculate_maxeig_percentiles(&percentiles)
+            .unwrap()
+    );
+
+    // 已完成的檔案直接讀取，結果不變
+    assert_eq!(
+        simulation.run_and_analyze(&percentiles).unwrap(),
+        (trace, maxeig)
+    );
+}
@@ -0,0 +1,262 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::sink::{MemorySink, ResultSink, TeeSink, TraceFileSink};
+use crate::data_storage::writer::AppendOnlyWriter;
+use crate::johansen_models::JohansenModel;
+
+#[test]
+fn test_memory_sink_collects_all_runs() {
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 321, 12);
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+
+    let mut results = simulation.run_with_sink(|| Ok(MemorySink::new())).unwrap();
+    results.sort_unstable_by_key(|(seed, _)| *seed);
+
+    // 記憶體接收端不寫入檔案
+    assert!(!simulation.data_exists(model));
+
+    let seeds: Vec<u32> = results.iter().map(|(seed, _)| *seed).collect();
+    assert_eq!(seeds, (1..=12).collect::<Vec<u32>>());
+
+    // 與檔案後端的結果一致
+    simulation.run_simulation_quiet();
+    let mut stored = simulation.read_data().unwrap();
+    stored.sort_unstable_by_key(|(seed, _)| *seed);
+    assert_eq!(results, stored);
+
+    let _ = std::fs::remove_file(simulation.get_filename(model));
+}
+
+#[test]
+fn test_file_writer_as_sink() {
+    let filename = "test_file_writer_as_sink.dat";
+    let _ = std::fs::remove_file(filename);
+
+    let mut sink =
+        AppendOnlyWriter::with_expected_size(filename, None, 0, 2, 100, None, true).unwrap();
+    ResultSink::append(&mut sink, 1, &[1.0, 2.0]).unwrap();
+    ResultSink::finish(sink).unwrap();
+
+    let (data, _model, _dim, _steps) =
+        crate::data_storage::reader::read_append_file(filename).unwrap();
+    assert_eq!(data, vec![(1, vec![1.0, 2.0])]);
+
+    let _ = std::fs::remove_file(filename);
+}
+
+/// 完成時回報所在執行緒名稱的接收端
+struct ThreadNameSink;
+
+impl ResultSink for ThreadNameSink {
+    type Output = Option<String>;
+
+    fn append(&mut self, _seed: u32, _eigenvalues: &[f64]) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<Self::Output> {
+        Ok(std::thread::current().name().map(str::to_string))
+    }
+}
+
+#[test]
+fn test_writer_thread_is_named() {
+    use crate::data_storage::thread_manager::{WRITER_THREAD_NAME, spawn_sink_thread};
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let handle = spawn_sink_thread(|| Ok(ThreadNameSink), 1, 0, true, receiver);
+    assert_eq!(handle.thread().name(), Some(WRITER_THREAD_NAME));
+
+    sender.send((1, vec![1.0])).unwrap();
+    drop(sender);
+    let name = handle.join().unwrap().unwrap();
+    assert_eq!(name.as_deref(), Some(WRITER_THREAD_NAME));
+}
+
+/// 可在寫入執行緒結束後取回內容的輸出緩衝區，模擬標準輸出
+#[derive(Clone, Default)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_stream_to_writes_readable_dat_bytes() {
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = EigenvalueSimulation::new(model, 2, 362, 15);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    let buffer = SharedBuffer::default();
+    simulation.stream_to(buffer.clone()).unwrap();
+    let bytes = buffer.0.lock().unwrap().clone();
+
+    // 串流模式不寫入 data/ 目錄
+    assert!(!simulation.data_exists(model));
+
+    let temp_path = "test_stream_to_stdout_bytes.dat";
+    std::fs::write(temp_path, &bytes).unwrap();
+    let (mut streamed, model_number, dim, steps) =
+        crate::data_storage::reader::read_append_file(temp_path).unwrap();
+    assert_eq!((model_number, dim, steps), (model.to_number(), 2, 362));
+    streamed.sort_unstable_by_key(|(seed, _)| *seed);
+
+    // 與檔案後端寫入的記錄相同
+    simulation.run_simulation_quiet();
+    let mut stored = simulation.read_data().unwrap();
+    stored.sort_unstable_by_key(|(seed, _)| *seed);
+    assert_eq!(streamed.len(), 15);
+    assert_eq!(streamed, stored);
+
+    let _ = std::fs::remove_file(temp_path);
+    let _ = std::fs::remove_file(&filename);
+}
+
+/// 讀取跡檔案的 `(seed, trace)` 資料列（依 seed 排序）
+fn read_trace_rows(path: &str) -> Vec<(u32, f64)> {
+    let content = std::fs::read_to_string(path).unwrap();
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("seed,trace"));
+    let mut traces: Vec<(u32, f64)> = lines
+        .map(|line| {
+            let (seed, trace) = line.split_once(',').unwrap();
+            (seed.parse().unwrap(), trace.parse().unwrap())
+        })
+        .collect();
+    traces.sort_unstable_by_key(|(seed, _)| *seed);
+    traces
+}
+
+/// 資料檔案中每筆記錄的 `(seed, 特徵值總和)`（依 seed 排序）
+fn expected_trace_rows(simulation: &EigenvalueSimulation) -> Vec<(u32, f64)> {
+    let mut data = simulation.read_data().unwrap();
+    data.sort_unstable_by_key(|(seed, _)| *seed);
+    data.iter()
+        .map(|(seed, eigenvalues)| (*seed, eigenvalues.iter().sum()))
+        .collect()
+}
+
+#[test]
+fn test_trace_file_matches_dat_eigenvalue_sums() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let simulation = |num_runs| {
+        EigenvalueSimulation::new(model, 3, 365, num_runs)
+            .with_data_dir(dir.path())
+            .with_trace_file(true)
+    };
+
+    // 先計算 20 次再續傳至 30 次，跡檔案只追加新的 seed
+    simulation(20).run_simulation_quiet();
+    simulation(30).run_simulation_quiet();
+
+    let trace_filename = simulation(30).trace_filename().unwrap();
+    assert!(trace_filename.ends_with(".trace"));
+    let traces = read_trace_rows(&trace_filename);
+    assert_eq!(traces.len(), 30);
+    assert_eq!(traces, expected_trace_rows(&simulation(30)));
+}
+
+#[test]
+fn test_trace_file_rejected_for_aggregate_files() {
+    use crate::johansen_statistics::AggregateMode;
+
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 380, 3)
+        .with_data_dir(dir.path())
+        .with_aggregate(AggregateMode::Both)
+        .with_trace_file(true);
+
+    let error = simulation.validate().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("trace file"), "{error}");
+
+    // 運行前返回錯誤，不建立資料檔案與跡檔案
+    assert!(simulation.try_run_simulation_quiet().is_err());
+    assert!(!simulation.data_exists(model));
+    assert!(!std::path::Path::new(&simulation.trace_filename().unwrap()).exists());
+}
+
+#[test]
+fn test_trace_file_reconciled_with_dat_on_resume() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation =
+        |num_runs| EigenvalueSimulation::new(model, 2, 366, num_runs).with_data_dir(dir.path());
+
+    // 對既有的資料檔案才啟用跡檔案：先前的 20 筆記錄從資料檔案補齊
+    simulation(20).run_simulation_quiet();
+    simulation(25).with_trace_file(true).run_simulation_quiet();
+    let trace_filename = simulation(25).trace_filename().unwrap();
+    assert_eq!(
+        read_trace_rows(&trace_filename),
+        expected_trace_rows(&simulation(25))
+    );
+
+    // 模擬中斷：跡檔案只剩前 10 列與一行不完整的資料
+    let content = std::fs::read_to_string(&trace_filename).unwrap();
+    let mut truncated: String = content
+        .lines()
+        .take(11)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    truncated.push_str("12,0.5");
+    std::fs::write(&trace_filename, truncated).unwrap();
+
+    simulation(30).with_trace_file(true).run_simulation_quiet();
+    let traces = read_trace_rows(&trace_filename);
+    assert_eq!(traces.len(), 30);
+    assert_eq!(traces, expected_trace_rows(&simulation(30)));
+}
+
+#[test]
+fn test_trace_file_from_other_data_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = |num_runs| {
+        EigenvalueSimulation::new(model, 2, 367, num_runs)
+            .with_data_dir(dir.path())
+            .with_trace_file(true)
+    };
+
+    simulation(10).run_simulation_quiet();
+    let trace_filename = simulation(10).trace_filename().unwrap();
+    std::fs::write(&trace_filename, "seed,trace\n999,1.5\n").unwrap();
+
+    let err = simulation(15).try_run_simulation_quiet().unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{err}");
+    // 資料檔案未被改動
+    assert_eq!(simulation(15).read_all_data().unwrap().len(), 10);
+}
+
+#[test]
+fn test_tee_flushes_trace_at_data_checkpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_path = dir.path().join("checkpoint.dat");
+    let trace_path = dir.path().join("checkpoint.trace");
+    let header = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 2, 368, 5)
+        .file_header()
+        .unwrap();
+
+    let writer = AppendOnlyWriter::with_header(&data_path, None, header, None, true)
+        .unwrap()
+        .with_checkpoint_every(Some(std::time::Duration::ZERO));
+    let trace = TraceFileSink::append_to(&trace_path, &data_path).unwrap();
+    let mut tee = TeeSink::new(writer, trace);
+    tee.append(1, &[0.25, 0.5]).unwrap();
+    tee.append(2, &[0.125, 0.75]).unwrap();
+
+    // 資料檔案每筆記錄都到達檢查點，跡檔案在完成前也已寫出
+    let content = std::fs::read_to_string(&trace_path).unwrap();
+    assert_eq!(content, "seed,trace\n1,0.75\n2,0.875\n");
+    tee.finish().unwrap();
+}
@@ -1,4 +1,4 @@
-use crate::data_storage::uleb128::{Uleb128Error, decode, encode, encoded_size};
+use crate::data_storage::uleb128::{Uleb128Error, decode, decode_all, encode, encoded_size};
 
 #[test]
 fn test_encode_basic_values() {
@@ -87,3 +87,29 @@ fn test_error_cases() {
     let too_long = vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x80];
     assert_eq!(decode(&too_long), Err(Uleb128Error::EncodingTooLong));
 }
+
+#[test]
+fn test_decode_all_multiple_values() {
+    let values = [0, 1, 127, 128, 300, 16384, u32::MAX];
+    let bytes: Vec<u8> = values.iter().flat_map(|&value| encode(value)).collect();
+    assert_eq!(decode_all(&bytes).unwrap(), values);
+}
+
+#[test]
+fn test_decode_all_empty_buffer() {
+    assert_eq!(decode_all(&[]).unwrap(), Vec::<u32>::new());
+}
+
+#[test]
+fn test_decode_all_trailing_partial_value() {
+    // 300 的編碼只剩第一個位元組
+    assert_eq!(
+        decode_all(&[0x01, 0x7F, 0xAC]),
+        Err(Uleb128Error::IncompleteEncoding)
+    );
+    // 中途的錯誤也會返回
+    assert_eq!(
+        decode_all(&[0x01, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80]),
+        Err(Uleb128Error::EncodingTooLong)
+    );
+}
@@ -1,6 +1,6 @@
 use crate::display_utils::{
-    format_duration, format_number_with_commas, format_percentage, format_progress_bar,
-    format_remaining_time,
+    DimensionTimings, estimate_remaining, format_bytes, format_duration, format_number_with_commas,
+    format_percentage, format_progress_bar, format_resumed_remaining_time,
 };
 use std::time::Duration;
 
@@ -29,24 +29,79 @@ fn test_format_number_with_commas() {
     assert_eq!(format_number_with_commas(1000000), "1,000,000");
 }
 
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1024), "1.00 KB");
+    assert_eq!(format_bytes(1536), "1.50 KB");
+    assert_eq!(format_bytes(1024 * 1024 - 1), "1.00 MB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.50 MB");
+    assert_eq!(format_bytes(1024 * 1024 * 1024), "1.00 GB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.50 GB");
+    assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.00 GB");
+    assert_eq!(format_bytes(1536 * 1024 * 1024 * 1024), "1.50 TB");
+    assert_eq!(format_bytes(u64::MAX), "16.00 EB");
+}
+
 #[test]
 fn test_format_remaining_time() {
-    // 測試正常情況
+    // 測試正常情況（沒有先前執行完成的部分）
     let elapsed = Duration::from_secs(60);
-    let remaining = format_remaining_time(elapsed, 100, 1000);
+    let remaining = format_resumed_remaining_time(elapsed, 100, 0, 1000);
     assert!(remaining.contains("estimated remaining"));
 
     // 測試邊界情況
     assert_eq!(
-        format_remaining_time(Duration::from_secs(60), 0, 1000),
+        format_resumed_remaining_time(Duration::from_secs(60), 0, 0, 1000),
         "unknown"
     );
     assert_eq!(
-        format_remaining_time(Duration::from_secs(60), 1000, 1000),
+        format_resumed_remaining_time(Duration::from_secs(60), 1000, 0, 1000),
         "unknown"
     );
 }
 
+#[test]
+fn test_estimate_remaining_after_resume() {
+    // 先前已完成 9,900 筆，本次 60 秒完成 50 筆：剩餘 50 筆約需 60 秒
+    let eta = estimate_remaining(Duration::from_secs(60), 50, 9_900, 10_000).unwrap();
+    assert!((eta.as_secs_f64() - 60.0).abs() < 1e-9);
+
+    // 與從頭計算相同的速率與剩餘量得到相同結果
+    let fresh = estimate_remaining(Duration::from_secs(60), 50, 0, 100).unwrap();
+    assert_eq!(eta, fresh);
+
+    // 先前完成的部分不影響速率，只減少剩餘量
+    let early = estimate_remaining(Duration::from_secs(60), 50, 0, 10_000).unwrap();
+    assert!((early.as_secs_f64() - 11_940.0).abs() < 1e-6);
+
+    assert_eq!(
+        estimate_remaining(Duration::from_secs(60), 0, 9_900, 10_000),
+        None
+    );
+    assert_eq!(
+        estimate_remaining(Duration::from_secs(60), 100, 9_900, 10_000),
+        None
+    );
+    assert_eq!(
+        estimate_remaining(Duration::from_secs(60), 150, 9_900, 10_000),
+        None
+    );
+
+    assert_eq!(
+        format_resumed_remaining_time(Duration::from_secs(60), 50, 9_900, 10_000),
+        format!(
+            "estimated remaining: {}",
+            format_duration(Duration::from_secs(60))
+        )
+    );
+    assert_eq!(
+        format_resumed_remaining_time(Duration::ZERO, 50, 9_900, 10_000),
+        "completing soon"
+    );
+}
+
 #[test]
 fn test_format_percentage() {
     assert_eq!(format_percentage(25, 100, Some(1)), "25.0%");
@@ -66,3 +121,34 @@ fn test_format_progress_bar() {
     let bar = format_progress_bar(100, 100, 10);
     assert!(bar.contains("[##########] 100.0%"));
 }
+
+#[test]
+fn test_dimension_timings_summary() {
+    let mut timings = DimensionTimings::new();
+    assert!(timings.is_empty());
+    assert_eq!(timings.fastest(), None);
+
+    timings.record(2, Duration::from_secs(30));
+    timings.record(3, Duration::from_secs(10));
+    timings.record(4, Duration::from_secs(80));
+
+    assert_eq!(timings.len(), 3);
+    assert_eq!(timings.total(), Duration::from_secs(120));
+    assert_eq!(timings.fastest(), Some((3, Duration::from_secs(10))));
+    assert_eq!(timings.slowest(), Some((4, Duration::from_secs(80))));
+
+    let summary = timings.format_summary();
+    assert!(
+        summary.contains("Fastest: dim 3 (10.00 seconds)"),
+        "{summary}"
+    );
+    assert!(
+        summary.contains("Slowest: dim 4 (1 minute 20.00 seconds)"),
+        "{summary}"
+    );
+    assert!(
+        summary.contains("Total: 2 minutes 0.00 seconds"),
+        "{summary}"
+    );
+    assert!(summary.contains("  2  30.00 seconds"), "{summary}");
+}
@@ -0,0 +1,53 @@
+use crate::eigenvalue_cache::{EigenvalueCache, cache_stats, clear_cache};
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::{
+    EigenvalueOptions, calculate_eigenvalues, calculate_eigenvalues_with_options,
+};
+
+#[test]
+fn test_repeated_call_hits_cache() {
+    let model = JohansenModel::InterceptTrendUnrestrictedBoth;
+    clear_cache();
+
+    let before = cache_stats();
+    let first = calculate_eigenvalues(3, 327, 11, model);
+    let second = calculate_eigenvalues(3, 327, 11, model);
+    let after = cache_stats();
+
+    assert_eq!(first, second);
+    assert!(after.hits > before.hits);
+    assert!(after.misses > before.misses);
+
+    // 快取結果與直接計算相同
+    let direct =
+        calculate_eigenvalues_with_options(3, 327, 11, model, EigenvalueOptions::default());
+    assert_eq!(second, direct);
+}
+
+#[test]
+fn test_lru_evicts_least_recently_used() {
+    let mut cache = EigenvalueCache::new(2);
+    cache.insert((1, 10, 1, 0), vec![1.0]);
+    cache.insert((1, 10, 2, 0), vec![2.0]);
+
+    // 存取第一筆後，第二筆成為最久未使用
+    assert_eq!(cache.get(&(1, 10, 1, 0)), Some(vec![1.0]));
+    cache.insert((1, 10, 3, 0), vec![3.0]);
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&(1, 10, 2, 0)), None);
+    assert_eq!(cache.get(&(1, 10, 1, 0)), Some(vec![1.0]));
+    assert_eq!(cache.get(&(1, 10, 3, 0)), Some(vec![3.0]));
+
+    cache.set_capacity(1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&(1, 10, 3, 0)), Some(vec![3.0]));
+
+    cache.clear();
+    assert!(cache.is_empty());
+
+    // 容量為 0 時不保存任何結果
+    let mut disabled = EigenvalueCache::new(0);
+    disabled.insert((1, 10, 1, 0), vec![1.0]);
+    assert!(disabled.is_empty());
+}
@@ -1,4 +1,4 @@
-use crate::johansen_models::JohansenModel;
+use crate::johansen_models::{JohansenModel, ModelError};
 
 #[test]
 fn test_model_numbers() {
@@ -11,7 +11,10 @@ fn test_model_numbers() {
         JohansenModel::InterceptNoTrendUnrestrictedIntercept.to_number(),
         2
     );
-    assert_eq!(JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend.to_number(), 3);
+    assert_eq!(
+        JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend.to_number(),
+        3
+    );
     assert_eq!(JohansenModel::InterceptTrendUnrestrictedBoth.to_number(), 4);
 }
 
@@ -41,6 +44,26 @@ fn test_from_number() {
     assert_eq!(JohansenModel::from_number(255), None);
 }
 
+#[test]
+fn test_try_from_u8() {
+    for model in JohansenModel::all_models() {
+        assert_eq!(JohansenModel::try_from(model.to_number()), Ok(model));
+    }
+
+    assert_eq!(
+        JohansenModel::try_from(5u8),
+        Err(ModelError::InvalidNumber(5))
+    );
+    assert_eq!(
+        JohansenModel::try_from(255u8),
+        Err(ModelError::InvalidNumber(255))
+    );
+    assert_eq!(
+        ModelError::InvalidNumber(5).to_string(),
+        "invalid model number: 5 (expected 0-4)"
+    );
+}
+
 #[test]
 fn test_model_descriptions() {
     assert_eq!(
@@ -95,7 +118,8 @@ fn test_intercept_fully_explained_by_cointegration() {
             .intercept_fully_explained_by_cointegration()
     );
     assert!(
-        !JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend.intercept_fully_explained_by_cointegration()
+        !JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend
+            .intercept_fully_explained_by_cointegration()
     );
     assert!(
         !JohansenModel::InterceptTrendUnrestrictedBoth.intercept_fully_explained_by_cointegration()
@@ -110,10 +134,33 @@ fn test_trend_fully_explained_by_cointegration() {
             .trend_fully_explained_by_cointegration()
     );
     assert!(
-        !JohansenModel::InterceptNoTrendUnrestrictedIntercept.trend_fully_explained_by_cointegration()
+        !JohansenModel::InterceptNoTrendUnrestrictedIntercept
+            .trend_fully_explained_by_cointegration()
     );
-    assert!(JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend.trend_fully_explained_by_cointegration());
-    assert!(!JohansenModel::InterceptTrendUnrestrictedBoth.trend_fully_explained_by_cointegration());
+    assert!(
+        JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend
+            .trend_fully_explained_by_cointegration()
+    );
+    assert!(
+        !JohansenModel::InterceptTrendUnrestrictedBoth.trend_fully_explained_by_cointegration()
+    );
+}
+
+#[test]
+fn test_model_number_helpers() {
+    assert_eq!(JohansenModel::all_model_numbers(), [0, 1, 2, 3, 4]);
+    for (model, number) in JohansenModel::all_models()
+        .into_iter()
+        .zip(JohansenModel::all_model_numbers())
+    {
+        assert_eq!(model.to_number(), number);
+        assert!(JohansenModel::is_valid_number(number));
+    }
+
+    // 邊界值：最後一個模型之後即無效
+    assert!(JohansenModel::is_valid_number(4));
+    assert!(!JohansenModel::is_valid_number(5));
+    assert!(!JohansenModel::is_valid_number(u8::MAX));
 }
 
 #[test]
@@ -129,7 +176,10 @@ fn test_all_models() {
         all_models[2],
         JohansenModel::InterceptNoTrendUnrestrictedIntercept
     );
-    assert_eq!(all_models[3], JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend);
+    assert_eq!(
+        all_models[3],
+        JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend
+    );
     assert_eq!(all_models[4], JohansenModel::InterceptTrendUnrestrictedBoth);
 }
 
@@ -218,3 +268,25 @@ fn test_comprehensive_model_properties() {
     assert!(!model4.intercept_fully_explained_by_cointegration());
     assert!(!model4.trend_fully_explained_by_cointegration());
 }
+
+#[test]
+fn test_model_ordering() {
+    let mut models = vec![
+        JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend,
+        JohansenModel::NoInterceptNoTrend,
+        JohansenModel::InterceptTrendUnrestrictedBoth,
+        JohansenModel::InterceptNoTrendUnrestrictedIntercept,
+        JohansenModel::InterceptNoTrendWithInterceptInCoint,
+    ];
+    models.sort();
+
+    let numbers: Vec<u8> = models.iter().map(|m| m.to_number()).collect();
+    assert_eq!(numbers, vec![0, 1, 2, 3, 4]);
+    assert_eq!(models, JohansenModel::all_models().to_vec());
+
+    // 可作為 BTreeMap 的鍵，迭代順序即模型編號順序
+    let map: std::collections::BTreeMap<JohansenModel, u8> =
+        models.iter().rev().map(|&m| (m, m.to_number())).collect();
+    let ordered: Vec<u8> = map.values().copied().collect();
+    assert_eq!(ordered, vec![0, 1, 2, 3, 4]);
+}
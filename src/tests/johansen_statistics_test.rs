@@ -0,0 +1,450 @@
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::{
+    EigenvalueOptions, MatrixShapeError, SolveDiagnostics, SortOrder, calculate_eigenvalues,
+    calculate_eigenvalues_from_matrices, calculate_eigenvalues_shared_path,
+    calculate_eigenvalues_unpooled, calculate_eigenvalues_with_diagnostics,
+    calculate_eigenvalues_with_dt, calculate_eigenvalues_with_options, max_eigen_statistic,
+    trace_statistic,
+};
+use crate::matrix_utils::reciprocal_condition;
+use nalgebra::DMatrix;
+
+fn with_order(sort_order: SortOrder) -> EigenvalueOptions {
+    EigenvalueOptions {
+        sort_order,
+        ..EigenvalueOptions::default()
+    }
+}
+
+fn on_stream(stream_id: u32) -> EigenvalueOptions {
+    EigenvalueOptions {
+        stream_id,
+        ..EigenvalueOptions::default()
+    }
+}
+
+#[test]
+fn test_sort_order_same_multiset() {
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let desc =
+        calculate_eigenvalues_with_options(3, 100, 7, model, with_order(SortOrder::Descending));
+    let asc =
+        calculate_eigenvalues_with_options(3, 100, 7, model, with_order(SortOrder::Ascending));
+
+    // 兩種順序互為反轉，即包含相同的特徵值
+    let mut reversed = asc.clone();
+    reversed.reverse();
+    assert_eq!(desc, reversed);
+
+    // 降序的第一個元素為最大值，升序的第一個元素為最小值
+    let max = desc.iter().cloned().fold(f64::MIN, f64::max);
+    let min = desc.iter().cloned().fold(f64::MAX, f64::min);
+    assert_eq!(desc[0], max);
+    assert_eq!(asc[0], min);
+    assert_ne!(desc[0], asc[0]);
+}
+
+#[test]
+fn test_sort_order_from_str() {
+    assert_eq!("asc".parse::<SortOrder>(), Ok(SortOrder::Ascending));
+    assert_eq!("DESC".parse::<SortOrder>(), Ok(SortOrder::Descending));
+    assert!("up".parse::<SortOrder>().is_err());
+    assert_eq!(SortOrder::default(), SortOrder::Descending);
+}
+
+#[test]
+fn test_stream_id_independent_and_reproducible() {
+    let model = JohansenModel::NoInterceptNoTrend;
+
+    // 串流 0 與未指定串流的結果一致，維持既有資料的可重現性
+    assert_eq!(
+        calculate_eigenvalues_with_options(2, 100, 11, model, on_stream(0)),
+        calculate_eigenvalues(2, 100, 11, model)
+    );
+
+    let stream1 = calculate_eigenvalues_with_options(2, 100, 11, model, on_stream(1));
+    let stream2 = calculate_eigenvalues_with_options(2, 100, 11, model, on_stream(2));
+
+    // 相同 seed 在不同串流得到不同結果
+    assert_ne!(stream1, stream2);
+
+    // 每個串流各自可重現
+    assert_eq!(
+        stream1,
+        calculate_eigenvalues_with_options(2, 100, 11, model, on_stream(1))
+    );
+    assert_eq!(
+        stream2,
+        calculate_eigenvalues_with_options(2, 100, 11, model, on_stream(2))
+    );
+}
+
+#[test]
+fn test_delta_t_default_and_scaling() {
+    let steps = 200;
+
+    // 預設等同於明確指定 delta_t = 1 / steps
+    for model in JohansenModel::all_models() {
+        assert_eq!(
+            calculate_eigenvalues(2, steps, 5, model),
+            calculate_eigenvalues_with_dt(2, steps, 5, model, 1.0 / steps as f64)
+        );
+    }
+
+    // 增量與 B 矩陣同以 delta_t 縮放，減半 delta_t 只改變數值尺度，特徵值不變
+    for model in JohansenModel::all_models() {
+        let full = calculate_eigenvalues_with_dt(2, steps, 5, model, 1.0 / steps as f64);
+        let half = calculate_eigenvalues_with_dt(2, steps, 5, model, 0.5 / steps as f64);
+        assert_eq!(full.len(), half.len());
+        for (a, b) in full.iter().zip(&half) {
+            assert!(
+                (a - b).abs() <= 1e-8 * a.abs().max(1.0),
+                "{model}: {full:?} vs {half:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_top_k_keeps_largest_eigenvalues() {
+    // 模型 1 有 dim + 1 個特徵值
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let full = calculate_eigenvalues(4, 100, 11, model);
+    assert_eq!(full.len(), 5);
+
+    for sort_order in [SortOrder::Descending, SortOrder::Ascending] {
+        let options = EigenvalueOptions {
+            sort_order,
+            top_k: Some(2),
+            ..EigenvalueOptions::default()
+        };
+        let top = calculate_eigenvalues_with_options(4, 100, 11, model, options);
+        assert_eq!(top.len(), 2);
+
+        // 截斷結果為完整結果中最大的 K 個，並依指定順序排列
+        let mut expected = full[..2].to_vec();
+        sort_order.sort(&mut expected);
+        assert_eq!(top, expected);
+    }
+}
+
+#[test]
+fn test_condition_guard_flags_ill_conditioned_solve() {
+    let model = JohansenModel::NoInterceptNoTrend;
+    let options = EigenvalueOptions::default();
+    let min_condition = 1e-10;
+
+    // 步數少於維度時 ∫F F' dt 的秩不足，右側矩陣奇異
+    let (_, diagnostics) = calculate_eigenvalues_with_diagnostics(4, 2, 1, model, options);
+    assert!(diagnostics.reciprocal_condition < min_condition);
+    assert!(diagnostics.is_ill_conditioned(min_condition));
+
+    // 正常步數下不觸發，且特徵值與不診斷時相同
+    let (eigenvalues, diagnostics) =
+        calculate_eigenvalues_with_diagnostics(2, 500, 1, model, options);
+    assert!(!diagnostics.is_ill_conditioned(min_condition));
+    assert_eq!(
+        eigenvalues,
+        calculate_eigenvalues_with_options(2, 500, 1, model, options)
+    );
+
+    // 兩列共線的矩陣
+    let collinear = DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+    assert!(reciprocal_condition(&collinear) < 1e-12);
+    assert_eq!(reciprocal_condition(&DMatrix::<f64>::identity(3, 3)), 1.0);
+}
+
+#[test]
+fn test_well_conditioned_solve_has_no_complex_eigenvalues() {
+    let options = EigenvalueOptions {
+        diagnose_complex: true,
+        ..EigenvalueOptions::default()
+    };
+    assert!(options.diagnoses());
+
+    for model in JohansenModel::all_models() {
+        for seed in 1..=5 {
+            let (eigenvalues, diagnostics) =
+                calculate_eigenvalues_with_diagnostics(3, 500, seed, model, options);
+            assert_eq!(
+                diagnostics.complex_eigenvalues, 0,
+                "model {model}, seed {seed}"
+            );
+            assert!(!options.flags(diagnostics));
+            assert_eq!(
+                eigenvalues,
+                calculate_eigenvalues_with_options(3, 500, seed, model, options)
+            );
+        }
+    }
+
+    // 只有啟用的檢查會標記 seed
+    let complex = SolveDiagnostics {
+        reciprocal_condition: 0.5,
+        complex_eigenvalues: 2,
+    };
+    assert!(complex.has_complex_eigenvalues());
+    assert!(options.flags(complex));
+    assert!(!EigenvalueOptions::default().flags(complex));
+    assert!(!EigenvalueOptions::default().diagnoses());
+}
+
+#[test]
+fn test_likelihood_ratio_statistics() {
+    // T = 10：λ = ρ / T 分別為 0.5、0.1、0.2（儲存順序不影響結果）
+    let eigenvalues = [5.0, 1.0, 2.0];
+    let t = 10.0;
+
+    let expected_trace = -t * (0.5f64.ln() + 0.9f64.ln() + 0.8f64.ln());
+    assert!((trace_statistic(&eigenvalues, 10, 0) - expected_trace).abs() < 1e-12);
+    let expected_trace_r1 = -t * (0.8f64.ln() + 0.9f64.ln());
+    assert!((trace_statistic(&eigenvalues, 10, 1) - expected_trace_r1).abs() < 1e-12);
+
+    assert!((max_eigen_statistic(&eigenvalues, 10, 0) - -t * 0.5f64.ln()).abs() < 1e-12);
+    assert!((max_eigen_statistic(&eigenvalues, 10, 1) - -t * 0.8f64.ln()).abs() < 1e-12);
+    assert!(max_eigen_statistic(&eigenvalues, 10, 3).is_nan());
+
+    // T 很大時轉換後的統計量趨近原始聚合
+    let raw_sum: f64 = eigenvalues.iter().sum();
+    assert!((trace_statistic(&eigenvalues, 1_000_000_000, 0) - raw_sum).abs() < 1e-6);
+    assert!((max_eigen_statistic(&eigenvalues, 1_000_000_000, 0) - 5.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_warmup_discards_initial_steps_deterministically() {
+    let model = JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend;
+    let with_warmup = |warmup: usize| EigenvalueOptions {
+        warmup,
+        ..EigenvalueOptions::default()
+    };
+
+    // W = 0 與原本的計算完全相同
+    let baseline = calculate_eigenvalues(3, 200, 17, model);
+    assert_eq!(
+        calculate_eigenvalues_with_options(3, 200, 17, model, with_warmup(0)),
+        baseline
+    );
+
+    // W > 0 改變結果，但相同參數的結果可重現
+    let burned = calculate_eigenvalues_with_options(3, 200, 17, model, with_warmup(50));
+    assert_eq!(burned.len(), baseline.len());
+    assert_ne!(burned, baseline);
+    assert_eq!(
+        calculate_eigenvalues_with_options(3, 200, 17, model, with_warmup(50)),
+        burned
+    );
+    assert_ne!(
+        calculate_eigenvalues_with_options(3, 200, 17, model, with_warmup(51)),
+        burned
+    );
+}
+
+#[test]
+fn test_pooled_buffers_match_unpooled() {
+    let options = [
+        EigenvalueOptions::default(),
+        EigenvalueOptions {
+            warmup: 20,
+            top_k: Some(1),
+            sort_order: SortOrder::Ascending,
+            ..EigenvalueOptions::default()
+        },
+    ];
+
+    // 同一執行緒上交錯不同維度與模型，緩衝區須隨形狀重新配置且不殘留上一次的內容
+    for dim in [3, 1, 4, 3] {
+        for model in JohansenModel::all_models() {
+            for options in options {
+                for seed in 1..=3 {
+                    let pooled = calculate_eigenvalues_with_options(dim, 200, seed, model, options);
+                    let unpooled = calculate_eigenvalues_unpooled(dim, 200, seed, model, options);
+                    assert_eq!(
+                        pooled, unpooled,
+                        "model {model}, dim {dim}, seed {seed}, options {options:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shared_path_matches_per_model() {
+    let models = JohansenModel::all_models();
+    let options = EigenvalueOptions {
+        warmup: 20,
+        top_k: Some(2),
+        stream_id: 3,
+        ..EigenvalueOptions::default()
+    };
+
+    for seed in 1..=3 {
+        let shared = calculate_eigenvalues_shared_path(3, 200, seed, &models, options, true);
+        assert_eq!(shared.len(), models.len());
+        for (&model, (eigenvalues, diagnostics)) in models.iter().zip(shared) {
+            let (expected, expected_diagnostics) =
+                calculate_eigenvalues_with_diagnostics(3, 200, seed, model, options);
+            assert_eq!(eigenvalues, expected, "model {model}, seed {seed}");
+            assert_eq!(diagnostics, Some(expected_diagnostics));
+        }
+    }
+
+    // 只選部分模型時順序依輸入
+    let subset = [models[4], models[1]];
+    let shared = calculate_eigenvalues_shared_path(2, 150, 9, &subset, options, false);
+    for (&model, (eigenvalues, diagnostics)) in subset.iter().zip(shared) {
+        assert_eq!(
+            eigenvalues,
+            calculate_eigenvalues_with_options(2, 150, 9, model, options)
+        );
+        assert_eq!(diagnostics, None);
+    }
+}
+
+/// 效能比較：`cargo test --release --features bench -- --nocapture bench_`
+#[cfg(feature = "bench")]
+#[test]
+fn bench_pooled_buffers() {
+    use std::time::Instant;
+
+    let model = JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend;
+    let options = EigenvalueOptions::default();
+    let runs = 200;
+
+    let start = Instant::now();
+    for seed in 1..=runs {
+        std::hint::black_box(calculate_eigenvalues_unpooled(
+            4, 10_000, seed, model, options,
+        ));
+    }
+    let unpooled = start.elapsed();
+
+    let start = Instant::now();
+    for seed in 1..=runs {
+        std::hint::black_box(calculate_eigenvalues_with_options(
+            4, 10_000, seed, model, options,
+        ));
+    }
+    let pooled = start.elapsed();
+
+    println!("unpooled: {unpooled:?}, pooled: {pooled:?} ({runs} runs)");
+}
+
+#[test]
+fn test_eigenvalues_independent_of_thread_count() {
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    // 3 x 10,000 的增量矩陣會切成多個 chunk；直接呼叫 with_options 以避開 cache feature
+    let compute = |threads: usize| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| {
+                calculate_eigenvalues_with_options(
+                    3,
+                    10_000,
+                    29,
+                    model,
+                    EigenvalueOptions::default(),
+                )
+            })
+    };
+
+    let single = compute(1);
+    assert_eq!(compute(2), single);
+    assert_eq!(compute(4), single);
+}
+
+#[test]
+fn test_cholesky_solver_known_eigenvalues() {
+    use crate::johansen_statistics::generalized_eigenvalues_cholesky;
+
+    // A = diag(2, 6)、B = diag(1, 2) 的廣義特徵值為 2 與 3
+    let a = DMatrix::from_diagonal(&nalgebra::DVector::from_vec(vec![2.0, 6.0]));
+    let b = DMatrix::from_diagonal(&nalgebra::DVector::from_vec(vec![1.0, 2.0]));
+    let mut values = generalized_eigenvalues_cholesky(a.clone(), b);
+    values.sort_by(f64::total_cmp);
+    assert!((values[0] - 2.0).abs() < 1e-12 && (values[1] - 3.0).abs() < 1e-12);
+
+    // 奇異的右側矩陣無法分解，返回 NaN
+    let singular = DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 1.0, 1.0]);
+    assert!(
+        generalized_eigenvalues_cholesky(a, singular)
+            .iter()
+            .all(|value| value.is_nan())
+    );
+}
+
+#[cfg(feature = "lapack")]
+#[test]
+fn test_cholesky_solver_matches_lapack() {
+    use crate::johansen_statistics::{
+        generalized_eigenvalues_cholesky, generalized_eigenvalues_lapack,
+    };
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, StandardNormal};
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    // 與模擬相同的結構：A = X'X 對稱半正定，B = Y'Y 對稱正定
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(5);
+    let mut random = |nrows, ncols| {
+        DMatrix::<f64>::from_fn(nrows, ncols, |_, _| StandardNormal.sample(&mut rng))
+    };
+    let x = random(4, 4);
+    let y = random(20, 4);
+    let a = x.transpose() * &x;
+    let b = y.transpose() * &y;
+
+    let mut lapack = generalized_eigenvalues_lapack(a.clone(), b.clone());
+    let mut cholesky = generalized_eigenvalues_cholesky(a, b);
+    lapack.sort_by(f64::total_cmp);
+    cholesky.sort_by(f64::total_cmp);
+    for (l, c) in lapack.iter().zip(&cholesky) {
+        assert!(
+            (l - c).abs() <= 1e-9 * l.abs().max(1.0),
+            "{lapack:?} vs {cholesky:?}"
+        );
+    }
+}
+
+#[test]
+fn test_mismatched_matrix_shapes_return_error() {
+    let model = JohansenModel::InterceptNoTrendWithInterceptInCoint;
+    let solve = |bm_previous: &DMatrix<f64>, dbm: &DMatrix<f64>| {
+        calculate_eigenvalues_from_matrices(
+            bm_previous,
+            dbm,
+            0.01,
+            model,
+            SortOrder::Descending,
+            false,
+        )
+    };
+    let bm_previous = DMatrix::from_fn(2, 100, |i, j| ((i + 1) * (j + 3) % 7) as f64 - 3.0);
+    let dbm = DMatrix::from_fn(2, 100, |i, j| ((i + 2) * (j + 5) % 5) as f64 - 2.0);
+
+    let (eigenvalues, _) = solve(&bm_previous, &dbm).unwrap();
+    assert_eq!(eigenvalues.len(), model.eigenvalues_per_run(2));
+
+    let err = solve(&bm_previous, &dbm.columns(0, 99).into_owned()).unwrap_err();
+    assert_eq!(
+        err,
+        MatrixShapeError::ColumnMismatch {
+            dbm: 99,
+            bm_previous: 100
+        }
+    );
+    assert!(err.to_string().contains("99 columns"), "{err}");
+
+    let err = solve(&bm_previous, &DMatrix::zeros(3, 100)).unwrap_err();
+    assert_eq!(
+        err,
+        MatrixShapeError::RowMismatch {
+            dbm: 3,
+            bm_previous: 2
+        }
+    );
+
+    let err = solve(&DMatrix::zeros(2, 0), &DMatrix::zeros(2, 0)).unwrap_err();
+    assert_eq!(err, MatrixShapeError::Empty { rows: 2, cols: 0 });
+}
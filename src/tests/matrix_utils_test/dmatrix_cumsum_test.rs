@@ -1,4 +1,4 @@
-use crate::matrix_utils::{CumsumOrder, dmatrix_cumsum};
+use crate::matrix_utils::{CumsumOrder, dmatrix_cumsum, dmatrix_cumsum_in_place};
 use nalgebra::DMatrix;
 
 #[test]
@@ -32,3 +32,23 @@ fn test_cumsum_row_major() {
     let expected = DMatrix::from_row_slice(2, 3, &[1.0, 3.0, 6.0, 10.0, 15.0, 21.0]);
     assert_eq!(result, expected);
 }
+
+#[test]
+fn test_cumsum_in_place_matches_allocating() {
+    // 非整數數值使捨入誤差依累加順序而不同，可驗證兩者的累加順序一致
+    let m = DMatrix::from_fn(5, 17, |row, col| {
+        ((row * 31 + col * 7) % 13) as f64 * 0.1 - 0.55
+    });
+    for order in [
+        CumsumOrder::ColumnMajor,
+        CumsumOrder::RowMajor,
+        CumsumOrder::ColumnWise,
+        CumsumOrder::RowWise,
+    ] {
+        let mut in_place = m.clone();
+        dmatrix_cumsum_in_place(&mut in_place, order);
+        let expected = dmatrix_cumsum(&m, order);
+        let bits = |matrix: &DMatrix<f64>| matrix.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&in_place), bits(&expected), "{order:?}");
+    }
+}
@@ -0,0 +1,71 @@
+use crate::matrix_utils::{gram_matrix, symmetric_gram_matrix};
+use crate::rng_matrix::gen_normal_matrix;
+use nalgebra::DMatrix;
+
+#[test]
+fn test_symmetric_gram_matrix_matches_gram_matrix() {
+    // 小整數的內積沒有捨入誤差，結果必須完全相同
+    let a = DMatrix::<f64>::from_row_slice(3, 2, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let expected = DMatrix::<f64>::from_row_slice(2, 2, &[35.0, 44.0, 44.0, 56.0]);
+    assert_eq!(gram_matrix(&a), expected);
+    assert_eq!(symmetric_gram_matrix(&a), expected);
+
+    let bits = |matrix: &DMatrix<f64>| matrix.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+
+    // 與模擬相同的形狀（dim x F 列數）
+    for (nrows, ncols, seed) in [(1, 2, 1), (6, 7, 2), (12, 13, 3)] {
+        // 整數值的內積可精確相加，與一般矩陣乘法逐位元相同
+        let integers = gen_normal_matrix(nrows, ncols, seed).map(|v| (v * 8.0).round());
+        assert_eq!(
+            bits(&symmetric_gram_matrix(&integers)),
+            bits(&gram_matrix(&integers)),
+            "{nrows}x{ncols}"
+        );
+
+        let a = gen_normal_matrix(nrows, ncols, seed);
+        let gram = symmetric_gram_matrix(&a);
+        let expected = gram_matrix(&a);
+
+        assert_eq!(gram.shape(), (ncols, ncols));
+        assert_eq!(gram, gram.transpose());
+
+        // 每個內積依列的順序累加，結果逐位元固定
+        let sequential = DMatrix::<f64>::from_fn(ncols, ncols, |i, j| {
+            (0..nrows).fold(0.0, |sum, k| sum + a[(k, i)] * a[(k, j)])
+        });
+        assert_eq!(gram, sequential);
+
+        // 一般矩陣乘法的捨入隨實作而異，只比較到容許誤差
+        let tolerance = 1e-12 * expected.abs().max();
+        assert!(
+            (&gram - &expected).abs().max() <= tolerance,
+            "{nrows}x{ncols}: {gram} vs {expected}"
+        );
+    }
+}
+
+/// 效能比較：`cargo test --release --features bench -- --nocapture bench_`
+#[cfg(feature = "bench")]
+#[test]
+fn bench_gram_matrix() {
+    use std::time::Instant;
+
+    let a = gen_normal_matrix(12, 13, 1);
+    let iterations = 100_000;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(gram_matrix(&a));
+    }
+    let transposed = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(symmetric_gram_matrix(&a));
+    }
+    let symmetric = start.elapsed();
+
+    println!(
+        "gram_matrix: {transposed:?}, symmetric_gram_matrix: {symmetric:?} ({iterations} iterations)"
+    );
+}
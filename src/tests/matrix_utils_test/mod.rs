@@ -1,2 +1,3 @@
 mod dmatrix_cumsum_test;
+mod gram_matrix_test;
 mod sum_of_outer_products_test;
@@ -1,6 +1,26 @@
-use crate::matrix_utils::sum_of_outer_products;
+use crate::matrix_utils::{sum_of_outer_products, sum_of_outer_products_into};
+use crate::rng_matrix::gen_normal_matrix;
 use nalgebra::DMatrix;
 
+/// 從零矩陣開始累加 `a` 與 `b` 各欄的外積
+fn outer_sum(a: &DMatrix<f64>, b: &DMatrix<f64>) -> DMatrix<f64> {
+    let mut acc = DMatrix::<f64>::zeros(a.nrows(), b.nrows());
+    sum_of_outer_products_into(&mut acc, a, b);
+    acc
+}
+
+/// 依欄的順序逐一累加外積的參考實作
+fn sequential_outer_sum(mut acc: DMatrix<f64>, a: &DMatrix<f64>, b: &DMatrix<f64>) -> DMatrix<f64> {
+    for k in 0..a.ncols() {
+        for j in 0..b.nrows() {
+            for i in 0..a.nrows() {
+                acc[(i, j)] += a[(i, k)] * b[(j, k)];
+            }
+        }
+    }
+    acc
+}
+
 #[test]
 fn test_sum_of_outer_products_basic() {
     // 兩個 2x2 矩陣，每個 column 都是 [1, 2] 和 [3, 4]
@@ -157,3 +177,75 @@ fn test_sum_of_outer_products_numerical_precision() {
         "Numerical precision test failed"
     );
 }
+
+#[test]
+fn test_sum_of_outer_products_into_matches_sequential_sum() {
+    // 隨機矩陣：a 是 4x500, b 是 5x500
+    let a = gen_normal_matrix(4, 500, 42);
+    let b = gen_normal_matrix(5, 500, 43);
+
+    // 依欄的順序累加，結果逐位元相同，與執行緒數無關
+    let expected = sequential_outer_sum(DMatrix::<f64>::zeros(4, 5), &a, &b);
+    assert_eq!(outer_sum(&a, &b), expected);
+}
+
+#[test]
+fn test_sum_of_outer_products_into_matches_original() {
+    // 整數值的乘積與加總沒有捨入誤差，平行歸約與依欄累加的結果必須逐位元相同
+    let a = gen_normal_matrix(4, 500, 44).map(|v| (v * 8.0).round());
+    let b = gen_normal_matrix(5, 500, 45).map(|v| (v * 8.0).round());
+    let bits = |matrix: &DMatrix<f64>| matrix.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+    assert_eq!(
+        bits(&outer_sum(&a, &b)),
+        bits(&sum_of_outer_products(&a, &b))
+    );
+
+    // 一般數值只有加總順序不同，差異在捨入誤差以內
+    let a = gen_normal_matrix(4, 500, 46);
+    let b = gen_normal_matrix(5, 500, 47);
+    let original = sum_of_outer_products(&a, &b);
+    let tolerance = 1e-12 * original.abs().max();
+    assert!((outer_sum(&a, &b) - &original).abs().max() <= tolerance);
+}
+
+#[test]
+fn test_sum_of_outer_products_into_accumulates() {
+    // 累加到非零的起始矩陣
+    let a = DMatrix::<f64>::from_fn(3, 4, |i, j| (i + 2 * j) as f64);
+    let b = DMatrix::<f64>::from_fn(2, 4, |i, j| (i * j + 1) as f64);
+
+    let mut acc = DMatrix::<f64>::from_element(3, 2, 1.5);
+    sum_of_outer_products_into(&mut acc, &a, &b);
+
+    let expected = sequential_outer_sum(DMatrix::<f64>::from_element(3, 2, 1.5), &a, &b);
+    assert_eq!(acc, expected, "Accumulate test failed");
+}
+
+/// 效能比較：`cargo test --release --features bench -- --nocapture bench_`
+#[cfg(feature = "bench")]
+#[test]
+fn bench_sum_of_outer_products_into() {
+    use std::time::Instant;
+
+    let a = gen_normal_matrix(6, 10_000, 1);
+    let b = gen_normal_matrix(7, 10_000, 2);
+    let iterations = 50;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(sum_of_outer_products(&a, &b));
+    }
+    let original = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let mut acc = DMatrix::<f64>::zeros(6, 7);
+        sum_of_outer_products_into(&mut acc, &a, &b);
+        std::hint::black_box(acc);
+    }
+    let accumulating = start.elapsed();
+
+    println!(
+        "sum_of_outer_products: {original:?}, sum_of_outer_products_into: {accumulating:?} ({iterations} iterations)"
+    );
+}
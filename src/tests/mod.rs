@@ -1,5 +1,20 @@
+mod adaptive_test;
+#[cfg(feature = "serde")]
+mod batch_config_test;
+mod cli_test;
 mod data_storage;
 mod display_utils_test;
+#[cfg(feature = "cache")]
+mod eigenvalue_cache_test;
 mod johansen_models_test;
+mod johansen_statistics_test;
 mod matrix_utils_test;
+mod profiling_test;
 mod rng_matrix_test;
+#[cfg(feature = "serde")]
+mod serde_test;
+mod simulation_analyzers_test;
+mod stats_test;
+#[cfg(feature = "slow-tests")]
+mod urca_reference_test;
+mod validation_test;
@@ -0,0 +1,33 @@
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::calculate_eigenvalues;
+use crate::profiling::{self, Stage, ThreadProfilingGuard};
+
+#[test]
+fn test_profile_report_includes_all_stages() {
+    // 只在此執行緒啟用，不影響同時執行的其他測試
+    let _profiling = ThreadProfilingGuard::enable();
+
+    for seed in 1..=3 {
+        calculate_eigenvalues(
+            2,
+            50,
+            seed,
+            JohansenModel::InterceptNoTrendWithInterceptInCoint,
+        );
+    }
+
+    let total: u128 = profiling::stage_durations()
+        .iter()
+        .map(|(_, d)| d.as_nanos())
+        .sum();
+    assert!(total > 0, "profiling should accumulate time after a run");
+
+    let report = profiling::format_report();
+    for stage in Stage::ALL {
+        assert!(
+            report.contains(stage.name()),
+            "report missing stage '{}':\n{report}",
+            stage.name()
+        );
+    }
+}
@@ -14,3 +14,23 @@ fn test_gen_normal_matrix_cdf() {
     let cdf_values = interpolated_cdf_sorted(&vec, STANDARD_NORMAL_QUANTILES);
     assert_vec_approx_eq(&cdf_values, STANDARD_NORMAL_EXPECTED_CDF, 1e-2);
 }
+
+#[test]
+fn test_gen_normal_matrix_chunks_follow_position() {
+    use rand::{Rng, SeedableRng};
+    use rand_distr::{Distribution, StandardNormal};
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    // 30,000 個元素固定切成 3 個 chunk，第 i 個 chunk 使用主 RNG 的第 i 個輸出
+    let seed: u64 = 17;
+    let matrix = gen_normal_matrix(3, 10_000, seed);
+    let mut base_rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+
+    for chunk in matrix.as_slice().chunks(10_000) {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(base_rng.random());
+        let expected: Vec<f64> = (0..chunk.len())
+            .map(|_| StandardNormal.sample(&mut rng))
+            .collect();
+        assert_eq!(chunk, &expected[..]);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::rng_matrix::{SeedHashing, derive_rng_seed, gen_normal_matrix};
+
+/// 相鄰 seed 推導出的 RNG 種子之間平均相差的位元數
+fn mean_bit_distance(hashing: SeedHashing) -> f64 {
+    let total: u32 = (1..=1000)
+        .map(|seed| {
+            let a = derive_rng_seed(seed, 0, None, hashing);
+            let b = derive_rng_seed(seed + 1, 0, None, hashing);
+            (a ^ b).count_ones()
+        })
+        .sum();
+    total as f64 / 1000.0
+}
+
+#[test]
+fn test_seed_hashing_parse_and_legacy_compatibility() {
+    assert_eq!("splitmix".parse(), Ok(SeedHashing::SplitMix));
+    assert_eq!(" SplitMix64 ".parse(), Ok(SeedHashing::SplitMix));
+    assert_eq!("legacy".parse(), Ok(SeedHashing::Legacy));
+    assert!("blake2".parse::<SeedHashing>().is_err());
+    assert_eq!(SeedHashing::default(), SeedHashing::SplitMix);
+
+    // legacy 的串流 0 即為原本的 `seed as u64`；其他串流兩種設定相同
+    assert_eq!(derive_rng_seed(7, 0, None, SeedHashing::Legacy), 7);
+    assert_ne!(derive_rng_seed(7, 0, None, SeedHashing::SplitMix), 7);
+    for master_seed in [None, Some(99)] {
+        assert_eq!(
+            derive_rng_seed(7, 3, master_seed, SeedHashing::Legacy),
+            derive_rng_seed(7, 3, master_seed, SeedHashing::SplitMix)
+        );
+    }
+    assert!(SeedHashing::SplitMix.differs_from_legacy(0));
+    assert!(!SeedHashing::SplitMix.differs_from_legacy(3));
+    assert!(!SeedHashing::Legacy.differs_from_legacy(0));
+}
+
+#[test]
+fn test_mixed_seeds_decorrelate_consecutive_runs() {
+    // 連續整數只差少數位元；混合後平均約一半（32 個）位元不同
+    assert!(mean_bit_distance(SeedHashing::Legacy) < 3.0);
+    let mixed = mean_bit_distance(SeedHashing::SplitMix);
+    assert!((28.0..36.0).contains(&mixed), "mean bit distance {mixed}");
+
+    // 相鄰 seed 的第一個常態抽樣不相關，且相同 seed 可重現
+    let first_draw = |seed: u32| {
+        gen_normal_matrix(1, 1, derive_rng_seed(seed, 0, None, SeedHashing::SplitMix))[(0, 0)]
+    };
+    let draws: Vec<f64> = (1..=2001).map(first_draw).collect();
+    assert_eq!(draws[41], first_draw(42));
+
+    let (x, y) = (&draws[..2000], &draws[1..]);
+    let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+    let (mx, my) = (mean(x), mean(y));
+    let covariance: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let sx = x.iter().map(|a| (a - mx).powi(2)).sum::<f64>().sqrt();
+    let sy = y.iter().map(|b| (b - my).powi(2)).sum::<f64>().sqrt();
+    let correlation = covariance / (sx * sy);
+    // 獨立時標準差約 1/sqrt(2000) ≈ 0.022
+    assert!(correlation.abs() < 0.1, "lag-1 correlation {correlation}");
+}
@@ -0,0 +1,98 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+use crate::johansen_statistics::SortOrder;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn test_model_serializes_as_number() {
+    for model in JohansenModel::all_models() {
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(json, model.to_number().to_string());
+        assert_eq!(serde_json::from_str::<JohansenModel>(&json).unwrap(), model);
+    }
+}
+
+#[test]
+fn test_model_deserializes_from_name_or_numeric_string() {
+    let by_name: JohansenModel =
+        serde_json::from_str("\"InterceptTrendUnrestrictedBoth\"").unwrap();
+    assert_eq!(by_name, JohansenModel::InterceptTrendUnrestrictedBoth);
+
+    let by_string: JohansenModel = serde_json::from_str("\"1\"").unwrap();
+    assert_eq!(
+        by_string,
+        JohansenModel::InterceptNoTrendWithInterceptInCoint
+    );
+
+    for invalid in ["5", "-1", "\"Model2\"", "300"] {
+        assert!(
+            serde_json::from_str::<JohansenModel>(invalid).is_err(),
+            "{invalid} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_simulation_round_trips_through_json() {
+    let mut simulation = EigenvalueSimulation::new(JohansenModel::NoInterceptNoTrend, 3, 500, 100)
+        .with_sort_order(SortOrder::Ascending)
+        .with_master_seed(42)
+        .with_top_k(2);
+    simulation.checkpoint_every = Some(Duration::from_secs(30));
+    simulation.seeds = Some(vec![3, 5, 8]);
+    simulation.data_dir = PathBuf::from("results");
+
+    let json = serde_json::to_string(&simulation).unwrap();
+    let restored: EigenvalueSimulation = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.model, simulation.model);
+    assert_eq!(restored.dim, 3);
+    assert_eq!(restored.steps, 500);
+    assert_eq!(restored.num_runs, 100);
+    assert_eq!(restored.sort_order, SortOrder::Ascending);
+    assert_eq!(restored.master_seed, Some(42));
+    assert_eq!(restored.top_k, Some(2));
+    assert_eq!(restored.checkpoint_every, Some(Duration::from_secs(30)));
+    assert_eq!(restored.seeds, Some(vec![3, 5, 8]));
+    assert_eq!(restored.data_dir, PathBuf::from("results"));
+    assert_eq!(
+        restored.get_filename(simulation.model),
+        simulation.get_filename(simulation.model)
+    );
+}
+
+#[test]
+fn test_simulation_optional_fields_use_defaults() {
+    let restored: EigenvalueSimulation =
+        serde_json::from_str(r#"{"model": 2, "dim": 4, "steps": 1000, "num_runs": 50}"#).unwrap();
+    let expected = EigenvalueSimulation::new(JohansenModel::default(), 4, 1000, 50);
+
+    assert_eq!(restored.sort_order, expected.sort_order);
+    assert_eq!(restored.data_dir, expected.data_dir);
+    assert_eq!(restored.deadline, None);
+    assert_eq!(
+        restored.get_filename(JohansenModel::default()),
+        expected.get_filename(JohansenModel::default())
+    );
+
+    // 必填欄位缺少時失敗
+    assert!(serde_json::from_str::<EigenvalueSimulation>(r#"{"model": 2, "dim": 4}"#).is_err());
+}
+
+#[test]
+fn test_simulation_deserialization_validates() {
+    for (json, expected) in [
+        (
+            r#"{"model": 2, "dim": 4, "steps": 1000, "num_runs": 50, "warmup": 1000}"#,
+            "warmup",
+        ),
+        (
+            r#"{"model": 2, "dim": 4, "steps": 1000, "num_runs": 50, "top_k": 5}"#,
+            "top-k",
+        ),
+    ] {
+        let err = serde_json::from_str::<EigenvalueSimulation>(json).unwrap_err();
+        assert!(err.to_string().contains(expected), "{json}: {err}");
+    }
+}
@@ -0,0 +1,283 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+use crate::simulation_analyzers::{
+    PARALLEL_SORT_THRESHOLD, SumAggregator, ks_statistic_sorted, sort_values,
+};
+use crate::stats::percentile::percentile_sorted;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+#[test]
+fn test_ks_statistic_sorted() {
+    let a = [1.0, 2.0, 3.0, 4.0];
+
+    // 相同樣本差距為 0
+    assert_eq!(ks_statistic_sorted(&a, &a), 0.0);
+
+    // 完全分離的樣本差距為 1
+    assert_eq!(ks_statistic_sorted(&a, &[10.0, 11.0]), 1.0);
+
+    // F1(2) = 0.5, F2(2) = 0.0
+    assert_eq!(ks_statistic_sorted(&a, &[2.5, 3.5, 4.5, 5.5]), 0.5);
+
+    // 重複值只在跳過所有相同值後比較
+    assert_eq!(
+        ks_statistic_sorted(&[1.0, 1.0, 2.0], &[1.0, 2.0, 2.0]),
+        1.0 / 3.0
+    );
+
+    assert!(ks_statistic_sorted(&[], &a).is_nan());
+}
+
+#[test]
+fn test_ks_statistic_between_simulations() {
+    let runs = 300;
+    let model = JohansenModel::NoInterceptNoTrend;
+
+    // 相同過程的兩個獨立子串流，以及維度不同的過程
+    let base = EigenvalueSimulation::new(model, 1, 311, runs);
+    let same_process = EigenvalueSimulation::new(model, 1, 311, runs).with_stream_id(1);
+    let different = EigenvalueSimulation::new(model, 2, 311, runs);
+
+    let files = [
+        base.get_filename(model),
+        same_process.get_filename(model),
+        different.get_filename(model),
+    ];
+    for file in &files {
+        let _ = std::fs::remove_file(file);
+    }
+
+    base.run_simulation_quiet();
+    same_process.run_simulation_quiet();
+    different.run_simulation_quiet();
+
+    let d_same = base.ks_statistic(&same_process, SumAggregator).unwrap();
+    let d_different = base.ks_statistic(&different, SumAggregator).unwrap();
+
+    // 300 對 300 樣本在 1% 顯著水準下的臨界值約為 0.133
+    assert!(
+        d_same < 0.133,
+        "same process KS statistic too large: {d_same}"
+    );
+    assert!(
+        d_different > 0.5,
+        "different process KS statistic too small: {d_different}"
+    );
+
+    for file in &files {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+#[test]
+fn test_parallel_sort_matches_serial_percentiles() {
+    // 含重複值的打亂資料，數量超過平行排序門檻
+    let n = PARALLEL_SORT_THRESHOLD * 2 + 17;
+    let mut values: Vec<f64> = (0..n).map(|i| (i % 1000) as f64 * 0.25).collect();
+    values.shuffle(&mut Xoshiro256PlusPlus::seed_from_u64(42));
+
+    let mut serial = values.clone();
+    serial.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut parallel = values;
+    sort_values(&mut parallel);
+
+    assert_eq!(parallel, serial);
+    for percentile in [0.0, 0.01, 0.5, 0.9, 0.95, 0.99, 1.0] {
+        assert_eq!(
+            percentile_sorted(&parallel, percentile),
+            percentile_sorted(&serial, percentile)
+        );
+    }
+}
+
+#[test]
+fn test_analyze_to_writer_captures_table() {
+    use crate::data_storage::writer::AppendOnlyWriter;
+    use crate::simulation_analyzers::MaxAggregator;
+
+    let model = JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 335, 5);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+    {
+        let mut writer =
+            AppendOnlyWriter::with_expected_size(&filename, None, 3, 2, 335, None, true).unwrap();
+        for seed in 1..=5u32 {
+            writer
+                .append_eigenvalues(seed, &[seed as f64, 1.0])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    let mut out = Vec::new();
+    simulation
+        .analyze_to_writer(&mut out, SumAggregator, &[0.5, 0.9])
+        .unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    // 跡統計量為 2..=6：中位數 4，第 90 百分位數內插為 5.6
+    let expected = format!(
+        "Trace for model {model}:\n\
+         Total calculated 5 values\n\
+         Percentile    Value\n\
+         -------------------\n\
+         50.0th     4.000000\n\
+         90.0th     5.600000\n"
+    );
+    assert_eq!(report, expected);
+
+    let mut out = Vec::new();
+    simulation
+        .analyze_to_writer(&mut out, MaxAggregator, &[1.0])
+        .unwrap();
+    let report = String::from_utf8(out).unwrap();
+    assert!(report.starts_with(&format!("MaxEig for model {model}:\n")));
+    assert!(report.contains("100.0th"));
+    assert!(report.ends_with(" 5.000000\n"));
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_analyze_with_bootstrap_to_writer() {
+    let model = JohansenModel::NoInterceptNoTrend;
+    let simulation = EigenvalueSimulation::new(model, 2, 360, 40);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+    simulation.run_simulation_quiet();
+
+    let report = |resamples: usize| {
+        let mut out = Vec::new();
+        simulation
+            .analyze_with_bootstrap_to_writer(&mut out, SumAggregator, &[0.5, 0.95], resamples)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    let first = report(100);
+    assert_eq!(first, report(100));
+
+    // 每列為 "百分位數 值 [lo, hi]"，且區間包含點估計
+    let lines: Vec<&str> = first.lines().collect();
+    assert!(lines[2].ends_with("Value [lo, hi]"));
+    for line in &lines[4..6] {
+        let (value, interval) = line.split_once('[').unwrap();
+        let value: f64 = value.split_whitespace().last().unwrap().parse().unwrap();
+        let (lo, hi) = interval.trim_end_matches(']').split_once(", ").unwrap();
+        let (lo, hi): (f64, f64) = (lo.parse().unwrap(), hi.parse().unwrap());
+        assert!(lo <= value && value <= hi, "{line}");
+    }
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_tail_grid_is_monotone() {
+    use crate::simulation_analyzers::{TAIL_PERCENTILES, top_order_statistics};
+
+    assert!(TAIL_PERCENTILES.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, 346, 50);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+    simulation.run_simulation_quiet();
+
+    let mut out = Vec::new();
+    simulation
+        .analyze_tail_to_writer(&mut out, SumAggregator, 3)
+        .unwrap();
+    let report = String::from_utf8(out).unwrap();
+
+    // 百分位數列之後為 "Largest 3 values:" 與三個順序統計量
+    let lines: Vec<&str> = report.lines().collect();
+    let tail: Vec<f64> = lines[4..4 + TAIL_PERCENTILES.len()]
+        .iter()
+        .map(|line| line.split_whitespace().last().unwrap().parse().unwrap())
+        .collect();
+    assert!(lines[4].starts_with("90.0th"));
+    assert!(lines[3 + TAIL_PERCENTILES.len()].starts_with("99.9th"));
+    assert!(tail.windows(2).all(|pair| pair[0] <= pair[1]), "{tail:?}");
+
+    assert_eq!(lines[4 + TAIL_PERCENTILES.len()], "Largest 3 values:");
+    let largest: Vec<f64> = lines[5 + TAIL_PERCENTILES.len()..]
+        .iter()
+        .map(|line| line.split_whitespace().last().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(largest.len(), 3);
+    assert!(largest.windows(2).all(|pair| pair[0] >= pair[1]));
+    // 最大值不小於第 99.9 百分位數
+    assert!(largest[0] >= tail[tail.len() - 1]);
+
+    assert_eq!(top_order_statistics(&[1.0, 2.0, 3.0], 2), vec![3.0, 2.0]);
+    assert_eq!(top_order_statistics(&[1.0], 5), vec![1.0]);
+
+    let _ = std::fs::remove_file(&filename);
+}
+
+#[test]
+fn test_summary_only_matches_in_memory_percentiles() {
+    use crate::data_storage::writer::AppendOnlyWriter;
+    use crate::simulation_analyzers::{Aggregator, MaxAggregator, calculate_percentiles};
+    use rand::Rng;
+
+    let model = JohansenModel::NoInterceptNoTrend;
+    let runs = 20_000;
+    let simulation = EigenvalueSimulation::new(model, 3, 357, runs);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    // 合成右偏的特徵值，再加上 seed 超出 num_runs 的記錄（兩種讀取都會略過）
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(357);
+    {
+        let mut writer = AppendOnlyWriter::with_header(
+            &filename,
+            None,
+            simulation.file_header().unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        for seed in 1..=runs as u32 + 100 {
+            let mut eigenvalues: Vec<f64> = (0..3)
+                .map(|_| -rng.random_range(f64::EPSILON..1.0f64).ln())
+                .collect();
+            eigenvalues.sort_by(|a, b| b.total_cmp(a));
+            writer.append_eigenvalues(seed, &eigenvalues).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    let percentiles = [0.5, 0.9, 0.95, 0.99];
+    let aggregators: [&dyn Aggregator; 2] = [&SumAggregator, &MaxAggregator];
+    let (estimates, count) = simulation
+        .streaming_percentiles(&aggregators, &percentiles)
+        .unwrap();
+    assert_eq!(count, runs);
+
+    let exact = [
+        calculate_percentiles(&simulation, SumAggregator, &percentiles).unwrap(),
+        calculate_percentiles(&simulation, MaxAggregator, &percentiles).unwrap(),
+    ];
+    for (estimated, exact) in estimates.iter().zip(&exact) {
+        for ((&q, &estimate), &exact) in percentiles.iter().zip(estimated).zip(exact) {
+            assert!(
+                ((estimate - exact) / exact).abs() < 0.02,
+                "q {q}: estimate {estimate}, exact {exact}"
+            );
+        }
+    }
+
+    let mut out = Vec::new();
+    simulation
+        .summarize_to_writer(&mut out, &percentiles)
+        .unwrap();
+    let report = String::from_utf8(out).unwrap();
+    assert!(report.contains(&format!("Trace (streaming estimate) for model {model}:")));
+    assert!(report.contains("MaxEig (streaming estimate)"));
+    assert!(report.contains("Total calculated 20,000 values"));
+
+    let _ = std::fs::remove_file(&filename);
+}
@@ -0,0 +1,222 @@
+use crate::johansen_statistics::SortOrder;
+use crate::simulation_analyzers::sort_values;
+use crate::stats::percentile::{nth_percentile, percentile_sorted};
+use crate::stats::total_cmp_f64;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+#[test]
+fn test_percentile_sorted_empty_and_single() {
+    assert!(percentile_sorted(&[], 0.5).is_nan());
+    for q in [0.0, 0.25, 0.5, 1.0] {
+        assert_eq!(percentile_sorted(&[4.2], q), 4.2);
+    }
+}
+
+#[test]
+fn test_percentile_sorted_exact_rank() {
+    let sorted = [10.0, 20.0, 30.0, 40.0, 50.0];
+    assert_eq!(percentile_sorted(&sorted, 0.0), 10.0);
+    assert_eq!(percentile_sorted(&sorted, 0.25), 20.0);
+    assert_eq!(percentile_sorted(&sorted, 0.5), 30.0);
+    assert_eq!(percentile_sorted(&sorted, 0.75), 40.0);
+    assert_eq!(percentile_sorted(&sorted, 1.0), 50.0);
+}
+
+#[test]
+fn test_percentile_sorted_interpolates_between_ranks() {
+    // rank = q·(n-1)，落在兩個順序統計量之間時線性內插（不截斷索引）
+    let sorted = [1.0, 2.0, 4.0, 8.0];
+    assert!((percentile_sorted(&sorted, 0.5) - 3.0).abs() < 1e-12);
+    assert!((percentile_sorted(&sorted, 0.9) - 6.8).abs() < 1e-12);
+    assert!((percentile_sorted(&[0.0, 1.0], 0.95) - 0.95).abs() < 1e-12);
+}
+
+#[test]
+fn test_percentile_sorted_rejects_out_of_range_q() {
+    let sorted = [1.0, 2.0, 3.0];
+    assert!(percentile_sorted(&sorted, -0.1).is_nan());
+    assert!(percentile_sorted(&sorted, 1.5).is_nan());
+    assert!(percentile_sorted(&sorted, f64::NAN).is_nan());
+}
+
+#[test]
+fn test_nth_percentile_matches_sorted_interpolation() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+
+    for n in [1, 2, 3, 10, 101, 1000] {
+        let values: Vec<f64> = (0..n).map(|_| rng.random_range(-50.0..50.0)).collect();
+        let mut sorted = values.clone();
+        sort_values(&mut sorted);
+
+        for q in [0.0, 0.05, 0.5, 0.9, 0.95, 0.99, 1.0] {
+            let mut scratch = values.clone();
+            assert_eq!(
+                nth_percentile(&mut scratch, q),
+                percentile_sorted(&sorted, q),
+                "n = {n}, q = {q}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_percentile_boundaries_and_ties_are_exact() {
+    // 單一樣本時任何 q 都返回該值
+    for q in [0.0, 0.5, 1.0] {
+        assert_eq!(percentile_sorted(&[0.1], q), 0.1);
+        assert_eq!(nth_percentile(&mut [0.1], q), 0.1);
+    }
+
+    // q = 0 為最小值、q = 1 為最大值，不受輸入順序影響
+    let mut unsorted = [3.0, -1.0, 7.5, 2.0, 7.5];
+    assert_eq!(nth_percentile(&mut unsorted, 0.0), -1.0);
+    assert_eq!(nth_percentile(&mut unsorted, 1.0), 7.5);
+    assert_eq!(percentile_sorted(&[-1.0, 2.0, 3.0, 7.5, 7.5], 0.0), -1.0);
+    assert_eq!(percentile_sorted(&[-1.0, 2.0, 3.0, 7.5, 7.5], 1.0), 7.5);
+
+    // 重複值之間不內插：0.1·0.8 + 0.1·0.2 在浮點數下為 0.10000000000000002
+    let ties = [0.1; 3];
+    for q in [0.05, 0.1, 0.5, 0.95, 1.0] {
+        assert_eq!(percentile_sorted(&ties, q), 0.1, "q = {q}");
+        assert_eq!(nth_percentile(&mut ties.clone(), q), 0.1, "q = {q}");
+    }
+    assert_eq!(percentile_sorted(&[13.7, 13.7, 13.7, 20.0], 0.1), 13.7);
+}
+
+#[test]
+fn test_nth_percentile_invalid_input() {
+    assert!(nth_percentile(&mut [], 0.5).is_nan());
+    assert!(nth_percentile(&mut [1.0, f64::NAN, 3.0], 0.5).is_nan());
+    assert!(nth_percentile(&mut [1.0, 2.0], 1.5).is_nan());
+    assert!(nth_percentile(&mut [1.0, 2.0], f64::NAN).is_nan());
+}
+
+#[test]
+fn test_sorting_with_nan_does_not_panic() {
+    let values = [3.0, f64::NAN, -1.0, 2.0, f64::NAN, 0.5];
+
+    let mut ascending = values;
+    ascending.sort_by(total_cmp_f64);
+    assert_eq!(&ascending[..4], &[-1.0, 0.5, 2.0, 3.0]);
+    assert!(ascending[4..].iter().all(|value| value.is_nan()));
+
+    // 共用排序函數的 NaN 位置固定
+    let mut sorted = values.to_vec();
+    sort_values(&mut sorted);
+    assert_eq!(&sorted[..4], &[-1.0, 0.5, 2.0, 3.0]);
+    assert!(sorted[4..].iter().all(|value| value.is_nan()));
+
+    let mut descending = values;
+    SortOrder::Descending.sort(&mut descending);
+    assert!(descending[..2].iter().all(|value| value.is_nan()));
+    assert_eq!(&descending[2..], &[3.0, 2.0, 0.5, -1.0]);
+}
+
+#[test]
+fn test_batch_quantile_standard_error() {
+    use crate::stats::percentile::batch_quantile_standard_error;
+
+    assert!(batch_quantile_standard_error(&[1.0, 2.0, 3.0], 0.5, 1).is_nan());
+    assert!(batch_quantile_standard_error(&[1.0, 2.0, 3.0], 0.5, 20).is_nan());
+    assert_eq!(batch_quantile_standard_error(&[2.5; 100], 0.5, 10), 0.0);
+
+    // 標準常態中位數估計量的漸近標準誤為 sqrt(0.25 / n) / φ(0)
+    let n = 40_000;
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+    let values: Vec<f64> = (0..n)
+        .map(|_| rng.sample::<f64, _>(rand_distr::StandardNormal))
+        .collect();
+    let expected = (0.25 / n as f64).sqrt() / (1.0 / (2.0 * std::f64::consts::PI).sqrt());
+    let estimated = batch_quantile_standard_error(&values, 0.5, 20);
+    assert!(
+        (0.5 * expected..1.5 * expected).contains(&estimated),
+        "estimated {estimated}, expected about {expected}"
+    );
+}
+
+#[test]
+fn test_p2_quantile_tracks_exact_percentiles() {
+    use crate::stats::p2_quantile::P2Quantile;
+
+    // 少於五個樣本時為精確值
+    let mut small = P2Quantile::new(0.5);
+    assert!(small.estimate().is_nan());
+    for value in [3.0, 1.0, 2.0] {
+        small.push(value);
+    }
+    small.push(f64::NAN);
+    assert_eq!(small.count(), 3);
+    assert_eq!(small.estimate(), 2.0);
+
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(357);
+    let mut values: Vec<f64> = (0..20_000)
+        .map(|_| rng.sample::<f64, _>(rand_distr::StandardNormal).exp())
+        .collect();
+    let mut estimators: Vec<P2Quantile> = [0.5, 0.9, 0.99]
+        .iter()
+        .map(|&q| P2Quantile::new(q))
+        .collect();
+    for &value in &values {
+        estimators.iter_mut().for_each(|e| e.push(value));
+    }
+
+    sort_values(&mut values);
+    for estimator in &estimators {
+        let exact = percentile_sorted(&values, estimator.q());
+        let estimate = estimator.estimate();
+        assert!(
+            ((estimate - exact) / exact).abs() < 0.02,
+            "q {}: estimate {estimate}, exact {exact}",
+            estimator.q()
+        );
+    }
+}
+
+#[test]
+fn test_bootstrap_intervals_bracket_estimate_and_narrow() {
+    use crate::stats::bootstrap::bootstrap_percentile_intervals;
+
+    assert!(
+        bootstrap_percentile_intervals(&[], &[0.5], 100, 1)[0]
+            .0
+            .is_nan()
+    );
+
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(359);
+    let population: Vec<f64> = (0..8_000)
+        .map(|_| rng.sample::<f64, _>(rand_distr::StandardNormal).exp())
+        .collect();
+    let percentiles = [0.5, 0.9, 0.95];
+
+    let widths = |values: &[f64]| -> Vec<f64> {
+        let mut sorted = values.to_vec();
+        sort_values(&mut sorted);
+        let intervals = bootstrap_percentile_intervals(values, &percentiles, 400, 7);
+        percentiles
+            .iter()
+            .zip(&intervals)
+            .map(|(&q, &(lo, hi))| {
+                let estimate = percentile_sorted(&sorted, q);
+                assert!(
+                    lo <= estimate && estimate <= hi,
+                    "q {q}: {estimate} not in [{lo}, {hi}]"
+                );
+                hi - lo
+            })
+            .collect()
+    };
+
+    // 樣本數增為 16 倍，區間寬度約縮為 1/4
+    let small = widths(&population[..500]);
+    let large = widths(&population);
+    for (narrow, wide) in large.iter().zip(&small) {
+        assert!(narrow < &(wide * 0.6), "{narrow} vs {wide}");
+    }
+
+    // 固定種子的結果可重現
+    assert_eq!(
+        bootstrap_percentile_intervals(&population[..500], &percentiles, 50, 7),
+        bootstrap_percentile_intervals(&population[..500], &percentiles, 50, 7)
+    );
+}
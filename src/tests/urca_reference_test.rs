@@ -0,0 +1,125 @@
+//! 與 R `urca` 套件（`ca.jo`）參考臨界值的回歸比對：`cargo test --release --features slow-tests urca`
+//!
+//! `ca.jo` 的臨界值取自 Osterwald-Lenum (1992)。`ecdet = "const"`（截距限制於協整空間）
+//! 對應 Model 1（Table 1*），`ecdet = "trend"`（趨勢限制於協整空間）對應 Model 3（Table 2*）；
+//! `ecdet = "none"` 所用的表與此處任何模型的設定都不同，因此不列入。
+//! 參考值本身也是有限樣本（T = 400）的模擬結果，容許誤差需涵蓋兩邊的 Monte Carlo 誤差與離散化偏誤。
+//! 新增案例只需在 `URCA_TABLES` 加入一列；第 `i` 列為 `dim = i + 1`。
+
+use crate::data_storage::EigenvalueSimulation;
+use crate::johansen_models::JohansenModel;
+use crate::simulation_analyzers::{MaxAggregator, SumAggregator};
+use crate::validation::{ReferenceValue, ValidationRow, write_validation_table};
+
+/// `ca.jo` 報告的分位數（10%、5%、1% 顯著水準）
+const URCA_PERCENTILES: [f64; 3] = [0.90, 0.95, 0.99];
+
+/// 相對容許誤差
+const TOLERANCE: f64 = 0.05;
+
+/// 每個模型與維度的模擬設定
+const STEPS: usize = 1_000;
+const RUNS: usize = 10_000;
+
+/// 一個 `ecdet` 設定的參考臨界值
+struct UrcaTable {
+    model: JohansenModel,
+    ecdet: &'static str,
+    trace: &'static [[f64; 3]],
+    max_eigen: &'static [[f64; 3]],
+}
+
+const URCA_TABLES: [UrcaTable; 2] = [
+    UrcaTable {
+        model: JohansenModel::InterceptNoTrendWithInterceptInCoint,
+        ecdet: "const",
+        trace: &[
+            [7.52, 9.24, 12.97],
+            [17.85, 19.96, 24.60],
+            [32.00, 34.91, 41.07],
+        ],
+        max_eigen: &[
+            [7.52, 9.24, 12.97],
+            [13.75, 15.67, 20.20],
+            [19.77, 22.00, 26.81],
+        ],
+    },
+    UrcaTable {
+        model: JohansenModel::InterceptTrendUnrestrictedInterceptRestrictedTrend,
+        ecdet: "trend",
+        trace: &[
+            [10.49, 12.25, 16.26],
+            [22.76, 25.32, 30.45],
+            [39.06, 42.44, 48.45],
+        ],
+        max_eigen: &[
+            [10.49, 12.25, 16.26],
+            [16.85, 18.96, 23.65],
+            [23.11, 25.54, 30.34],
+        ],
+    },
+];
+
+/// 將依維度排列的臨界值展開為 `validation` 使用的參考表
+fn reference_values(rows: &[[f64; 3]]) -> Vec<ReferenceValue> {
+    rows.iter()
+        .enumerate()
+        .flat_map(|(index, values)| {
+            URCA_PERCENTILES
+                .iter()
+                .zip(values)
+                .map(move |(&percentile, &value)| ReferenceValue {
+                    dim: index + 1,
+                    percentile,
+                    value,
+                })
+        })
+        .collect()
+}
+
+#[test]
+fn test_critical_values_match_urca() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut failures = Vec::new();
+
+    for table in &URCA_TABLES {
+        let trace = reference_values(table.trace);
+        let max_eigen = reference_values(table.max_eigen);
+        let dims = table.trace.len().max(table.max_eigen.len());
+
+        for dim in 1..=dims {
+            let simulation =
+                EigenvalueSimulation::new(table.model, dim, STEPS, RUNS).with_data_dir(dir.path());
+            simulation.run_simulation_quiet();
+
+            let checks: [(&str, Vec<ValidationRow>); 2] = [
+                (
+                    "trace",
+                    simulation
+                        .validate_statistic_against(SumAggregator, &trace, TOLERANCE)
+                        .unwrap(),
+                ),
+                (
+                    "max-eigen",
+                    simulation
+                        .validate_statistic_against(MaxAggregator, &max_eigen, TOLERANCE)
+                        .unwrap(),
+                ),
+            ];
+            for (statistic, rows) in checks {
+                if rows.iter().any(|row| !row.passed) {
+                    let mut report = Vec::new();
+                    write_validation_table(&rows, TOLERANCE, &mut report).unwrap();
+                    failures.push(format!(
+                        "ecdet = \"{}\" ({}), {statistic}:\n{}",
+                        table.ecdet,
+                        table.model,
+                        String::from_utf8(report).unwrap()
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
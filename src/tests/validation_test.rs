@@ -0,0 +1,87 @@
+use crate::data_storage::EigenvalueSimulation;
+use crate::data_storage::writer::AppendOnlyWriter;
+use crate::johansen_models::JohansenModel;
+use crate::validation::{ReferenceValue, all_passed, read_reference_csv, write_validation_table};
+
+/// 以已知特徵值建立一個維度 2 的資料檔案
+fn simulation_with_data(steps: usize) -> EigenvalueSimulation {
+    let model = JohansenModel::InterceptNoTrendUnrestrictedIntercept;
+    let simulation = EigenvalueSimulation::new(model, 2, steps, 20);
+    let filename = simulation.get_filename(model);
+    let _ = std::fs::remove_file(&filename);
+
+    let mut writer =
+        AppendOnlyWriter::with_expected_size(&filename, None, 2, 2, steps as u32, None, true)
+            .unwrap();
+    for seed in 1..=20u32 {
+        writer
+            .append_eigenvalues(seed, &[seed as f64 * 0.5, 0.25])
+            .unwrap();
+    }
+    writer.finish().unwrap();
+    simulation
+}
+
+#[test]
+fn test_validate_against_self_and_perturbed_reference() {
+    let simulation = simulation_with_data(343);
+    let percentiles = [0.5, 0.9, 0.95];
+    let computed = simulation
+        .calculate_trace_percentiles(&percentiles)
+        .unwrap();
+
+    // 由自身數據產生的參考表（另含一個不會被比對的維度）
+    let csv_path = "test_validate_reference.csv";
+    let mut csv = String::from("dim,percentile,value\n");
+    for (p, value) in percentiles.iter().zip(&computed) {
+        csv.push_str(&format!("2,{p},{value}\n"));
+    }
+    csv.push_str("7,0.95,123.0\n");
+    std::fs::write(csv_path, &csv).unwrap();
+    let reference = read_reference_csv(csv_path).unwrap();
+    assert_eq!(reference.len(), 4);
+
+    let rows = simulation.validate_against(&reference, 1e-9).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(all_passed(&rows));
+
+    // 將一個臨界值偏移 5%，在 1% 容許誤差下應失敗
+    let perturbed: Vec<ReferenceValue> = reference
+        .iter()
+        .map(|entry| match entry.percentile {
+            0.95 => ReferenceValue {
+                value: entry.value * 1.05,
+                ..*entry
+            },
+            _ => *entry,
+        })
+        .collect();
+    let rows = simulation.validate_against(&perturbed, 0.01).unwrap();
+    assert!(!all_passed(&rows));
+    assert_eq!(rows.iter().filter(|row| !row.passed).count(), 1);
+
+    let mut out = Vec::new();
+    write_validation_table(&rows, 0.01, &mut out).unwrap();
+    let report = String::from_utf8(out).unwrap();
+    assert!(report.contains("FAIL"));
+    assert!(report.contains("1 of 3 critical values failed"));
+
+    // 沒有任何對應的維度時不視為通過
+    assert!(!all_passed(&[]));
+
+    let _ = std::fs::remove_file(csv_path);
+    let _ = std::fs::remove_file(simulation.get_filename(simulation.model));
+}
+
+#[test]
+fn test_read_reference_csv_rejects_invalid_rows() {
+    let csv_path = "test_validate_invalid.csv";
+
+    std::fs::write(csv_path, "2,95,5.0\n").unwrap();
+    assert!(read_reference_csv(csv_path).is_err());
+
+    std::fs::write(csv_path, "2,0.95\n").unwrap();
+    assert!(read_reference_csv(csv_path).is_err());
+
+    let _ = std::fs::remove_file(csv_path);
+}
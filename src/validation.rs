@@ -0,0 +1,195 @@
+//! 臨界值回歸檢查 - 比對模擬結果與參考臨界值表
+//!
+//! 參考 CSV 每列為 `dim,percentile,value`，`percentile` 為 0 至 1 之間的分位數，
+//! `value` 為跡統計量（特徵值總和，與分析報告中的 Trace 相同）的臨界值。
+//! 第一列若無法解析則視為標題列。
+
+use crate::data_storage::EigenvalueSimulation;
+use crate::simulation_analyzers::{Aggregator, SumAggregator, calculate_percentiles};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// 參考表中的一個臨界值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceValue {
+    pub dim: usize,
+    pub percentile: f64,
+    pub value: f64,
+}
+
+/// 單一臨界值的比對結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidationRow {
+    pub dim: usize,
+    pub percentile: f64,
+    pub expected: f64,
+    pub computed: f64,
+    /// 相對誤差 `|computed - expected| / |expected|`
+    pub relative_error: f64,
+    pub passed: bool,
+}
+
+/// 讀取參考臨界值 CSV，空白列會被略過
+pub fn read_reference_csv<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<ReferenceValue>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut reference = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [dim, percentile, value] = fields[..] else {
+            return Err(invalid_row(
+                line_number,
+                format!("expected 3 fields, found {}", fields.len()),
+            ));
+        };
+
+        let dim = match dim.parse::<usize>() {
+            Ok(dim) => dim,
+            Err(_) if line_number == 1 => continue, // 標題列
+            Err(_) => return Err(invalid_row(line_number, format!("invalid dim '{dim}'"))),
+        };
+        let percentile = percentile
+            .parse::<f64>()
+            .ok()
+            .filter(|p| (0.0..=1.0).contains(p))
+            .ok_or_else(|| {
+                invalid_row(
+                    line_number,
+                    format!("percentile '{percentile}' must be a number in [0, 1]"),
+                )
+            })?;
+        let value = value
+            .parse::<f64>()
+            .map_err(|_| invalid_row(line_number, format!("invalid value '{value}'")))?;
+
+        reference.push(ReferenceValue {
+            dim,
+            percentile,
+            value,
+        });
+    }
+
+    Ok(reference)
+}
+
+fn invalid_row(line_number: usize, message: String) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("reference CSV line {line_number}: {message}"),
+    )
+}
+
+/// 判斷計算值是否在參考值的相對容許誤差內；參考值為 0 時改以絕對誤差判斷
+fn compare(reference: &ReferenceValue, computed: f64, tolerance: f64) -> ValidationRow {
+    let difference = (computed - reference.value).abs();
+    let relative_error = if reference.value == 0.0 {
+        difference
+    } else {
+        difference / reference.value.abs()
+    };
+
+    ValidationRow {
+        dim: reference.dim,
+        percentile: reference.percentile,
+        expected: reference.value,
+        computed,
+        relative_error,
+        // NaN（例如沒有數據）不會通過
+        passed: relative_error <= tolerance,
+    }
+}
+
+impl EigenvalueSimulation {
+    /// 以此模擬的數據計算跡統計量臨界值，並與參考表中同維度的列比對
+    ///
+    /// 其他維度的參考列會被忽略；沒有對應列時返回空向量。
+    pub fn validate_against(
+        &self,
+        reference: &[ReferenceValue],
+        tolerance: f64,
+    ) -> Result<Vec<ValidationRow>, Box<dyn std::error::Error>> {
+        self.validate_statistic_against(SumAggregator, reference, tolerance)
+    }
+
+    /// 與 `validate_against` 相同，但以 `aggregator` 的統計量（例如最大特徵值）計算臨界值
+    pub fn validate_statistic_against<A: Aggregator>(
+        &self,
+        aggregator: A,
+        reference: &[ReferenceValue],
+        tolerance: f64,
+    ) -> Result<Vec<ValidationRow>, Box<dyn std::error::Error>> {
+        let rows: Vec<&ReferenceValue> = reference
+            .iter()
+            .filter(|entry| entry.dim == self.dim)
+            .collect();
+        if rows.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let percentiles: Vec<f64> = rows.iter().map(|entry| entry.percentile).collect();
+        let computed = calculate_percentiles(self, aggregator, &percentiles)?;
+
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                compare(
+                    entry,
+                    computed.get(i).copied().unwrap_or(f64::NAN),
+                    tolerance,
+                )
+            })
+            .collect())
+    }
+}
+
+/// 所有比對結果皆通過（沒有結果時視為失敗）
+pub fn all_passed(rows: &[ValidationRow]) -> bool {
+    !rows.is_empty() && rows.iter().all(|row| row.passed)
+}
+
+/// 以表格輸出比對結果
+pub fn write_validation_table(
+    rows: &[ValidationRow],
+    tolerance: f64,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "=== Critical value validation (tolerance {tolerance}) ==="
+    )?;
+    writeln!(
+        out,
+        "{:>4}  {:>10}  {:>12}  {:>12}  {:>10}  status",
+        "dim", "percentile", "expected", "computed", "rel_error"
+    )?;
+    for row in rows {
+        writeln!(
+            out,
+            "{:>4}  {:>9.1}%  {:>12.6}  {:>12.6}  {:>10.6}  {}",
+            row.dim,
+            row.percentile * 100.0,
+            row.expected,
+            row.computed,
+            row.relative_error,
+            if row.passed { "PASS" } else { "FAIL" }
+        )?;
+    }
+
+    let failed = rows.iter().filter(|row| !row.passed).count();
+    if rows.is_empty() {
+        writeln!(out, "No reference values matched the simulated dimensions")?;
+    } else if failed == 0 {
+        writeln!(out, "All {} critical values passed", rows.len())?;
+    } else {
+        writeln!(out, "{failed} of {} critical values failed", rows.len())?;
+    }
+    Ok(())
+}